@@ -1,9 +1,25 @@
+use std::path::Path;
+
+/// Исходник иконки приложения
+const ICON_SVG: &str = "src/icons/cedar.svg";
+
+/// Иконка для установщика WiX, он берёт её из исходного дерева. Файл хранится
+/// в репозитории: скрипт сборки пишет только в OUT_DIR и останавливает сборку,
+/// если файл не совпадает с SVG, чтобы установщик не вышел со старой иконкой
+const INSTALLER_ICON: &str = "wix/Product.ico";
+
+/// Размеры изображений внутри ICO
+const ICON_SIZES: [u32; 6] = [16, 32, 48, 64, 128, 256];
+
 fn main() {
+    source_strings();
+    application_icon();
+
     // Встраиваем иконку только для Windows
     #[cfg(windows)]
     {
         let mut res = winresource::WindowsResource::new();
-        res.set_icon("wix/Product.ico");
+        res.set_icon(generated_icon().to_str().unwrap());
         res.set("ProductName", "Cedar Folder Size Analyzer");
         res.set("FileDescription", "Cedar Folder Size Analyzer - Disk space analyzer for Windows");
         res.set("CompanyName", "Oleg Orlov");
@@ -20,3 +36,141 @@ fn main() {
     }
 }
 
+/// ICO из cedar.svg в OUT_DIR для ресурсов exe. Если wix/Product.ico от него
+/// отличается, сборка завершается с ошибкой и подсказкой, откуда его скопировать
+fn application_icon() {
+    println!("cargo:rerun-if-changed={}", ICON_SVG);
+    let svg = std::fs::read(ICON_SVG).unwrap();
+    let ico = rendered_icon(&svg);
+    println!("cargo:rerun-if-changed={}", INSTALLER_ICON);
+    if std::fs::read(INSTALLER_ICON).ok().as_deref() != Some(ico.as_slice()) {
+        panic!(
+            "{} differs from {}; copy {} over it",
+            INSTALLER_ICON,
+            ICON_SVG,
+            generated_icon().display()
+        );
+    }
+}
+
+/// ICO для SVG. Скрипт перезапускается и при правке исходников, поэтому
+/// рисунок берётся из OUT_DIR, пока SVG тот же, что при прошлой отрисовке
+fn rendered_icon(svg: &[u8]) -> Vec<u8> {
+    let rendered_from = generated_icon().with_extension("svg");
+    if std::fs::read(&rendered_from).ok().as_deref() == Some(svg) {
+        if let Ok(ico) = std::fs::read(generated_icon()) {
+            return ico;
+        }
+    }
+    let tree = usvg::Tree::from_data(svg, &usvg::Options::default()).unwrap();
+    let images: Vec<(u32, Vec<u8>)> = ICON_SIZES.iter().map(|&size| (size, render_png(&tree, size))).collect();
+    let ico = encode_ico(&images);
+    std::fs::write(generated_icon(), &ico).unwrap();
+    std::fs::write(rendered_from, svg).unwrap();
+    ico
+}
+
+fn generated_icon() -> std::path::PathBuf {
+    Path::new(&std::env::var("OUT_DIR").unwrap()).join("cedar.ico")
+}
+
+/// Квадратное изображение `size` пикселей, рисунок вписан по центру
+fn render_png(tree: &usvg::Tree, size: u32) -> Vec<u8> {
+    let mut pixmap = tiny_skia::Pixmap::new(size, size).unwrap();
+    let svg_size = tree.size();
+    let scale = (size as f32 / svg_size.width()).min(size as f32 / svg_size.height());
+    let offset_x = (size as f32 - svg_size.width() * scale) / 2.0;
+    let offset_y = (size as f32 - svg_size.height() * scale) / 2.0;
+    let transform = tiny_skia::Transform::from_translate(offset_x, offset_y).pre_scale(scale, scale);
+    resvg::render(tree, transform, &mut pixmap.as_mut());
+    pixmap.encode_png().unwrap()
+}
+
+/// ICO с изображениями в PNG (поддерживается с Windows Vista)
+fn encode_ico(images: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut ico = Vec::new();
+    ico.extend_from_slice(&[0, 0]); // Reserved
+    ico.extend_from_slice(&1u16.to_le_bytes()); // Type (1 = ICO)
+    ico.extend_from_slice(&(images.len() as u16).to_le_bytes());
+
+    let mut offset = 6 + images.len() * 16;
+    for (size, png) in images {
+        // Размер 256 записывается как 0
+        let side = if *size < 256 { *size as u8 } else { 0 };
+        ico.extend_from_slice(&[side, side, 0, 0]); // Width, height, colors, reserved
+        ico.extend_from_slice(&1u16.to_le_bytes()); // Planes
+        ico.extend_from_slice(&32u16.to_le_bytes()); // Bit count
+        ico.extend_from_slice(&(png.len() as u32).to_le_bytes());
+        ico.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += png.len();
+    }
+    for (_, png) in images {
+        ico.extend_from_slice(png);
+    }
+    ico
+}
+
+/// Строковые литералы исходников, похожие на ключи переводов, - для проверки
+/// неиспользуемых ключей в окне покрытия переводов. Список обновляется при
+/// каждой правке исходников
+fn source_strings() {
+    println!("cargo:rerun-if-changed=src");
+    let mut strings = std::collections::BTreeSet::new();
+    for entry in std::fs::read_dir("src").unwrap().flatten() {
+        let Ok(source) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        strings.extend(literals(&source).into_iter().filter(|literal| {
+            !literal.is_empty() && literal.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        }));
+    }
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("source_strings.txt");
+    let list: Vec<_> = strings.into_iter().collect();
+    std::fs::write(out, list.join("\n")).unwrap();
+}
+
+/// Содержимое строковых литералов без разбора escape-последовательностей.
+/// Комментарии, символ '"' и сырые строки r#"..."# пропускаются корректно
+fn literals(source: &str) -> Vec<String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '\'' if chars.get(i + 1) == Some(&'"') => i += 2,
+            '"' => {
+                // Сырая строка: перед кавычкой r и несколько #
+                let hashes = chars[..i].iter().rev().take_while(|&&c| c == '#').count();
+                let raw = i > hashes && chars[i - hashes - 1] == 'r';
+                let mut literal = String::new();
+                i += 1;
+                while i < chars.len() {
+                    if raw {
+                        if chars[i] == '"' && chars[i + 1..].iter().take(hashes).filter(|&&c| c == '#').count() == hashes {
+                            i += hashes;
+                            break;
+                        }
+                    } else if chars[i] == '\\' {
+                        literal.push(chars[i]);
+                        i += 1;
+                    } else if chars[i] == '"' {
+                        break;
+                    }
+                    if let Some(&c) = chars.get(i) {
+                        literal.push(c);
+                    }
+                    i += 1;
+                }
+                result.push(literal);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    result
+}
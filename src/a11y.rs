@@ -0,0 +1,63 @@
+// Доступность: имена элементов для экранного диктора (AccessKit) и
+// высококонтрастная тема
+
+use eframe::egui::{self, Color32, Stroke};
+
+/// Кнопка-значок. Без явного имени диктор прочитал бы только символ шрифта иконок.
+pub fn icon_button(ui: &mut egui::Ui, icon: &str, label: &str) -> egui::Response {
+    let response = ui.small_button(icon).on_hover_text(label);
+    set_name(&response, egui::WidgetType::Button, label);
+    response
+}
+
+/// Значок-пометка с подсказкой; подсказка же служит и его именем для диктора
+pub fn icon_label(ui: &mut egui::Ui, icon: egui::RichText, label: &str) -> egui::Response {
+    let response = ui.label(icon).on_hover_text(label);
+    set_name(&response, egui::WidgetType::Label, label);
+    response
+}
+
+/// Заменяет имя элемента, которое слышит пользователь экранного диктора
+pub fn set_name(response: &egui::Response, typ: egui::WidgetType, label: &str) {
+    let enabled = response.enabled();
+    response.widget_info(|| egui::WidgetInfo::labeled(typ, enabled, label));
+}
+
+/// Высококонтрастный вариант темы: чистые чёрный и белый, толстые рамки
+/// и яркое выделение
+pub fn high_contrast(mut visuals: egui::Visuals, dark_mode: bool) -> egui::Visuals {
+    let (background, foreground, accent) = if dark_mode {
+        (Color32::BLACK, Color32::WHITE, Color32::from_rgb(255, 255, 0))
+    } else {
+        (Color32::WHITE, Color32::BLACK, Color32::from_rgb(0, 0, 160))
+    };
+
+    visuals.panel_fill = background;
+    visuals.window_fill = background;
+    visuals.extreme_bg_color = background;
+    visuals.faint_bg_color = background;
+    visuals.window_stroke = Stroke::new(2.0, foreground);
+    visuals.hyperlink_color = accent;
+
+    visuals.selection.bg_fill = accent;
+    visuals.selection.stroke = Stroke::new(2.0, background);
+
+    let widgets = &mut visuals.widgets;
+    for (state, width) in [
+        (&mut widgets.noninteractive, 1.0),
+        (&mut widgets.inactive, 1.5),
+        (&mut widgets.hovered, 2.0),
+        (&mut widgets.active, 2.5),
+        (&mut widgets.open, 2.0),
+    ] {
+        state.bg_fill = background;
+        state.weak_bg_fill = background;
+        state.bg_stroke = Stroke::new(width, foreground);
+        state.fg_stroke = Stroke::new(width, foreground);
+    }
+    // Наведение и нажатие заметны не только по толщине рамки
+    widgets.hovered.bg_stroke = Stroke::new(2.0, accent);
+    widgets.active.bg_stroke = Stroke::new(2.5, accent);
+
+    visuals
+}
@@ -0,0 +1,202 @@
+// Папки программы. Настройки всегда лежат у пользователя (AppData\Roaming,
+// ~/.local/share, ~/Library/Application Support). Снимки, журнал операций и кэши
+// зависят от установки: если программа установлена на весь компьютер (MSI в
+// Program Files), они хранятся в общей папке %ProgramData%, у каждого
+// пользователя своя подпапка, закрытая от остальных; при установке для одного
+// пользователя или без установки - рядом с настройками. Если в общую папку
+// писать нельзя, данные остаются у пользователя. Пути показывает окно "Диагностика"
+
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const APP_DIR: &str = "cedar-folder-size-analyzer";
+const CONFIG_FILE: &str = "config.json";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum InstallScope {
+    /// Установлена в общую папку программ для всех пользователей
+    PerMachine,
+    /// Установлена в папку пользователя или запущена без установки
+    PerUser,
+}
+
+impl InstallScope {
+    pub fn translation_key(self) -> &'static str {
+        match self {
+            InstallScope::PerMachine => "install_per_machine",
+            InstallScope::PerUser => "install_per_user",
+        }
+    }
+}
+
+/// Выбранные при запуске папки
+pub struct AppPaths {
+    pub scope: InstallScope,
+    /// Папка настроек
+    pub config_dir: Option<PathBuf>,
+    /// Папка снимков, журнала операций и кэшей
+    pub data_dir: Option<PathBuf>,
+    /// Почему общая папка не подошла при установке на весь компьютер
+    pub shared_error: Option<String>,
+}
+
+/// Общая папка программы для всего компьютера; там же лежит политика организации
+pub fn machine_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        std::env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join(APP_DIR))
+    } else if cfg!(target_os = "macos") {
+        Some(PathBuf::from("/Library/Application Support").join(APP_DIR))
+    } else {
+        Some(PathBuf::from("/etc").join(APP_DIR))
+    }
+}
+
+/// Начинается ли путь с папки; в Windows без учёта регистра
+fn is_inside(path: &Path, dir: &Path) -> bool {
+    if cfg!(windows) {
+        let path = path.to_string_lossy().to_lowercase();
+        let dir = dir.to_string_lossy().to_lowercase();
+        path.strip_prefix(dir.trim_end_matches('\\')).is_some_and(|rest| rest.starts_with('\\'))
+    } else {
+        path.starts_with(dir)
+    }
+}
+
+/// Установка определяется по папке исполняемого файла
+fn detect_scope() -> InstallScope {
+    let Ok(exe) = std::env::current_exe() else {
+        return InstallScope::PerUser;
+    };
+    let machine_dirs: Vec<PathBuf> = if cfg!(windows) {
+        ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from)
+            .collect()
+    } else if cfg!(target_os = "macos") {
+        vec![PathBuf::from("/Applications")]
+    } else {
+        vec![PathBuf::from("/usr"), PathBuf::from("/opt")]
+    };
+    // Сам файл может быть ссылкой, например /usr/bin на /opt или обратно
+    let resolved = std::fs::canonicalize(&exe).ok().filter(|_| !cfg!(windows));
+    if machine_dirs.iter().any(|dir| is_inside(&exe, dir) || resolved.as_deref().is_some_and(|exe| is_inside(exe, dir))) {
+        InstallScope::PerMachine
+    } else {
+        InstallScope::PerUser
+    }
+}
+
+/// Подпапка пользователя в общей папке, если в неё можно писать. Общие данные
+/// бывают только в Windows: в /etc и /Library обычный пользователь не пишет
+fn shared_data_dir() -> Result<PathBuf, String> {
+    let users = machine_dir()
+        .filter(|_| cfg!(windows))
+        .ok_or_else(|| "no shared data folder on this system".to_string())?
+        .join("users");
+    let dir = crate::platform::private_user_dir(&users).map_err(|e| format!("{}: {}", users.display(), e))?;
+    let probe = dir.join(".write-test");
+    std::fs::write(&probe, b"").map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(probe);
+    Ok(dir)
+}
+
+/// Папки программы; выбираются один раз за запуск
+pub fn get() -> &'static AppPaths {
+    static PATHS: OnceLock<AppPaths> = OnceLock::new();
+    PATHS.get_or_init(|| {
+        let scope = detect_scope();
+        let config_dir = dirs::data_dir().map(|dir| dir.join(APP_DIR));
+        let (data_dir, shared_error) = match scope {
+            InstallScope::PerMachine => match shared_data_dir() {
+                Ok(dir) => (Some(dir), None),
+                Err(e) => (config_dir.clone(), Some(e)),
+            },
+            InstallScope::PerUser => (config_dir.clone(), None),
+        };
+        AppPaths { scope, config_dir, data_dir, shared_error }
+    })
+}
+
+/// Файл настроек
+pub fn config_file() -> Option<PathBuf> {
+    get().config_dir.as_ref().map(|dir| dir.join(CONFIG_FILE))
+}
+
+/// Файл или папка с данными программы: снимки, журнал, кэши
+pub fn data_path(name: &str) -> Option<PathBuf> {
+    get().data_dir.as_ref().map(|dir| dir.join(name))
+}
+
+impl CedarApp {
+    /// Окно "Диагностика": установка и папки, куда программа пишет
+    pub fn show_diagnostics_window(&mut self, ctx: &egui::Context) {
+        if !self.show_diagnostics {
+            return;
+        }
+        let paths = get();
+        let rows = [
+            ("diagnostics_program", std::env::current_exe().ok()),
+            ("diagnostics_config", config_file()),
+            ("auto_snapshots", crate::snapshot_store::store_dir()),
+            ("op_log", crate::op_log::log_path()),
+            ("diagnostics_hash_cache", crate::hash_cache::cache_file()),
+            ("diagnostics_checkpoint", crate::checkpoint::checkpoint_dir()),
+            ("diagnostics_policy", crate::policy::policy_file()),
+        ];
+
+        let mut open = true;
+        let mut reveal = None;
+        egui::Window::new(format!("{} {}", regular::STETHOSCOPE, self.translations.get("diagnostics")))
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong(self.translations.get("diagnostics_install"));
+                    ui.label(self.translations.get(paths.scope.translation_key()));
+                });
+                if let Some(error) = &paths.shared_error {
+                    let warning = self.config.palette.status_text(crate::palette::Status::Warning, self.config.dark_mode);
+                    ui.label(
+                        egui::RichText::new(self.translations.get_fmt("diagnostics_shared_failed", &[error]))
+                            .color(warning),
+                    );
+                }
+                ui.add_space(5.0);
+                ui.separator();
+
+                egui::Grid::new("diagnostics_grid").striped(true).num_columns(3).show(ui, |ui| {
+                    for (key, path) in &rows {
+                        ui.strong(self.translations.get(key));
+                        match path {
+                            Some(path) => {
+                                ui.add(egui::Label::new(egui::RichText::new(path.display().to_string()).monospace()).selectable(true));
+                                let exists = path.exists();
+                                let button = ui
+                                    .add_enabled(exists, egui::Button::new(regular::FOLDER_OPEN))
+                                    .on_hover_text(self.translations.get("show_in_folder"));
+                                if button.clicked() {
+                                    reveal = Some(path.clone());
+                                }
+                            }
+                            None => {
+                                ui.label(egui::RichText::new("—").color(ui.visuals().weak_text_color()));
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        self.show_diagnostics = open;
+
+        if let Some(path) = reveal {
+            if let Err(e) = crate::platform::reveal_in_file_manager(&path) {
+                self.log.error(format!("{}: {}", path.display(), e));
+            }
+        }
+    }
+}
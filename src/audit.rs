@@ -0,0 +1,71 @@
+// Режим аудита: только просмотр. Всё, что меняет файлы, отключено во всём приложении.
+// Администратор может закрепить режим ключом запуска --audit или политикой
+// организации - тогда из меню его не выключить
+
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+
+/// Ключ запуска, закрепляющий режим аудита
+const AUDIT_ARG: &str = "--audit";
+
+/// Режим закреплён ключом запуска
+pub fn locked_by_command_line() -> bool {
+    std::env::args().skip(1).any(|arg| arg == AUDIT_ARG)
+}
+
+impl CedarApp {
+    /// Включён ли режим аудита - в настройках или ключом запуска
+    pub fn audit_mode(&self) -> bool {
+        self.audit_locked || self.config.audit_mode
+    }
+
+    /// Удаление недоступно: режим аудита или дерево импортировано из ncdu
+    /// и его пути могут относиться к другому компьютеру
+    pub fn read_only(&self) -> bool {
+        self.audit_mode() || self.imported_tree
+    }
+
+    /// Подсказка к отключённым кнопкам удаления
+    pub fn read_only_hint(&self) -> String {
+        self.translations.get(if self.imported_tree { "imported_read_only_hint" } else { "audit_mode_hint" })
+    }
+
+    /// Проверка перед действием, меняющим файлы: в режиме только просмотра действие отклоняется
+    pub fn audit_blocked(&mut self) -> bool {
+        let key = if self.imported_tree {
+            "imported_read_only_hint"
+        } else if self.audit_mode() {
+            "audit_blocked"
+        } else {
+            return false;
+        };
+        self.set_status(self.translations.get(key));
+        true
+    }
+
+    /// Переключатель режима в меню
+    pub fn show_audit_toggle(&mut self, ui: &mut egui::Ui) {
+        let mut enabled = self.audit_mode();
+        let label = format!("{} {}", regular::SHIELD_CHECK, self.translations.get("audit_mode"));
+        let hint = if crate::policy::get().disable_delete {
+            "policy_locked"
+        } else if self.audit_locked {
+            "audit_mode_locked"
+        } else {
+            "audit_mode_hint"
+        };
+        let response = ui
+            .add_enabled(!self.audit_locked, egui::Checkbox::new(&mut enabled, label))
+            .on_hover_text(self.translations.get(hint))
+            .on_disabled_hover_text(self.translations.get(hint));
+        if response.changed() {
+            self.config.audit_mode = enabled;
+            // Уже начатые удаления не должны завершиться после включения
+            self.basket.pending = None;
+            self.path_to_delete = None;
+            self.show_delete_confirm = false;
+            self.save_config_to_file();
+        }
+    }
+}
@@ -0,0 +1,399 @@
+// Корзина подготовки к очистке: элементы собираются со всего дерева,
+// просматриваются вместе и удаляются одним пакетом
+
+use crate::{a11y, file_ops, format_size, CedarApp};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Пакетное действие, ожидающее подтверждения
+#[derive(Clone, Copy, PartialEq)]
+pub enum BasketAction {
+    Trash,
+    Permanent,
+}
+
+#[derive(Default)]
+pub struct Basket {
+    pub items: Vec<PathBuf>,
+    pub pending: Option<BasketAction>,
+    /// Введённая фраза подтверждения крупного безвозвратного удаления
+    pub phrase_input: String,
+    /// Поместится ли пакет в корзину - проверяется при запросе удаления в корзину
+    pub trash_fit: Option<(file_ops::TrashFit, crate::platform::TrashCapacity)>,
+    /// Идущее удаление пакета
+    job: Option<BasketJob>,
+}
+
+/// Результат удаления одного элемента: ошибка удаления и ошибка записи журнала
+type Deleted = (PathBuf, Result<(), String>, Option<String>);
+
+/// Удаление пакета в фоновом потоке. Окно каждый кадр забирает готовые
+/// результаты и убирает удалённое из дерева
+struct BasketJob {
+    total: usize,
+    done: Arc<AtomicUsize>,
+    results: Arc<Mutex<Vec<Deleted>>>,
+    handle: JoinHandle<()>,
+    failed: Vec<PathBuf>,
+}
+
+impl BasketJob {
+    fn start(ctx: &egui::Context, items: Vec<(PathBuf, u64)>, permanent: bool) -> Self {
+        let done = Arc::new(AtomicUsize::new(0));
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let total = items.len();
+        let (thread_done, thread_results, ctx) = (done.clone(), results.clone(), ctx.clone());
+        let handle = thread::spawn(move || {
+            for (path, size) in items {
+                let (result, written) = crate::op_log::delete_and_record(&path, size, permanent);
+                thread_results.lock().unwrap().push((path, result, written.err().map(|e| e.to_string())));
+                thread_done.fetch_add(1, Ordering::Relaxed);
+                ctx.request_repaint();
+            }
+        });
+        Self {
+            total,
+            done,
+            results,
+            handle,
+            failed: Vec::new(),
+        }
+    }
+}
+
+impl Basket {
+    /// Добавляет путь; вложенные друг в друга пути не дублируются
+    pub fn add(&mut self, path: PathBuf) {
+        if self.items.iter().any(|p| path.starts_with(p)) {
+            return;
+        }
+        self.items.retain(|p| !p.starts_with(&path));
+        self.items.push(path);
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.items.retain(|p| p != path);
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.items.iter().any(|p| p == path)
+    }
+}
+
+/// Том элемента: серийный номер, а если он неизвестен - начало пути (буква диска)
+fn volume_key(path: &Path) -> String {
+    match crate::platform::volume_serial(path) {
+        Some(serial) => format!("{:08x}", serial),
+        None => path.components().next().map(|c| c.as_os_str().to_string_lossy().into_owned()).unwrap_or_default(),
+    }
+}
+
+impl CedarApp {
+    /// Суммарный размер элементов корзины по данным дерева
+    fn basket_total_size(&self) -> u64 {
+        let Some(root) = &self.root_node else {
+            return 0;
+        };
+        self.basket
+            .items
+            .iter()
+            .filter_map(|p| root.find(p))
+            .map(|n| n.size)
+            .sum()
+    }
+
+    /// Поместится ли пакет в корзину: у каждого тома своя корзина, поэтому
+    /// элементы проверяются по томам и показывается худший результат
+    fn basket_trash_fit(&self) -> Option<(file_ops::TrashFit, crate::platform::TrashCapacity)> {
+        let root = self.root_node.as_ref()?;
+        // Том -> (элемент тома, крупнейший элемент, всего)
+        let mut volumes: HashMap<String, (&PathBuf, u64, u64)> = HashMap::new();
+        for path in &self.basket.items {
+            let size = root.find(path).map_or(0, |n| n.size);
+            let volume = volumes.entry(volume_key(path)).or_insert((path, 0, 0));
+            volume.1 = volume.1.max(size);
+            volume.2 += size;
+        }
+        volumes
+            .into_values()
+            .filter_map(|(path, largest, total)| {
+                let capacity = crate::platform::trash_capacity(path)?;
+                Some((file_ops::trash_fit(&capacity, largest, total), capacity))
+            })
+            .max_by_key(|(fit, _)| match fit {
+                file_ops::TrashFit::Fits => 0,
+                file_ops::TrashFit::Evicts => 1,
+                file_ops::TrashFit::TooLarge => 2,
+            })
+    }
+
+    /// Запускает удаление всех элементов корзины в фоне; неудавшиеся вернутся в неё
+    fn execute_basket(&mut self, ctx: &egui::Context, action: BasketAction) {
+        if self.audit_blocked() || self.basket.job.is_some() {
+            return;
+        }
+        let permanent = action == BasketAction::Permanent;
+        let items: Vec<(PathBuf, u64)> = std::mem::take(&mut self.basket.items)
+            .into_iter()
+            .map(|path| {
+                let size = self.root_node.as_ref().and_then(|r| r.find(&path)).map_or(0, |n| n.size);
+                (path, size)
+            })
+            .collect();
+        self.basket.job = Some(BasketJob::start(ctx, items, permanent));
+    }
+
+    /// Забирает результаты фонового удаления пакета
+    pub fn poll_basket(&mut self) {
+        let Some(job) = &mut self.basket.job else {
+            return;
+        };
+        let finished = job.handle.is_finished();
+        let results = std::mem::take(&mut *job.results.lock().unwrap());
+        for (path, result, log_error) in results {
+            if let Some(e) = log_error {
+                self.log.error(self.translations.get_fmt("op_log_write_failed", &[&e]));
+            }
+            match result {
+                Ok(()) => self.remove_from_tree(&path),
+                Err(e) => {
+                    self.log.error(format!("{}: {}", path.display(), e));
+                    if let Some(job) = &mut self.basket.job {
+                        job.failed.push(path);
+                    }
+                }
+            }
+        }
+        if !finished {
+            return;
+        }
+        let Some(job) = self.basket.job.take() else {
+            return;
+        };
+        self.set_status(self.translations.get_fmt(
+            "basket_done",
+            &[&job.total.to_string(), &job.failed.len().to_string()],
+        ));
+        // Добавленное в корзину во время удаления остаётся в ней
+        for path in job.failed {
+            self.basket.add(path);
+        }
+    }
+
+    /// Открывает корзину сразу на шаге подтверждения удаления в корзину
+    pub fn review_basket_deletion(&mut self) {
+        self.basket.trash_fit = self.basket_trash_fit();
+        self.basket.pending = Some(BasketAction::Trash);
+        self.basket.phrase_input.clear();
+        self.show_basket = true;
+    }
+
+    /// Окно корзины подготовки к очистке
+    pub fn show_basket_window(&mut self, ctx: &egui::Context) {
+        if !self.show_basket {
+            return;
+        }
+
+        let total_size = self.basket_total_size();
+        let item_count = self.basket.items.len().to_string();
+        let red_color = egui::Color32::from_rgb(198, 61, 53);
+
+        let mut open = self.show_basket;
+        let mut to_remove = None;
+        let mut clicked_path = None;
+        let mut clear = false;
+        let mut confirmed = None;
+        let mut requested = None;
+        let read_only = self.read_only();
+        let read_only_hint = self.read_only_hint();
+        let mut switch_to_permanent = false;
+
+        egui::Window::new(format!("{} {}", regular::BASKET, self.translations.get("basket")))
+            .open(&mut open)
+            .default_size([640.0, 420.0])
+            .show(ctx, |ui| {
+                if let Some(job) = &self.basket.job {
+                    let done = job.done.load(Ordering::Relaxed);
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / job.total.max(1) as f32)
+                            .text(self.translations.get_fmt("basket_deleting", &[&done.to_string(), &job.total.to_string()])),
+                    );
+                    ui.separator();
+                }
+                if self.basket.items.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("basket_empty"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                ui.label(egui::RichText::new(self.translations.get_fmt(
+                    "basket_total",
+                    &[&item_count, &format_size(total_size)],
+                )).strong());
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(260.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        egui::Grid::new("basket_grid")
+                            .striped(true)
+                            .num_columns(3)
+                            .show(ui, |ui| {
+                                for path in &self.basket.items {
+                                    let node = self.root_node.as_ref().and_then(|r| r.find(path));
+                                    let icon = if node.is_some_and(|n| n.is_file) {
+                                        regular::FILE
+                                    } else {
+                                        regular::FOLDER
+                                    };
+
+                                    let response = ui.selectable_label(
+                                        self.selected_path.as_ref() == Some(path),
+                                        format!("{} {}", icon, path.display()),
+                                    );
+                                    if response.clicked() {
+                                        clicked_path = Some(path.clone());
+                                    }
+                                    ui.label(node.map(|n| format_size(n.size)).unwrap_or_else(|| "—".to_string()));
+                                    if a11y::icon_button(ui, regular::X, &self.translations.get("remove_from_basket")).clicked()
+                                    {
+                                        to_remove = Some(path.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+
+                if let Some(action) = self.basket.pending {
+                    // Шаг подтверждения перед пакетным удалением
+                    let message_key = match action {
+                        BasketAction::Trash => "basket_confirm_trash",
+                        BasketAction::Permanent => "basket_confirm_permanent",
+                    };
+                    ui.label(egui::RichText::new(self.translations.get_fmt(
+                        message_key,
+                        &[&item_count, &format_size(total_size)],
+                    )).strong());
+                    ui.add_space(5.0);
+
+                    let trash_warning = self
+                        .basket
+                        .trash_fit
+                        .filter(|_| action == BasketAction::Trash)
+                        .and_then(|(fit, capacity)| self.trash_warning(fit, &capacity));
+                    if let Some((text, color)) = &trash_warning {
+                        ui.label(egui::RichText::new(format!("{} {}", regular::WARNING, text)).color(*color));
+                        ui.add_space(5.0);
+                    }
+
+                    // Крупный безвозвратный пакет: кнопка доступна только после ввода фразы
+                    let mut phrase_matches = true;
+                    if action == BasketAction::Permanent && self.config.confirm.needs_phrase(self.basket.items.len(), total_size) {
+                        let phrase = crate::confirm::phrase(total_size);
+                        ui.label(self.translations.get_fmt("confirm_phrase_prompt", &[&phrase]));
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.basket.phrase_input)
+                                .hint_text(phrase.as_str())
+                                .desired_width(220.0),
+                        );
+                        if self.basket.phrase_input.is_empty() {
+                            response.request_focus();
+                        }
+                        phrase_matches = self.basket.phrase_input.trim() == phrase;
+                        ui.add_space(5.0);
+                    }
+
+                    ui.horizontal(|ui| {
+                        let confirm_button = egui::Button::new(
+                            egui::RichText::new(format!("{} {}", regular::CHECK, self.translations.get("confirm")))
+                                .color(egui::Color32::WHITE)
+                        )
+                        .fill(red_color);
+                        let too_large = trash_warning.is_some()
+                            && self.basket.trash_fit.is_some_and(|(fit, _)| fit == file_ops::TrashFit::TooLarge);
+                        if !too_large && ui.add_enabled(phrase_matches, confirm_button).clicked() {
+                            confirmed = Some(action);
+                        }
+                        // Корзина переполнится: безвозвратное удаление предлагается явно
+                        if trash_warning.is_some()
+                            && ui.button(format!("{} {}", regular::WARNING, self.translations.get("delete_permanently_instead"))).clicked()
+                        {
+                            switch_to_permanent = true;
+                        }
+                        if ui.button(format!("{} {}", regular::X, self.translations.get("cancel"))).clicked() {
+                            self.basket.pending = None;
+                        }
+                    });
+                } else {
+                    ui.horizontal(|ui| {
+                        ui.add_enabled_ui(!read_only && self.basket.job.is_none(), |ui| {
+                            if ui.button(format!("{} {}", regular::TRASH, self.translations.get("delete"))).clicked() {
+                                requested = Some(BasketAction::Trash);
+                            }
+
+                            let permanent_button = egui::Button::new(
+                                egui::RichText::new(format!("{} {}", regular::WARNING, self.translations.get("delete_permanently")))
+                                    .color(egui::Color32::WHITE)
+                            )
+                            .fill(red_color);
+                            if ui.add(permanent_button).clicked() {
+                                requested = Some(BasketAction::Permanent);
+                            }
+                        })
+                        .response
+                        .on_disabled_hover_text(&read_only_hint);
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button(format!("{} {}", regular::BROOM, self.translations.get("clear"))).clicked() {
+                                clear = true;
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(path) = to_remove {
+            self.basket.remove(&path);
+        }
+        if let Some(path) = clicked_path {
+            self.selected_path = Some(path);
+        }
+        if clear {
+            self.basket.items.clear();
+        }
+        // Без подтверждения по настройкам удаляем сразу
+        if let Some(action) = requested {
+            let permanent = action == BasketAction::Permanent;
+            self.basket.trash_fit = if permanent { None } else { self.basket_trash_fit() };
+            let fits = self.basket.trash_fit.is_none_or(|(fit, _)| fit == file_ops::TrashFit::Fits);
+            if self.config.confirm.needs_confirmation(permanent, self.basket.items.len(), total_size) || !fits {
+                self.basket.pending = Some(action);
+                self.basket.phrase_input.clear();
+            } else {
+                confirmed = Some(action);
+            }
+        }
+        if switch_to_permanent {
+            self.basket.pending = Some(BasketAction::Permanent);
+            self.basket.phrase_input.clear();
+        }
+        if let Some(action) = confirmed {
+            self.basket.pending = None;
+            self.execute_basket(ctx, action);
+        }
+        if !open {
+            self.basket.pending = None;
+        }
+        self.show_basket = open;
+    }
+}
@@ -0,0 +1,365 @@
+// Данные браузеров в профиле пользователя: кэш, хранилище сервис-воркеров и
+// IndexedDB по каждому профилю Chrome, Edge и Firefox - с пояснением, что из
+// этого можно удалять без потерь
+
+use crate::palette::Status;
+use crate::storage_sense::measure;
+use crate::{format_size, CedarApp};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Firefox,
+}
+
+impl Browser {
+    fn name(self) -> &'static str {
+        match self {
+            Browser::Chrome => "Google Chrome",
+            Browser::Edge => "Microsoft Edge",
+            Browser::Firefox => "Mozilla Firefox",
+        }
+    }
+}
+
+/// Вид данных профиля
+#[derive(Clone, Copy, PartialEq)]
+pub enum DataKind {
+    /// HTTP-кэш, кэш кода и шейдеров - браузер восстановит его сам
+    Cache,
+    /// Cache Storage и скрипты сервис-воркеров - офлайн-режим сайтов
+    ServiceWorkers,
+    /// Базы сайтов: офлайн-почта, черновики, данные веб-приложений
+    IndexedDb,
+}
+
+impl DataKind {
+    fn all() -> [DataKind; 3] {
+        [DataKind::Cache, DataKind::ServiceWorkers, DataKind::IndexedDb]
+    }
+
+    fn translation_key(self) -> &'static str {
+        match self {
+            DataKind::Cache => "browser_cache",
+            DataKind::ServiceWorkers => "browser_service_workers",
+            DataKind::IndexedDb => "browser_indexeddb",
+        }
+    }
+
+    fn guidance_key(self) -> &'static str {
+        match self {
+            DataKind::Cache => "browser_cache_guidance",
+            DataKind::ServiceWorkers => "browser_service_workers_guidance",
+            DataKind::IndexedDb => "browser_indexeddb_guidance",
+        }
+    }
+
+    /// Насколько безопасно удалять файлы вручную
+    fn safety(self) -> Status {
+        match self {
+            DataKind::Cache => Status::Good,
+            DataKind::ServiceWorkers => Status::Warning,
+            DataKind::IndexedDb => Status::Critical,
+        }
+    }
+}
+
+/// Папки одного вида данных в профиле и их общий размер
+pub struct ProfileData {
+    kind: DataKind,
+    paths: Vec<PathBuf>,
+    size: u64,
+}
+
+pub struct BrowserProfile {
+    browser: Browser,
+    name: String,
+    path: PathBuf,
+    data: Vec<ProfileData>,
+}
+
+impl BrowserProfile {
+    fn total(&self) -> u64 {
+        self.data.iter().map(|d| d.size).sum()
+    }
+}
+
+/// Размер набора папок; несуществующие не учитываются
+fn sized(kind: DataKind, paths: Vec<PathBuf>) -> ProfileData {
+    let paths: Vec<PathBuf> = paths.into_iter().filter(|p| p.is_dir()).collect();
+    let size = paths.iter().map(|p| measure(p).0).sum();
+    ProfileData { kind, paths, size }
+}
+
+/// Папки пользовательских данных браузеров на основе Chromium; для каждой -
+/// папка, где браузер держит кэш (вне Windows кэш лежит отдельно от профиля)
+fn chromium_roots() -> Vec<(Browser, PathBuf, Option<PathBuf>)> {
+    let mut roots = Vec::new();
+    if cfg!(windows) {
+        if let Some(local) = dirs::data_local_dir() {
+            roots.push((Browser::Chrome, local.join(r"Google\Chrome\User Data"), None));
+            roots.push((Browser::Edge, local.join(r"Microsoft\Edge\User Data"), None));
+        }
+    } else if cfg!(target_os = "macos") {
+        if let (Some(support), Some(caches)) = (dirs::data_dir(), dirs::cache_dir()) {
+            roots.push((Browser::Chrome, support.join("Google/Chrome"), Some(caches.join("Google/Chrome"))));
+            roots.push((Browser::Edge, support.join("Microsoft Edge"), Some(caches.join("Microsoft Edge"))));
+        }
+    } else if let (Some(config), Some(cache)) = (dirs::config_dir(), dirs::cache_dir()) {
+        roots.push((Browser::Chrome, config.join("google-chrome"), Some(cache.join("google-chrome"))));
+        roots.push((Browser::Edge, config.join("microsoft-edge"), Some(cache.join("microsoft-edge"))));
+    }
+    roots
+}
+
+/// Имя профиля Chromium из его настроек
+fn chromium_profile_name(profile: &Path) -> Option<String> {
+    let preferences = std::fs::read_to_string(profile.join("Preferences")).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&preferences).ok()?;
+    value["profile"]["name"].as_str().map(str::to_string)
+}
+
+fn chromium_profiles() -> Vec<BrowserProfile> {
+    let mut profiles = Vec::new();
+    for (browser, root, cache_root) in chromium_roots() {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Профиль - папка с файлом Preferences: "Default", "Profile 1" и т.д.
+            if !path.join("Preferences").is_file() {
+                continue;
+            }
+            let folder = entry.file_name();
+            let cache_dir = cache_root.as_ref().map_or(path.clone(), |c| c.join(&folder));
+            let data = vec![
+                sized(
+                    DataKind::Cache,
+                    vec![cache_dir.join("Cache"), cache_dir.join("Code Cache"), path.join("GPUCache")],
+                ),
+                sized(DataKind::ServiceWorkers, vec![path.join("Service Worker")]),
+                sized(DataKind::IndexedDb, vec![path.join("IndexedDB")]),
+            ];
+            profiles.push(BrowserProfile {
+                browser,
+                name: chromium_profile_name(&path).unwrap_or_else(|| folder.to_string_lossy().to_string()),
+                path,
+                data,
+            });
+        }
+    }
+    profiles
+}
+
+/// Папка profiles.ini Firefox и папка, относительно которой лежат кэши профилей
+fn firefox_roots() -> Option<(PathBuf, PathBuf)> {
+    if cfg!(windows) {
+        Some((dirs::data_dir()?.join(r"Mozilla\Firefox"), dirs::data_local_dir()?.join(r"Mozilla\Firefox")))
+    } else if cfg!(target_os = "macos") {
+        Some((dirs::data_dir()?.join("Firefox"), dirs::cache_dir()?.join("Firefox")))
+    } else {
+        Some((dirs::home_dir()?.join(".mozilla/firefox"), dirs::cache_dir()?.join("mozilla/firefox")))
+    }
+}
+
+/// Профили из profiles.ini: имя и путь (относительный или абсолютный)
+pub fn parse_profiles_ini(text: &str) -> Vec<(String, String, bool)> {
+    let mut profiles = Vec::new();
+    let mut current: Option<(String, String, bool)> = None;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            profiles.extend(current.take().filter(|(_, path, _)| !path.is_empty()));
+            if line.starts_with("[Profile") {
+                current = Some((String::new(), String::new(), true));
+            }
+        } else if let (Some(profile), Some((key, value))) = (&mut current, line.split_once('=')) {
+            match key {
+                "Name" => profile.0 = value.to_string(),
+                "Path" => profile.1 = value.to_string(),
+                "IsRelative" => profile.2 = value == "1",
+                _ => {}
+            }
+        }
+    }
+    profiles.extend(current.filter(|(_, path, _)| !path.is_empty()));
+    profiles
+}
+
+fn firefox_profiles() -> Vec<BrowserProfile> {
+    let Some((root, cache_root)) = firefox_roots() else {
+        return Vec::new();
+    };
+    let Ok(ini) = std::fs::read_to_string(root.join("profiles.ini")) else {
+        return Vec::new();
+    };
+
+    let mut profiles = Vec::new();
+    for (name, relative_path, is_relative) in parse_profiles_ini(&ini) {
+        let path = if is_relative { root.join(&relative_path) } else { PathBuf::from(&relative_path) };
+        if !path.is_dir() {
+            continue;
+        }
+        // Кэш профиля лежит в локальной папке под тем же относительным путём
+        let cache = if is_relative { cache_root.join(&relative_path) } else { path.clone() };
+
+        // storage/default/<сайт>/cache - Cache Storage сервис-воркеров, .../idb - IndexedDB
+        let mut workers = Vec::new();
+        let mut indexed = Vec::new();
+        for origin in std::fs::read_dir(path.join("storage").join("default")).into_iter().flatten().flatten() {
+            workers.push(origin.path().join("cache"));
+            indexed.push(origin.path().join("idb"));
+        }
+        let data = vec![
+            sized(DataKind::Cache, vec![cache.join("cache2"), cache.join("startupCache"), path.join("shader-cache")]),
+            sized(DataKind::ServiceWorkers, workers),
+            sized(DataKind::IndexedDb, indexed),
+        ];
+        profiles.push(BrowserProfile {
+            browser: Browser::Firefox,
+            name,
+            path,
+            data,
+        });
+    }
+    profiles
+}
+
+/// Поиск профилей и подсчёт размеров в фоновом потоке
+pub struct BrowserScan {
+    profiles: Option<Vec<BrowserProfile>>,
+    result: Arc<Mutex<Option<Vec<BrowserProfile>>>>,
+}
+
+impl BrowserScan {
+    pub fn start() -> Self {
+        let result = Arc::new(Mutex::new(None));
+        let result_clone = result.clone();
+        thread::spawn(move || {
+            let mut profiles = chromium_profiles();
+            profiles.extend(firefox_profiles());
+            profiles.sort_by_key(|p| std::cmp::Reverse(p.total()));
+            *result_clone.lock().unwrap() = Some(profiles);
+        });
+        Self { profiles: None, result }
+    }
+
+    /// Забирает результат фонового потока, если он уже готов
+    fn poll(&mut self) {
+        if self.profiles.is_none() {
+            if let Ok(mut result) = self.result.try_lock() {
+                self.profiles = result.take();
+            }
+        }
+    }
+}
+
+impl CedarApp {
+    /// Окно данных браузеров
+    pub fn show_browser_data_window(&mut self, ctx: &egui::Context) {
+        let Some(scan) = &mut self.browser_scan else {
+            return;
+        };
+        scan.poll();
+
+        let mut open = true;
+        let mut to_basket: Vec<PathBuf> = Vec::new();
+        let copy_label = self.translations.get("copy_path");
+
+        egui::Window::new(format!("{} {}", regular::GLOBE, self.translations.get("browser_data")))
+            .id(egui::Id::new("browser_data_window"))
+            .open(&mut open)
+            .default_size([620.0, 480.0])
+            .show(ctx, |ui| {
+                let Some(profiles) = &scan.profiles else {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(self.translations.get("browser_data_reading"));
+                    });
+                    ctx.request_repaint();
+                    return;
+                };
+
+                if profiles.is_empty() {
+                    ui.weak(self.translations.get("browser_data_none"));
+                    return;
+                }
+
+                ui.label(
+                    egui::RichText::new(self.translations.get("browser_data_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.separator();
+
+                egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+                    for (index, profile) in profiles.iter().enumerate() {
+                        let title = format!(
+                            "{} - {}    {}",
+                            profile.browser.name(),
+                            profile.name,
+                            format_size(profile.total())
+                        );
+                        egui::CollapsingHeader::new(egui::RichText::new(title).strong())
+                            .id_salt(("browser_profile", index))
+                            .default_open(index == 0)
+                            .show(ui, |ui| {
+                                let response = ui.weak(profile.path.display().to_string());
+                                crate::clipboard::path_tooltip(response, &profile.path, &copy_label);
+
+                                egui::Grid::new(("browser_profile_grid", index))
+                                    .num_columns(3)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for kind in DataKind::all() {
+                                            let Some(data) = profile.data.iter().find(|d| d.kind == kind) else {
+                                                continue;
+                                            };
+                                            let color = self.config.palette.status_text(kind.safety(), self.config.dark_mode);
+                                            ui.label(egui::RichText::new(self.translations.get(kind.translation_key())).color(color))
+                                                .on_hover_text(self.translations.get(kind.guidance_key()));
+                                            ui.strong(format_size(data.size));
+                                            // IndexedDB чистится только из настроек браузера, по сайтам
+                                            if kind != DataKind::IndexedDb && data.size > 0 {
+                                                if ui.small_button(format!("{} {}", regular::BASKET, self.translations.get("add_to_basket"))).clicked() {
+                                                    to_basket.extend(data.paths.iter().cloned());
+                                                }
+                                            } else {
+                                                ui.label("");
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+
+                                for kind in DataKind::all() {
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "• {}: {}",
+                                            self.translations.get(kind.translation_key()),
+                                            self.translations.get(kind.guidance_key())
+                                        ))
+                                        .small()
+                                        .color(ui.visuals().weak_text_color()),
+                                    );
+                                }
+                            });
+                    }
+                });
+            });
+
+        if !to_basket.is_empty() {
+            for path in to_basket {
+                self.basket.add(path);
+            }
+            self.show_basket = true;
+        }
+        if !open {
+            self.browser_scan = None;
+        }
+    }
+}
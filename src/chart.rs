@@ -0,0 +1,297 @@
+// Общее для графических представлений (icicle, карта): раскладка дерева в блоки,
+// которые рисуются на экране или выгружаются в файл. Карта и кольца держат
+// заливку в текстуре, которая перерисовывается только при смене данных,
+// фокуса, размера или оформления: сотни тысяч блоков иначе рисовались бы каждый кадр
+
+use crate::palette::Palette;
+use crate::{CedarApp, DirNode};
+use eframe::egui::{self, Color32};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Размер шрифта подписей блоков
+pub const LABEL_SIZE: f32 = 12.0;
+
+/// Оформление графика
+pub struct ChartStyle {
+    pub palette: Palette,
+    pub dark_mode: bool,
+    /// Момент отсчёта для раскраски по возрасту; None - раскраска по веткам
+    pub age_now: Option<SystemTime>,
+}
+
+impl ChartStyle {
+    pub fn fill(&self, node: &DirNode, hue: f32, depth: usize) -> Color32 {
+        match self.age_now {
+            Some(now) => crate::heatmap::age_color(node.modified, now, self.palette, self.dark_mode),
+            None => self.palette.category(hue, depth, node.is_file, self.dark_mode),
+        }
+    }
+
+    pub fn text_color(&self) -> Color32 {
+        if self.dark_mode { Color32::WHITE } else { Color32::BLACK }
+    }
+
+    pub fn background(&self) -> Color32 {
+        if self.dark_mode { egui::Visuals::dark().panel_fill } else { egui::Visuals::light().panel_fill }
+    }
+
+    /// Заливка блока, в который объединены мелкие элементы
+    pub fn merged_fill(&self) -> Color32 {
+        if self.dark_mode { Color32::from_gray(85) } else { Color32::from_gray(190) }
+    }
+}
+
+/// Мелкие элементы папки, объединённые в один блок: по отдельности их не разглядеть
+#[derive(Clone)]
+pub struct Merged {
+    pub count: usize,
+    pub size: u64,
+    /// Крупнейший из них: к нему переходит дерево при раскрытии блока
+    pub largest: PathBuf,
+}
+
+impl Merged {
+    pub fn of(nodes: &[&DirNode]) -> Self {
+        let largest = nodes.iter().max_by_key(|n| n.size).map(|n| n.path.clone()).unwrap_or_default();
+        Self {
+            count: nodes.len(),
+            size: nodes.iter().map(|n| n.size).sum(),
+            largest,
+        }
+    }
+
+    /// Подпись без перевода: число элементов и их общий размер
+    pub fn label(&self) -> String {
+        format!("+{} ({})", self.count, crate::format_size(self.size))
+    }
+}
+
+/// Подпись блока; обрезается по его границам
+#[derive(Clone)]
+pub struct Label {
+    pub pos: egui::Pos2,
+    pub align: egui::Align2,
+    pub text: String,
+}
+
+pub struct Block<'a> {
+    /// У объединённого блока - папка, чьи элементы в нём собраны
+    pub node: &'a DirNode,
+    pub rect: egui::Rect,
+    pub fill: Color32,
+    pub label: Option<Label>,
+    pub merged: Option<Merged>,
+}
+
+/// Индекс самого глубокого блока под точкой: вложенные блоки идут после родителей
+pub fn block_at(blocks: &[Block], pos: egui::Pos2) -> Option<usize> {
+    blocks.iter().rposition(|block| block.rect.contains(pos))
+}
+
+/// Отрисовка блоков на экране
+pub fn paint(painter: &egui::Painter, blocks: &[Block], style: &ChartStyle) {
+    for block in blocks {
+        painter.rect_filled(block.rect, 2.0, block.fill);
+        if let Some(label) = &block.label {
+            painter.with_clip_rect(block.rect.shrink(2.0)).text(
+                label.pos,
+                label.align,
+                &label.text,
+                egui::FontId::proportional(LABEL_SIZE),
+                style.text_color(),
+            );
+        }
+    }
+}
+
+/// Заливка блоков в растровый слой
+pub fn rasterize_blocks(pixmap: &mut tiny_skia::Pixmap, transform: tiny_skia::Transform, blocks: &[Block]) {
+    for block in blocks {
+        let rect = block.rect;
+        if let Some(rect) = tiny_skia::Rect::from_ltrb(rect.left(), rect.top(), rect.right(), rect.bottom()) {
+            pixmap.fill_rect(rect, &fill_paint(block.fill), transform, None);
+        }
+    }
+}
+
+/// Сплошная заливка цветом egui
+pub fn fill_paint(color: Color32) -> tiny_skia::Paint<'static> {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    let mut paint = tiny_skia::Paint::default();
+    paint.set_color_rgba8(r, g, b, a);
+    paint.anti_alias = true;
+    paint
+}
+
+/// От чего зависит растровый слой; при любом отличии он рисуется заново
+#[derive(Clone, PartialEq)]
+pub struct LayerKey {
+    focus: PathBuf,
+    rect: egui::Rect,
+    pixels_per_point: f32,
+    palette: Palette,
+    dark_mode: bool,
+    by_age: bool,
+}
+
+impl LayerKey {
+    pub fn new(focus: &Path, rect: egui::Rect, pixels_per_point: f32, style: &ChartStyle) -> Self {
+        Self {
+            focus: focus.to_path_buf(),
+            rect,
+            pixels_per_point,
+            palette: style.palette,
+            dark_mode: style.dark_mode,
+            by_age: style.age_now.is_some(),
+        }
+    }
+}
+
+/// Область элемента на графике; `A` - её форма: прямоугольник у карты, сектор у колец
+pub struct Area<A> {
+    pub shape: A,
+    pub path: PathBuf,
+    pub merged: Option<Merged>,
+}
+
+/// Готовый кадр графика: заливка в текстуре, подписи и области элементов для наведения
+pub struct ChartLayer<A> {
+    key: LayerKey,
+    texture: egui::TextureHandle,
+    /// Вложенные области идут после родителей
+    pub areas: Vec<Area<A>>,
+    /// Подписи с областью, по которой они обрезаются
+    labels: Vec<(egui::Rect, Label)>,
+}
+
+impl<A> ChartLayer<A> {
+    /// Рисует заливку в текстуру размером с область графика в пикселях экрана
+    pub fn new(
+        ctx: &egui::Context,
+        key: LayerKey,
+        areas: Vec<Area<A>>,
+        labels: Vec<(egui::Rect, Label)>,
+        draw: impl FnOnce(&mut tiny_skia::Pixmap, tiny_skia::Transform),
+    ) -> Option<Self> {
+        let size = key.rect.size() * key.pixels_per_point;
+        let mut pixmap = tiny_skia::Pixmap::new(size.x.round() as u32, size.y.round() as u32)?;
+        let transform = tiny_skia::Transform::from_translate(-key.rect.left(), -key.rect.top())
+            .post_scale(key.pixels_per_point, key.pixels_per_point);
+        draw(&mut pixmap, transform);
+
+        let image = egui::ColorImage::from_rgba_premultiplied(
+            [pixmap.width() as usize, pixmap.height() as usize],
+            pixmap.data(),
+        );
+        let texture = ctx.load_texture("chart_layer", image, egui::TextureOptions::NEAREST);
+        Some(Self { key, texture, areas, labels })
+    }
+
+    pub fn is_current(&self, key: &LayerKey) -> bool {
+        self.key == *key
+    }
+
+    pub fn paint(&self, painter: &egui::Painter, style: &ChartStyle) {
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        painter.image(self.texture.id(), self.key.rect, uv, Color32::WHITE);
+        for (clip, label) in &self.labels {
+            painter.with_clip_rect(*clip).text(
+                label.pos,
+                label.align,
+                &label.text,
+                egui::FontId::proportional(LABEL_SIZE),
+                style.text_color(),
+            );
+        }
+    }
+
+    /// Область элемента; объединённые блоки папки не в счёт
+    pub fn position(&self, path: &Path) -> Option<usize> {
+        self.areas.iter().position(|area| area.merged.is_none() && area.path == path)
+    }
+}
+
+/// Растровые слои графических представлений
+#[derive(Default)]
+pub struct ChartLayers {
+    pub treemap: Option<ChartLayer<egui::Rect>>,
+    pub sunburst: Option<ChartLayer<crate::sunburst::Sector>>,
+}
+
+impl ChartLayers {
+    /// Дерево изменилось: слои придётся нарисовать заново
+    pub fn invalidate(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Область последней отрисовки представления на экране
+    pub fn rect(&self, view_mode: crate::ViewMode) -> Option<egui::Rect> {
+        match view_mode {
+            crate::ViewMode::Treemap => self.treemap.as_ref().map(|layer| layer.key.rect),
+            crate::ViewMode::Sunburst => self.sunburst.as_ref().map(|layer| layer.key.rect),
+            _ => None,
+        }
+    }
+}
+
+/// Что будет при щелчке по объединённому блоку
+pub enum MergedClick {
+    /// Приближение к папке, чтобы её элементы стали крупнее
+    Zoom(PathBuf),
+    /// Папка уже в фокусе: её мелкие элементы показываются в дереве
+    ShowInTree(PathBuf),
+}
+
+/// Подсказка объединённого блока и действие по щелчку
+pub fn merged_hover(
+    response: egui::Response,
+    area: &Area<impl Sized>,
+    merged: &Merged,
+    focus: &DirNode,
+    t: &crate::i18n::Translations,
+) -> Option<MergedClick> {
+    let click = if area.path == focus.path {
+        MergedClick::ShowInTree(merged.largest.clone())
+    } else {
+        MergedClick::Zoom(area.path.clone())
+    };
+    let clicked = response.clicked();
+    let percent = merged.size as f64 / focus.size.max(1) as f64 * 100.0;
+    let hint = match click {
+        MergedClick::Zoom(_) => "chart_merged_zoom",
+        MergedClick::ShowInTree(_) => "chart_merged_tree",
+    };
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.strong(t.get_fmt("chart_merged", &[&t.format_count(merged.count as u64), &area.path.display().to_string()]));
+        ui.label(format!("{} ({:.1}%)", crate::format_size(merged.size), percent));
+        ui.label(egui::RichText::new(t.get(hint)).weak());
+    });
+    clicked.then_some(click)
+}
+
+impl CedarApp {
+    /// Выполняет щелчок по объединённому блоку
+    pub fn apply_merged_click(&mut self, click: MergedClick) {
+        match click {
+            MergedClick::Zoom(folder) => self.view_focus = Some(folder),
+            MergedClick::ShowInTree(largest) => {
+                if let Some(root) = &mut self.root_node {
+                    root.expand_to(&largest);
+                }
+                self.search.scroll_to = Some(largest.clone());
+                self.selected_path = Some(largest);
+                self.config.view_mode = crate::ViewMode::Tree;
+                self.view_layout_changed();
+            }
+        }
+    }
+
+    pub fn chart_style(&self) -> ChartStyle {
+        ChartStyle {
+            palette: self.config.palette,
+            dark_mode: self.config.dark_mode,
+            age_now: self.config.color_by_age.then(SystemTime::now),
+        }
+    }
+}
@@ -0,0 +1,384 @@
+// Контрольные точки сканирования: завершённые поддеревья записываются на диск по
+// мере готовности, и прерванное сканирование продолжается с них. Точка - папка из
+// двух файлов. Маленький заголовок (корень, фильтр, объём готового, время) читается
+// при запуске ради предложения продолжить. Поддеревья дописываются строками JSON в
+// отдельный файл и читаются только при продолжении; в памяти остаётся лишь их
+// оглавление. Поддерево папки заменяет записи её подпапок, и когда устаревших
+// записей становится больше живых, файл переписывается без них
+
+use crate::filter::ScanFilter;
+use crate::DirNode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Завершённые папки фиксируются только до этой глубины: глубже запись
+/// поддеревьев обходится дороже, чем их повторное сканирование
+const CHECKPOINT_DEPTH: usize = 3;
+
+/// Минимальный интервал между сохранениями заголовка
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const CHECKPOINT_DIR: &str = "scan_checkpoint";
+const HEADER_FILE: &str = "header.json";
+const BODY_FILE: &str = "subtrees.jsonl";
+/// Точка прежних версий - одним файлом
+const LEGACY_FILE: &str = "scan_checkpoint.json";
+
+/// Заголовок контрольной точки без самих поддеревьев - для уведомления в интерфейсе
+#[derive(Serialize, Deserialize)]
+pub struct CheckpointInfo {
+    #[serde(with = "crate::os_serde")]
+    pub root: PathBuf,
+    pub scanned_size: u64,
+    #[serde(default)]
+    pub filter: ScanFilter,
+    #[serde(default)]
+    pub saved_at: Option<SystemTime>,
+}
+
+/// Строка поддерева в файле поддеревьев
+#[derive(Clone, Copy)]
+struct Record {
+    offset: u64,
+    len: u64,
+    size: u64,
+}
+
+/// Файл поддеревьев и оглавление его живых записей
+#[derive(Default)]
+struct Body {
+    /// None - в этом сеансе файл ещё не открыт
+    file: Option<File>,
+    len: u64,
+    records: HashMap<PathBuf, Record>,
+    /// Байт в записях, которые заменены поддеревьями родителей
+    dead: u64,
+}
+
+impl Body {
+    /// Дописывает поддерево в конец файла; первая запись сеанса начинает точку заново
+    fn append(&mut self, node: &DirNode) -> io::Result<Record> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let dir = checkpoint_dir().ok_or_else(|| io::Error::other("no data folder"))?;
+                // Заголовок прошлой точки к новым записям не относится
+                discard();
+                std::fs::create_dir_all(&dir)?;
+                self.len = 0;
+                self.file.insert(File::create(dir.join(BODY_FILE))?)
+            }
+        };
+        let mut writer = BufWriter::new(&*file);
+        serde_json::to_writer(&mut writer, node)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        drop(writer);
+        let end = file.stream_position()?;
+        let record = Record { offset: self.len, len: end - self.len, size: node.size };
+        self.len = end;
+        Ok(record)
+    }
+
+    fn forget(&mut self, path: &Path) {
+        if let Some(record) = self.records.remove(path) {
+            self.dead += record.len;
+        }
+    }
+
+    /// Запись папки заменяет записи её подпапок
+    fn replace(&mut self, node: &DirNode, record: Record) {
+        for child in node.children.iter().filter(|c| !c.is_file) {
+            self.forget(&child.path);
+        }
+        self.forget(&node.path);
+        self.records.insert(node.path.clone(), record);
+    }
+
+    fn scanned_size(&self) -> u64 {
+        self.records.values().map(|r| r.size).sum()
+    }
+
+    /// Переписывает файл только с живыми записями, если устаревших больше
+    fn compact_if_wasteful(&mut self) -> io::Result<()> {
+        let live: u64 = self.records.values().map(|r| r.len).sum();
+        if self.file.is_none() || self.dead <= live {
+            return Ok(());
+        }
+        let path = checkpoint_dir().ok_or_else(|| io::Error::other("no data folder"))?.join(BODY_FILE);
+        let tmp = path.with_extension("tmp");
+
+        let mut records: Vec<(PathBuf, Record)> = self.records.iter().map(|(p, r)| (p.clone(), *r)).collect();
+        records.sort_by_key(|(_, r)| r.offset);
+        let mut source = File::open(&path)?;
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        let mut len = 0;
+        for (_, record) in &mut records {
+            source.seek(SeekFrom::Start(record.offset))?;
+            io::copy(&mut (&mut source).take(record.len), &mut out)?;
+            record.offset = len;
+            len += record.len;
+        }
+        let file = out.into_inner().map_err(|e| e.into_error())?;
+        drop(source);
+        std::fs::rename(&tmp, &path)?;
+
+        self.file = Some(file);
+        self.len = len;
+        self.records = records.into_iter().collect();
+        self.dead = 0;
+        Ok(())
+    }
+}
+
+/// Контрольная точка текущего сканирования
+pub struct Checkpoint {
+    root: PathBuf,
+    // Поддеревья, собранные с другим фильтром, для продолжения не годятся
+    filter: ScanFilter,
+    resume: bool,
+    // Пишется ли точка на диск; вспомогательные сканирования не трогают файл основного
+    persistent: bool,
+    // Готовые поддеревья из прерванного сеанса, ещё не подхваченные сканером
+    resumed: Mutex<HashMap<PathBuf, DirNode>>,
+    body: Mutex<Body>,
+    // Запись не удалась - дальше точка не ведётся
+    failed: AtomicBool,
+    dirty: AtomicBool,
+    last_save: Mutex<Option<(Instant, Duration)>>,
+}
+
+impl Checkpoint {
+    pub fn new(root: PathBuf, filter: ScanFilter, resume: bool) -> Self {
+        Self {
+            root,
+            filter,
+            resume,
+            persistent: true,
+            resumed: Mutex::new(HashMap::new()),
+            body: Mutex::new(Body::default()),
+            failed: AtomicBool::new(false),
+            dirty: AtomicBool::new(false),
+            last_save: Mutex::new(None),
+        }
+    }
+
+    /// Контрольная точка, которая не сохраняется на диск
+    pub fn in_memory(root: PathBuf) -> Self {
+        Self {
+            persistent: false,
+            ..Self::new(root, ScanFilter::default(), false)
+        }
+    }
+
+    pub fn is_resuming(&self) -> bool {
+        self.resume
+    }
+
+    /// Подгружает сохранённые поддеревья, если сканирование продолжает прерванное,
+    /// и продолжает дописывать тот же файл. Вызывается из потока сканирования -
+    /// файл может быть большим.
+    pub fn load_resumed(&self) {
+        if !self.resume || !self.persistent {
+            return;
+        }
+        if load_info().is_none_or(|info| info.root != self.root || info.filter != self.filter) {
+            return;
+        }
+        let Some(path) = checkpoint_dir().map(|dir| dir.join(BODY_FILE)) else {
+            return;
+        };
+        let Ok(mut file) = OpenOptions::new().read(true).write(true).open(path) else {
+            return;
+        };
+
+        let mut body = self.body.lock().unwrap();
+        let mut resumed = self.resumed.lock().unwrap();
+        let mut reader = BufReader::new(&file);
+        let mut line = Vec::new();
+        let mut offset = 0;
+        loop {
+            line.clear();
+            if !matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+                break;
+            }
+            // Оборванная последняя строка остаётся от сбоя во время записи
+            if !line.ends_with(b"\n") {
+                break;
+            }
+            let Ok(node) = serde_json::from_slice::<DirNode>(&line) else {
+                break;
+            };
+            let record = Record { offset, len: line.len() as u64, size: node.size };
+            offset += record.len;
+            for child in node.children.iter().filter(|c| !c.is_file) {
+                resumed.remove(&child.path);
+            }
+            body.replace(&node, record);
+            resumed.insert(node.path.clone(), node);
+        }
+        drop(reader);
+        if file.set_len(offset).and_then(|_| file.seek(SeekFrom::Start(offset))).is_err() {
+            body.records.clear();
+            resumed.clear();
+            return;
+        }
+        body.file = Some(file);
+        body.len = offset;
+    }
+
+    /// Корень и объём уже готовых данных - для предложения продолжить
+    pub fn info(&self) -> CheckpointInfo {
+        CheckpointInfo {
+            root: self.root.clone(),
+            scanned_size: self.body.lock().unwrap().scanned_size(),
+            filter: self.filter.clone(),
+            saved_at: Some(SystemTime::now()),
+        }
+    }
+
+    /// Забирает готовое поддерево из прерванного сеанса
+    pub fn take_resumed(&self, path: &Path) -> Option<DirNode> {
+        let mut resumed = self.resumed.lock().unwrap();
+        if resumed.is_empty() {
+            return None;
+        }
+        resumed.remove(path)
+    }
+
+    /// Записывает полностью просканированную папку. Записи её подпапок
+    /// становятся лишними - они входят в поддерево.
+    pub fn complete(&self, node: &DirNode, depth: usize) {
+        if !self.persistent || depth == 0 || depth > CHECKPOINT_DEPTH || self.failed.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut body = self.body.lock().unwrap();
+        match body.append(node) {
+            Ok(record) => body.replace(node, record),
+            Err(e) => {
+                eprintln!("Failed to save scan checkpoint: {}", e);
+                self.failed.store(true, Ordering::Relaxed);
+                return;
+            }
+        }
+        // Не подхваченные сканером остатки внутри папки устарели (например, удалены)
+        let mut resumed = self.resumed.lock().unwrap();
+        if !resumed.is_empty() {
+            resumed.retain(|path, _| {
+                let stale = path.starts_with(&node.path);
+                if stale {
+                    body.forget(path);
+                }
+                !stale
+            });
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Сохраняет контрольную точку, если с прошлого раза прошло достаточно времени.
+    /// Интервал растёт вместе со временем записи, чтобы не тормозить сканирование.
+    pub fn save_if_due(&self) {
+        let due = match *self.last_save.lock().unwrap() {
+            Some((at, took)) => at.elapsed() >= SAVE_INTERVAL.max(took * 10),
+            None => true,
+        };
+        if due {
+            self.save();
+        }
+    }
+
+    /// Записывает заголовок контрольной точки; поддеревья уже на диске
+    pub fn save(&self) {
+        if !self.persistent || !self.dirty.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let Some(dir) = checkpoint_dir() else {
+            return;
+        };
+
+        let started = Instant::now();
+        let result = {
+            let mut body = self.body.lock().unwrap();
+            body.compact_if_wasteful().and_then(|_| {
+                let info = CheckpointInfo {
+                    root: self.root.clone(),
+                    scanned_size: body.scanned_size(),
+                    filter: self.filter.clone(),
+                    saved_at: Some(SystemTime::now()),
+                };
+                write_atomically(&dir.join(HEADER_FILE), &info)
+            })
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to save scan checkpoint: {}", e);
+        }
+        *self.last_save.lock().unwrap() = Some((Instant::now(), started.elapsed()));
+    }
+
+    /// Сканирование завершилось успешно - сохранённая точка больше не нужна
+    pub fn finish(&self) {
+        if self.persistent {
+            self.body.lock().unwrap().file = None;
+            discard();
+        }
+    }
+}
+
+/// Пишет во временный файл и переименовывает, чтобы сбой не оставил битый заголовок
+fn write_atomically(file: &Path, info: &CheckpointInfo) -> io::Result<()> {
+    let tmp = file.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    serde_json::to_writer(&mut writer, info)?;
+    writer.flush()?;
+    drop(writer);
+    std::fs::rename(&tmp, file)
+}
+
+pub fn checkpoint_dir() -> Option<PathBuf> {
+    crate::app_paths::data_path(CHECKPOINT_DIR)
+}
+
+/// Читает заголовок сохранённой контрольной точки
+pub fn load_info() -> Option<CheckpointInfo> {
+    let file = File::open(checkpoint_dir()?.join(HEADER_FILE)).ok()?;
+    serde_json::from_reader(BufReader::new(file)).ok()
+}
+
+/// Удаляет контрольную точку после успешного сканирования
+pub fn discard() {
+    if let Some(dir) = checkpoint_dir() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+    if let Some(file) = crate::app_paths::data_path(LEGACY_FILE) {
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+/// Раньше точка лежала в папке настроек. При установке на весь компьютер данные
+/// теперь в общей папке: оставшаяся там точка переносится, а если перенести
+/// не удалось - удаляется. Точка одним файлом прежних версий только удаляется
+pub fn move_from_config_dir() {
+    let paths = crate::app_paths::get();
+    let (Some(config_dir), Some(data_dir)) = (&paths.config_dir, &paths.data_dir) else {
+        return;
+    };
+    if config_dir == data_dir {
+        return;
+    }
+    let _ = std::fs::remove_file(config_dir.join(LEGACY_FILE));
+    let old = config_dir.join(CHECKPOINT_DIR);
+    if !old.exists() {
+        return;
+    }
+    let new = data_dir.join(CHECKPOINT_DIR);
+    if new.exists() || std::fs::rename(&old, &new).is_err() {
+        let _ = std::fs::remove_dir_all(old);
+    }
+}
@@ -0,0 +1,55 @@
+// Копирование путей: кнопки рядом с путями в строке состояния и прогрессе,
+// выделяемый путь с кнопкой в подсказках и Ctrl+C для выбранного элемента
+
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::Path;
+
+/// Кнопка копирования текста в буфер обмена; возвращает true после нажатия
+pub fn copy_button(ui: &mut egui::Ui, text: &str, label: &str) -> bool {
+    let clicked = crate::a11y::icon_button(ui, regular::COPY, label).clicked();
+    if clicked {
+        ui.output_mut(|o| o.copied_text = text.to_string());
+    }
+    clicked
+}
+
+/// Путь, который можно выделить мышью, и кнопка копирования после него.
+/// Длинный путь обрезается, целиком он виден в подсказке
+pub fn path_label(ui: &mut egui::Ui, path: &str, copy_label: &str) -> bool {
+    let copied = copy_button(ui, path, copy_label);
+    ui.add(egui::Label::new(path).truncate()).on_hover_text(path);
+    copied
+}
+
+/// Подсказка с путём: в неё можно навести мышь, выделить путь или скопировать его кнопкой
+pub fn path_tooltip(response: egui::Response, path: &Path, copy_label: &str) -> egui::Response {
+    response.on_hover_ui(|ui| {
+        let text = path.display().to_string();
+        ui.horizontal(|ui| {
+            ui.add(egui::Label::new(&text).selectable(true));
+            copy_button(ui, &text, copy_label);
+        });
+    })
+}
+
+impl CedarApp {
+    /// Ctrl+C вне полей ввода копирует путь выбранного элемента
+    pub fn handle_copy_keys(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let copy = ctx.input(|i| i.events.iter().any(|e| matches!(e, egui::Event::Copy)));
+        // Выделенный мышью текст копирует сам egui
+        let selecting = egui::text_selection::LabelSelectionState::load(ctx).has_selection();
+        if !copy || selecting {
+            return;
+        }
+        if let Some(path) = &self.selected_path {
+            let text = path.display().to_string();
+            ctx.output_mut(|o| o.copied_text = text.clone());
+            self.set_status(self.translations.get_fmt("path_copied", &[&text]));
+        }
+    }
+}
@@ -0,0 +1,352 @@
+// Сравнение двух папок, например исходной и её резервной копии: обе
+// сканируются, и в отчёт попадает то, что есть только с одной стороны,
+// и файлы, размер которых различается. Содержимое файлов не читается
+
+use crate::checkpoint::Checkpoint;
+use crate::palette::Status;
+use crate::pattern::name_key;
+use crate::{format_size, headless, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const ROW_HEIGHT: f32 = 20.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Difference {
+    OnlyInA,
+    OnlyInB,
+    /// Файл есть с обеих сторон, но разного размера
+    SizeMismatch,
+}
+
+impl Difference {
+    fn all() -> [Difference; 3] {
+        [Difference::OnlyInA, Difference::OnlyInB, Difference::SizeMismatch]
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            Difference::OnlyInA => regular::ARROW_LEFT,
+            Difference::OnlyInB => regular::ARROW_RIGHT,
+            Difference::SizeMismatch => regular::NOT_EQUALS,
+        }
+    }
+
+    fn translation_key(self) -> &'static str {
+        match self {
+            Difference::OnlyInA => "compare_only_a",
+            Difference::OnlyInB => "compare_only_b",
+            Difference::SizeMismatch => "compare_size_mismatch",
+        }
+    }
+}
+
+/// Расхождение; папка, которой нет с другой стороны, идёт одной записью
+pub struct Entry {
+    /// Путь относительно сравниваемых папок
+    pub relative: PathBuf,
+    pub is_file: bool,
+    pub difference: Difference,
+    /// Размер со стороны A (0 - элемента там нет)
+    pub size_a: u64,
+    pub size_b: u64,
+}
+
+pub struct Comparison {
+    pub a: PathBuf,
+    pub b: PathBuf,
+    pub size_a: u64,
+    pub size_b: u64,
+    /// Файлы, совпавшие по пути и размеру
+    pub matching: u64,
+    pub entries: Vec<Entry>,
+}
+
+impl Comparison {
+    /// (число, объём) расхождений одного вида
+    fn totals(&self, difference: Difference) -> (usize, u64) {
+        let entries = self.entries.iter().filter(|e| e.difference == difference);
+        let size = |e: &Entry| match difference {
+            Difference::OnlyInB => e.size_b,
+            _ => e.size_a,
+        };
+        entries.fold((0, 0), |(count, total), e| (count + 1, total + size(e)))
+    }
+}
+
+/// Сравнивает содержимое двух просканированных папок
+fn compare(a: &DirNode, b: &DirNode) -> Comparison {
+    let mut comparison = Comparison {
+        a: a.path.clone(),
+        b: b.path.clone(),
+        size_a: a.size,
+        size_b: b.size,
+        matching: 0,
+        entries: Vec::new(),
+    };
+    compare_children(a, b, Path::new(""), &mut comparison);
+    comparison
+}
+
+fn compare_children(a: &DirNode, b: &DirNode, relative: &Path, comparison: &mut Comparison) {
+    let only = |node: &DirNode, difference| Entry {
+        relative: relative.join(&node.name),
+        is_file: node.is_file,
+        difference,
+        size_a: if difference == Difference::OnlyInA { node.size } else { 0 },
+        size_b: if difference == Difference::OnlyInB { node.size } else { 0 },
+    };
+
+    // Имена сравниваются как их видит файловая система: "Déjà Vu" с копии
+    // с macOS - та же папка
+    let in_b: HashMap<String, &DirNode> = b.children.iter().map(|c| (name_key(&c.display_name()), c)).collect();
+    for child_a in &a.children {
+        // Файл на месте папки (или наоборот) - это два разных элемента
+        match in_b.get(&name_key(&child_a.display_name())).filter(|c| c.is_file == child_a.is_file) {
+            None => comparison.entries.push(only(child_a, Difference::OnlyInA)),
+            Some(child_b) if child_a.is_file => {
+                if child_a.size == child_b.size {
+                    comparison.matching += 1;
+                } else {
+                    comparison.entries.push(Entry {
+                        relative: relative.join(&child_a.name),
+                        is_file: true,
+                        difference: Difference::SizeMismatch,
+                        size_a: child_a.size,
+                        size_b: child_b.size,
+                    });
+                }
+            }
+            Some(child_b) => compare_children(child_a, child_b, &relative.join(&child_a.name), comparison),
+        }
+    }
+
+    let in_a: HashSet<(String, bool)> = a.children.iter().map(|c| (name_key(&c.display_name()), c.is_file)).collect();
+    for child_b in &b.children {
+        if !in_a.contains(&(name_key(&child_b.display_name()), child_b.is_file)) {
+            comparison.entries.push(only(child_b, Difference::OnlyInB));
+        }
+    }
+}
+
+/// Сравнение, идущее в фоновом потоке
+struct CompareJob {
+    cancel: Arc<AtomicBool>,
+    /// 0 - сканируется A, 1 - B, 2 - сравнение
+    phase: Arc<AtomicUsize>,
+    result: Arc<Mutex<Option<Result<Comparison, String>>>>,
+}
+
+impl Drop for CompareJob {
+    // Закрытие окна или новое сравнение останавливают старое
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl CompareJob {
+    fn start(a: PathBuf, b: PathBuf) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let phase = Arc::new(AtomicUsize::new(0));
+        let result = Arc::new(Mutex::new(None));
+        let (thread_cancel, thread_phase, thread_result) = (cancel.clone(), phase.clone(), result.clone());
+        thread::spawn(move || {
+            let config = CedarApp::load_config_from_file();
+            // Сканирования сравнения не должны затирать контрольную точку основного
+            let scan = |path: &Path| {
+                let root = path.to_string_lossy();
+                headless::scan_with(&root, &config, thread_cancel.clone(), Checkpoint::in_memory(path.to_path_buf()))
+                    .map(|(node, _)| node)
+            };
+            let compared = scan(&a).and_then(|node_a| {
+                thread_phase.store(1, Ordering::Relaxed);
+                let node_b = scan(&b)?;
+                thread_phase.store(2, Ordering::Relaxed);
+                Ok(compare(&node_a, &node_b))
+            });
+            if !thread_cancel.load(Ordering::Relaxed) {
+                *thread_result.lock().unwrap() = Some(compared);
+            }
+        });
+        Self { cancel, phase, result }
+    }
+}
+
+/// Окно сравнения: выбор папок, ход сравнения и результат
+pub struct CompareWindow {
+    a: String,
+    b: String,
+    job: Option<CompareJob>,
+    result: Option<Result<Comparison, String>>,
+    /// Какие виды расхождений показывать
+    shown: [bool; 3],
+}
+
+impl CompareWindow {
+    pub fn new(a: Option<&Path>) -> Self {
+        Self {
+            a: a.map(|p| p.display().to_string()).unwrap_or_default(),
+            b: String::new(),
+            job: None,
+            result: None,
+            shown: [true; 3],
+        }
+    }
+
+    /// Забирает результат фонового потока
+    fn poll(&mut self) {
+        let finished = self.job.as_ref().and_then(|job| job.result.try_lock().ok().and_then(|mut r| r.take()));
+        if let Some(result) = finished {
+            self.result = Some(result);
+            self.job = None;
+        }
+    }
+}
+
+/// Поле ввода папки с кнопкой выбора
+fn folder_field(ui: &mut egui::Ui, label: &str, path: &mut String, browse: &str) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(path).desired_width(420.0));
+        if ui.button(format!("{} {}", regular::FOLDER_OPEN, browse)).clicked() {
+            if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                *path = folder.display().to_string();
+            }
+        }
+    });
+    ui.end_row();
+}
+
+impl CedarApp {
+    /// Окно сравнения двух папок
+    pub fn show_compare_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.compare else {
+            return;
+        };
+        window.poll();
+
+        let mut open = true;
+        let t = &self.translations;
+        let copy_label = t.get("copy_path");
+        let error_color = self.config.palette.status_text(Status::Critical, self.config.dark_mode);
+        egui::Window::new(format!("{} {}", regular::SCALES, t.get("compare_folders")))
+            .id(egui::Id::new("compare_window"))
+            .open(&mut open)
+            .default_size([760.0, 520.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(t.get("compare_hint")).weak());
+                egui::Grid::new("compare_folders").num_columns(2).show(ui, |ui| {
+                    folder_field(ui, "A", &mut window.a, &t.get("browse"));
+                    folder_field(ui, "B", &mut window.b, &t.get("browse"));
+                });
+
+                ui.horizontal(|ui| {
+                    let (a, b) = (window.a.trim(), window.b.trim());
+                    let ready = !a.is_empty() && !b.is_empty() && a != b && window.job.is_none();
+                    let button = egui::Button::new(format!("{} {}", regular::SCALES, t.get("compare")));
+                    if ui.add_enabled(ready, button).clicked() {
+                        window.job = Some(CompareJob::start(PathBuf::from(a), PathBuf::from(b)));
+                        window.result = None;
+                    }
+                    if let Some(job) = &window.job {
+                        ui.spinner();
+                        let label = match job.phase.load(Ordering::Relaxed) {
+                            0 => t.get_fmt("compare_scanning", &["A"]),
+                            1 => t.get_fmt("compare_scanning", &["B"]),
+                            _ => t.get("compare_comparing"),
+                        };
+                        ui.label(label);
+                        if ui.button(t.get("cancel")).clicked() {
+                            window.job = None;
+                        }
+                        ui.ctx().request_repaint();
+                    }
+                });
+                ui.separator();
+
+                let comparison = match &window.result {
+                    Some(Ok(comparison)) => comparison,
+                    Some(Err(error)) => {
+                        ui.colored_label(error_color, error);
+                        return;
+                    }
+                    None => return,
+                };
+
+                ui.label(t.get_fmt(
+                    "compare_summary",
+                    &[&format_size(comparison.size_a), &format_size(comparison.size_b), &comparison.matching.to_string()],
+                ));
+                if comparison.entries.is_empty() {
+                    ui.strong(format!("{} {}", regular::CHECK_CIRCLE, t.get("compare_identical")));
+                    return;
+                }
+
+                // Счётчики служат и переключателями видов расхождений
+                ui.horizontal(|ui| {
+                    for (difference, shown) in Difference::all().into_iter().zip(&mut window.shown) {
+                        let (count, size) = comparison.totals(difference);
+                        let text = format!(
+                            "{} {}: {} ({})",
+                            difference.icon(),
+                            t.get(difference.translation_key()),
+                            count,
+                            format_size(size)
+                        );
+                        ui.checkbox(shown, text);
+                    }
+                });
+                ui.separator();
+
+                let entries: Vec<&Entry> = comparison
+                    .entries
+                    .iter()
+                    .filter(|e| Difference::all().iter().position(|d| *d == e.difference).is_some_and(|i| window.shown[i]))
+                    .collect();
+                let size_width = 90.0;
+                let path_width = (ui.available_width() - 2.0 * size_width - 48.0).max(200.0);
+                ui.horizontal(|ui| {
+                    ui.add_space(24.0);
+                    ui.add_sized([path_width, ROW_HEIGHT], egui::Label::new(egui::RichText::new(t.get("path")).strong()));
+                    ui.add_sized([size_width, ROW_HEIGHT], egui::Label::new(egui::RichText::new("A").strong()));
+                    ui.add_sized([size_width, ROW_HEIGHT], egui::Label::new(egui::RichText::new("B").strong()));
+                });
+                let side_size = |size: u64, present: bool| if present { format_size(size) } else { "—".to_string() };
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show_rows(ui, ROW_HEIGHT, entries.len(), |ui, range| {
+                        for entry in &entries[range] {
+                            ui.horizontal(|ui| {
+                                ui.label(entry.difference.icon()).on_hover_text(t.get(entry.difference.translation_key()));
+                                let icon = if entry.is_file { regular::FILE } else { regular::FOLDER };
+                                let response = ui.add_sized(
+                                    [path_width, ROW_HEIGHT],
+                                    egui::Label::new(format!("{} {}", icon, entry.relative.display()))
+                                        .truncate()
+                                        .sense(egui::Sense::click()),
+                                );
+                                let side = if entry.difference == Difference::OnlyInB { &comparison.b } else { &comparison.a };
+                                crate::clipboard::path_tooltip(response, &side.join(&entry.relative), &copy_label);
+                                ui.add_sized(
+                                    [size_width, ROW_HEIGHT],
+                                    egui::Label::new(side_size(entry.size_a, entry.difference != Difference::OnlyInB)),
+                                );
+                                ui.add_sized(
+                                    [size_width, ROW_HEIGHT],
+                                    egui::Label::new(side_size(entry.size_b, entry.difference != Difference::OnlyInA)),
+                                );
+                            });
+                        }
+                    });
+            });
+
+        if !open {
+            self.compare = None;
+        }
+    }
+}
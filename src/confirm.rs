@@ -0,0 +1,207 @@
+// Какие удаления требуют подтверждения: одиночное в корзину, пакетное, безвозвратное.
+// Для крупного безвозвратного пакета нужно ещё и ввести фразу вроде "DELETE 120 GB"
+
+use crate::CedarApp;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+const MB: u64 = 1024 * 1024;
+const GB: u64 = 1024 * MB;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfirmSettings {
+    /// Удаление одного элемента в корзину
+    pub trash: bool,
+    /// Безвозвратное удаление; порог малых элементов на него не действует
+    pub permanent: bool,
+    /// Пакетное удаление из корзины подготовки
+    pub batch: bool,
+    /// Пакет подтверждается, если в нём больше элементов...
+    pub batch_items: usize,
+    /// ...или он больше стольких гигабайт. 0 отключает порог; без обоих
+    /// порогов подтверждается любой пакет
+    pub batch_gb: u64,
+    /// Не спрашивать для удаления в корзину меньше стольких мегабайт; 0 - спрашивать всегда
+    pub skip_below_mb: u64,
+    /// Безвозвратный пакет больше стольких гигабайт подтверждается вводом фразы; 0 - не требовать
+    pub phrase_above_gb: u64,
+}
+
+impl Default for ConfirmSettings {
+    fn default() -> Self {
+        Self {
+            trash: true,
+            permanent: true,
+            batch: true,
+            batch_items: 0,
+            batch_gb: 0,
+            skip_below_mb: 0,
+            phrase_above_gb: 50,
+        }
+    }
+}
+
+impl ConfirmSettings {
+    /// Нужно ли подтверждение для удаления `count` элементов общим размером `size`
+    pub fn needs_confirmation(&self, permanent: bool, count: usize, size: u64) -> bool {
+        if permanent {
+            return self.permanent || self.needs_phrase(count, size);
+        }
+        if size < self.skip_below_mb * MB {
+            return false;
+        }
+        if count > 1 {
+            let many = (self.batch_items > 0).then_some(count > self.batch_items);
+            let large = (self.batch_gb > 0).then_some(size > self.batch_gb * GB);
+            self.batch
+                && match (many, large) {
+                    (None, None) => true,
+                    (many, large) => many.unwrap_or(false) || large.unwrap_or(false),
+                }
+        } else {
+            self.trash
+        }
+    }
+
+    /// Нужно ли вводить фразу для безвозвратного удаления пакета
+    pub fn needs_phrase(&self, count: usize, size: u64) -> bool {
+        self.phrase_above_gb > 0 && count > 1 && size > self.phrase_above_gb * GB
+    }
+}
+
+/// Фраза подтверждения; одинакова на всех языках, размер округлён до гигабайт
+pub fn phrase(size: u64) -> String {
+    format!("DELETE {} GB", (size as f64 / GB as f64).round())
+}
+
+impl CedarApp {
+    /// Раздел настроек подтверждений; возвращает true, если что-то изменилось
+    pub fn show_confirm_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        let settings = &mut self.config.confirm;
+        let mut changed = false;
+
+        ui.heading(self.translations.get("confirmations"));
+        ui.add_space(4.0);
+
+        changed |= ui.checkbox(&mut settings.trash, self.translations.get("confirm_trash")).changed();
+        changed |= ui.checkbox(&mut settings.permanent, self.translations.get("confirm_permanent")).changed();
+        changed |= ui.checkbox(&mut settings.batch, self.translations.get("confirm_batch")).changed();
+
+        ui.add_enabled_ui(settings.batch, |ui| {
+            ui.indent("confirm_batch", |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(self.translations.get("confirm_batch_items"));
+                    changed |= ui.add(egui::DragValue::new(&mut settings.batch_items).range(0..=100_000)).changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label(self.translations.get("confirm_batch_size"));
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut settings.batch_gb).range(0..=100_000).suffix(" GB"))
+                        .changed();
+                });
+            })
+            .response
+            .on_hover_text(self.translations.get("confirm_batch_hint"));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(self.translations.get("confirm_skip_below"));
+            changed |= ui
+                .add(egui::DragValue::new(&mut settings.skip_below_mb).range(0..=1_000_000).suffix(" MB"))
+                .changed();
+        })
+        .response
+        .on_hover_text(self.translations.get("confirm_skip_below_hint"));
+
+        ui.horizontal(|ui| {
+            ui.label(self.translations.get("confirm_phrase_above"));
+            changed |= ui
+                .add(egui::DragValue::new(&mut settings.phrase_above_gb).range(0..=100_000).suffix(" GB"))
+                .changed();
+        })
+        .response
+        .on_hover_text(self.translations.get("confirm_phrase_above_hint"));
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(batch_items: usize, batch_gb: u64) -> ConfirmSettings {
+        ConfirmSettings {
+            batch_items,
+            batch_gb,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn single_items_follow_their_switches() {
+        let mut confirm = ConfirmSettings::default();
+        assert!(confirm.needs_confirmation(false, 1, 10));
+        assert!(confirm.needs_confirmation(true, 1, 10));
+        confirm.trash = false;
+        confirm.permanent = false;
+        assert!(!confirm.needs_confirmation(false, 1, 10));
+        assert!(!confirm.needs_confirmation(true, 1, 10));
+    }
+
+    #[test]
+    fn large_permanent_batches_need_the_phrase_even_when_not_confirmed() {
+        let confirm = ConfirmSettings {
+            permanent: false,
+            phrase_above_gb: 1,
+            ..Default::default()
+        };
+        assert!(!confirm.needs_confirmation(true, 2, GB));
+        assert!(confirm.needs_confirmation(true, 2, GB + 1));
+        assert!(!confirm.needs_phrase(1, 10 * GB));
+    }
+
+    #[test]
+    fn small_trash_deletions_skip_confirmation() {
+        let confirm = ConfirmSettings {
+            skip_below_mb: 5,
+            ..Default::default()
+        };
+        assert!(!confirm.needs_confirmation(false, 1, 5 * MB - 1));
+        assert!(confirm.needs_confirmation(false, 1, 5 * MB));
+        assert!(!confirm.needs_confirmation(false, 3, MB));
+    }
+
+    #[test]
+    fn small_permanent_deletions_still_ask() {
+        let confirm = ConfirmSettings {
+            skip_below_mb: 5,
+            ..Default::default()
+        };
+        assert!(confirm.needs_confirmation(true, 1, MB));
+        assert!(confirm.needs_confirmation(true, 3, MB));
+    }
+
+    #[test]
+    fn batch_thresholds_are_independent() {
+        // Без порогов - любой пакет
+        assert!(settings(0, 0).needs_confirmation(false, 2, 1));
+        // Только число элементов
+        assert!(!settings(10, 0).needs_confirmation(false, 10, 100 * GB));
+        assert!(settings(10, 0).needs_confirmation(false, 11, 1));
+        // Только размер
+        assert!(!settings(0, 2).needs_confirmation(false, 1000, 2 * GB));
+        assert!(settings(0, 2).needs_confirmation(false, 2, 2 * GB + 1));
+        // Оба: хватает любого
+        assert!(!settings(10, 2).needs_confirmation(false, 10, 2 * GB));
+        assert!(settings(10, 2).needs_confirmation(false, 11, 1));
+        assert!(settings(10, 2).needs_confirmation(false, 2, 3 * GB));
+        // Выключенное подтверждение пакетов
+        let off = ConfirmSettings {
+            batch: false,
+            ..Default::default()
+        };
+        assert!(!off.needs_confirmation(false, 1000, 100 * GB));
+    }
+}
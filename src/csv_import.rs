@@ -0,0 +1,367 @@
+// Отчёты WinDirStat и WizTree в CSV: строка на файл или папку с полным путём,
+// размером и датой изменения. Столбцы находятся по заголовку, поэтому
+// подходят выгрузки разных версий; строки до заголовка (у WizTree - "Generated
+// by...") пропускаются. Размеры папок пересчитываются по файлам, как при сканировании
+
+use crate::{item_counts, largest_file, CedarApp, DirNode};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const PATH_COLUMNS: &[&str] = &["file name", "name", "path", "full path"];
+const SIZE_COLUMNS: &[&str] = &["size", "size logical", "logical size"];
+const ALLOCATED_COLUMNS: &[&str] = &["allocated", "size physical", "physical size", "size on disk"];
+const MODIFIED_COLUMNS: &[&str] = &["modified", "last change", "date modified", "last modified"];
+const ATTRIBUTES_COLUMN: &str = "attributes";
+
+/// Форматы дат, которые встречаются в выгрузках
+const DATE_FORMATS: &[&str] = &[
+    "%Y/%m/%d %H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%d.%m.%Y %H:%M:%S",
+    "%d.%m.%Y %H:%M",
+    "%m/%d/%Y %H:%M:%S",
+    "%m/%d/%Y %I:%M:%S %p",
+];
+
+/// FILE_ATTRIBUTE_DIRECTORY в числовом поле атрибутов WizTree
+const DIRECTORY_ATTRIBUTE: u32 = 0x10;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Разбивает строку CSV на поля; кавычки и удвоенные кавычки внутри них поддерживаются
+fn split_line(line: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Разбивает текст на записи CSV: перевод строки внутри кавычек остаётся в поле
+fn split_records(text: &str) -> Vec<&str> {
+    let mut records = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            // Удвоенная кавычка переключает дважды и ничего не меняет
+            '"' => quoted = !quoted,
+            '\n' if !quoted => {
+                records.push(text[start..i].trim_end_matches('\r'));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < text.len() {
+        records.push(text[start..].trim_end_matches('\r'));
+    }
+    records
+}
+
+/// Строка отчёта
+struct Row {
+    path: PathBuf,
+    is_dir: bool,
+    size: u64,
+    allocated: Option<u64>,
+    modified: Option<SystemTime>,
+}
+
+/// Номера нужных столбцов
+struct Columns {
+    separator: char,
+    path: usize,
+    size: usize,
+    allocated: Option<usize>,
+    modified: Option<usize>,
+    attributes: Option<usize>,
+}
+
+impl Columns {
+    /// Узнаёт заголовок; None - это ещё не он
+    fn from_header(line: &str) -> Option<Self> {
+        let separator = [',', ';', '\t'].into_iter().max_by_key(|&s| line.matches(s).count())?;
+        let names: Vec<String> = split_line(line, separator).iter().map(|n| n.trim().to_lowercase()).collect();
+        let find = |candidates: &[&str]| names.iter().position(|n| candidates.contains(&n.as_str()));
+        Some(Self {
+            separator,
+            path: find(PATH_COLUMNS)?,
+            size: find(SIZE_COLUMNS)?,
+            allocated: find(ALLOCATED_COLUMNS),
+            modified: find(MODIFIED_COLUMNS),
+            attributes: find(&[ATTRIBUTES_COLUMN]),
+        })
+    }
+
+    fn parse(&self, line: &str) -> Option<Row> {
+        let fields = split_line(line, self.separator);
+        let raw = fields.get(self.path)?.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        // Папки WizTree заканчиваются разделителем; атрибуты бывают числом или буквами
+        let trailing = raw.ends_with(['\\', '/']);
+        let attributes = self.attributes.and_then(|i| fields.get(i)).map(|a| a.trim());
+        let directory_attribute = attributes.is_some_and(|a| match a.parse::<u32>() {
+            Ok(bits) => bits & DIRECTORY_ATTRIBUTE != 0,
+            Err(_) => a.contains('D'),
+        });
+        let number = |i: usize| fields.get(i).and_then(|f| parse_number(f));
+        Some(Row {
+            path: to_path(raw),
+            is_dir: trailing || directory_attribute,
+            size: number(self.size).unwrap_or(0),
+            allocated: self.allocated.and_then(number),
+            modified: self.modified.and_then(|i| fields.get(i)).and_then(|f| parse_date(f)),
+        })
+    }
+}
+
+/// Число с разделителями разрядов или без них
+fn parse_number(text: &str) -> Option<u64> {
+    let digits: String = text.chars().filter(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+fn parse_date(text: &str) -> Option<SystemTime> {
+    let text = text.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .and_then(|date| Local.from_local_datetime(&date).earliest())
+        .map(SystemTime::from)
+}
+
+/// Путь из отчёта; обратные слэши Windows в других системах становятся прямыми
+fn to_path(raw: &str) -> PathBuf {
+    let path = if cfg!(windows) { raw.to_string() } else { raw.replace('\\', "/") };
+    let trimmed = path.trim_end_matches(['\\', '/']);
+    // Корень диска ("C:\", "/") сохраняет разделитель
+    if trimmed.is_empty() || trimmed.ends_with(':') {
+        PathBuf::from(&path[..(trimmed.len() + 1).min(path.len())])
+    } else {
+        PathBuf::from(trimmed)
+    }
+}
+
+/// Собирает дерево из строк; корень - общая папка всех строк
+fn build_tree(rows: Vec<Row>) -> io::Result<DirNode> {
+    let root = rows
+        .iter()
+        .filter(|row| row.is_dir)
+        .map(|row| row.path.as_path())
+        .min_by_key(|path| path.components().count())
+        .ok_or_else(|| invalid("no folders in the report"))?
+        .to_path_buf();
+
+    // Папки, которых нет отдельной строкой, восстанавливаются по путям файлов
+    let mut dirs: HashSet<PathBuf> = HashSet::from([root.clone()]);
+    let mut contents: HashMap<PathBuf, Vec<DirNode>> = HashMap::new();
+    let mut dir_modified: HashMap<PathBuf, SystemTime> = HashMap::new();
+    for row in rows {
+        if row.path == root || !row.path.starts_with(&root) {
+            continue;
+        }
+        let mut ancestor = row.path.parent();
+        while let Some(dir) = ancestor.filter(|dir| dir.starts_with(&root)) {
+            if !dirs.insert(dir.to_path_buf()) {
+                break;
+            }
+            ancestor = dir.parent();
+        }
+        if row.is_dir {
+            dirs.insert(row.path.clone());
+            if let Some(modified) = row.modified {
+                dir_modified.insert(row.path, modified);
+            }
+            continue;
+        }
+        let name = row.path.file_name().map(OsString::from).unwrap_or_default();
+        let parent = row.path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut node = DirNode::new(row.path, name, row.size, true);
+        node.allocated = row.allocated.unwrap_or(row.size);
+        node.modified = row.modified;
+        contents.entry(parent).or_default().push(node);
+    }
+
+    // Папки собираются от самых глубоких к корню
+    let mut dirs: Vec<PathBuf> = dirs.into_iter().collect();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+    for dir in dirs {
+        let mut children = contents.remove(&dir).unwrap_or_default();
+        children.sort_unstable_by_key(|c| std::cmp::Reverse(c.size));
+        let name = dir.file_name().map(OsString::from).unwrap_or_else(|| dir.as_os_str().to_os_string());
+        let mut node = DirNode::new(dir.clone(), name, children.iter().map(|c| c.size).sum(), false);
+        node.allocated = children.iter().map(|c| c.allocated).sum();
+        node.modified = children.iter().filter_map(|c| c.modified).max().or(dir_modified.get(&dir).copied());
+        node.largest_file = largest_file(&children);
+        (node.files, node.folders) = item_counts(&children);
+        node.children = children;
+        if dir == root {
+            node.is_expanded = true;
+            return Ok(node);
+        }
+        let parent = dir.parent().map(Path::to_path_buf).unwrap_or_default();
+        contents.entry(parent).or_default().push(node);
+    }
+    Err(invalid("no folders in the report"))
+}
+
+/// Дерево из текста отчёта
+fn parse_report(text: &str) -> io::Result<DirNode> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let records = split_records(text);
+    let mut records = records.into_iter();
+    let columns = records
+        .by_ref()
+        .take(20)
+        .find_map(Columns::from_header)
+        .ok_or_else(|| invalid("no header with name and size columns"))?;
+    let rows: Vec<Row> = records.filter_map(|line| columns.parse(line)).collect();
+    build_tree(rows)
+}
+
+/// Читает отчёт WinDirStat или WizTree
+pub fn import(path: &Path) -> io::Result<DirNode> {
+    let bytes = std::fs::read(path)?;
+    parse_report(&String::from_utf8_lossy(&bytes))
+}
+
+impl CedarApp {
+    /// Открывает отчёт WinDirStat или WizTree вместо текущего дерева
+    pub fn import_csv_report(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv", "txt"]).pick_file() else {
+            return;
+        };
+        let scanned_at = std::fs::metadata(&path).and_then(|m| m.modified()).unwrap_or_else(|_| SystemTime::now());
+        match import(&path) {
+            Ok(root) => {
+                let text = self.translations.get_fmt(
+                    "csv_imported",
+                    &[&root.path.display().to_string(), &self.translations.format_count(root.file_count())],
+                );
+                self.open_tree(root, scanned_at);
+                self.set_status(text);
+            }
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("csv_import_failed", &[&e.to_string()]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::child;
+
+    #[test]
+    fn quoted_fields_keep_separators_quotes_and_newlines() {
+        assert_eq!(split_line(r#"a,"b,c","say ""hi""",,d"#, ','), ["a", "b,c", r#"say "hi""#, "", "d"]);
+        assert_eq!(split_line("x;\"1;2\";y", ';'), ["x", "1;2", "y"]);
+        assert_eq!(split_records("a,b\r\n\"multi\nline\",2\n\"\"\"q\"\"\",3"), ["a,b", "\"multi\nline\",2", "\"\"\"q\"\"\",3"]);
+    }
+
+    #[test]
+    fn windirstat_report() {
+        let root = parse_report(
+            "\u{feff}Name,Size,Last Change,Attributes\r\n\
+             /data/,1300,2024-01-02 03:04:05,D\r\n\
+             /data/a.txt,300,2024-01-01 00:00:00,A\r\n\
+             \"/data/with, comma.txt\",\"1,000\",2023-06-01 12:00:00,A\r\n\
+             \"/data/line\nbreak\",0,,A\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(root.path, PathBuf::from("/data"));
+        assert!(root.is_expanded);
+        assert_eq!(root.size, 1300);
+        assert_eq!((root.files, root.folders), (3, 0));
+        assert_eq!(child(&root, "with, comma.txt").size, 1000);
+        assert_eq!(child(&root, "line\nbreak").size, 0);
+        assert!(child(&root, "a.txt").modified.is_some());
+    }
+
+    #[test]
+    fn wiztree_report_restores_missing_folders() {
+        let root = parse_report(
+            "Generated by WizTree 4.0 1/2/2024 (You can turn off this line in the options)\n\
+             File Name;Size;Allocated;Modified;Attributes;Files;Folders\n\
+             /srv/;0;0;2024/01/02 03:04:05;16;2;1\n\
+             /srv/deep/er/x.bin;4096;8192;2024/01/02 03:04:05;32\n\
+             /srv/y.bin;10;4096;2024/01/02 03:04:05;32\n",
+        )
+        .unwrap();
+
+        assert_eq!(root.path, PathBuf::from("/srv"));
+        assert_eq!(root.size, 4106);
+        assert_eq!(root.allocated, 12288);
+        let deeper = child(child(&root, "deep"), "er");
+        assert_eq!(deeper.path, PathBuf::from("/srv/deep/er"));
+        assert_eq!(deeper.size, 4096);
+        assert_eq!((root.files, root.folders), (2, 2));
+    }
+
+    #[test]
+    fn missing_columns_and_fields() {
+        // Без столбца размера или пути заголовок не находится
+        assert!(parse_report("Name,Modified\n/a/,2024-01-01 00:00:00\n").is_err());
+        assert!(parse_report("Size,Modified\n10,2024-01-01 00:00:00\n").is_err());
+        assert!(parse_report("").is_err());
+        // Только файлы - корень не определить
+        assert!(parse_report("Name,Size\n/a/b.txt,1\n").is_err());
+
+        // Строки короче заголовка: без пути пропускаются, без размера - нулевые
+        let root = parse_report("Name,Size,Allocated\n/a/\n/a/short.txt\n,5\n/a/full.txt,7,4096\n").unwrap();
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(child(&root, "short.txt").size, 0);
+        assert_eq!(child(&root, "short.txt").allocated, 0);
+        assert_eq!(child(&root, "full.txt").allocated, 4096);
+    }
+
+    #[test]
+    fn bad_sizes_count_as_zero() {
+        let root = parse_report(
+            "Name,Size\n/a/\n/a/text.txt,unknown\n/a/huge.txt,999999999999999999999999\n/a/spaced.txt,1 234\n",
+        )
+        .unwrap();
+        assert_eq!(child(&root, "text.txt").size, 0);
+        assert_eq!(child(&root, "huge.txt").size, 0);
+        assert_eq!(child(&root, "spaced.txt").size, 1234);
+        assert_eq!(root.size, 1234);
+    }
+
+    #[test]
+    fn report_paths() {
+        assert_eq!(to_path("/"), PathBuf::from("/"));
+        assert_eq!(to_path("/data/"), PathBuf::from("/data"));
+        if cfg!(windows) {
+            assert_eq!(to_path("C:\\"), PathBuf::from("C:\\"));
+            assert_eq!(to_path("C:\\Users\\"), PathBuf::from("C:\\Users"));
+        } else {
+            assert_eq!(to_path("C:\\"), PathBuf::from("C:/"));
+            assert_eq!(to_path("C:\\Users\\"), PathBuf::from("C:/Users"));
+        }
+    }
+}
@@ -0,0 +1,260 @@
+// Выбор стратегии сканирования для тома. sysinfo часто не знает тип внешних и
+// виртуальных дисков ("Unknown"), поэтому такие тома проверяются замером задержки
+// чтения, а пользователь может задать стратегию для диска вручную.
+// Скорость завершённых сканирований запоминается по тому, и при следующих
+// сканированиях выбирается самое быстрое число потоков
+
+use crate::CedarApp;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Сколько файлов читается при замере
+const PROBE_FILES: usize = 12;
+/// Сколько папок просматривается в поисках файлов для замера
+const PROBE_DIRS: usize = 200;
+/// Файлы меньше этого обычно уже в кэше ОС и ничего не говорят о диске
+const PROBE_MIN_FILE: u64 = 256 * 1024;
+const PROBE_READ: usize = 4096;
+/// Медианная задержка, выше которой диск считается механическим
+const SLOW_LATENCY: Duration = Duration::from_millis(2);
+/// Сканирования меньше этого числа записей слишком коротки для сравнения скорости
+const MIN_PROFILE_ENTRIES: usize = 10_000;
+
+/// Стратегия сканирования, заданная для диска
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ScanStrategy {
+    /// По типу диска, а если он неизвестен - по замеру
+    #[default]
+    Auto,
+    Parallel,
+    Sequential,
+}
+
+impl ScanStrategy {
+    pub fn all() -> [ScanStrategy; 3] {
+        [ScanStrategy::Auto, ScanStrategy::Parallel, ScanStrategy::Sequential]
+    }
+
+    pub fn translation_key(self) -> &'static str {
+        match self {
+            ScanStrategy::Auto => "strategy_auto",
+            ScanStrategy::Parallel => "strategy_parallel",
+            ScanStrategy::Sequential => "strategy_sequential",
+        }
+    }
+}
+
+/// Откуда взялось число потоков сканирования
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Tuning {
+    /// По типу диска, замеру или ручной настройке
+    #[default]
+    Detected,
+    /// Проверяется вариант, для которого ещё нет замера скорости
+    Trial,
+    /// Самый быстрый вариант по прошлым сканированиям тома
+    Tuned,
+}
+
+/// Скорость сканирования тома при разном числе потоков
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanProfile {
+    /// Записей в секунду по числу потоков (1 - однопоточный обход)
+    pub speeds: BTreeMap<usize, f64>,
+}
+
+impl ScanProfile {
+    fn best(&self) -> Option<usize> {
+        self.speeds.iter().max_by(|a, b| a.1.total_cmp(b.1)).map(|(&threads, _)| threads)
+    }
+
+    /// Учитывает завершённое сканирование. Скорость усредняется с прошлыми:
+    /// повторные сканирования идут по прогретому кэшу ОС и иначе всегда выигрывали бы
+    pub fn record(&mut self, threads: usize, entries: usize, elapsed: Duration) {
+        if entries < MIN_PROFILE_ENTRIES || elapsed.is_zero() {
+            return;
+        }
+        let speed = entries as f64 / elapsed.as_secs_f64();
+        self.speeds
+            .entry(threads)
+            .and_modify(|average| *average = (*average + speed) / 2.0)
+            .or_insert(speed);
+    }
+}
+
+/// Варианты числа потоков: однопоточно, половина ядер и все ядра
+fn thread_candidates() -> Vec<usize> {
+    let all = rayon::current_num_threads();
+    let mut candidates = vec![1, (all / 2).max(2), all];
+    candidates.retain(|&threads| threads <= all);
+    candidates.dedup();
+    candidates
+}
+
+/// Число потоков для сканирования тома. Ручная стратегия важнее профиля;
+/// в профиле сначала по разу проверяются все варианты, потом берётся самый быстрый
+pub fn choose_threads(
+    strategy: ScanStrategy,
+    profile: Option<&ScanProfile>,
+    detected_parallel: impl FnOnce() -> bool,
+) -> (usize, Tuning) {
+    let all = rayon::current_num_threads();
+    match strategy {
+        ScanStrategy::Parallel => return (all, Tuning::Detected),
+        ScanStrategy::Sequential => return (1, Tuning::Detected),
+        ScanStrategy::Auto => {}
+    }
+    if let Some(profile) = profile.filter(|p| !p.speeds.is_empty()) {
+        if let Some(&untried) = thread_candidates().iter().find(|t| !profile.speeds.contains_key(t)) {
+            return (untried, Tuning::Trial);
+        }
+        if let Some(best) = profile.best() {
+            return (best.min(all), Tuning::Tuned);
+        }
+    }
+    (if detected_parallel() { all } else { 1 }, Tuning::Detected)
+}
+
+/// Замеры задержки по томам за время работы программы
+#[derive(Clone, Default)]
+pub struct ProbeCache(Arc<Mutex<HashMap<PathBuf, Option<Duration>>>>);
+
+impl ProbeCache {
+    pub fn get(&self, volume: &Path) -> Option<Option<Duration>> {
+        self.0.lock().unwrap().get(volume).copied()
+    }
+
+    /// Задержка чтения тома; замер делается один раз, внутри `path`
+    pub fn latency(&self, volume: &Path, path: &Path) -> Option<Duration> {
+        if let Some(latency) = self.get(volume) {
+            return latency;
+        }
+        let latency = probe_latency(path);
+        self.0.lock().unwrap().insert(volume.to_path_buf(), latency);
+        latency
+    }
+}
+
+/// Файлы для замера: обход в ширину, пока не наберётся достаточно крупных
+fn probe_candidates(root: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut queue = std::collections::VecDeque::from([root.to_path_buf()]);
+    let mut visited = 0;
+    while let Some(dir) = queue.pop_front() {
+        visited += 1;
+        if visited > PROBE_DIRS || files.len() >= PROBE_FILES {
+            break;
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                queue.push_back(entry.path());
+            } else if file_type.is_file() {
+                if let Ok(metadata) = entry.metadata() {
+                    if metadata.len() >= PROBE_MIN_FILE && files.len() < PROBE_FILES {
+                        files.push((entry.path(), metadata.len()));
+                    }
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Медианная задержка чтения небольшого блока из середины файлов под `root`.
+/// None, если подходящих файлов нет
+pub fn probe_latency(root: &Path) -> Option<Duration> {
+    let mut samples: Vec<Duration> = probe_candidates(root)
+        .into_iter()
+        .filter_map(|(path, len)| {
+            let mut buffer = [0u8; PROBE_READ];
+            let start = Instant::now();
+            let mut file = std::fs::File::open(&path).ok()?;
+            // Смещение выровнено по блоку, чтобы чтение не задело два блока
+            file.seek(SeekFrom::Start(len / 2 / PROBE_READ as u64 * PROBE_READ as u64)).ok()?;
+            file.read_exact(&mut buffer).ok()?;
+            Some(start.elapsed())
+        })
+        .collect();
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort();
+    Some(samples[samples.len() / 2])
+}
+
+/// Сканировать ли параллельно. `kind` - тип диска по sysinfo
+pub fn use_parallel(
+    strategy: ScanStrategy,
+    kind: sysinfo::DiskKind,
+    latency: impl FnOnce() -> Option<Duration>,
+) -> bool {
+    match (strategy, kind) {
+        (ScanStrategy::Parallel, _) => true,
+        (ScanStrategy::Sequential, _) => false,
+        (ScanStrategy::Auto, sysinfo::DiskKind::SSD) => true,
+        (ScanStrategy::Auto, sysinfo::DiskKind::HDD) => false,
+        // Без файлов для замера считаем диск быстрым: сканировать там почти нечего
+        (ScanStrategy::Auto, _) => latency().is_none_or(|latency| latency < SLOW_LATENCY),
+    }
+}
+
+impl CedarApp {
+    /// Раздел настроек: стратегия сканирования для каждого диска;
+    /// возвращает true, если что-то изменилось
+    pub fn show_disk_strategies(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+
+        ui.heading(self.translations.get("disk_strategies"));
+        ui.label(
+            egui::RichText::new(self.translations.get("disk_strategies_hint"))
+                .small()
+                .color(ui.visuals().weak_text_color()),
+        );
+        ui.add_space(4.0);
+
+        egui::Grid::new("disk_strategies").num_columns(3).striped(true).show(ui, |ui| {
+            for drive in &self.available_drives {
+                ui.label(&drive.path).on_hover_text(&drive.name);
+
+                let mut detected = drive.kind.clone();
+                if let Some(latency) = self.probe_cache.get(Path::new(&drive.path)).flatten() {
+                    detected = format!("{}, {:.1} ms", detected, latency.as_secs_f64() * 1000.0);
+                }
+                ui.label(egui::RichText::new(detected).color(ui.visuals().weak_text_color()));
+
+                let current = self.config.disk_strategies.get(&drive.path).copied().unwrap_or_default();
+                let mut selected = current;
+                egui::ComboBox::from_id_salt(("disk_strategy", &drive.path))
+                    .selected_text(self.translations.get(current.translation_key()))
+                    .show_ui(ui, |ui| {
+                        for strategy in ScanStrategy::all() {
+                            ui.selectable_value(&mut selected, strategy, self.translations.get(strategy.translation_key()));
+                        }
+                    });
+                if selected != current {
+                    if selected == ScanStrategy::Auto {
+                        self.config.disk_strategies.remove(&drive.path);
+                    } else {
+                        self.config.disk_strategies.insert(drive.path.clone(), selected);
+                    }
+                    changed = true;
+                }
+                ui.end_row();
+            }
+        });
+
+        changed
+    }
+}
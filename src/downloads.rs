@@ -0,0 +1,284 @@
+// Отчёт о папке "Загрузки": старые установщики, образы дисков, архивы, которые
+// уже распакованы рядом, и просто крупные файлы. С неё обычно и начинают
+// освобождать место, а в общем дереве она теряется среди остальных папок
+
+use crate::{format_size, pattern, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Файлы других типов попадают в отчёт, начиная с этого размера
+const LARGE_FILE: u64 = 100 * 1024 * 1024;
+
+/// Установщики и образы старше стольких дней выбраны для удаления сразу
+const STALE_DAYS: u64 = 30;
+
+const INSTALLERS: &[&str] = &["exe", "msi", "msix", "msixbundle", "appx", "appxbundle", "dmg", "pkg", "deb", "rpm", "appimage"];
+const DISK_IMAGES: &[&str] = &["iso", "img"];
+/// Расширения архивов; составные идут первыми, чтобы имя распакованной папки
+/// у "name.tar.gz" получалось "name", а не "name.tar"
+const ARCHIVES: &[&str] = &[".tar.gz", ".tar.xz", ".tar.bz2", ".tgz", ".zip", ".rar", ".7z", ".tar", ".gz", ".xz", ".bz2"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DownloadKind {
+    Installer,
+    DiskImage,
+    Archive,
+    Large,
+}
+
+impl DownloadKind {
+    fn translation_key(self) -> &'static str {
+        match self {
+            DownloadKind::Installer => "download_installer",
+            DownloadKind::DiskImage => "download_disk_image",
+            DownloadKind::Archive => "download_archive",
+            DownloadKind::Large => "download_large",
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            DownloadKind::Installer => regular::PACKAGE,
+            DownloadKind::DiskImage => regular::DISC,
+            DownloadKind::Archive => regular::FILE_ZIP,
+            DownloadKind::Large => regular::FILE,
+        }
+    }
+}
+
+/// Строка отчёта
+pub struct DownloadEntry {
+    pub path: PathBuf,
+    pub kind: DownloadKind,
+    pub size: u64,
+    pub age_days: Option<u64>,
+    /// Папка рядом с тем же именем, что у архива
+    pub extracted: Option<PathBuf>,
+    pub selected: bool,
+}
+
+pub enum DownloadsReport {
+    /// Система не сообщает, где папка загрузок
+    NoFolder,
+    /// Папки загрузок нет в текущем дереве
+    NotScanned(PathBuf),
+    Ready { folder: PathBuf, entries: Vec<DownloadEntry> },
+}
+
+/// Имя архива без расширения архива; None - это не архив
+fn archive_stem(name: &str) -> Option<&str> {
+    ARCHIVES.iter().find_map(|ext| {
+        let split = name.len().checked_sub(ext.len()).filter(|&split| split > 0 && name.is_char_boundary(split))?;
+        name[split..].eq_ignore_ascii_case(ext).then(|| &name[..split])
+    })
+}
+
+fn classify(node: &DirNode) -> Option<DownloadKind> {
+    let name = node.display_name();
+    let extension = Path::new(name.as_ref()).extension().map(|e| e.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some(ext) if INSTALLERS.contains(&ext) => Some(DownloadKind::Installer),
+        Some(ext) if DISK_IMAGES.contains(&ext) => Some(DownloadKind::DiskImage),
+        _ if archive_stem(&name).is_some() => Some(DownloadKind::Archive),
+        _ if node.size >= LARGE_FILE => Some(DownloadKind::Large),
+        _ => None,
+    }
+}
+
+/// Собирает файлы папки загрузок из дерева, крупнейшие первыми. Подпапки не
+/// просматриваются: чаще всего это распакованные архивы, и их .exe - не установщики
+pub fn collect_downloads(root: &DirNode, now: SystemTime) -> DownloadsReport {
+    let Some(folder) = dirs::download_dir() else {
+        return DownloadsReport::NoFolder;
+    };
+    let Some(node) = root.find(&folder) else {
+        return DownloadsReport::NotScanned(folder);
+    };
+
+    let mut entries = Vec::new();
+    for child in node.children.iter().filter(|c| c.is_file && c.link_target.is_none()) {
+        let Some(kind) = classify(child) else {
+            continue;
+        };
+        let name = child.display_name();
+        let extracted = archive_stem(&name).and_then(|stem| {
+            let stem = pattern::name_key(stem);
+            node.children
+                .iter()
+                .find(|sibling| !sibling.is_file && pattern::name_key(&sibling.display_name()) == stem)
+                .map(|sibling| sibling.path.clone())
+        });
+        let age_days = child
+            .modified
+            .map(|modified| now.duration_since(modified).unwrap_or_default().as_secs() / SECONDS_PER_DAY);
+        let stale = age_days.is_some_and(|days| days >= STALE_DAYS)
+            && matches!(kind, DownloadKind::Installer | DownloadKind::DiskImage);
+        entries.push(DownloadEntry {
+            path: child.path.clone(),
+            kind,
+            size: child.size,
+            age_days,
+            selected: extracted.is_some() || stale,
+            extracted,
+        });
+    }
+    entries.sort_unstable_by_key(|e| std::cmp::Reverse(e.size));
+    DownloadsReport::Ready { folder, entries }
+}
+
+impl CedarApp {
+    /// Окно отчёта о папке загрузок
+    pub fn show_downloads_report_window(&mut self, ctx: &egui::Context) {
+        if !self.show_downloads_report {
+            return;
+        }
+
+        if self.downloads_report.is_none() {
+            self.downloads_report = self.root_node.as_ref().map(|root| collect_downloads(root, SystemTime::now()));
+        }
+
+        let mut open = self.show_downloads_report;
+        let mut refresh = false;
+        let mut clicked_path = None;
+        let mut scan = None;
+        let mut add_to_basket = false;
+        let t = &self.translations;
+
+        egui::Window::new(format!("{} {}", regular::DOWNLOAD_SIMPLE, t.get("downloads_report")))
+            .open(&mut open)
+            .default_size([820.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(t.get("downloads_report_hint")).color(ui.visuals().weak_text_color()));
+                ui.add_space(5.0);
+
+                let entries = match &mut self.downloads_report {
+                    None => return,
+                    Some(DownloadsReport::NoFolder) => {
+                        ui.label(t.get("downloads_no_folder"));
+                        return;
+                    }
+                    Some(DownloadsReport::NotScanned(folder)) => {
+                        ui.label(t.get_fmt("downloads_not_scanned", &[&folder.display().to_string()]));
+                        if ui.button(format!("{} {}", regular::MAGNIFYING_GLASS, t.get("downloads_scan"))).clicked() {
+                            scan = Some(folder.display().to_string());
+                        }
+                        return;
+                    }
+                    Some(DownloadsReport::Ready { folder, entries }) => {
+                        ui.label(egui::RichText::new(folder.display().to_string()).small());
+                        entries
+                    }
+                };
+
+                let total: u64 = entries.iter().map(|e| e.size).sum();
+                let selected: Vec<&DownloadEntry> = entries.iter().filter(|e| e.selected).collect();
+                let selected_size: u64 = selected.iter().map(|e| e.size).sum();
+                ui.horizontal(|ui| {
+                    ui.strong(t.get_fmt(
+                        "downloads_summary",
+                        &[
+                            &t.format_count(entries.len() as u64),
+                            &format_size(total),
+                            &t.format_count(selected.len() as u64),
+                            &format_size(selected_size),
+                        ],
+                    ));
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, t.get("refresh"))).clicked() {
+                            refresh = true;
+                        }
+                        ui.add_enabled_ui(!selected.is_empty(), |ui| {
+                            if ui.button(format!("{} {}", regular::BASKET, t.get("add_selected_to_basket"))).clicked() {
+                                add_to_basket = true;
+                            }
+                        });
+                    });
+                });
+                ui.separator();
+
+                if entries.is_empty() {
+                    ui.label(egui::RichText::new(t.get("no_matches")).color(ui.visuals().weak_text_color()));
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("downloads_report_grid")
+                            .striped(true)
+                            .num_columns(5)
+                            .show(ui, |ui| {
+                                ui.strong(t.get("file"));
+                                ui.strong(t.get("type"));
+                                ui.strong(t.get("size"));
+                                ui.strong(t.get("age"));
+                                ui.label("");
+                                ui.end_row();
+
+                                for entry in entries.iter_mut() {
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut entry.selected, "");
+                                        let response = ui.selectable_label(
+                                            self.selected_path.as_ref() == Some(&entry.path),
+                                            entry.path.file_name().unwrap_or_default().to_string_lossy(),
+                                        );
+                                        if response.on_hover_text(entry.path.display().to_string()).clicked() {
+                                            clicked_path = Some(entry.path.clone());
+                                        }
+                                    });
+                                    ui.label(format!("{} {}", entry.kind.icon(), t.get(entry.kind.translation_key())));
+                                    ui.strong(format_size(entry.size));
+                                    ui.label(match entry.age_days {
+                                        Some(days) => format!("{} {}", t.format_count(days), t.get("days")),
+                                        None => "—".to_string(),
+                                    });
+                                    match &entry.extracted {
+                                        Some(folder) => {
+                                            ui.label(
+                                                egui::RichText::new(t.get("download_extracted"))
+                                                    .small()
+                                                    .color(ui.visuals().weak_text_color()),
+                                            )
+                                            .on_hover_text(folder.display().to_string());
+                                        }
+                                        None => {
+                                            ui.label("");
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.search.scroll_to = Some(path.clone());
+            self.selected_path = Some(path);
+        }
+        if let Some(path) = scan {
+            self.scan_path = path.clone();
+            self.start_scan(path);
+        }
+        if add_to_basket {
+            if let Some(DownloadsReport::Ready { entries, .. }) = &self.downloads_report {
+                for entry in entries.iter().filter(|e| e.selected) {
+                    self.basket.add(entry.path.clone());
+                }
+            }
+            self.downloads_report = None;
+            self.show_basket = true;
+        }
+        if refresh {
+            self.downloads_report = None;
+        }
+        self.show_downloads_report = open;
+    }
+}
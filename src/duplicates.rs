@@ -0,0 +1,555 @@
+// Поиск дубликатов в папке: одинаковые по содержимому файлы или визуально
+// одинаковые фотографии (перцептивный хеш), в том числе в разном разрешении
+// и с разным сжатием
+
+use crate::hash_cache::{self, HashCache};
+use crate::{a11y, format_size, throttle, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use rayon::prelude::*;
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Сколько байт с начала файла сравнивается до полного хеширования
+pub const PARTIAL_HASH_SIZE: u64 = 64 * 1024;
+/// Фотографии с расстоянием Хэмминга между хешами не больше этого считаются одинаковыми
+const MAX_IMAGE_DISTANCE: u32 = 5;
+const THUMBNAIL_SIZE: u32 = 64;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DuplicateMode {
+    /// Побайтно одинаковые файлы
+    Exact,
+    /// Визуально одинаковые фотографии
+    Images,
+}
+
+pub struct DuplicateFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub resolution: Option<(u32, u32)>,
+}
+
+/// Группа дубликатов; первый файл - тот, что стоит оставить
+pub struct DuplicateGroup {
+    pub files: Vec<DuplicateFile>,
+}
+
+impl DuplicateGroup {
+    /// Сколько освободится, если оставить только первый файл
+    pub fn reclaimable(&self) -> u64 {
+        self.files.iter().skip(1).map(|f| f.size).sum()
+    }
+}
+
+struct SearchResult {
+    groups: Vec<DuplicateGroup>,
+    thumbnails: Vec<(PathBuf, egui::ColorImage)>,
+}
+
+/// Поиск, идущий в фоновом потоке
+struct Search {
+    cancel: Arc<AtomicBool>,
+    done: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+    result: Arc<Mutex<Option<SearchResult>>>,
+}
+
+impl Drop for Search {
+    // Закрытие окна или новый поиск останавливают старый
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Счётчики прогресса фонового хеширования, общие с окном или панелью
+#[derive(Clone)]
+pub struct Progress {
+    pub cancel: Arc<AtomicBool>,
+    pub done: Arc<AtomicUsize>,
+    pub total: Arc<AtomicUsize>,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self {
+            cancel: Arc::new(AtomicBool::new(false)),
+            done: Arc::new(AtomicUsize::new(0)),
+            total: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn start_phase(&self, total: usize) {
+        self.done.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn step(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+pub struct DuplicateFinder {
+    pub root: PathBuf,
+    pub mode: DuplicateMode,
+    /// Все файлы папки из дерева сканирования
+    files: Vec<(PathBuf, u64)>,
+    search: Option<Search>,
+    pub groups: Option<Vec<DuplicateGroup>>,
+    thumbnails: HashMap<PathBuf, egui::TextureHandle>,
+}
+
+impl DuplicateFinder {
+    pub fn new(node: &DirNode) -> Self {
+        fn walk(node: &DirNode, files: &mut Vec<(PathBuf, u64)>) {
+            for child in &node.children {
+                if child.is_file {
+                    files.push((child.path.clone(), child.size));
+                } else {
+                    walk(child, files);
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        walk(node, &mut files);
+        Self {
+            root: node.path.clone(),
+            mode: DuplicateMode::Exact,
+            files,
+            search: None,
+            groups: None,
+            thumbnails: HashMap::new(),
+        }
+    }
+
+    fn start(&mut self) {
+        let progress = Progress::new();
+        let result = Arc::new(Mutex::new(None));
+
+        let root = self.root.clone();
+        let files = self.files.clone();
+        let mode = self.mode;
+        let thread_progress = progress.clone();
+        let thread_result = result.clone();
+        thread::spawn(move || {
+            let cache = HashCache::load();
+            let present: HashSet<&Path> = files.iter().map(|(path, _)| path.as_path()).collect();
+            cache.forget_missing(&root, &present);
+            let found = match mode {
+                DuplicateMode::Exact => find_exact(files, &cache, &thread_progress),
+                DuplicateMode::Images => find_images(files, &cache, &thread_progress),
+            };
+            // Уже посчитанное пригодится и после отмены
+            cache.save();
+            if let Some(found) = found {
+                *thread_result.lock().unwrap() = Some(found);
+            }
+        });
+
+        self.groups = None;
+        self.thumbnails.clear();
+        self.search = Some(Search {
+            cancel: progress.cancel,
+            done: progress.done,
+            total: progress.total,
+            result,
+        });
+    }
+
+    /// Забирает результат фонового поиска; миниатюры превращаются в текстуры
+    fn poll(&mut self, ctx: &egui::Context) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let Some(found) = search.result.try_lock().ok().and_then(|mut r| r.take()) else {
+            return;
+        };
+
+        for (path, image) in found.thumbnails {
+            let name = format!("duplicate_thumbnail{}", path.display());
+            let texture = ctx.load_texture(name, image, egui::TextureOptions::LINEAR);
+            self.thumbnails.insert(path, texture);
+        }
+        self.groups = Some(found.groups);
+        self.search = None;
+    }
+}
+
+fn is_decodable_image(path: &Path) -> bool {
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    matches!(ext.as_deref(), Some("jpg" | "jpeg" | "png"))
+}
+
+/// SHA-1 первых `limit` байт файла (None - весь файл)
+pub fn hash_file(path: &Path, limit: Option<u64>) -> Option<[u8; 20]> {
+    let file = File::open(path).ok()?;
+    let mut reader: Box<dyn Read> = match limit {
+        Some(limit) => Box::new(file.take(limit)),
+        None => Box::new(file),
+    };
+
+    let mut hasher = Sha1::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = reader.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        throttle::HASHING.consume(read as u64);
+    }
+    Some(hasher.finalize().into())
+}
+
+/// Оставляет только файлы, у которых есть пары с тем же ключом
+fn group_by_key<K: std::hash::Hash + Eq + Send>(
+    files: Vec<(PathBuf, u64)>,
+    progress: &Progress,
+    key: impl Fn(&(PathBuf, u64)) -> Option<K> + Sync,
+) -> Vec<Vec<(PathBuf, u64)>> {
+    progress.start_phase(files.len());
+    let keyed: Vec<(Option<K>, (PathBuf, u64))> = files
+        .into_par_iter()
+        .map(|file| {
+            if progress.is_cancelled() {
+                return (None, file);
+            }
+            let k = key(&file);
+            progress.step();
+            (k, file)
+        })
+        .collect();
+
+    let mut groups: HashMap<K, Vec<(PathBuf, u64)>> = HashMap::new();
+    for (k, file) in keyed {
+        if let Some(k) = k {
+            groups.entry(k).or_default().push(file);
+        }
+    }
+    groups.into_values().filter(|g| g.len() > 1).collect()
+}
+
+/// Побайтные дубликаты: размер, затем хеш начала файла, затем полный хеш
+fn find_exact(files: Vec<(PathBuf, u64)>, cache: &HashCache, progress: &Progress) -> Option<SearchResult> {
+    let mut by_size: HashMap<u64, Vec<(PathBuf, u64)>> = HashMap::new();
+    for file in files.into_iter().filter(|(_, size)| *size > 0) {
+        by_size.entry(file.1).or_default().push(file);
+    }
+
+    let candidates: Vec<(PathBuf, u64)> = by_size.into_values().filter(|g| g.len() > 1).flatten().collect();
+    // Размер входит в ключ: одинаковое начало у файлов разного размера не в счёт
+    let partial = group_by_key(candidates, progress, |(path, size)| {
+        let limit = Some(PARTIAL_HASH_SIZE);
+        Some((*size, cache.hash(path, limit, || hash_file(path, limit))?))
+    });
+
+    let candidates: Vec<(PathBuf, u64)> = partial.into_iter().flatten().collect();
+    let full = group_by_key(candidates, progress, |(path, size)| {
+        Some((*size, cache.hash(path, None, || hash_file(path, None))?))
+    });
+    if progress.is_cancelled() {
+        return None;
+    }
+
+    let mut groups: Vec<DuplicateGroup> = full
+        .into_iter()
+        .map(|mut files| {
+            files.sort_by(|a, b| a.0.cmp(&b.0));
+            DuplicateGroup {
+                files: files
+                    .into_iter()
+                    .map(|(path, size)| DuplicateFile { path, size, resolution: None })
+                    .collect(),
+            }
+        })
+        .collect();
+    groups.sort_unstable_by_key(|g| std::cmp::Reverse(g.reclaimable()));
+
+    Some(SearchResult { groups, thumbnails: Vec::new() })
+}
+
+/// Разностный хеш (dHash): 64 бита "соседний пиксель справа ярче" на уменьшенной
+/// до 9x8 серой копии. Не меняется от масштаба и пережатия.
+fn image_hash(image: &image::DynamicImage) -> u64 {
+    let small = image
+        .resize_exact(9, 8, image::imageops::FilterType::Triangle)
+        .to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let bit = small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | bit as u64;
+        }
+    }
+    hash
+}
+
+fn thumbnail(path: &Path) -> Option<egui::ColorImage> {
+    let image = image::open(path).ok()?.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+/// Визуально одинаковые фотографии
+fn find_images(files: Vec<(PathBuf, u64)>, cache: &HashCache, progress: &Progress) -> Option<SearchResult> {
+    let images: Vec<(PathBuf, u64)> = files.into_iter().filter(|(path, _)| is_decodable_image(path)).collect();
+    progress.start_phase(images.len());
+
+    let hashed: Vec<(DuplicateFile, u64)> = images
+        .into_par_iter()
+        .filter_map(|(path, size)| {
+            if progress.is_cancelled() {
+                return None;
+            }
+            let hashed = cache.image(&path, || {
+                let image = image::open(&path).ok()?;
+                Some((image_hash(&image), (image.width(), image.height())))
+            });
+            progress.step();
+            let (hash, resolution) = hashed?;
+            Some((DuplicateFile { path, size, resolution: Some(resolution) }, hash))
+        })
+        .collect();
+    if progress.is_cancelled() {
+        return None;
+    }
+
+    // Каждая фотография сравнивается с первой фотографией группы, а не с любой из
+    // её участниц: иначе цепочка A~B~C собрала бы в одну группу разные снимки
+    let mut by_representative: Vec<(u64, Vec<DuplicateFile>)> = Vec::new();
+    for (file, hash) in hashed {
+        if progress.is_cancelled() {
+            return None;
+        }
+        let closest = by_representative
+            .iter_mut()
+            .map(|group| ((group.0 ^ hash).count_ones(), group))
+            .filter(|(distance, _)| *distance <= MAX_IMAGE_DISTANCE)
+            .min_by_key(|(distance, _)| *distance);
+        match closest {
+            Some((_, group)) => group.1.push(file),
+            None => by_representative.push((hash, vec![file])),
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = by_representative
+        .into_iter()
+        .map(|(_, files)| files)
+        .filter(|files| files.len() > 1)
+        .map(|mut files| {
+            // Оставить стоит самую крупную по разрешению копию
+            let pixels = |f: &DuplicateFile| f.resolution.map_or(0, |(w, h)| w as u64 * h as u64);
+            files.sort_by(|a, b| pixels(b).cmp(&pixels(a)).then(b.size.cmp(&a.size)));
+            DuplicateGroup { files }
+        })
+        .collect();
+    groups.sort_unstable_by_key(|g| std::cmp::Reverse(g.reclaimable()));
+
+    // Миниатюры только для найденных групп, чтобы не держать их для всей библиотеки
+    let paths: Vec<&PathBuf> = groups.iter().flat_map(|g| g.files.iter().map(|f| &f.path)).collect();
+    progress.start_phase(paths.len());
+    let thumbnails = paths
+        .into_par_iter()
+        .filter_map(|path| {
+            let image = thumbnail(path);
+            progress.step();
+            Some((path.clone(), image?))
+        })
+        .collect();
+
+    Some(SearchResult { groups, thumbnails })
+}
+
+impl CedarApp {
+    /// Окно поиска дубликатов
+    pub fn show_duplicates_window(&mut self, ctx: &egui::Context) {
+        let Some(finder) = &mut self.duplicate_finder else {
+            return;
+        };
+        finder.poll(ctx);
+
+        let mut open = true;
+        let mut clicked_path = None;
+        let mut start = false;
+
+        egui::Window::new(format!(
+            "{} {} - {}",
+            regular::COPY_SIMPLE,
+            self.translations.get("duplicates"),
+            finder.root.display()
+        ))
+        .id(egui::Id::new("duplicates_window"))
+        .open(&mut open)
+        .default_size([760.0, 520.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let mut mode = finder.mode;
+                ui.selectable_value(&mut mode, DuplicateMode::Exact, self.translations.get("duplicates_exact"));
+                ui.selectable_value(&mut mode, DuplicateMode::Images, self.translations.get("duplicates_images"));
+                if mode != finder.mode {
+                    finder.mode = mode;
+                    finder.search = None;
+                    finder.groups = None;
+                }
+
+                ui.separator();
+                let label = if finder.groups.is_some() { "refresh" } else { "duplicates_search" };
+                if ui.button(format!("{} {}", regular::MAGNIFYING_GLASS, self.translations.get(label))).clicked() {
+                    start = true;
+                }
+
+                // Кэш нельзя чистить, пока им пользуется поиск
+                let cache_size = hash_cache::size();
+                if cache_size > 0 {
+                    let button = egui::Button::new(format!(
+                        "{} {}",
+                        regular::BROOM,
+                        self.translations.get_fmt("hash_cache_clear", &[&format_size(cache_size)])
+                    ));
+                    let response = ui
+                        .add_enabled(finder.search.is_none(), button)
+                        .on_hover_text(self.translations.get("hash_cache_hint"));
+                    if response.clicked() {
+                        hash_cache::clear();
+                    }
+                }
+            });
+            if finder.mode == DuplicateMode::Images {
+                ui.label(
+                    egui::RichText::new(self.translations.get("duplicates_images_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+            }
+            ui.separator();
+
+            if let Some(search) = &finder.search {
+                let done = search.done.load(Ordering::Relaxed);
+                let total = search.total.load(Ordering::Relaxed);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(self.translations.get_fmt(
+                        "duplicates_progress",
+                        &[&done.to_string(), &total.to_string()],
+                    ));
+                    ui.weak(throttle::rate_label(&throttle::HASHING));
+                });
+                ctx.request_repaint();
+                return;
+            }
+
+            let Some(groups) = &finder.groups else {
+                return;
+            };
+            if groups.is_empty() {
+                ui.label(
+                    egui::RichText::new(self.translations.get("duplicates_none"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                return;
+            }
+
+            let reclaimable: u64 = groups.iter().map(|g| g.reclaimable()).sum();
+            ui.strong(self.translations.get_fmt(
+                "duplicates_summary",
+                &[&groups.len().to_string(), &format_size(reclaimable)],
+            ));
+            ui.add_space(5.0);
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    for (index, group) in groups.iter().enumerate() {
+                        egui::Frame::group(ui.style()).show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            ui.horizontal(|ui| {
+                                ui.strong(self.translations.get_fmt(
+                                    "duplicates_group",
+                                    &[&group.files.len().to_string(), &format_size(group.reclaimable())],
+                                ));
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.button(format!("{} {}", regular::BASKET, self.translations.get("duplicates_add_copies"))).clicked() {
+                                        for file in group.files.iter().skip(1) {
+                                            self.basket.add(file.path.clone());
+                                        }
+                                    }
+                                });
+                            });
+
+                            egui::Grid::new(("duplicate_group", index))
+                                .num_columns(5)
+                                .show(ui, |ui| {
+                                    for (position, file) in group.files.iter().enumerate() {
+                                        match finder.thumbnails.get(&file.path) {
+                                            Some(texture) => {
+                                                ui.add(egui::Image::new(texture).max_size(egui::vec2(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32)));
+                                            }
+                                            None => {
+                                                ui.label(if position == 0 { regular::CHECK } else { "" });
+                                            }
+                                        }
+
+                                        let response = ui.selectable_label(
+                                            self.selected_path.as_ref() == Some(&file.path),
+                                            file.path.display().to_string(),
+                                        );
+                                        if response.clicked() {
+                                            clicked_path = Some(file.path.clone());
+                                        }
+                                        ui.label(
+                                            file.resolution
+                                                .map(|(w, h)| format!("{}×{}", w, h))
+                                                .unwrap_or_default(),
+                                        );
+                                        ui.label(format_size(file.size));
+
+                                        let in_basket = self.basket.contains(&file.path);
+                                        let basket_icon = egui::RichText::new(regular::BASKET);
+                                        let basket_icon = if in_basket {
+                                            basket_icon.color(ui.visuals().selection.stroke.color)
+                                        } else {
+                                            basket_icon
+                                        };
+                                        let hint = if in_basket { "remove_from_basket" } else { "add_to_basket" };
+                                        let hint = self.translations.get(hint);
+                                        let response = ui.small_button(basket_icon).on_hover_text(&hint);
+                                        a11y::set_name(&response, egui::WidgetType::Button, &hint);
+                                        if response.clicked() {
+                                            if in_basket {
+                                                self.basket.remove(&file.path);
+                                            } else {
+                                                self.basket.add(file.path.clone());
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    }
+                });
+        });
+
+        if start {
+            finder.start();
+        }
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if !open {
+            self.duplicate_finder = None;
+        }
+    }
+}
@@ -0,0 +1,260 @@
+// Постоянный список исключений: такие папки, файлы и шаблоны имён не учитываются
+// при сканировании и сразу убираются из текущего дерева. Размер исключённых папок
+// запоминается, пока они ещё есть в дереве, - по нему видно, сколько сэкономило исключение
+
+use crate::{a11y, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum Exclusion {
+    /// Конкретная папка или файл
+    Path(#[serde(with = "crate::os_serde")] PathBuf),
+    /// Шаблон имени файла, например `*.iso`
+    Pattern(String),
+}
+
+impl Exclusion {
+    /// Шаблон по расширению файла; None - расширения нет
+    pub fn extension_of(name: &str) -> Option<Self> {
+        let (stem, extension) = name.rsplit_once('.')?;
+        (!stem.is_empty() && !extension.is_empty()).then(|| Exclusion::Pattern(format!("*.{}", extension.to_lowercase())))
+    }
+
+    pub fn matches(&self, path: &Path, name: &str, is_file: bool) -> bool {
+        match self {
+            Exclusion::Path(excluded) => crate::pattern::same_path(excluded, path),
+            Exclusion::Pattern(pattern) => is_file && crate::pattern::wildcard_match(pattern, name),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Exclusion::Path(path) => path.display().to_string(),
+            Exclusion::Pattern(pattern) => pattern.clone(),
+        }
+    }
+}
+
+/// Размер исключённой папки по последнему дереву, где она ещё была
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SubtreeSize {
+    /// Записей внутри папки на всех уровнях
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+impl SubtreeSize {
+    fn of(node: &DirNode) -> Self {
+        let mut size = SubtreeSize {
+            entries: node.children.len(),
+            bytes: node.size,
+        };
+        for child in node.children.iter().filter(|c| !c.is_file) {
+            size.entries += Self::of(child).entries;
+        }
+        size
+    }
+}
+
+/// Что исключения пропустили за одно сканирование
+#[derive(Clone, Copy, Default)]
+pub struct ExclusionSavings {
+    /// Пропущенные записи вместе с содержимым исключённых папок
+    pub entries: usize,
+    pub bytes: u64,
+    /// Исключённые папки, размер которых ещё не известен
+    pub unknown: usize,
+}
+
+impl ExclusionSavings {
+    /// Учитывает пропущенный элемент; размер папки берётся из запомненных
+    pub fn add(&mut self, path: &Path, file_size: Option<u64>, sizes: &BTreeMap<PathBuf, SubtreeSize>) {
+        self.entries += 1;
+        match file_size {
+            Some(size) => self.bytes += size,
+            None => match sizes.get(path).or_else(|| {
+                sizes.iter().find(|(excluded, _)| crate::pattern::same_path(excluded, path)).map(|(_, size)| size)
+            }) {
+                Some(size) => {
+                    self.entries += size.entries;
+                    self.bytes += size.bytes;
+                }
+                None => self.unknown += 1,
+            },
+        }
+    }
+
+    /// Примерно сколько заняло бы чтение пропущенного при скорости этого сканирования
+    pub fn time_saved(&self, scanned_entries: usize, duration: Duration) -> Duration {
+        if scanned_entries == 0 {
+            return Duration::ZERO;
+        }
+        duration.mul_f64(self.entries as f64 / scanned_entries as f64)
+    }
+}
+
+/// Запоминает размеры папок дерева, исключённых по пути; забывает размеры
+/// папок, которые больше не исключены
+pub fn remember_sizes(root: &DirNode, exclusions: &[Exclusion], sizes: &mut BTreeMap<PathBuf, SubtreeSize>) {
+    sizes.retain(|path, _| {
+        exclusions.iter().any(|e| matches!(e, Exclusion::Path(excluded) if crate::pattern::same_path(excluded, path)))
+    });
+    for exclusion in exclusions {
+        if let Exclusion::Path(path) = exclusion {
+            if let Some(node) = root.find(path).or_else(|| find_folded(root, path)).filter(|n| !n.is_file) {
+                sizes.insert(path.clone(), SubtreeSize::of(node));
+            }
+        }
+    }
+}
+
+/// Узел по пути, записанному в другом регистре или нормализации
+fn find_folded<'a>(node: &'a DirNode, path: &Path) -> Option<&'a DirNode> {
+    if crate::pattern::same_path(&node.path, path) {
+        return Some(node);
+    }
+    node.children
+        .iter()
+        .filter(|c| !c.is_file)
+        .find(|c| crate::pattern::path_starts_with(path, &c.path))
+        .and_then(|c| find_folded(c, path))
+}
+
+/// Исключения для сканирования: шаблоны приводятся к NFC и нижнему регистру
+/// один раз, а не для каждого проверяемого имени
+pub struct Matcher {
+    paths: Vec<PathBuf>,
+    patterns: Vec<crate::pattern::Wildcard>,
+}
+
+impl Matcher {
+    pub fn new(exclusions: &[Exclusion]) -> Self {
+        let mut matcher = Matcher { paths: Vec::new(), patterns: Vec::new() };
+        for exclusion in exclusions {
+            match exclusion {
+                Exclusion::Path(path) => matcher.paths.push(path.clone()),
+                Exclusion::Pattern(pattern) => matcher.patterns.push(crate::pattern::Wildcard::new(pattern)),
+            }
+        }
+        matcher
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Исключён ли элемент хотя бы одним правилом
+    pub fn matches(&self, path: &Path, name: &str, is_file: bool) -> bool {
+        self.paths.iter().any(|excluded| crate::pattern::same_path(excluded, path))
+            || (is_file && self.patterns.iter().any(|pattern| pattern.matches(name)))
+    }
+}
+
+/// Пути всех узлов поддерева, подпадающих под исключение; вложенные в них не собираются
+fn collect_matches(node: &DirNode, exclusion: &Exclusion, paths: &mut Vec<PathBuf>) {
+    for child in &node.children {
+        if exclusion.matches(&child.path, &child.display_name(), child.is_file) {
+            paths.push(child.path.clone());
+        } else if !child.is_file {
+            collect_matches(child, exclusion, paths);
+        }
+    }
+}
+
+fn exclusion_icon(exclusion: &Exclusion) -> &'static str {
+    match exclusion {
+        Exclusion::Path(_) => regular::FOLDER_SIMPLE,
+        Exclusion::Pattern(_) => regular::ASTERISK,
+    }
+}
+
+impl CedarApp {
+    /// Сводка пропущенного исключениями в последнем сканировании
+    pub fn exclusion_savings_text(&self) -> Option<String> {
+        let (savings, saved) = self.last_exclusion_savings?;
+        let mut text = self.translations.get_fmt(
+            "exclusions_saved",
+            &[
+                &savings.entries.to_string(),
+                &crate::format_size(savings.bytes),
+                &format!("{:.1}", saved.as_secs_f64()),
+            ],
+        );
+        if savings.unknown > 0 {
+            text.push('\n');
+            text.push_str(&self.translations.get_fmt("exclusions_saved_unknown", &[&savings.unknown.to_string()]));
+        }
+        Some(text)
+    }
+
+    /// Добавляет исключение и убирает подпадающие под него элементы из текущего дерева
+    pub fn add_exclusion(&mut self, exclusion: Exclusion) {
+        if !self.config.exclusions.contains(&exclusion) {
+            self.config.exclusions.push(exclusion.clone());
+            self.save_config_to_file();
+        }
+
+        let mut paths = Vec::new();
+        if let Some(root) = &self.root_node {
+            collect_matches(root, &exclusion, &mut paths);
+            remember_sizes(root, &self.config.exclusions, &mut self.config.excluded_sizes);
+            self.save_config_to_file();
+        }
+        for path in &paths {
+            self.remove_from_tree(path);
+        }
+        self.set_status(self.translations.get_fmt("exclusion_added", &[&exclusion.label()]));
+    }
+
+    /// Раздел настроек со списком исключений; возвращает true, если список изменился
+    pub fn show_exclusions(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.heading(self.translations.get("exclusions"));
+        ui.label(
+            egui::RichText::new(self.translations.get("exclusions_hint"))
+                .small()
+                .color(ui.visuals().weak_text_color()),
+        );
+        ui.add_space(4.0);
+
+        // Обязательные исключения политики показываются без кнопки удаления
+        let required: Vec<Exclusion> = crate::policy::get()
+            .exclusions()
+            .into_iter()
+            .filter(|exclusion| !self.config.exclusions.contains(exclusion))
+            .collect();
+        if self.config.exclusions.is_empty() && required.is_empty() {
+            ui.label(egui::RichText::new(self.translations.get("exclusions_empty")).italics());
+            return false;
+        }
+
+        let mut remove = None;
+        egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+            for exclusion in &required {
+                ui.horizontal(|ui| {
+                    ui.label(regular::LOCK_SIMPLE).on_hover_text(self.translations.get("policy_locked"));
+                    ui.label(format!("{} {}", exclusion_icon(exclusion), exclusion.label()));
+                });
+            }
+            for (index, exclusion) in self.config.exclusions.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if a11y::icon_button(ui, regular::X, &self.translations.get("remove_exclusion")).clicked() {
+                        remove = Some(index);
+                    }
+                    ui.label(format!("{} {}", exclusion_icon(exclusion), exclusion.label()));
+                });
+            }
+        });
+
+        if let Some(index) = remove {
+            self.config.exclusions.remove(index);
+            self.set_status(self.translations.get("exclusions_rescan"));
+            return true;
+        }
+        false
+    }
+}
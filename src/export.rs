@@ -0,0 +1,373 @@
+// Выгрузка графических представлений в PNG и SVG. Icicle, карта и кольца заново
+// раскладываются в заданном размере; дерево сохраняется снимком экрана
+
+use crate::chart::{Block, ChartStyle};
+use crate::{icicle, sunburst, treemap, CedarApp, ViewMode};
+use eframe::egui::{self, Color32};
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Параметры выгрузки; размер - в точках интерфейса, масштаб задаёт плотность пикселей PNG
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub scale: f32,
+    /// Без фона - для вставки в документы с другим фоном
+    pub transparent: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            format: ImageFormat::Png,
+            width: 1600,
+            height: 900,
+            scale: 2.0,
+            transparent: false,
+        }
+    }
+}
+
+/// Готовые размеры: ширина и высота раскладки и масштаб; итог в пикселях - их произведение
+const PRESETS: [(&str, u32, u32, f32); 4] = [
+    ("HD", 1280, 720, 1.0),
+    ("Full HD", 1920, 1080, 1.0),
+    ("4K", 1920, 1080, 2.0),
+    ("A4 300 dpi", 1754, 1240, 2.0),
+];
+
+/// Снимок дерева: команду отправляем кадром позже, когда окно выгрузки уже закрыто
+pub enum Screenshot {
+    Scheduled(PathBuf),
+    Waiting(PathBuf),
+}
+
+pub fn hex(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// SVG-документ, с фоном или прозрачный; `scale` задаёт размер в пикселях относительно раскладки
+pub fn to_svg(size: egui::Vec2, scale: f32, style: &ChartStyle, transparent: bool, content: impl FnOnce(&mut String)) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" font-family="sans-serif" font-size="{}">"#,
+        size.x * scale,
+        size.y * scale,
+        size.x,
+        size.y,
+        crate::chart::LABEL_SIZE,
+    );
+    if !transparent {
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(style.background()));
+    }
+    content(&mut svg);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// SVG-элементы блоков без обёртки документа - для вставки в другие документы
+pub fn write_blocks(svg: &mut String, blocks: &[Block], style: &ChartStyle) {
+    for (index, block) in blocks.iter().enumerate() {
+        let rect = block.rect;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="2" fill="{}"/>"#,
+            rect.left(),
+            rect.top(),
+            rect.width(),
+            rect.height(),
+            hex(block.fill),
+        );
+        if let Some(label) = &block.label {
+            let clip = rect.shrink(2.0);
+            let anchor = match label.align.x() {
+                egui::Align::Min => "start",
+                egui::Align::Center => "middle",
+                egui::Align::Max => "end",
+            };
+            let _ = writeln!(
+                svg,
+                r#"<clipPath id="c{}"><rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}"/></clipPath>"#,
+                index,
+                clip.left(),
+                clip.top(),
+                clip.width(),
+                clip.height(),
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" fill="{}" text-anchor="{}" dominant-baseline="central" clip-path="url(#c{})">{}</text>"#,
+                label.pos.x,
+                label.pos.y,
+                hex(style.text_color()),
+                anchor,
+                index,
+                escape(&label.text),
+            );
+        }
+    }
+}
+
+/// Шрифты для подписей PNG: тот же, что в интерфейсе, и системные для остальных алфавитов
+pub fn load_fonts(options: &mut usvg::Options) {
+    let db = options.fontdb_mut();
+    db.load_system_fonts();
+    let fonts = egui::FontDefinitions::default();
+    if let Some(data) = fonts.font_data.get("Ubuntu-Light") {
+        let first = db.len();
+        db.load_font_data(data.font.to_vec());
+        let family = db.faces().nth(first).and_then(|face| face.families.first()).map(|(name, _)| name.clone());
+        if let Some(family) = family {
+            db.set_sans_serif_family(family);
+        }
+    }
+}
+
+/// Сохраняет SVG-документ в файл в выбранном формате
+fn save_chart(svg: String, format: ImageFormat, path: &Path) -> Result<(), String> {
+    match format {
+        ImageFormat::Svg => std::fs::write(path, svg).map_err(|e| e.to_string()),
+        ImageFormat::Png => {
+            let mut options = usvg::Options::default();
+            load_fonts(&mut options);
+            let tree = usvg::Tree::from_str(&svg, &options).map_err(|e| e.to_string())?;
+            let pixel_size = tree.size().to_int_size();
+            let mut pixmap = tiny_skia::Pixmap::new(pixel_size.width(), pixel_size.height())
+                .ok_or_else(|| "image is too large".to_string())?;
+            resvg::render(&tree, tiny_skia::Transform::identity(), &mut pixmap.as_mut());
+            pixmap.save_png(path).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Вырезает из снимка экрана область `rect` (в точках интерфейса) и сохраняет в PNG
+fn save_screenshot(image: &egui::ColorImage, rect: egui::Rect, pixels_per_point: f32, path: &Path) -> Result<(), String> {
+    let [width, height] = image.size;
+    let left = ((rect.left() * pixels_per_point).round().max(0.0) as usize).min(width);
+    let top = ((rect.top() * pixels_per_point).round().max(0.0) as usize).min(height);
+    let right = ((rect.right() * pixels_per_point).round() as usize).clamp(left, width);
+    let bottom = ((rect.bottom() * pixels_per_point).round() as usize).clamp(top, height);
+
+    let mut pixels = Vec::with_capacity((right - left) * (bottom - top) * 4);
+    for y in top..bottom {
+        for color in &image.pixels[y * width + left..y * width + right] {
+            pixels.extend_from_slice(&color.to_srgba_unmultiplied());
+        }
+    }
+    image::save_buffer(path, &pixels, (right - left) as u32, (bottom - top) as u32, image::ColorType::Rgba8)
+        .map_err(|e| e.to_string())
+}
+
+impl CedarApp {
+    fn export_finished(&mut self, path: &Path, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.set_status(self.translations.get_fmt("export_done", &[&path.display().to_string()])),
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("export_failed", &[&e]));
+            }
+        }
+    }
+
+    /// Окно "Экспорт изображения" для текущего представления
+    pub fn show_export_window(&mut self, ctx: &egui::Context) {
+        if !self.show_export {
+            return;
+        }
+        let Some(root) = &self.root_node else {
+            self.show_export = false;
+            return;
+        };
+
+        let is_tree = self.config.view_mode == ViewMode::Tree;
+        let focus = self.view_focus.as_ref().and_then(|p| root.find(p)).unwrap_or(root);
+        let file_name = focus.display_name().into_owned();
+        let on_screen = self.chart_layers.rect(self.config.view_mode).map(|rect| (rect.size(), ctx.pixels_per_point()));
+
+        let mut open = true;
+        let mut export = false;
+        let settings = &mut self.config.export;
+        if is_tree {
+            settings.format = ImageFormat::Png;
+        }
+
+        egui::Window::new(format!("{} {}", regular::IMAGE, self.translations.get("export_image")))
+            .id(egui::Id::new("export_window"))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("export_grid").num_columns(2).show(ui, |ui| {
+                    ui.label(self.translations.get("export_format"));
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut settings.format, ImageFormat::Png, "PNG");
+                        ui.add_enabled_ui(!is_tree, |ui| {
+                            ui.radio_value(&mut settings.format, ImageFormat::Svg, "SVG");
+                        });
+                    });
+                    ui.end_row();
+
+                    ui.add_enabled_ui(!is_tree, |ui| {
+                        ui.label(self.translations.get("export_size"));
+                    });
+                    ui.add_enabled_ui(!is_tree, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut settings.width).range(200..=8000));
+                            ui.label("×");
+                            ui.add(egui::DragValue::new(&mut settings.height).range(200..=8000));
+                        });
+                    });
+                    ui.end_row();
+
+                    let scalable = !is_tree && settings.format == ImageFormat::Png;
+                    ui.add_enabled_ui(scalable, |ui| {
+                        ui.label(self.translations.get("export_scale"));
+                    });
+                    ui.add_enabled_ui(scalable, |ui| {
+                        ui.add(egui::DragValue::new(&mut settings.scale).range(1.0..=4.0).speed(0.05).suffix("×"));
+                    });
+                    ui.end_row();
+
+                    ui.add_enabled_ui(!is_tree, |ui| {
+                        ui.label(self.translations.get("export_preset"));
+                    });
+                    ui.add_enabled_ui(!is_tree, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            // Размер и чёткость как у представления в окне
+                            if let Some((size, pixels_per_point)) = on_screen {
+                                let current = settings.width == size.x.round() as u32
+                                    && settings.height == size.y.round() as u32
+                                    && settings.scale == pixels_per_point;
+                                if ui.selectable_label(current, self.translations.get("export_on_screen")).clicked() {
+                                    settings.width = size.x.round() as u32;
+                                    settings.height = size.y.round() as u32;
+                                    settings.scale = pixels_per_point;
+                                }
+                            }
+                            for (name, width, height, scale) in PRESETS {
+                                let current = settings.width == width && settings.height == height && settings.scale == scale;
+                                let pixels = format!("{} × {}", (width as f32 * scale) as u32, (height as f32 * scale) as u32);
+                                if ui.selectable_label(current, name).on_hover_text(pixels).clicked() {
+                                    settings.width = width;
+                                    settings.height = height;
+                                    settings.scale = scale;
+                                }
+                            }
+                        });
+                    });
+                    ui.end_row();
+                });
+                ui.add_enabled_ui(!is_tree, |ui| {
+                    ui.checkbox(&mut settings.transparent, self.translations.get("export_transparent"));
+                });
+
+                ui.add_space(4.0);
+                let hint = if is_tree {
+                    self.translations.get("export_tree_hint")
+                } else if settings.format == ImageFormat::Png {
+                    self.translations.get_fmt(
+                        "export_pixels",
+                        &[
+                            &((settings.width as f32 * settings.scale).round() as u32).to_string(),
+                            &((settings.height as f32 * settings.scale).round() as u32).to_string(),
+                        ],
+                    )
+                } else {
+                    self.translations.get("export_svg_hint")
+                };
+                ui.label(egui::RichText::new(hint).small().color(ui.visuals().weak_text_color()));
+
+                ui.separator();
+                export = ui.button(format!("{} {}", regular::FLOPPY_DISK, self.translations.get("export_save"))).clicked();
+            });
+
+        if export {
+            let format = self.config.export.format;
+            let path = rfd::FileDialog::new()
+                .add_filter(format.extension().to_uppercase(), &[format.extension()])
+                .set_file_name(format!("{}.{}", file_name, format.extension()))
+                .save_file();
+            if let Some(path) = path {
+                open = false;
+                if is_tree {
+                    self.screenshot = Some(Screenshot::Scheduled(path));
+                } else if let Some(root) = &self.root_node {
+                    let focus = self.view_focus.as_ref().and_then(|p| root.find(p)).unwrap_or(root);
+                    let settings = &self.config.export;
+                    let rect = egui::Rect::from_min_size(
+                        egui::Pos2::ZERO,
+                        egui::vec2(settings.width as f32, settings.height as f32),
+                    );
+                    let style = self.chart_style();
+                    let svg = to_svg(rect.size(), settings.scale, &style, settings.transparent, |svg| match self.config.view_mode {
+                        ViewMode::Sunburst => sunburst::layout(focus, rect, &style).write_svg(svg, &style),
+                        ViewMode::Treemap => write_blocks(svg, &treemap::layout(focus, rect, &style), &style),
+                        _ => write_blocks(svg, &icicle::layout(focus, rect, &style), &style),
+                    });
+                    let result = save_chart(svg, settings.format, &path);
+                    self.export_finished(&path, result);
+                }
+            }
+            self.save_config_to_file();
+        }
+        self.show_export = open;
+    }
+
+    /// Снимок дерева: отправка команды и приём изображения
+    pub fn handle_screenshot(&mut self, ctx: &egui::Context) {
+        match self.screenshot.take() {
+            Some(Screenshot::Scheduled(path)) => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+                self.screenshot = Some(Screenshot::Waiting(path));
+                ctx.request_repaint();
+            }
+            Some(Screenshot::Waiting(path)) => {
+                let image = ctx.input(|i| {
+                    i.events.iter().find_map(|event| match event {
+                        egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                        _ => None,
+                    })
+                });
+                match image {
+                    Some(image) => {
+                        let result = match self.tree_view_rect {
+                            Some(rect) => save_screenshot(&image, rect, ctx.pixels_per_point(), &path),
+                            None => Err(self.translations.get("export_tree_hidden")),
+                        };
+                        self.export_finished(&path, result);
+                    }
+                    None => {
+                        self.screenshot = Some(Screenshot::Waiting(path));
+                        ctx.request_repaint();
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,215 @@
+// Плоский список всех файлов папки (рекурсивно) с сортировкой по столбцам -
+// быстрый способ найти крупные файлы, спрятанные глубоко в дереве
+
+use crate::{format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const ROW_HEIGHT: f32 = 20.0;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Name,
+    Folder,
+    Size,
+    Modified,
+}
+
+pub struct FlatFile {
+    pub path: PathBuf,
+    pub name: String,
+    /// Папка относительно корня списка
+    pub folder: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+pub struct FileList {
+    pub root: PathBuf,
+    pub files: Vec<FlatFile>,
+    pub total_size: u64,
+    pub sort: SortColumn,
+    pub ascending: bool,
+}
+
+impl FileList {
+    /// Собирает все файлы поддерева
+    pub fn new(node: &DirNode) -> Self {
+        fn walk(node: &DirNode, root: &Path, files: &mut Vec<FlatFile>) {
+            for child in &node.children {
+                if child.is_file {
+                    let folder = child
+                        .path
+                        .parent()
+                        .and_then(|p| p.strip_prefix(root).ok())
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_default();
+                    files.push(FlatFile {
+                        path: child.path.clone(),
+                        name: child.display_name().into_owned(),
+                        folder,
+                        size: child.size,
+                        modified: child.modified,
+                    });
+                } else {
+                    walk(child, root, files);
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        walk(node, &node.path, &mut files);
+        let mut list = Self {
+            root: node.path.clone(),
+            total_size: files.iter().map(|f| f.size).sum(),
+            files,
+            sort: SortColumn::Size,
+            ascending: false,
+        };
+        list.sort_files();
+        list
+    }
+
+    fn sort_files(&mut self) {
+        match self.sort {
+            SortColumn::Name => self.files.sort_by_cached_key(|f| f.name.to_lowercase()),
+            SortColumn::Folder => self.files.sort_by(|a, b| a.folder.cmp(&b.folder)),
+            SortColumn::Size => self.files.sort_by_key(|f| f.size),
+            SortColumn::Modified => self.files.sort_by_key(|f| f.modified),
+        }
+        if !self.ascending {
+            self.files.reverse();
+        }
+    }
+
+    /// Щелчок по заголовку: тот же столбец меняет направление, новый - сортирует по нему
+    fn set_sort(&mut self, column: SortColumn) {
+        if self.sort == column {
+            self.ascending = !self.ascending;
+        } else {
+            self.sort = column;
+            // Размер и дату удобнее сразу видеть от больших и свежих
+            self.ascending = matches!(column, SortColumn::Name | SortColumn::Folder);
+        }
+        self.sort_files();
+    }
+}
+
+impl CedarApp {
+    /// Окно плоского списка файлов
+    pub fn show_file_list_window(&mut self, ctx: &egui::Context) {
+        let Some(list) = &mut self.file_list else {
+            return;
+        };
+
+        let mut open = true;
+        let mut clicked_path = None;
+        let now = SystemTime::now();
+        let days_text = self.translations.get("days");
+
+        let title = format!(
+            "{} {} - {}",
+            regular::LIST_BULLETS,
+            self.translations.get("all_files_in_folder"),
+            list.root.display()
+        );
+
+        egui::Window::new(title)
+            .id(egui::Id::new("file_list_window"))
+            .open(&mut open)
+            .default_size([860.0, 520.0])
+            .show(ctx, |ui| {
+                ui.strong(self.translations.get_fmt(
+                    "file_list_summary",
+                    &[&list.files.len().to_string(), &format_size(list.total_size)],
+                ));
+                ui.separator();
+
+                let name_width = 260.0;
+                let size_width = 90.0;
+                let modified_width = 90.0;
+                let folder_width = (ui.available_width() - name_width - size_width - modified_width - 24.0).max(120.0);
+
+                // Заголовки-кнопки сортировки
+                let mut sort_by = None;
+                ui.horizontal(|ui| {
+                    let columns = [
+                        (SortColumn::Name, "name", name_width),
+                        (SortColumn::Folder, "folder", folder_width),
+                        (SortColumn::Size, "size", size_width),
+                        (SortColumn::Modified, "age", modified_width),
+                    ];
+                    for (column, key, width) in columns {
+                        let arrow = match (list.sort == column, list.ascending) {
+                            (true, true) => regular::CARET_UP,
+                            (true, false) => regular::CARET_DOWN,
+                            (false, _) => "",
+                        };
+                        let header = egui::Button::new(egui::RichText::new(format!("{} {}", self.translations.get(key), arrow)).strong())
+                            .frame(false);
+                        if ui.add_sized([width, ROW_HEIGHT], header).clicked() {
+                            sort_by = Some(column);
+                        }
+                    }
+                });
+                if let Some(column) = sort_by {
+                    list.set_sort(column);
+                }
+                ui.separator();
+
+                let copy_label = self.translations.get("copy_path");
+                // Виртуальная прокрутка: рисуются только видимые строки
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show_rows(ui, ROW_HEIGHT, list.files.len(), |ui, range| {
+                        for file in &list.files[range] {
+                            ui.horizontal(|ui| {
+                                let selected = self.selected_path.as_ref() == Some(&file.path);
+                                let response = ui.add_sized(
+                                    [name_width, ROW_HEIGHT],
+                                    egui::SelectableLabel::new(selected, &file.name),
+                                );
+                                if response.clicked() {
+                                    clicked_path = Some(file.path.clone());
+                                }
+                                crate::clipboard::path_tooltip(response, &file.path, &copy_label);
+
+                                ui.add_sized(
+                                    [folder_width, ROW_HEIGHT],
+                                    egui::Label::new(
+                                        egui::RichText::new(&file.folder).color(ui.visuals().weak_text_color()),
+                                    )
+                                    .truncate(),
+                                );
+                                ui.add_sized([size_width, ROW_HEIGHT], egui::Label::new(format_size(file.size)));
+
+                                let age = file.modified.map(|m| {
+                                    let days = now.duration_since(m).unwrap_or_default().as_secs() / SECONDS_PER_DAY;
+                                    format!("{} {}", self.translations.format_count(days), days_text)
+                                });
+                                let response = ui.add_sized(
+                                    [modified_width, ROW_HEIGHT],
+                                    egui::Label::new(age.unwrap_or_else(|| "—".to_string())),
+                                );
+                                if let Some(modified) = file.modified {
+                                    response.on_hover_text(self.translations.format_date(modified));
+                                }
+                            });
+                        }
+                    });
+            });
+
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if !open {
+            self.file_list = None;
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// Файловые операции, выполняемые из интерфейса
+
+use crate::palette::Status;
+use crate::platform::TrashCapacity;
+use crate::{format_size, CedarApp};
+use eframe::egui;
+use std::path::Path;
+
+/// Удаляет путь в корзину или безвозвратно
+pub fn delete_path(path: &Path, permanent: bool) -> Result<(), String> {
+    if !permanent {
+        return trash::delete(path).map_err(|e| e.to_string());
+    }
+
+    // symlink_metadata - чтобы удалять саму ссылку, а не то, на что она указывает
+    let metadata = std::fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    let result = if metadata.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Как корзина примет удаляемое
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrashFit {
+    Fits,
+    /// Поместится, но Windows удалит из корзины старые элементы
+    Evicts,
+    /// Не поместится или корзина отключена: удаление будет безвозвратным
+    TooLarge,
+}
+
+/// Поместится ли `largest` (крупнейший элемент) и всё удаляемое `total` в корзину
+pub fn trash_fit(capacity: &TrashCapacity, largest: u64, total: u64) -> TrashFit {
+    if capacity.disabled || largest > capacity.max {
+        TrashFit::TooLarge
+    } else if capacity.used + total > capacity.max {
+        TrashFit::Evicts
+    } else {
+        TrashFit::Fits
+    }
+}
+
+impl CedarApp {
+    /// Предупреждение о переполнении корзины для диалогов удаления и его цвет
+    pub fn trash_warning(&self, fit: TrashFit, capacity: &TrashCapacity) -> Option<(String, egui::Color32)> {
+        let (key, status) = match fit {
+            TrashFit::Fits => return None,
+            TrashFit::Evicts => ("trash_evicts", Status::Warning),
+            TrashFit::TooLarge if capacity.disabled => ("trash_disabled", Status::Critical),
+            TrashFit::TooLarge => ("trash_too_large", Status::Critical),
+        };
+        let free = format_size(capacity.max.saturating_sub(capacity.used));
+        let text = self.translations.get_fmt(key, &[&format_size(capacity.max), &free]);
+        Some((text, self.config.palette.status_text(status, self.config.dark_mode)))
+    }
+}
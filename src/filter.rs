@@ -0,0 +1,170 @@
+// Фильтр файлов при сканировании: в дерево попадают только подходящие файлы,
+// а папки суммируют только их размеры
+
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+const MB: u64 = 1024 * 1024;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanFilter {
+    /// Шаблоны имён через `;`; пустая строка - любые файлы
+    pub patterns: String,
+    /// Минимальный размер файла в мегабайтах; 0 - без ограничения
+    pub min_size_mb: u64,
+    /// Минимальный возраст последнего изменения в днях; 0 - без ограничения
+    pub min_age_days: u32,
+}
+
+impl ScanFilter {
+    pub fn is_active(&self) -> bool {
+        !self.patterns.trim().is_empty() || self.min_size_mb > 0 || self.min_age_days > 0
+    }
+
+    pub fn matches(&self, name: &str, size: u64, modified: Option<SystemTime>, now: SystemTime) -> bool {
+        if size < self.min_size_mb * MB || !crate::pattern::matches_any(&self.patterns, name) {
+            return false;
+        }
+        if self.min_age_days == 0 {
+            return true;
+        }
+        // Файлы без времени изменения считаем старыми, как и правила очистки
+        modified.is_none_or(|m| {
+            let age = now.duration_since(m).unwrap_or_default();
+            age.as_secs() / SECONDS_PER_DAY >= self.min_age_days as u64
+        })
+    }
+}
+
+/// Именованный набор условий фильтра, например "Видео > 1 ГБ"
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    pub filter: ScanFilter,
+}
+
+impl CedarApp {
+    /// Выпадающее меню фильтра для следующего сканирования
+    pub fn show_filter_menu(&mut self, ui: &mut egui::Ui) {
+        let mut filter_text = egui::RichText::new(format!("{} {}", regular::FUNNEL, self.translations.get("scan_filter")));
+        if self.config.scan_filter.is_active() {
+            filter_text = filter_text.color(ui.visuals().selection.stroke.color).strong();
+        }
+
+        ui.menu_button(filter_text, |ui| {
+            let mut changed = false;
+            ui.label(
+                egui::RichText::new(self.translations.get("scan_filter_hint"))
+                    .color(ui.visuals().weak_text_color()),
+            );
+            egui::Grid::new("scan_filter_grid").num_columns(2).show(ui, |ui| {
+                ui.label(self.translations.get("rule_pattern"));
+                changed |= ui.add(
+                    egui::TextEdit::singleline(&mut self.config.scan_filter.patterns)
+                        .hint_text("*.mp4;*.mkv")
+                        .desired_width(180.0)
+                ).changed();
+                ui.end_row();
+
+                ui.label(self.translations.get("scan_filter_min_size"));
+                changed |= ui.add(
+                    egui::DragValue::new(&mut self.config.scan_filter.min_size_mb)
+                        .range(0..=1_000_000)
+                        .suffix(" MB")
+                ).changed();
+                ui.end_row();
+
+                ui.label(self.translations.get("rule_min_age"));
+                changed |= ui.add(
+                    egui::DragValue::new(&mut self.config.scan_filter.min_age_days)
+                        .range(0..=36500)
+                        .suffix(format!(" {}", self.translations.get("days")))
+                ).changed();
+                ui.end_row();
+            });
+            if ui.button(format!("{} {}", regular::X, self.translations.get("clear"))).clicked() {
+                self.config.scan_filter = ScanFilter::default();
+                changed = true;
+            }
+
+            ui.separator();
+
+            // Сохранение текущих условий как пресета
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.preset_name)
+                        .hint_text(self.translations.get("preset_name"))
+                        .desired_width(140.0)
+                );
+                let can_save = self.config.scan_filter.is_active() && !self.preset_name.trim().is_empty();
+                ui.add_enabled_ui(can_save, |ui| {
+                    if ui.button(format!("{} {}", regular::FLOPPY_DISK, self.translations.get("save_preset"))).clicked() {
+                        let name = self.preset_name.trim().to_string();
+                        let filter = self.config.scan_filter.clone();
+                        // Пресет с тем же именем перезаписываем
+                        match self.config.filter_presets.iter_mut().find(|p| p.name == name) {
+                            Some(preset) => preset.filter = filter,
+                            None => self.config.filter_presets.push(FilterPreset { name, filter }),
+                        }
+                        self.preset_name.clear();
+                        changed = true;
+                    }
+                });
+            });
+
+            if changed {
+                self.save_config_to_file();
+            }
+        });
+    }
+
+    /// Ряд пресетов над деревом: выбор пресета пересканирует текущий корень с его фильтром
+    pub fn show_filter_chips(&mut self, ui: &mut egui::Ui) {
+        if self.config.filter_presets.is_empty() && !self.config.scan_filter.is_active() {
+            return;
+        }
+
+        let mut apply = None;
+        let mut remove = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new(regular::FUNNEL).color(ui.visuals().weak_text_color()));
+
+            if ui.selectable_label(!self.config.scan_filter.is_active(), self.translations.get("all_files")).clicked() {
+                apply = Some(ScanFilter::default());
+            }
+
+            for (index, preset) in self.config.filter_presets.iter().enumerate() {
+                let response = ui.selectable_label(self.config.scan_filter == preset.filter, &preset.name);
+                if response.clicked() {
+                    apply = Some(preset.filter.clone());
+                }
+                response.context_menu(|ui| {
+                    if ui.button(format!("{} {}", regular::TRASH, self.translations.get("delete_preset"))).clicked() {
+                        remove = Some(index);
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        if let Some(index) = remove {
+            self.config.filter_presets.remove(index);
+            self.save_config_to_file();
+        }
+        if let Some(filter) = apply {
+            if filter != self.config.scan_filter && !self.is_scanning {
+                self.config.scan_filter = filter;
+                self.save_config_to_file();
+                if let Some(root) = &self.root_node {
+                    self.start_scan(root.path.display().to_string());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// Кэш хешей файлов для поиска дубликатов. Хеш файла не меняется, пока не
+// изменились его размер и время изменения, поэтому повторный поиск в большой
+// фотоколлекции читает с диска только новые и изменённые файлы
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// SHA-1 содержимого файла
+type Hash = [u8; 20];
+
+/// Запомненные хеши одного файла
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Entry {
+    size: u64,
+    /// Время изменения в наносекундах от начала эпохи
+    modified: u128,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "hex")]
+    partial: Option<Hash>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "hex")]
+    full: Option<Hash>,
+    /// Перцептивный хеш и разрешение фотографии
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<(u64, (u32, u32))>,
+}
+
+/// Хеши хранятся строками - так файл кэша заметно меньше
+mod hex {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(hash: &Option<Hash>, serializer: S) -> Result<S::Ok, S::Error> {
+        let text: Option<String> = hash.map(|h| h.iter().map(|b| format!("{:02x}", b)).collect());
+        text.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Hash>, D::Error> {
+        let text: Option<String> = Option::deserialize(deserializer)?;
+        Ok(text.and_then(|text| {
+            let mut hash = [0u8; 20];
+            for (i, byte) in hash.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(text.get(i * 2..i * 2 + 2)?, 16).ok()?;
+            }
+            Some(hash)
+        }))
+    }
+}
+
+pub struct HashCache {
+    entries: Mutex<HashMap<PathBuf, Entry>>,
+    dirty: AtomicBool,
+}
+
+/// Размер и время изменения файла сейчас; без них хеш не кэшируется
+fn stamp(path: &Path) -> Option<(u64, u128)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    Some((metadata.len(), modified))
+}
+
+impl HashCache {
+    pub fn load() -> Self {
+        let entries = cache_file()
+            .and_then(|file| std::fs::File::open(file).ok())
+            .and_then(|file| {
+                let mut reader = serde_json::Deserializer::from_reader(std::io::BufReader::new(file));
+                crate::os_serde::map::deserialize(&mut reader).ok()
+            })
+            .unwrap_or_default();
+        Self {
+            entries: Mutex::new(entries),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Запись файла, если он не менялся с момента её создания, иначе пустая
+    fn lookup(&self, path: &Path, stamp: (u64, u128)) -> Entry {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(path) {
+            Some(entry) if (entry.size, entry.modified) == stamp => entry.clone(),
+            _ => Entry {
+                size: stamp.0,
+                modified: stamp.1,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn store(&self, path: &Path, entry: Entry) {
+        self.entries.lock().unwrap().insert(path.to_path_buf(), entry);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Хеш первых `limit` байт файла (None - всего файла)
+    pub fn hash(&self, path: &Path, limit: Option<u64>, compute: impl FnOnce() -> Option<Hash>) -> Option<Hash> {
+        let Some(stamp) = stamp(path) else {
+            return compute();
+        };
+        let mut entry = self.lookup(path, stamp);
+        // Файл не длиннее префикса: хеш начала и есть полный хеш
+        let slot = if limit.is_some_and(|limit| stamp.0 > limit) { &mut entry.partial } else { &mut entry.full };
+        if slot.is_some() {
+            return *slot;
+        }
+
+        let hash = compute()?;
+        *slot = Some(hash);
+        self.store(path, entry);
+        Some(hash)
+    }
+
+    /// Перцептивный хеш и разрешение фотографии
+    pub fn image(&self, path: &Path, compute: impl FnOnce() -> Option<(u64, (u32, u32))>) -> Option<(u64, (u32, u32))> {
+        let Some(stamp) = stamp(path) else {
+            return compute();
+        };
+        let mut entry = self.lookup(path, stamp);
+        if entry.image.is_some() {
+            return entry.image;
+        }
+        entry.image = Some(compute()?);
+        self.store(path, entry.clone());
+        entry.image
+    }
+
+    /// Забывает файлы папки `root`, которых больше нет среди `present`
+    pub fn forget_missing(&self, root: &Path, present: &HashSet<&Path>) {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|path, _| !path.starts_with(root) || present.contains(path.as_path()));
+        if entries.len() != before {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Записывает кэш на диск, если в нём что-то изменилось
+    pub fn save(&self) {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let Some(file) = cache_file() else {
+            return;
+        };
+        let entries = self.entries.lock().unwrap();
+        if let Err(e) = write_atomically(&file, &entries) {
+            eprintln!("Failed to save hash cache: {}", e);
+        }
+    }
+}
+
+/// Пишет во временный файл и переименовывает, чтобы сбой не оставил битый кэш
+fn write_atomically(file: &Path, entries: &HashMap<PathBuf, Entry>) -> std::io::Result<()> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = file.with_extension("tmp");
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+    crate::os_serde::map::serialize(entries, &mut serde_json::Serializer::new(&mut writer))?;
+    writer.flush()?;
+    drop(writer);
+    std::fs::rename(&tmp, file)
+}
+
+pub fn cache_file() -> Option<PathBuf> {
+    crate::app_paths::data_path("hash_cache.json")
+}
+
+/// Размер файла кэша на диске
+pub fn size() -> u64 {
+    cache_file().and_then(|file| std::fs::metadata(file).ok()).map_or(0, |m| m.len())
+}
+
+/// Удаляет кэш целиком
+pub fn clear() {
+    if let Some(file) = cache_file() {
+        let _ = std::fs::remove_file(file);
+    }
+}
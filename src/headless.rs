@@ -0,0 +1,415 @@
+// Сканирование без окна: `--scan <папка>` проходит папку с сохранёнными настройками,
+// `--export <файл>` пишет полное дерево в JSON, `--ncdu <файл>` - в формате ncdu, `--html <файл>` -
+// отчётом для браузера. Краткая сводка (итоги, крупнейшие
+// папки верхнего уровня, ошибки чтения) выдаётся всегда - мониторингу не нужно
+// разбирать всё дерево. С `--every <минуты>` программа работает фоновым агентом
+// и повторяет сканирование, а `--metrics <адрес>` отдаёт его итоги в формате
+// Prometheus. Если в настройках задан веб-хук, туда уходят сводка и превышения
+// пределов наблюдаемых папок, а с `--email <адреса>` - отчёт по почте. Папки с
+// правилом хранения снимков сохраняются в хранилище снимков, как и в окне
+
+use crate::panels::PanelData;
+use crate::{
+    checkpoint, disk_probe, find_disk, get_disk_info, os_serde, platform, scan_directory, AppConfig, CedarApp, DirNode,
+    ScanOptions, ScanProgress, ScanResult,
+};
+use crate::{html_report, ncdu, smtp, snapshot_store, watchlist, webhook};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const SCAN_ARG: &str = "--scan";
+const EXPORT_ARG: &str = "--export";
+const NCDU_ARG: &str = "--ncdu";
+const HTML_ARG: &str = "--html";
+const SUMMARY_ARG: &str = "--summary";
+const EVERY_ARG: &str = "--every";
+const METRICS_ARG: &str = "--metrics";
+const EMAIL_ARG: &str = "--email";
+/// Сколько крупнейших папок верхнего уровня попадает в сводку
+const TOP_FOLDERS: usize = 20;
+/// Сколько папок с ошибками чтения попадает в сводку
+const ERRORS_LIMIT: usize = 100;
+
+/// Параметры запуска без окна
+pub struct HeadlessArgs {
+    root: String,
+    /// Куда записать полное дерево
+    export: Option<PathBuf>,
+    /// Куда записать дерево для ncdu
+    ncdu: Option<PathBuf>,
+    /// Куда записать HTML-отчёт
+    html: Option<PathBuf>,
+    /// Куда записать сводку; без него - рядом с деревом или в стандартный вывод
+    summary: Option<PathBuf>,
+    /// Интервал повторного сканирования в режиме агента
+    every: Option<Duration>,
+    /// Адрес, на котором агент отдаёт метрики
+    metrics: Option<String>,
+    /// Кому отправлять отчёт после каждого сканирования
+    email: Vec<String>,
+}
+
+/// Разбирает ключи запуска; None - обычный запуск с окном
+pub fn parse_args() -> Option<Result<HeadlessArgs, String>> {
+    let mut root = None;
+    let mut export = None;
+    let mut ncdu = None;
+    let mut html = None;
+    let mut summary = None;
+    let mut every = None;
+    let mut metrics = None;
+    let mut email = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            SCAN_ARG => &mut root,
+            EXPORT_ARG => &mut export,
+            NCDU_ARG => &mut ncdu,
+            HTML_ARG => &mut html,
+            SUMMARY_ARG => &mut summary,
+            EVERY_ARG => &mut every,
+            METRICS_ARG => &mut metrics,
+            EMAIL_ARG => &mut email,
+            _ => continue,
+        };
+        match args.next() {
+            Some(value) => *slot = Some(value),
+            None => return Some(Err(format!("{} requires a value", arg))),
+        }
+    }
+
+    let Some(root) = root else {
+        let options = [&export, &ncdu, &html, &summary, &every, &metrics, &email];
+        if options.iter().any(|option| option.is_some()) {
+            let options = [EXPORT_ARG, NCDU_ARG, HTML_ARG, SUMMARY_ARG, EVERY_ARG, METRICS_ARG, EMAIL_ARG].join(", ");
+            return Some(Err(format!("{} require {} <path>", options, SCAN_ARG)));
+        }
+        return None;
+    };
+    let every = match every.map(|minutes| minutes.parse::<u64>()) {
+        Some(Ok(minutes)) if minutes > 0 => Some(Duration::from_secs(minutes * 60)),
+        Some(_) => return Some(Err(format!("{} expects a number of minutes", EVERY_ARG))),
+        None => None,
+    };
+    if metrics.is_some() && every.is_none() {
+        return Some(Err(format!("{} requires {} <minutes>", METRICS_ARG, EVERY_ARG)));
+    }
+    Some(Ok(HeadlessArgs {
+        root: crate::scan_path::normalize(&root),
+        export: export.map(PathBuf::from),
+        ncdu: ncdu.map(PathBuf::from),
+        html: html.map(PathBuf::from),
+        summary: summary.map(PathBuf::from),
+        every,
+        metrics,
+        // Несколько адресов - через запятую
+        email: email
+            .iter()
+            .flat_map(|list| list.split(','))
+            .map(|address| address.trim().to_string())
+            .filter(|address| !address.is_empty())
+            .collect(),
+    }))
+}
+
+#[derive(Serialize)]
+pub struct FolderSummary {
+    #[serde(serialize_with = "os_serde::serialize")]
+    pub path: PathBuf,
+    pub size: u64,
+    pub allocated: u64,
+    pub files: u64,
+}
+
+#[derive(Serialize)]
+pub struct ReadErrors {
+    #[serde(serialize_with = "os_serde::serialize")]
+    pub path: PathBuf,
+    pub skipped: u64,
+}
+
+/// Краткая сводка сканирования
+#[derive(Serialize)]
+pub struct Summary {
+    #[serde(serialize_with = "os_serde::serialize")]
+    pub root: PathBuf,
+    /// Время окончания, секунды Unix
+    pub finished: u64,
+    pub duration_secs: f64,
+    pub size: u64,
+    pub allocated: u64,
+    pub files: u64,
+    pub folders: u64,
+    /// Сколько элементов не удалось прочитать
+    pub skipped: u64,
+    pub top_folders: Vec<FolderSummary>,
+    pub errors: Vec<ReadErrors>,
+}
+
+impl Summary {
+    pub fn collect(root: &DirNode, duration: Duration) -> Self {
+        let data = PanelData::collect(root);
+        let mut folders: Vec<&DirNode> = root.children.iter().filter(|c| !c.is_file).collect();
+        folders.sort_unstable_by_key(|c| std::cmp::Reverse(c.size));
+        Self {
+            root: root.path.clone(),
+            finished: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            duration_secs: duration.as_secs_f64(),
+            size: root.size,
+            allocated: root.allocated,
+            files: data.files,
+            folders: data.folders,
+            skipped: root.skipped,
+            top_folders: folders
+                .into_iter()
+                .take(TOP_FOLDERS)
+                .map(|folder| FolderSummary {
+                    path: folder.path.clone(),
+                    size: folder.size,
+                    allocated: folder.allocated,
+                    files: folder.file_count(),
+                })
+                .collect(),
+            errors: data
+                .errors
+                .into_iter()
+                .take(ERRORS_LIMIT)
+                .map(|(path, skipped)| ReadErrors { path, skipped })
+                .collect(),
+        }
+    }
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut writer = std::io::BufWriter::new(file);
+    serde_json::to_writer(&mut writer, value).map_err(|e| format!("{}: {}", path.display(), e))?;
+    writer.flush().map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Одно сканирование с сохранёнными настройками
+fn scan(root: &str, config: &AppConfig) -> Result<(DirNode, Duration), String> {
+    // Файл контрольной точки принадлежит окну: сканирование без окна его не трогает
+    let checkpoint = checkpoint::Checkpoint::in_memory(PathBuf::from(root));
+    scan_with(root, config, Arc::new(AtomicBool::new(false)), checkpoint)
+}
+
+/// Сканирование в текущем потоке; `cancel` останавливает его из другого потока
+pub fn scan_with(
+    root: &str,
+    config: &AppConfig,
+    cancel: Arc<AtomicBool>,
+    checkpoint: checkpoint::Checkpoint,
+) -> Result<(DirNode, Duration), String> {
+    let root_path = Path::new(root);
+    let disk = get_disk_info(root);
+    let strategy = config.disk_strategies.get(&*disk.mount.to_string_lossy()).copied().unwrap_or_default();
+    let parallel = disk_probe::use_parallel(strategy, disk.kind, || disk_probe::probe_latency(root_path));
+
+    let options = ScanOptions {
+        threads: if parallel { rayon::current_num_threads() } else { 1 },
+        cluster_size: platform::cluster_size(root_path),
+        filter: Default::default(),
+        follow_links: config.follow_links,
+        link_targets: config.link_targets,
+        exclusions: crate::policy::exclusions(config),
+        excluded_sizes: Arc::new(config.excluded_sizes.clone()),
+        previous: None,
+        scan_first: config.scan_first.clone(),
+        // Выгрузки и отчёты читают дерево от больших к меньшим, как бы ни было отсортировано окно
+        sort: Default::default(),
+        encode_snapshot: false,
+    };
+    let progress = Arc::new(Mutex::new(ScanProgress::default()));
+    let result = Arc::new(Mutex::new(None));
+    let start = Instant::now();
+    scan_directory(root, progress, result.clone(), cancel, options, Arc::new(checkpoint));
+
+    let scanned = result.lock().unwrap().take();
+    match scanned {
+        Some(ScanResult::Complete(node, _)) => Ok((node, start.elapsed())),
+        Some(ScanResult::Error(error)) => Err(error),
+        _ => Err(format!("Scan did not complete: {}", root)),
+    }
+}
+
+/// Пишет полное дерево, если его просили, и сводку
+fn write_results(args: &HeadlessArgs, root: &DirNode, summary: &Summary, config: &AppConfig) -> Result<(), String> {
+    if let Some(path) = &args.export {
+        write_json(path, root)?;
+    }
+    if let Some(path) = &args.ncdu {
+        ncdu::export(path, root, SystemTime::now()).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    if let Some(path) = &args.html {
+        let translations = crate::pdf_report::report_translations(config);
+        html_report::save(path, root, &translations, config.palette).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    let summary_path = args.summary.clone().or_else(|| args.export.as_ref().map(|path| path.with_extension("summary.json")));
+    match summary_path {
+        Some(path) => write_json(&path, summary),
+        None => serde_json::to_string_pretty(summary).map(|json| println!("{}", json)).map_err(|e| e.to_string()),
+    }
+}
+
+/// Экранирование значения метки Prometheus
+fn label(value: &Path) -> String {
+    value.display().to_string().replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Метрики последнего сканирования в текстовом формате Prometheus
+fn metrics_text(root: &DirNode, summary: &Summary, config: &AppConfig) -> String {
+    let root_label = label(&root.path);
+    let mut text = String::new();
+    let mut gauge = |name: &str, help: &str, samples: &[(String, f64)]| {
+        let _ = writeln!(text, "# HELP {} {}", name, help);
+        let _ = writeln!(text, "# TYPE {} gauge", name);
+        for (labels, value) in samples {
+            let _ = writeln!(text, "{}{{{}}} {}", name, labels, value);
+        }
+    };
+    let root_sample = |value: f64| [(format!("root=\"{}\"", root_label), value)];
+
+    gauge("cedar_scanned_bytes", "Total size of the scanned folder", &root_sample(summary.size as f64));
+    gauge("cedar_scanned_allocated_bytes", "Space the scanned folder takes on disk", &root_sample(summary.allocated as f64));
+    gauge("cedar_scanned_files", "Files in the scanned folder", &root_sample(summary.files as f64));
+    gauge("cedar_scan_skipped_items", "Items that could not be read", &root_sample(summary.skipped as f64));
+    gauge("cedar_scan_duration_seconds", "Duration of the last scan", &root_sample(summary.duration_secs));
+    gauge("cedar_scan_timestamp_seconds", "Unix time the last scan finished", &root_sample(summary.finished as f64));
+
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    if let Some(disk) = find_disk(&disks, &root.path) {
+        let mount = format!("mount=\"{}\"", label(disk.mount_point()));
+        gauge("cedar_disk_free_bytes", "Free space on the disk holding the scanned folder", &[(mount.clone(), disk.available_space() as f64)]);
+        gauge("cedar_disk_size_bytes", "Size of the disk holding the scanned folder", &[(mount, disk.total_space() as f64)]);
+    }
+
+    let watched: Vec<(String, f64)> = config
+        .watchlist
+        .iter()
+        .filter_map(|watched| {
+            let node = root.find(&watched.path)?;
+            Some((format!("path=\"{}\"", label(&node.path)), node.size as f64))
+        })
+        .collect();
+    if !watched.is_empty() {
+        gauge("cedar_watched_folder_bytes", "Size of each watched folder", &watched);
+    }
+    text
+}
+
+/// Отдаёт метрики на любой запрос. Один номер порта означает только локальный адрес
+fn serve_metrics(address: &str, metrics: Arc<Mutex<String>>) -> Result<(), String> {
+    let address = match address.parse::<u16>() {
+        Ok(port) => format!("127.0.0.1:{}", port),
+        Err(_) => address.to_string(),
+    };
+    let listener = TcpListener::bind(&address).map_err(|e| format!("{}: {}", address, e))?;
+    thread::spawn(move || {
+        // Каждое соединение в своём потоке: молчащий клиент не задерживает остальных
+        for stream in listener.incoming().flatten() {
+            let body = metrics.lock().unwrap().clone();
+            thread::spawn(move || respond(stream, &body));
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, body: &str) -> std::io::Result<()> {
+    // Ответ один на любой путь - запрос достаточно принять
+    stream.set_read_timeout(Some(Duration::from_millis(500)))?;
+    let mut request = [0u8; 1024];
+    let _ = stream.read(&mut request);
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+/// Уведомления через веб-хук. О превышении предела сообщается один раз,
+/// пока папка снова не станет меньше него
+fn notify(config: &AppConfig, root: &DirNode, summary: &Summary, alerted: &mut HashSet<PathBuf>) {
+    let url = config.webhook_url.trim();
+    if url.is_empty() {
+        return;
+    }
+    if let Err(error) = webhook::scan_complete(url, summary) {
+        eprintln!("{}", error);
+    }
+
+    let over_limit = watchlist::over_limit(&config.watchlist, root);
+    alerted.retain(|path| over_limit.iter().any(|f| &f.path == path));
+    let new: Vec<webhook::OverLimit> = over_limit.into_iter().filter(|f| !alerted.contains(&f.path)).collect();
+    if new.is_empty() {
+        return;
+    }
+    match webhook::over_limit(url, &root.path, &new) {
+        Ok(()) => alerted.extend(new.into_iter().map(|f| f.path)),
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
+/// Сканирует без окна и пишет результаты; возвращает код завершения процесса.
+/// В режиме агента не возвращается: ошибки очередного сканирования только выводятся
+pub fn run(args: HeadlessArgs) -> i32 {
+    let metrics = Arc::new(Mutex::new(String::new()));
+    if let Some(address) = &args.metrics {
+        if let Err(error) = serve_metrics(address, metrics.clone()) {
+            eprintln!("{}", error);
+            return 1;
+        }
+    }
+
+    let mut alerted = HashSet::new();
+    loop {
+        // Настройки перечитываются: исключения и наблюдаемые папки могли поменять в окне
+        let config = CedarApp::load_config_from_file();
+        let result = scan(&args.root, &config).and_then(|(root, duration)| {
+            let summary = Summary::collect(&root, duration);
+            write_results(&args, &root, &summary, &config)?;
+            *metrics.lock().unwrap() = metrics_text(&root, &summary, &config);
+            notify(&config, &root, &summary, &mut alerted);
+            if let Some(policy) = snapshot_store::policy_for(&config.snapshot_policies, &root.path) {
+                match snapshot_store::save(policy, &root, SystemTime::now()) {
+                    Ok(saved) => {
+                        if let Some(error) = saved.prune_error {
+                            eprintln!("{}: {}", policy.root.display(), error);
+                        }
+                    }
+                    Err(error) => eprintln!("{}", error),
+                }
+            }
+            if !args.email.is_empty() {
+                let translations = crate::pdf_report::report_translations(&config);
+                if let Err(error) = smtp::send_report(&config.smtp, &args.email, &root, &summary, &translations, config.palette) {
+                    eprintln!("{}", error);
+                }
+            }
+            Ok(())
+        });
+
+        let Some(every) = args.every else {
+            return match result {
+                Ok(()) => 0,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    1
+                }
+            };
+        };
+        if let Err(error) = result {
+            eprintln!("{}", error);
+        }
+        thread::sleep(every);
+    }
+}
@@ -0,0 +1,41 @@
+// Раскраска по давности изменения: свежие данные "горячие", давно не
+// тронутые - "холодные"
+
+use crate::palette::Palette;
+use eframe::egui;
+use std::time::SystemTime;
+
+const SECONDS_PER_DAY: f32 = 24.0 * 60.0 * 60.0;
+/// Возраст, начиная с которого цвет уже не меняется (5 лет)
+const COLDEST_AGE_DAYS: f32 = 5.0 * 365.0;
+
+/// Доля шкалы от 0 (только что) до 1 (старше 5 лет); логарифмическая,
+/// чтобы дни, месяцы и годы различались одинаково хорошо
+fn age_fraction(modified: Option<SystemTime>, now: SystemTime) -> f32 {
+    let Some(modified) = modified else {
+        return 1.0;
+    };
+    let days = now.duration_since(modified).unwrap_or_default().as_secs_f32() / SECONDS_PER_DAY;
+    ((1.0 + days).ln() / (1.0 + COLDEST_AGE_DAYS).ln()).clamp(0.0, 1.0)
+}
+
+/// Цвет по давности изменения
+pub fn age_color(modified: Option<SystemTime>, now: SystemTime, palette: Palette, dark_mode: bool) -> egui::Color32 {
+    palette.ramp(age_fraction(modified, now), dark_mode)
+}
+
+/// Легенда шкалы: "новое" - полоса - "старое"
+pub fn legend(ui: &mut egui::Ui, new_text: &str, old_text: &str, palette: Palette, dark_mode: bool) {
+    const STEPS: usize = 24;
+    const STEP_WIDTH: f32 = 4.0;
+
+    ui.label(egui::RichText::new(new_text).small());
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(STEPS as f32 * STEP_WIDTH, 10.0), egui::Sense::hover());
+    for step in 0..STEPS {
+        let x = rect.left() + step as f32 * STEP_WIDTH;
+        let cell = egui::Rect::from_min_size(egui::pos2(x, rect.top()), egui::vec2(STEP_WIDTH, rect.height()));
+        let fraction = step as f32 / (STEPS - 1) as f32;
+        ui.painter().rect_filled(cell, 0.0, palette.ramp(fraction, dark_mode));
+    }
+    ui.label(egui::RichText::new(old_text).small());
+}
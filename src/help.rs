@@ -0,0 +1,84 @@
+// Справка: окно с сочетаниями клавиш и описанием представлений (F1 или меню)
+// и краткая подсказка по сочетаниям поверх окна по "?". Оба списка строятся
+// из реестра keymap
+
+use crate::keymap::{self, Command};
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+
+/// Представления и ключи их описаний
+const VIEW_MODES: [(&str, &str, &str); 4] = [
+    (regular::TREE_STRUCTURE, "view_tree", "help_view_tree"),
+    (regular::FLAME, "view_icicle", "help_view_icicle"),
+    (regular::SQUARES_FOUR, "view_treemap", "help_view_treemap"),
+    (regular::CHART_DONUT, "view_sunburst", "help_view_sunburst"),
+];
+
+impl CedarApp {
+    /// F1 - окно справки, "?" вне полей ввода - подсказка по сочетаниям
+    pub fn handle_help_keys(&mut self, ctx: &egui::Context) {
+        if keymap::consume(ctx, Command::Help) {
+            self.show_help = true;
+        }
+        if !ctx.wants_keyboard_input() && keymap::consume(ctx, Command::ShortcutOverlay) {
+            self.show_shortcut_overlay = !self.show_shortcut_overlay;
+        }
+    }
+
+    fn shortcut_grid(&self, ui: &mut egui::Ui, id: &str) {
+        egui::Grid::new(id).num_columns(2).striped(true).show(ui, |ui| {
+            for binding in keymap::BINDINGS {
+                ui.monospace(ui.ctx().format_shortcut(&binding.shortcut));
+                ui.label(self.translations.get(binding.description));
+                ui.end_row();
+            }
+        });
+    }
+
+    pub fn show_help_window(&mut self, ctx: &egui::Context) {
+        if !self.show_help {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new(format!("{} {}", regular::QUESTION, self.translations.get("help")))
+            .open(&mut open)
+            .default_width(460.0)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.heading(self.translations.get("help_shortcuts"));
+                self.shortcut_grid(ui, "help_shortcuts_grid");
+                ui.weak(self.translations.get("help_zoom_wheel"));
+
+                ui.add_space(8.0);
+                ui.heading(self.translations.get("help_views"));
+                for (icon, name, description) in VIEW_MODES {
+                    ui.strong(format!("{} {}", icon, self.translations.get(name)));
+                    ui.label(self.translations.get(description));
+                    ui.add_space(4.0);
+                }
+            });
+        self.show_help = open;
+    }
+
+    /// Подсказка по сочетаниям посередине окна; закрывается по Escape или щелчку
+    pub fn show_shortcut_overlay(&mut self, ctx: &egui::Context) {
+        if !self.show_shortcut_overlay {
+            return;
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.pointer.any_click()) {
+            self.show_shortcut_overlay = false;
+            return;
+        }
+        egui::Area::new(egui::Id::new("shortcut_overlay"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.heading(format!("{} {}", regular::KEYBOARD, self.translations.get("help_shortcuts")));
+                    self.shortcut_grid(ui, "overlay_shortcuts_grid");
+                    ui.weak(self.translations.get("help_overlay_close"));
+                });
+            });
+    }
+}
@@ -0,0 +1,147 @@
+// Отчёт в виде одного HTML-файла: сводка, карта с переходом по папкам и
+// раскрывающееся дерево. Данные встроены в страницу как JSON, карта рисуется
+// встроенным скриптом в SVG, так что файл можно отправить по почте тому, у
+// кого программы нет, и открыть в любом браузере без сети. В файл попадают
+// крупнейшие элементы; остальные в каждой папке сведены в одну строку
+
+use crate::chart::ChartStyle;
+use crate::export::{escape, hex};
+use crate::i18n::Translations;
+use crate::palette::Palette;
+use crate::pdf_report::report_translations;
+use crate::{CedarApp, DirNode};
+use serde_json::{json, Value};
+use std::collections::{BinaryHeap, HashSet};
+use std::path::Path;
+use std::time::SystemTime;
+
+const TEMPLATE: &str = include_str!("html_report.html");
+
+/// Сколько элементов попадает в отчёт; без предела файл большого диска не откроется в браузере
+const MAX_NODES: usize = 50_000;
+
+/// Сколько цветов у папок верхнего уровня карты
+const COLORS: usize = 12;
+
+/// Крупнейшие элементы дерева: обход от больших к меньшим, пока не набран предел
+fn largest_nodes(root: &DirNode) -> HashSet<*const DirNode> {
+    let mut included = HashSet::new();
+    let mut nodes: Vec<&DirNode> = root.children.iter().collect();
+    let mut queue: BinaryHeap<(u64, usize)> = nodes.iter().enumerate().map(|(index, node)| (node.size, index)).collect();
+    while let Some((_, index)) = queue.pop() {
+        if included.len() >= MAX_NODES {
+            break;
+        }
+        let node = nodes[index];
+        included.insert(node as *const DirNode);
+        for child in &node.children {
+            queue.push((child.size, nodes.len()));
+            nodes.push(child);
+        }
+    }
+    included
+}
+
+/// Элемент в JSON страницы: n - имя, s - размер, f - файлов, c - элементы папки, m - сколько элементов сведено
+fn to_json(node: &DirNode, included: &HashSet<*const DirNode>) -> Value {
+    let mut value = json!({ "n": node.display_name(), "s": node.size });
+    if node.is_file {
+        return value;
+    }
+    let mut children = Vec::new();
+    let (mut rest_count, mut rest_size) = (0u64, 0u64);
+    for child in &node.children {
+        if included.contains(&(child as *const DirNode)) {
+            children.push(to_json(child, included));
+        } else {
+            rest_count += 1;
+            rest_size += child.size;
+        }
+    }
+    // Раскладка карты рассчитана на порядок от больших к меньшим, а дерево в окне могло быть отсортировано иначе
+    children.sort_by_key(|child| std::cmp::Reverse(child["s"].as_u64()));
+    if rest_count > 0 {
+        children.push(json!({ "n": "", "s": rest_size, "m": rest_count }));
+    }
+    value["f"] = json!(node.file_count());
+    value["c"] = Value::Array(children);
+    value
+}
+
+/// Собирает страницу отчёта по дереву `root`
+pub fn report_html(root: &DirNode, t: &Translations, palette: Palette) -> String {
+    let included = largest_nodes(root);
+    let mut data = to_json(root, &included);
+    data["n"] = json!(root.path.display().to_string());
+
+    let summary = [
+        (t.get("pdf_report_folder"), root.path.display().to_string()),
+        (t.get("pdf_report_date"), t.format_date(SystemTime::now())),
+        (t.get("total_size"), t.format_size(root.size)),
+        (t.get("size_on_disk"), t.format_size(root.allocated)),
+        (t.get("files"), t.format_count(root.file_count())),
+    ];
+    let summary: String = summary
+        .iter()
+        .map(|(label, value)| format!("<tr><td>{}</td><td>{}</td></tr>\n", escape(label), escape(value)))
+        .collect();
+
+    // Цвета папок верхнего уровня - из палитры программы, в светлом оформлении
+    let colors: Vec<[String; 2]> = (0..COLORS)
+        .map(|index| {
+            let hue = index as f32 / COLORS as f32;
+            [hex(palette.category(hue, 0, false, false)), hex(palette.category(hue, 0, true, false))]
+        })
+        .collect();
+    let decimal = t.format_decimal(1.5, 1).chars().nth(1).unwrap_or('.');
+    let group = t.format_count(1000).chars().nth(1).unwrap_or(',');
+    let text = json!({
+        "decimal": decimal.to_string(),
+        "group": group.to_string(),
+        "separator": std::path::MAIN_SEPARATOR.to_string(),
+        "more": t.get("html_report_more"),
+        "colors": colors,
+        "merged": hex(ChartStyle { palette, dark_mode: false, age_now: None }.merged_fill()),
+    });
+
+    let title = t.get("pdf_report_title");
+    // Внутри <script> последовательность "</" закрыла бы его раньше времени
+    let script_json = |value: &Value| value.to_string().replace("</", "<\\/");
+    TEMPLATE
+        .replace("{{LANG}}", t.language().code())
+        .replace("{{TITLE}}", &escape(&title))
+        .replace("{{SUMMARY}}", &summary)
+        .replace("{{HINT}}", &escape(&t.get("html_report_hint")))
+        .replace("{{TREE}}", &escape(&t.get("html_report_tree")))
+        .replace("{{FOOTER}}", &escape(&format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))))
+        .replace("{{TEXT}}", &script_json(&text))
+        .replace("{{DATA}}", &script_json(&data))
+}
+
+/// Записывает отчёт; без окна - по ключу `--html`
+pub fn save(path: &Path, root: &DirNode, t: &Translations, palette: Palette) -> std::io::Result<()> {
+    std::fs::write(path, report_html(root, t, palette))
+}
+
+impl CedarApp {
+    /// Пункт меню "HTML-отчёт": выбор файла и сохранение
+    pub fn export_html_report(&mut self) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        let path = rfd::FileDialog::new()
+            .add_filter("HTML", &["html"])
+            .set_file_name(format!("{}.html", root.display_name()))
+            .save_file();
+        let Some(path) = path else {
+            return;
+        };
+        match save(&path, root, &report_translations(&self.config), self.config.palette) {
+            Ok(()) => self.set_status(self.translations.get_fmt("pdf_report_done", &[&path.display().to_string()])),
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("pdf_report_failed", &[&e.to_string()]));
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::time::SystemTime;
 
 // Встраиваем языковые файлы в бинарник
 const LANG_EN: &str = include_str!("../languages/en.json");
@@ -53,6 +54,45 @@ impl Language {
         }
     }
 
+    /// Разделитель дробной части
+    fn decimal_separator(&self) -> char {
+        match self {
+            Language::English | Language::Chinese => '.',
+            _ => ',',
+        }
+    }
+
+    /// Разделитель групп разрядов
+    fn group_separator(&self) -> char {
+        match self {
+            Language::English | Language::Chinese => ',',
+            Language::German | Language::Spanish => '.',
+            // Неразрывный пробел
+            Language::Russian | Language::French => '\u{a0}',
+        }
+    }
+
+    /// Формат даты (chrono)
+    fn date_format(&self) -> &'static str {
+        match self {
+            Language::English | Language::Chinese => "%Y-%m-%d",
+            Language::Russian | Language::German => "%d.%m.%Y",
+            Language::Spanish | Language::French => "%d/%m/%Y",
+        }
+    }
+
+    /// Встроенный JSON с переводами
+    fn content(&self) -> &'static str {
+        match self {
+            Language::English => LANG_EN,
+            Language::Russian => LANG_RU,
+            Language::German => LANG_DE,
+            Language::Chinese => LANG_ZH,
+            Language::Spanish => LANG_ES,
+            Language::French => LANG_FR,
+        }
+    }
+
     pub fn all() -> Vec<Language> {
         vec![
             Language::English,
@@ -65,37 +105,32 @@ impl Language {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct Translations {
+    language: Language,
     translations: HashMap<String, String>,
 }
 
 impl Translations {
     pub fn load(lang: Language) -> Self {
-        // Получаем встроенный JSON для выбранного языка
-        let content = match lang {
-            Language::English => LANG_EN,
-            Language::Russian => LANG_RU,
-            Language::German => LANG_DE,
-            Language::Chinese => LANG_ZH,
-            Language::Spanish => LANG_ES,
-            Language::French => LANG_FR,
-        };
-        
-        match serde_json::from_str(content) {
-            Ok(translations) => Self { translations },
+        match serde_json::from_str(lang.content()) {
+            Ok(translations) => Self { language: lang, translations },
             Err(e) => {
                 eprintln!("Failed to parse language {}: {}", lang.code(), e);
-                Self::fallback()
+                Self::fallback(lang)
             }
         }
     }
 
-    fn fallback() -> Self {
+    fn fallback(language: Language) -> Self {
         // Minimal English fallback
         let mut translations = HashMap::new();
         translations.insert("app_title".to_string(), "Cedar Folder Size Analyzer".to_string());
-        Self { translations }
+        Self { language, translations }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
     }
 
     pub fn get(&self, key: &str) -> String {
@@ -114,6 +149,61 @@ impl Translations {
         }
         result
     }
+
+    /// Размер с разделителем дробной части языка
+    pub fn format_size(&self, size: u64) -> String {
+        crate::format_size(size).replace('.', &self.language.decimal_separator().to_string())
+    }
+
+    /// Число с разделителями разрядов языка
+    pub fn format_count(&self, count: u64) -> String {
+        let digits = count.to_string();
+        let mut result = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                result.push(self.language.group_separator());
+            }
+            result.push(digit);
+        }
+        result
+    }
+
+    /// Дробное число с заданным числом знаков после запятой
+    pub fn format_decimal(&self, value: f64, decimals: usize) -> String {
+        format!("{:.*}", decimals, value).replace('.', &self.language.decimal_separator().to_string())
+    }
+
+    /// Доля в процентах с одним знаком после запятой
+    pub fn format_percent(&self, fraction: f64) -> String {
+        format!("{}%", self.format_decimal(fraction * 100.0, 1))
+    }
+
+    /// "N лет назад" с одним знаком после запятой
+    pub fn format_years_ago(&self, years: f64) -> String {
+        self.get_fmt("years_ago", &[&self.format_decimal(years, 1)])
+    }
+
+    /// Дата по местному времени
+    pub fn format_day(&self, time: SystemTime) -> String {
+        chrono::DateTime::<chrono::Local>::from(time).format(self.language.date_format()).to_string()
+    }
+
+    /// Время суток по местному времени
+    pub fn format_time(&self, time: SystemTime) -> String {
+        chrono::DateTime::<chrono::Local>::from(time).format("%H:%M").to_string()
+    }
+
+    /// Дата и время по местному времени
+    pub fn format_date(&self, time: SystemTime) -> String {
+        format!("{} {}", self.format_day(time), self.format_time(time))
+    }
+}
+
+/// Ключи встроенного языкового файла; пусто, если файл не разбирается
+pub fn keys(lang: Language) -> BTreeSet<String> {
+    serde_json::from_str::<HashMap<String, String>>(lang.content())
+        .map(|translations| translations.into_keys().collect())
+        .unwrap_or_default()
 }
 
 /// Определение системного языка
@@ -134,7 +224,7 @@ pub fn detect_system_theme() -> bool {
         
         // Проверяем реестр Windows для темы
         if let Ok(output) = Command::new("reg")
-            .args(&["query", "HKCU\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize", "/v", "AppsUseLightTheme"])
+            .args(["query", "HKCU\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize", "/v", "AppsUseLightTheme"])
             .output()
         {
             let output_str = String::from_utf8_lossy(&output.stdout);
@@ -148,6 +238,45 @@ pub fn detect_system_theme() -> bool {
         }
     }
     
+    // macOS: ключ AppleInterfaceStyle есть только у тёмной темы
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+        
+        if let Ok(output) = Command::new("defaults").args(["read", "-g", "AppleInterfaceStyle"]).output() {
+            return String::from_utf8_lossy(&output.stdout).trim() == "Dark";
+        }
+    }
+    
+    // Linux: предпочтение схемы GNOME, иначе имя темы GTK
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        use std::process::Command;
+        
+        let gsettings = |key: &str| {
+            Command::new("gsettings")
+                .args(["get", "org.gnome.desktop.interface", key])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_lowercase())
+        };
+        if let Some(scheme) = gsettings("color-scheme") {
+            if scheme.contains("dark") {
+                return true;
+            }
+            if scheme.contains("light") {
+                return false;
+            }
+        }
+        if let Ok(theme) = std::env::var("GTK_THEME") {
+            return theme.to_lowercase().contains("dark");
+        }
+        if let Some(theme) = gsettings("gtk-theme") {
+            return theme.contains("dark");
+        }
+    }
+    
     // По умолчанию светлая тема
     false
 }
@@ -0,0 +1,108 @@
+// Окно покрытия переводов для разработчиков, открывается с `--debug-translations`:
+// каких ключей из en.json не хватает в каждом встроенном языке, какие ключи
+// лишние и какие не встречаются в исходниках. Исходники просматривает build.rs
+
+use crate::i18n::{self, Language};
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+use std::collections::BTreeSet;
+
+const FLAG: &str = "--debug-translations";
+
+/// Строковые литералы исходников, похожие на ключи
+const SOURCE_STRINGS: &str = include_str!(concat!(env!("OUT_DIR"), "/source_strings.txt"));
+
+struct LanguageCoverage {
+    language: Language,
+    /// Есть в en.json, нет в языке
+    missing: Vec<String>,
+    /// Есть в языке, нет в en.json
+    extra: Vec<String>,
+}
+
+pub struct Coverage {
+    languages: Vec<LanguageCoverage>,
+    /// Ключи en.json, которых нет в исходниках; ключи, собираемые в коде из частей, тоже попадут сюда
+    unused: Vec<String>,
+}
+
+impl Coverage {
+    fn collect() -> Self {
+        let english = i18n::keys(Language::English);
+        let languages = Language::all()
+            .into_iter()
+            .filter(|&language| language != Language::English)
+            .map(|language| {
+                let keys = i18n::keys(language);
+                LanguageCoverage {
+                    language,
+                    missing: english.difference(&keys).cloned().collect(),
+                    extra: keys.difference(&english).cloned().collect(),
+                }
+            })
+            .collect();
+        let used: BTreeSet<&str> = SOURCE_STRINGS.lines().collect();
+        let unused = english.iter().filter(|key| !used.contains(key.as_str())).cloned().collect();
+        Self { languages, unused }
+    }
+
+    /// Данные окна, если программа запущена с флагом
+    pub fn requested() -> Option<Self> {
+        std::env::args().any(|arg| arg == FLAG).then(Self::collect)
+    }
+}
+
+fn key_list(ui: &mut egui::Ui, id: impl std::hash::Hash, title: String, keys: &[String]) {
+    if keys.is_empty() {
+        return;
+    }
+    egui::CollapsingHeader::new(title).id_salt(id).show(ui, |ui| {
+        for key in keys {
+            ui.monospace(key);
+        }
+    });
+}
+
+impl CedarApp {
+    /// Окно покрытия переводов; строки интерфейса в нём не переводятся - оно для разработчиков
+    pub fn show_translation_coverage(&mut self, ctx: &egui::Context) {
+        let Some(coverage) = &self.translation_coverage else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("{} Translation coverage", regular::TRANSLATE))
+            .open(&mut open)
+            .default_size([420.0, 480.0])
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let warning = self.config.palette.status_text(crate::palette::Status::Warning, self.config.dark_mode);
+                for entry in &coverage.languages {
+                    let name = format!("{} ({})", entry.language.name(), entry.language.code());
+                    if entry.missing.is_empty() && entry.extra.is_empty() {
+                        ui.label(format!("{} {}", regular::CHECK, name));
+                        continue;
+                    }
+                    ui.colored_label(
+                        warning,
+                        format!("{} {}: {} missing, {} extra", regular::WARNING, name, entry.missing.len(), entry.extra.len()),
+                    );
+                    ui.indent(entry.language.code(), |ui| {
+                        key_list(ui, ("missing", entry.language.code()), "Missing".to_string(), &entry.missing);
+                        key_list(ui, ("extra", entry.language.code()), "Not in en.json".to_string(), &entry.extra);
+                    });
+                }
+
+                ui.separator();
+                if coverage.unused.is_empty() {
+                    ui.label(format!("{} No unused keys", regular::CHECK));
+                } else {
+                    ui.colored_label(warning, format!("{} {} keys not found in the sources", regular::WARNING, coverage.unused.len()));
+                    key_list(ui, "unused", "Unused".to_string(), &coverage.unused);
+                }
+            });
+        if !open {
+            self.translation_coverage = None;
+        }
+    }
+}
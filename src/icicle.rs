@@ -0,0 +1,168 @@
+// Представление "icicle" (перевёрнутый flame graph): каждый уровень дерева -
+// ряд блоков, ширина которых пропорциональна размеру
+
+use crate::chart::{self, Block, ChartStyle, Label};
+use crate::{format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
+
+const ROW_HEIGHT: f32 = 24.0;
+/// Блоки уже этой ширины не рисуются - их всё равно не разглядеть
+const MIN_BLOCK_WIDTH: f32 = 1.0;
+const MIN_LABEL_WIDTH: f32 = 40.0;
+
+/// Оттенок ветки по имени верхнего предка, чтобы цвета не менялись между кадрами
+pub fn name_hue(name: &str) -> f32 {
+    let hash = name.bytes().fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    (hash % 360) as f32 / 360.0
+}
+
+/// Состояние одного прохода раскладки
+struct IcicleLayout<'a, 's> {
+    blocks: Vec<Block<'a>>,
+    style: &'s ChartStyle,
+    bounds: egui::Rect,
+}
+
+fn layout_node<'a>(layout: &mut IcicleLayout<'a, '_>, node: &'a DirNode, x: f32, width: f32, depth: usize, hue: f32) {
+    let y = layout.bounds.top() + depth as f32 * ROW_HEIGHT;
+    if width < MIN_BLOCK_WIDTH || y + ROW_HEIGHT > layout.bounds.bottom() {
+        return;
+    }
+
+    let rect = egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(width, ROW_HEIGHT)).shrink(0.5);
+    let label = (width >= MIN_LABEL_WIDTH).then(|| Label {
+        pos: rect.left_center() + egui::vec2(4.0, 0.0),
+        align: egui::Align2::LEFT_CENTER,
+        text: format!("{} ({})", node.display_name(), format_size(node.size)),
+    });
+    layout.blocks.push(Block {
+        node,
+        rect,
+        fill: layout.style.fill(node, hue, depth),
+        label,
+        merged: None,
+    });
+
+    if node.size == 0 {
+        return;
+    }
+
+    let mut child_x = x;
+    for child in &node.children {
+        let child_width = width * (child.size as f64 / node.size as f64) as f32;
+        // На первом уровне под фокусом каждая ветка получает свой оттенок
+        let child_hue = if depth == 0 { name_hue(&child.display_name()) } else { hue };
+        layout_node(layout, child, child_x, child_width, depth + 1, child_hue);
+        child_x += child_width;
+    }
+}
+
+/// Раскладка поддерева `focus` в прямоугольнике `rect`: уровень дерева - ряд блоков
+pub fn layout<'a>(focus: &'a DirNode, rect: egui::Rect, style: &ChartStyle) -> Vec<Block<'a>> {
+    let mut layout = IcicleLayout {
+        blocks: Vec::new(),
+        style,
+        bounds: rect,
+    };
+    layout_node(&mut layout, focus, rect.left(), rect.width(), 0, name_hue(&focus.display_name()));
+    layout.blocks
+}
+
+/// Цепочка узлов от корня до фокуса - для навигационной строки
+fn breadcrumbs<'a>(root: &'a DirNode, focus: &Path) -> Vec<&'a DirNode> {
+    let mut chain = vec![root];
+    let mut current = root;
+    while current.path != focus {
+        match current.children.iter().find(|c| focus.starts_with(&c.path)) {
+            Some(child) => {
+                chain.push(child);
+                current = child;
+            }
+            None => break,
+        }
+    }
+    chain
+}
+
+/// Навигационная строка от корня до фокуса с подсказкой справа; возвращает выбранный уровень
+pub fn breadcrumb_bar(ui: &mut egui::Ui, root: &DirNode, focus: &Path, hint: &str) -> Option<PathBuf> {
+    let mut new_focus = None;
+    ui.horizontal_wrapped(|ui| {
+        for (index, node) in breadcrumbs(root, focus).iter().enumerate() {
+            if index > 0 {
+                ui.label(regular::CARET_RIGHT);
+            }
+            if ui.selectable_label(node.path == focus, node.display_name()).clicked() {
+                new_focus = Some(node.path.clone());
+            }
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(egui::RichText::new(hint).small().color(ui.visuals().weak_text_color()));
+        });
+    });
+    new_focus
+}
+
+impl CedarApp {
+    /// Icicle-представление текущего дерева
+    pub fn show_icicle_view(&mut self, ui: &mut egui::Ui) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+
+        // Фокус общий для графических представлений; удалённый или чужой путь сбрасывает его на корень
+        let focus = self
+            .view_focus
+            .as_ref()
+            .and_then(|p| root.find(p))
+            .unwrap_or(root);
+
+        let mut clicked_path: Option<PathBuf> = None;
+        let mut new_focus = breadcrumb_bar(ui, root, &focus.path, &self.translations.get("icicle_hint"));
+
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::click());
+        let style = self.chart_style();
+        let mut blocks = layout(focus, rect, &style);
+        let hovered = response.hover_pos().and_then(|pos| chart::block_at(&blocks, pos));
+        if let Some(index) = hovered {
+            blocks[index].fill = blocks[index].fill.gamma_multiply(1.2);
+        }
+        let painter = ui.painter_at(rect);
+        chart::paint(&painter, &blocks, &style);
+        if let Some(block) = blocks.iter().find(|b| self.selected_path.as_deref() == Some(b.node.path.as_path())) {
+            painter.rect_stroke(block.rect, 2.0, egui::Stroke::new(2.0, style.text_color()));
+        }
+        let hovered = hovered.map(|index| blocks[index].node);
+
+        if let Some(node) = hovered {
+            // Двойной щелчок по папке - фокус на неё, по текущему фокусу - уровень вверх
+            if response.double_clicked() {
+                if node.path == focus.path {
+                    new_focus = node.path.parent().map(Path::to_path_buf);
+                } else if !node.is_file {
+                    new_focus = Some(node.path.clone());
+                }
+            } else if response.clicked() {
+                clicked_path = Some(node.path.clone());
+            }
+
+            response.on_hover_ui_at_pointer(|ui| {
+                ui.strong(node.path.display().to_string());
+                ui.label(format_size(node.size));
+            });
+        }
+
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if let Some(path) = new_focus {
+            self.view_focus = Some(path);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+// Встроенные SVG-иконки. Растеризуются в фоновом потоке под текущий масштаб
+// экрана, а не при запуске; готовые текстуры кэшируются по размеру в пикселях,
+// так что при переносе окна между мониторами они не рисуются заново. Пока иконка
+// не готова или если её SVG не разобрался, вместо неё выводится глиф Phosphor.
+// Цвет монохромных иконок задаётся при отрисовке, поэтому от темы они не зависят
+
+use crate::rasterize_svg;
+use eframe::egui;
+use egui_phosphor::regular;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+const CEDAR_SVG: &[u8] = include_bytes!("icons/cedar.svg");
+const FOLDER_SVG: &[u8] = include_bytes!("icons/folder.svg");
+const FILE_SVG: &[u8] = include_bytes!("icons/file.svg");
+const SEARCH_SVG: &[u8] = include_bytes!("icons/search.svg");
+const STOP_SVG: &[u8] = include_bytes!("icons/stop.svg");
+
+/// Размер иконок и логотипа в точках интерфейса
+const ICON_SIZE: u32 = 20;
+const LOGO_SIZE: u32 = 80;
+/// Размер иконки окна в пикселях
+const WINDOW_ICON_SIZE: u32 = 256;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Icon {
+    Folder,
+    File,
+    Search,
+    Stop,
+    Cedar,
+}
+
+impl Icon {
+    const ALL: [Icon; 5] = [Icon::Folder, Icon::File, Icon::Search, Icon::Stop, Icon::Cedar];
+
+    fn svg(self) -> &'static [u8] {
+        match self {
+            Icon::Folder => FOLDER_SVG,
+            Icon::File => FILE_SVG,
+            Icon::Search => SEARCH_SVG,
+            Icon::Stop => STOP_SVG,
+            Icon::Cedar => CEDAR_SVG,
+        }
+    }
+
+    fn points(self) -> u32 {
+        if self == Icon::Cedar {
+            LOGO_SIZE
+        } else {
+            ICON_SIZE
+        }
+    }
+
+    /// Глиф вместо иконки, которая ещё не готова или не разобралась
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Icon::Folder => regular::FOLDER,
+            Icon::File => regular::FILE,
+            Icon::Search => regular::MAGNIFYING_GLASS,
+            Icon::Stop => regular::STOP,
+            Icon::Cedar => regular::TREE_EVERGREEN,
+        }
+    }
+}
+
+enum Slot {
+    Pending,
+    Ready(egui::TextureHandle),
+    Failed,
+}
+
+/// Готовое изображение из фонового потока; None - SVG не разобрался
+type Rendered = (Icon, u32, Option<egui::ColorImage>);
+
+pub struct Icons {
+    /// Текстуры по иконке и размеру в пикселях
+    slots: HashMap<(Icon, u32), Slot>,
+    pixels_per_point: f32,
+    sender: Sender<Rendered>,
+    receiver: Receiver<Rendered>,
+}
+
+impl Icons {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            slots: HashMap::new(),
+            pixels_per_point: 0.0,
+            sender,
+            receiver,
+        }
+    }
+
+    fn pixels(&self, icon: Icon) -> u32 {
+        (icon.points() as f32 * self.pixels_per_point).ceil() as u32
+    }
+
+    /// Заказывает иконки под текущий масштаб и забирает готовые; вызывается каждый кадр
+    pub fn update(&mut self, ctx: &egui::Context) {
+        while let Ok((icon, pixels, image)) = self.receiver.try_recv() {
+            let slot = match image {
+                Some(image) => {
+                    let name = format!("icon_{:?}_{}", icon, pixels);
+                    Slot::Ready(ctx.load_texture(name, image, egui::TextureOptions::LINEAR))
+                }
+                None => {
+                    eprintln!("Failed to render built-in icon {:?}", icon);
+                    Slot::Failed
+                }
+            };
+            self.slots.insert((icon, pixels), slot);
+        }
+
+        let pixels_per_point = ctx.pixels_per_point();
+        if (self.pixels_per_point - pixels_per_point).abs() < 0.01 {
+            return;
+        }
+        self.pixels_per_point = pixels_per_point;
+        let wanted: Vec<(Icon, u32)> = Icon::ALL
+            .into_iter()
+            .map(|icon| (icon, self.pixels(icon)))
+            .filter(|key| !self.slots.contains_key(key))
+            .collect();
+        if wanted.is_empty() {
+            return;
+        }
+        for key in &wanted {
+            self.slots.insert(*key, Slot::Pending);
+        }
+        let sender = self.sender.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            for (icon, pixels) in wanted {
+                let _ = sender.send((icon, pixels, rasterize_svg(icon.svg(), pixels)));
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Изображение иконки: под текущий масштаб, а пока его нет - любого готового размера.
+    /// None - выводить глиф
+    pub fn image(&self, icon: Icon) -> Option<egui::Image<'static>> {
+        let texture = |pixels: u32| match self.slots.get(&(icon, pixels)) {
+            Some(Slot::Ready(texture)) => Some(texture),
+            _ => None,
+        };
+        let texture = texture(self.pixels(icon)).or_else(|| {
+            self.slots
+                .iter()
+                .filter(|((other, _), _)| *other == icon)
+                .find_map(|(&(_, pixels), _)| texture(pixels))
+        })?;
+        Some(egui::Image::new(texture))
+    }
+}
+
+/// Кнопка с иконкой цвета текста и подписью
+impl Icons {
+    pub fn button<'a>(&self, ui: &egui::Ui, icon: Icon, text: &str) -> egui::Button<'a> {
+        match self.image(icon) {
+            Some(image) => egui::Button::image_and_text(
+                image.max_size(egui::vec2(16.0, 16.0)).tint(ui.visuals().text_color()),
+                text.to_string(),
+            ),
+            None => egui::Button::new(format!("{} {}", icon.glyph(), text)),
+        }
+    }
+}
+
+/// Иконка окна, вписанная по центру; без неё окно получит системную
+pub fn window_icon() -> Option<egui::IconData> {
+    let tree = usvg::Tree::from_data(CEDAR_SVG, &usvg::Options::default()).ok()?;
+    let size = WINDOW_ICON_SIZE;
+    let svg_size = tree.size();
+    let scale = (size as f32 / svg_size.width()).min(size as f32 / svg_size.height());
+    let mut pixmap = tiny_skia::Pixmap::new(size, size)?;
+    let offset_x = (size as f32 - svg_size.width() * scale) / 2.0;
+    let offset_y = (size as f32 - svg_size.height() * scale) / 2.0;
+    let transform = tiny_skia::Transform::from_translate(offset_x, offset_y).post_scale(scale, scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    Some(egui::IconData {
+        rgba: pixmap.data().to_vec(),
+        width: size,
+        height: size,
+    })
+}
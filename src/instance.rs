@@ -0,0 +1,129 @@
+// Открытие снимков из файлового менеджера. Установщик связывает файлы .cedar с
+// программой, и двойной щелчок запускает её с путём к снимку. Если окно уже
+// открыто, новый процесс передаёт путь ему и сразу завершается: окно слушает
+// локальный порт, номер которого вместе со случайным ключом лежит в файле
+// instance в папке настроек. Без ключа путь не принимается - порт виден всем
+// программам компьютера, а файл только этому пользователю
+
+use crate::snapshot;
+use eframe::egui;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const INSTANCE_FILE: &str = "instance";
+const TIMEOUT: Duration = Duration::from_secs(2);
+const ACCEPTED: &str = "ok";
+
+fn instance_file() -> Option<PathBuf> {
+    crate::app_paths::get().config_dir.as_ref().map(|dir| dir.join(INSTANCE_FILE))
+}
+
+/// Снимок из командной строки: первый аргумент, который является файлом .cedar
+pub fn file_arg() -> Option<PathBuf> {
+    std::env::args_os()
+        .skip(1)
+        .map(PathBuf::from)
+        .find(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(snapshot::EXTENSION)) && path.is_file())
+}
+
+/// Передаёт снимок уже открытому окну; false - окна нет или оно не ответило
+pub fn send(path: &Path) -> bool {
+    let Some(contents) = instance_file().and_then(|file| std::fs::read_to_string(file).ok()) else {
+        return false;
+    };
+    let mut parts = contents.split_whitespace();
+    let (Some(Ok(port)), Some(key)) = (parts.next().map(str::parse::<u16>), parts.next()) else {
+        return false;
+    };
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let send = || -> std::io::Result<bool> {
+        let mut stream = TcpStream::connect_timeout(&SocketAddr::from((Ipv4Addr::LOCALHOST, port)), TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        writeln!(stream, "{}\n{}", key, path.display())?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        Ok(reply.trim() == ACCEPTED)
+    };
+    // Окно, открытое раньше, само выйти на передний план не может - разрешаем ему
+    #[cfg(windows)]
+    unsafe {
+        windows_sys::Win32::UI::WindowsAndMessaging::AllowSetForegroundWindow(
+            windows_sys::Win32::UI::WindowsAndMessaging::ASFW_ANY,
+        );
+    }
+    send().unwrap_or(false)
+}
+
+/// Приём путей от следующих запусков; файл instance удаляется при закрытии окна
+pub struct Listener {
+    paths: mpsc::Receiver<PathBuf>,
+    port: u16,
+}
+
+impl Listener {
+    /// Запускает приём в фоне; None - порт не открылся или файл не записался
+    pub fn start(ctx: &egui::Context) -> Option<Self> {
+        let file = instance_file()?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).ok()?;
+        let port = listener.local_addr().ok()?.port();
+        let key = {
+            let state = std::collections::hash_map::RandomState::new();
+            let mut hasher = state.build_hasher();
+            hasher.write_u32(std::process::id());
+            format!("{:016x}{:016x}", hasher.finish(), state.hash_one(port))
+        };
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir).ok()?;
+        }
+        std::fs::write(&file, format!("{} {}", port, key)).ok()?;
+
+        let (sender, paths) = mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(path) = receive(stream, &key) {
+                    if sender.send(path).is_err() {
+                        break;
+                    }
+                    ctx.request_repaint();
+                }
+            }
+        });
+        Some(Self { paths, port })
+    }
+
+    /// Снимки, присланные с прошлого кадра
+    pub fn received(&self) -> Vec<PathBuf> {
+        self.paths.try_iter().collect()
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        // Файл мог уже переписать другой экземпляр - удаляем только свой
+        let Some(file) = instance_file() else {
+            return;
+        };
+        let ours = std::fs::read_to_string(&file)
+            .is_ok_and(|contents| contents.split_whitespace().next() == Some(self.port.to_string().as_str()));
+        if ours {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
+
+/// Читает ключ и путь; отвечает, только если ключ совпал
+fn receive(mut stream: TcpStream, key: &str) -> Option<PathBuf> {
+    stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+    let mut lines = BufReader::new(stream.try_clone().ok()?).lines();
+    if lines.next()?.ok()? != key {
+        return None;
+    }
+    let path = PathBuf::from(lines.next()?.ok()?);
+    writeln!(stream, "{}", ACCEPTED).ok()?;
+    Some(path)
+}
@@ -0,0 +1,344 @@
+// Список переходов кнопки на панели задач Windows: недавние папки сканирования
+// и задача "Сканировать путь из буфера обмена". Пункты запускают программу с
+// `--open <папка>` или `--scan-clipboard`, и сканирование начинается сразу
+
+use crate::i18n::Translations;
+use std::path::{Path, PathBuf};
+
+const OPEN_ARG: &str = "--open";
+const CLIPBOARD_ARG: &str = "--scan-clipboard";
+/// Сколько недавних папок помнится и попадает в список
+pub const RECENT_LIMIT: usize = 8;
+
+/// Папка, которую надо сканировать сразу после запуска из списка переходов
+pub fn launch_path() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let path = match arg.as_str() {
+            OPEN_ARG => args.next(),
+            CLIPBOARD_ARG => clipboard_text().and_then(|text| text.lines().next().map(str::to_string)),
+            _ => continue,
+        };
+        return path.map(|path| crate::scan_path::normalize(&path)).filter(|path| !path.is_empty());
+    }
+    None
+}
+
+/// Поднимает папку в начало списка недавних
+pub fn remember(recent: &mut Vec<PathBuf>, root: &Path) {
+    recent.retain(|path| path != root);
+    recent.insert(0, root.to_path_buf());
+    recent.truncate(RECENT_LIMIT);
+}
+
+/// Подписи списка на языке интерфейса
+#[cfg(windows)]
+struct Labels {
+    recent: String,
+    scan_clipboard: String,
+}
+
+/// Перестраивает список переходов в фоне; вне Windows ничего не делает
+pub fn update(recent: Vec<PathBuf>, translations: &Translations) {
+    #[cfg(windows)]
+    {
+        let labels = Labels {
+            recent: translations.get("jump_recent"),
+            scan_clipboard: translations.get("jump_scan_clipboard"),
+        };
+        std::thread::spawn(move || {
+            if let Err(e) = windows::build(&recent, &labels) {
+                eprintln!("Failed to update jump list: {:#x}", e);
+            }
+        });
+    }
+    #[cfg(not(windows))]
+    let _ = (recent, translations);
+}
+
+fn clipboard_text() -> Option<String> {
+    #[cfg(windows)]
+    {
+        windows::clipboard_text()
+    }
+    #[cfg(not(windows))]
+    {
+        None
+    }
+}
+
+/// Аргумент командной строки в кавычках; обратные слэши перед закрывающей
+/// кавычкой удваиваются, иначе "D:\" прочиталось бы как D:"
+#[cfg(windows)]
+fn quote(arg: &str) -> String {
+    let slashes = arg.len() - arg.trim_end_matches('\\').len();
+    format!("\"{}{}\"", arg, "\\".repeat(slashes))
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{quote, Labels, CLIPBOARD_ARG, OPEN_ARG};
+    use std::ffi::c_void;
+    use std::path::PathBuf;
+    use std::ptr::{null, null_mut};
+    use windows_sys::core::{GUID, HRESULT};
+    use windows_sys::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED};
+    use windows_sys::Win32::System::DataExchange::{CloseClipboard, GetClipboardData, OpenClipboard};
+    use windows_sys::Win32::System::Memory::{GlobalLock, GlobalUnlock};
+    use windows_sys::Win32::System::Ole::CF_UNICODETEXT;
+    use windows_sys::Win32::System::Variant::VT_LPWSTR;
+    use windows_sys::Win32::UI::Shell::{DestinationList, EnumerableObjectCollection, ShellLink};
+
+    const IID_ICUSTOMDESTINATIONLIST: GUID = GUID::from_u128(0x6332debf_87b5_4670_90c0_5e57b408a49e);
+    const IID_IOBJECTARRAY: GUID = GUID::from_u128(0x92ca9dcd_5622_4bba_a805_5e9f541bd8c9);
+    const IID_IOBJECTCOLLECTION: GUID = GUID::from_u128(0x5632b1a4_e38a_400a_928a_d4cd63230295);
+    const IID_ISHELLLINKW: GUID = GUID::from_u128(0x000214f9_0000_0000_c000_000000000046);
+    const IID_IPROPERTYSTORE: GUID = GUID::from_u128(0x886d8eeb_8cf2_4446_8d02_cdba1dbdcf99);
+
+    /// PKEY_Title: заголовок пункта списка
+    #[repr(C)]
+    struct PropertyKey {
+        fmtid: GUID,
+        pid: u32,
+    }
+    const PKEY_TITLE: PropertyKey = PropertyKey {
+        fmtid: GUID::from_u128(0xf29f85e0_4ff9_1068_ab91_08002b27b3d9),
+        pid: 2,
+    };
+
+    /// PROPVARIANT со строкой - другие типы не нужны
+    #[repr(C)]
+    struct PropVariant {
+        vt: u16,
+        reserved: [u16; 3],
+        text: *const u16,
+        padding: usize,
+    }
+
+    type Method = usize;
+
+    /// Начало любой таблицы методов - IUnknown
+    #[repr(C)]
+    struct Unknown {
+        query_interface: unsafe extern "system" fn(*mut c_void, *const GUID, *mut *mut c_void) -> HRESULT,
+        add_ref: Method,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+    }
+
+    #[repr(C)]
+    struct CustomDestinationList {
+        unknown: Unknown,
+        set_app_id: Method,
+        begin_list: unsafe extern "system" fn(*mut c_void, *mut u32, *const GUID, *mut *mut c_void) -> HRESULT,
+        append_category: unsafe extern "system" fn(*mut c_void, *const u16, *mut c_void) -> HRESULT,
+        append_known_category: Method,
+        add_user_tasks: unsafe extern "system" fn(*mut c_void, *mut c_void) -> HRESULT,
+        commit_list: unsafe extern "system" fn(*mut c_void) -> HRESULT,
+    }
+
+    #[repr(C)]
+    struct ObjectArray {
+        unknown: Unknown,
+        get_count: unsafe extern "system" fn(*mut c_void, *mut u32) -> HRESULT,
+        get_at: unsafe extern "system" fn(*mut c_void, u32, *const GUID, *mut *mut c_void) -> HRESULT,
+    }
+
+    #[repr(C)]
+    struct ObjectCollection {
+        array: ObjectArray,
+        add_object: unsafe extern "system" fn(*mut c_void, *mut c_void) -> HRESULT,
+    }
+
+    #[repr(C)]
+    struct ShellLinkW {
+        unknown: Unknown,
+        get_path: Method,
+        get_id_list: Method,
+        set_id_list: Method,
+        get_description: Method,
+        set_description: unsafe extern "system" fn(*mut c_void, *const u16) -> HRESULT,
+        get_working_directory: Method,
+        set_working_directory: Method,
+        get_arguments: unsafe extern "system" fn(*mut c_void, *mut u16, i32) -> HRESULT,
+        set_arguments: unsafe extern "system" fn(*mut c_void, *const u16) -> HRESULT,
+        get_hotkey: Method,
+        set_hotkey: Method,
+        get_show_cmd: Method,
+        set_show_cmd: Method,
+        get_icon_location: Method,
+        set_icon_location: unsafe extern "system" fn(*mut c_void, *const u16, i32) -> HRESULT,
+        set_relative_path: Method,
+        resolve: Method,
+        set_path: unsafe extern "system" fn(*mut c_void, *const u16) -> HRESULT,
+    }
+
+    #[repr(C)]
+    struct PropertyStore {
+        unknown: Unknown,
+        get_count: Method,
+        get_at: Method,
+        get_value: Method,
+        set_value: unsafe extern "system" fn(*mut c_void, *const PropertyKey, *const PropVariant) -> HRESULT,
+        commit: unsafe extern "system" fn(*mut c_void) -> HRESULT,
+    }
+
+    /// Указатель на COM-объект; освобождается при выходе из области видимости
+    struct Object(*mut c_void);
+
+    impl Object {
+        fn create(class: &GUID, interface: &GUID) -> Result<Self, HRESULT> {
+            let mut this = null_mut();
+            check(unsafe { CoCreateInstance(class, null_mut(), CLSCTX_INPROC_SERVER, interface, &mut this) })?;
+            Ok(Self(this))
+        }
+
+        /// Таблица методов интерфейса, с которым объект получен
+        fn vtbl<T>(&self) -> &T {
+            unsafe { &**(self.0 as *const *const T) }
+        }
+
+        fn cast(&self, interface: &GUID) -> Result<Self, HRESULT> {
+            let mut other = null_mut();
+            check(unsafe { (self.vtbl::<Unknown>().query_interface)(self.0, interface, &mut other) })?;
+            Ok(Self(other))
+        }
+    }
+
+    impl Drop for Object {
+        fn drop(&mut self) {
+            if !self.0.is_null() {
+                unsafe { (self.vtbl::<Unknown>().release)(self.0) };
+            }
+        }
+    }
+
+    fn check(result: HRESULT) -> Result<(), HRESULT> {
+        if result < 0 {
+            Err(result)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    /// Ярлык на эту программу с аргументами
+    fn link(exe: &[u16], arguments: &str, title: &str, description: &str) -> Result<Object, HRESULT> {
+        let link = Object::create(&ShellLink, &IID_ISHELLLINKW)?;
+        let vtbl = link.vtbl::<ShellLinkW>();
+        unsafe {
+            check((vtbl.set_path)(link.0, exe.as_ptr()))?;
+            check((vtbl.set_arguments)(link.0, wide(arguments).as_ptr()))?;
+            check((vtbl.set_description)(link.0, wide(description).as_ptr()))?;
+            check((vtbl.set_icon_location)(link.0, exe.as_ptr(), 0))?;
+        }
+
+        let title = wide(title);
+        let value = PropVariant {
+            vt: VT_LPWSTR,
+            reserved: [0; 3],
+            text: title.as_ptr(),
+            padding: 0,
+        };
+        let store = link.cast(&IID_IPROPERTYSTORE)?;
+        let vtbl = store.vtbl::<PropertyStore>();
+        unsafe {
+            check((vtbl.set_value)(store.0, &PKEY_TITLE, &value))?;
+            check((vtbl.commit)(store.0))?;
+        }
+        Ok(link)
+    }
+
+    /// Аргументы пунктов, которые пользователь убрал из списка: их нельзя добавлять снова
+    fn removed_arguments(removed: &Object) -> Vec<String> {
+        let vtbl = removed.vtbl::<ObjectArray>();
+        let mut count = 0;
+        if unsafe { (vtbl.get_count)(removed.0, &mut count) } < 0 {
+            return Vec::new();
+        }
+        (0..count)
+            .filter_map(|i| {
+                let mut item = null_mut();
+                check(unsafe { (vtbl.get_at)(removed.0, i, &IID_ISHELLLINKW, &mut item) }).ok()?;
+                let item = Object(item);
+                let mut buffer = [0u16; 1024];
+                check(unsafe { (item.vtbl::<ShellLinkW>().get_arguments)(item.0, buffer.as_mut_ptr(), buffer.len() as i32) })
+                    .ok()?;
+                let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+                Some(String::from_utf16_lossy(&buffer[..len]))
+            })
+            .collect()
+    }
+
+    fn collection(links: Vec<Object>) -> Result<Object, HRESULT> {
+        let collection = Object::create(&EnumerableObjectCollection, &IID_IOBJECTCOLLECTION)?;
+        for link in links {
+            check(unsafe { (collection.vtbl::<ObjectCollection>().add_object)(collection.0, link.0) })?;
+        }
+        collection.cast(&IID_IOBJECTARRAY)
+    }
+
+    pub fn build(recent: &[PathBuf], labels: &Labels) -> Result<(), HRESULT> {
+        unsafe { CoInitializeEx(null(), COINIT_MULTITHREADED as u32) };
+        let result = build_list(recent, labels);
+        unsafe { CoUninitialize() };
+        result
+    }
+
+    fn build_list(recent: &[PathBuf], labels: &Labels) -> Result<(), HRESULT> {
+        let exe = std::env::current_exe().map_err(|e| e.raw_os_error().unwrap_or(-1))?;
+        let exe = wide(&exe.display().to_string());
+
+        let list = Object::create(&DestinationList, &IID_ICUSTOMDESTINATIONLIST)?;
+        let vtbl = list.vtbl::<CustomDestinationList>();
+        let mut slots = 0;
+        let mut removed = null_mut();
+        check(unsafe { (vtbl.begin_list)(list.0, &mut slots, &IID_IOBJECTARRAY, &mut removed) })?;
+        let removed = removed_arguments(&Object(removed));
+
+        let links = recent
+            .iter()
+            .take(slots as usize)
+            .map(|path| (path, format!("{} {}", OPEN_ARG, quote(&path.display().to_string()))))
+            .filter(|(_, arguments)| !removed.contains(arguments))
+            .map(|(path, arguments)| {
+                let title = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+                link(&exe, &arguments, &title, &path.display().to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !links.is_empty() {
+            let category = collection(links)?;
+            check(unsafe { (vtbl.append_category)(list.0, wide(&labels.recent).as_ptr(), category.0) })?;
+        }
+
+        let tasks = collection(vec![link(&exe, CLIPBOARD_ARG, &labels.scan_clipboard, &labels.scan_clipboard)?])?;
+        check(unsafe { (vtbl.add_user_tasks)(list.0, tasks.0) })?;
+        check(unsafe { (vtbl.commit_list)(list.0) })
+    }
+
+    pub fn clipboard_text() -> Option<String> {
+        unsafe {
+            if OpenClipboard(null_mut()) == 0 {
+                return None;
+            }
+            let text = (|| {
+                let data = GetClipboardData(CF_UNICODETEXT as u32);
+                if data.is_null() {
+                    return None;
+                }
+                let chars = GlobalLock(data) as *const u16;
+                if chars.is_null() {
+                    return None;
+                }
+                let len = (0..).take_while(|&i| *chars.add(i) != 0).count();
+                let text = String::from_utf16_lossy(std::slice::from_raw_parts(chars, len));
+                GlobalUnlock(data);
+                Some(text)
+            })();
+            CloseClipboard();
+            text
+        }
+    }
+}
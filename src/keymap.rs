@@ -0,0 +1,52 @@
+// Реестр сочетаний клавиш. Обработчики спрашивают нажатия отсюда, и отсюда же
+// строятся окно справки и подсказка по "?", так что список не расходится с кодом
+
+use eframe::egui::{self, gui_zoom::kb_shortcuts, Key, KeyboardShortcut, Modifiers};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Command {
+    Help,
+    ShortcutOverlay,
+    FocusSearch,
+    PreviousMatch,
+    NextMatch,
+    CopyPath,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+}
+
+pub struct Binding {
+    pub command: Command,
+    pub shortcut: KeyboardShortcut,
+    /// Ключ перевода описания
+    pub description: &'static str,
+}
+
+const fn bind(command: Command, shortcut: KeyboardShortcut, description: &'static str) -> Binding {
+    Binding { command, shortcut, description }
+}
+
+/// Все сочетания в порядке показа в справке. Сочетание с Shift идёт раньше
+/// такого же без него: проверка без модификаторов пропускает и нажатый Shift
+pub const BINDINGS: &[Binding] = &[
+    bind(Command::Help, KeyboardShortcut::new(Modifiers::NONE, Key::F1), "shortcut_help"),
+    bind(Command::ShortcutOverlay, KeyboardShortcut::new(Modifiers::NONE, Key::Questionmark), "shortcut_overlay"),
+    bind(Command::FocusSearch, KeyboardShortcut::new(Modifiers::COMMAND, Key::F), "shortcut_search"),
+    bind(Command::PreviousMatch, KeyboardShortcut::new(Modifiers::SHIFT, Key::F3), "shortcut_previous_match"),
+    bind(Command::NextMatch, KeyboardShortcut::new(Modifiers::NONE, Key::F3), "shortcut_next_match"),
+    // Копирование и масштаб обрабатывает egui, здесь они только для справки
+    bind(Command::CopyPath, KeyboardShortcut::new(Modifiers::COMMAND, Key::C), "shortcut_copy_path"),
+    bind(Command::ZoomIn, kb_shortcuts::ZOOM_IN, "shortcut_zoom_in"),
+    bind(Command::ZoomOut, kb_shortcuts::ZOOM_OUT, "shortcut_zoom_out"),
+    bind(Command::ZoomReset, kb_shortcuts::ZOOM_RESET, "shortcut_zoom_reset"),
+];
+
+fn binding(command: Command) -> &'static Binding {
+    BINDINGS.iter().find(|b| b.command == command).expect("every command has a binding")
+}
+
+/// Было ли нажато сочетание команды; нажатие поглощается
+pub fn consume(ctx: &egui::Context, command: Command) -> bool {
+    ctx.input_mut(|i| i.consume_shortcut(&binding(command).shortcut))
+}
@@ -0,0 +1,238 @@
+// Забытые временные файлы: *.tmp, резервные копии, файлы блокировки Office,
+// недокачанные загрузки и дампы памяти. Находятся по имени во всём дереве,
+// показываются по группам с итогами и отправляются в корзину подготовки
+
+use crate::{a11y, format_size, pattern, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::PathBuf;
+
+/// Сколько строк группы показывается; выбор действует на все её файлы
+const MAX_ROWS: usize = 200;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Temporary,
+    Backup,
+    OfficeLock,
+    PartialDownload,
+    CoreDump,
+}
+
+impl Kind {
+    const ALL: [Kind; 5] = [Kind::Temporary, Kind::Backup, Kind::OfficeLock, Kind::PartialDownload, Kind::CoreDump];
+
+    /// Шаблоны имён через `;`, как в правилах очистки
+    fn patterns(self) -> &'static str {
+        match self {
+            Kind::Temporary => "*.tmp;*.temp",
+            Kind::Backup => "*.bak;*.old;*~",
+            Kind::OfficeLock => "~$*;.~lock.*#",
+            Kind::PartialDownload => "*.crdownload;*.part;*.partial",
+            Kind::CoreDump => "core;*.core;*.dmp;*.hprof",
+        }
+    }
+
+    fn translation_key(self) -> &'static str {
+        match self {
+            Kind::Temporary => "leftover_temporary",
+            Kind::Backup => "leftover_backup",
+            Kind::OfficeLock => "leftover_office_lock",
+            Kind::PartialDownload => "leftover_partial_download",
+            Kind::CoreDump => "leftover_core_dump",
+        }
+    }
+
+    fn matches(self, name: &str) -> bool {
+        pattern::matches_any(self.patterns(), name) || (self == Kind::CoreDump && is_numbered_core(name))
+    }
+}
+
+/// core.1234 - дамп процесса с номером; core.js и подобные не в счёт
+fn is_numbered_core(name: &str) -> bool {
+    name.strip_prefix("core.").is_some_and(|pid| !pid.is_empty() && pid.chars().all(|c| c.is_ascii_digit()))
+}
+
+pub struct Item {
+    pub path: PathBuf,
+    pub size: u64,
+    pub selected: bool,
+}
+
+pub struct Group {
+    pub kind: Kind,
+    pub items: Vec<Item>,
+}
+
+impl Group {
+    fn total(&self) -> u64 {
+        self.items.iter().map(|i| i.size).sum()
+    }
+}
+
+/// Найденные файлы по группам; пустые группы не показываются
+pub struct Leftovers {
+    pub groups: Vec<Group>,
+}
+
+impl Leftovers {
+    pub fn find(root: &DirNode) -> Self {
+        fn walk(node: &DirNode, groups: &mut [Group]) {
+            for child in &node.children {
+                if !child.is_file {
+                    walk(child, groups);
+                    continue;
+                }
+                if child.link_target.is_some() {
+                    continue;
+                }
+                let name = child.display_name();
+                if let Some(group) = groups.iter_mut().find(|g| g.kind.matches(&name)) {
+                    group.items.push(Item {
+                        path: child.path.clone(),
+                        size: child.size,
+                        selected: true,
+                    });
+                }
+            }
+        }
+
+        let mut groups: Vec<Group> = Kind::ALL.iter().map(|&kind| Group { kind, items: Vec::new() }).collect();
+        walk(root, &mut groups);
+        groups.retain(|g| !g.items.is_empty());
+        for group in &mut groups {
+            group.items.sort_unstable_by_key(|item| std::cmp::Reverse(item.size));
+        }
+        groups.sort_by_key(|g| std::cmp::Reverse(g.total()));
+        Self { groups }
+    }
+
+    fn selected(&self) -> impl Iterator<Item = &Item> {
+        self.groups.iter().flat_map(|g| &g.items).filter(|i| i.selected)
+    }
+}
+
+impl CedarApp {
+    /// Окно забытых временных файлов
+    pub fn show_leftovers_window(&mut self, ctx: &egui::Context) {
+        let read_only = self.read_only();
+        let read_only_hint = self.read_only_hint();
+        let Some(leftovers) = &mut self.leftovers else {
+            return;
+        };
+
+        let mut open = true;
+        let mut refresh = false;
+        let mut delete = false;
+        let mut clicked_path = None;
+        let t = &self.translations;
+
+        egui::Window::new(format!("{} {}", regular::BROOM, t.get("leftovers")))
+            .open(&mut open)
+            .default_size([720.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(t.get("leftovers_hint")).color(ui.visuals().weak_text_color()));
+                ui.add_space(5.0);
+
+                let count = leftovers.selected().count();
+                let size: u64 = leftovers.selected().map(|i| i.size).sum();
+                ui.horizontal(|ui| {
+                    ui.strong(t.get_fmt("leftovers_selected", &[&t.format_count(count as u64), &format_size(size)]));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_enabled_ui(count > 0 && !read_only, |ui| {
+                            if ui.button(format!("{} {}", regular::TRASH, t.get("leftovers_delete"))).clicked() {
+                                delete = true;
+                            }
+                        })
+                        .response
+                        .on_disabled_hover_text(&read_only_hint);
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, t.get("refresh"))).clicked() {
+                            refresh = true;
+                        }
+                    });
+                });
+                ui.separator();
+
+                if leftovers.groups.is_empty() {
+                    ui.label(egui::RichText::new(t.get("leftovers_none")).color(ui.visuals().weak_text_color()));
+                    return;
+                }
+
+                egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+                    for group in &mut leftovers.groups {
+                        ui.horizontal(|ui| {
+                            // Флажок группы отражает и меняет выбор всех её файлов
+                            let mut all = group.items.iter().all(|i| i.selected);
+                            let response = ui.checkbox(&mut all, "");
+                            a11y::set_name(&response, egui::WidgetType::Checkbox, &t.get(group.kind.translation_key()));
+                            if response.changed() {
+                                group.items.iter_mut().for_each(|i| i.selected = all);
+                            }
+                            ui.strong(t.get(group.kind.translation_key()));
+                            ui.label(
+                                egui::RichText::new(t.get_fmt(
+                                    "leftovers_group_total",
+                                    &[&t.format_count(group.items.len() as u64), &format_size(group.total())],
+                                ))
+                                .color(ui.visuals().weak_text_color()),
+                            );
+                        });
+
+                        egui::CollapsingHeader::new(group.kind.patterns().replace(';', "  "))
+                            .id_salt(group.kind.translation_key())
+                            .show(ui, |ui| {
+                                egui::Grid::new(("leftovers_grid", group.kind.translation_key()))
+                                    .striped(true)
+                                    .num_columns(2)
+                                    .show(ui, |ui| {
+                                        for item in group.items.iter_mut().take(MAX_ROWS) {
+                                            ui.horizontal(|ui| {
+                                                ui.checkbox(&mut item.selected, "");
+                                                if ui.selectable_label(false, item.path.display().to_string()).clicked() {
+                                                    clicked_path = Some(item.path.clone());
+                                                }
+                                            });
+                                            ui.label(format_size(item.size));
+                                            ui.end_row();
+                                        }
+                                    });
+                                if group.items.len() > MAX_ROWS {
+                                    ui.label(
+                                        egui::RichText::new(t.get_fmt(
+                                            "leftovers_more",
+                                            &[&t.format_count((group.items.len() - MAX_ROWS) as u64)],
+                                        ))
+                                        .small()
+                                        .color(ui.visuals().weak_text_color()),
+                                    );
+                                }
+                            });
+                        ui.add_space(4.0);
+                    }
+                });
+            });
+
+        if delete {
+            let paths: Vec<PathBuf> = leftovers.selected().map(|i| i.path.clone()).collect();
+            for path in paths {
+                self.basket.add(path);
+            }
+            open = false;
+            self.review_basket_deletion();
+        } else if refresh {
+            if let Some(root) = &self.root_node {
+                self.leftovers = Some(Leftovers::find(root));
+            }
+        }
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.search.scroll_to = Some(path.clone());
+            self.selected_path = Some(path);
+        }
+        if !open {
+            self.leftovers = None;
+        }
+    }
+}
@@ -0,0 +1,281 @@
+// Почтовые хранилища в дереве сканирования: файлы данных Outlook, базы
+// Thunderbird и выгрузки mbox. Хранилище без почтового профиля - брошенное:
+// его часто забывают после переустановки или смены почтового клиента
+
+use crate::{format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MailKind {
+    OutlookPst,
+    OutlookOst,
+    OutlookMac,
+    OutlookExpress,
+    LotusNotes,
+    Thunderbird,
+    Mbox,
+}
+
+impl MailKind {
+    fn from_extension(extension: &str) -> Option<MailKind> {
+        match extension {
+            "pst" => Some(MailKind::OutlookPst),
+            "ost" => Some(MailKind::OutlookOst),
+            "olm" => Some(MailKind::OutlookMac),
+            "dbx" => Some(MailKind::OutlookExpress),
+            "nsf" => Some(MailKind::LotusNotes),
+            "mbox" | "mbx" => Some(MailKind::Mbox),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            MailKind::OutlookPst => "Outlook PST",
+            MailKind::OutlookOst => "Outlook OST",
+            MailKind::OutlookMac => "Outlook for Mac",
+            MailKind::OutlookExpress => "Outlook Express",
+            MailKind::LotusNotes => "Lotus Notes",
+            MailKind::Thunderbird => "Thunderbird",
+            MailKind::Mbox => "mbox",
+        }
+    }
+}
+
+/// Строка отчёта о почтовых хранилищах
+pub struct MailStore {
+    pub path: PathBuf,
+    pub kind: MailKind,
+    pub size: u64,
+    /// `None` - принадлежность профилю проверить нельзя (выгрузки, архивы)
+    pub orphaned: Option<bool>,
+}
+
+/// Профили Thunderbird из profiles.ini
+fn thunderbird_profiles() -> Vec<PathBuf> {
+    let root = if cfg!(windows) {
+        dirs::data_dir().map(|d| d.join("Thunderbird"))
+    } else if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|d| d.join("Library/Thunderbird"))
+    } else {
+        dirs::home_dir().map(|d| d.join(".thunderbird"))
+    };
+    let Some(root) = root else {
+        return Vec::new();
+    };
+    let Ok(ini) = std::fs::read_to_string(root.join("profiles.ini")) else {
+        return Vec::new();
+    };
+    crate::browsers::parse_profiles_ini(&ini)
+        .into_iter()
+        .map(|(_, path, is_relative)| if is_relative { root.join(path) } else { PathBuf::from(path) })
+        .collect()
+}
+
+/// Сравнение путей без учёта регистра и разделителей, как их сохранил почтовый клиент
+fn same_path(a: &Path, b: &Path) -> bool {
+    let normalize = |p: &Path| {
+        let p = p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+        p.to_string_lossy().replace('/', "\\").to_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Размер почтовых баз Thunderbird в папке: файлы без расширения, рядом с которыми
+/// лежит индекс `<имя>.msf`
+fn thunderbird_mail_size(node: &DirNode) -> u64 {
+    let mut size = 0;
+    for child in &node.children {
+        if child.is_file {
+            let name = child.display_name();
+            let index = format!("{}.msf", name);
+            if !name.contains('.') && node.children.iter().any(|c| c.is_file && c.name == *index) {
+                size += child.size;
+            }
+        } else {
+            size += thunderbird_mail_size(child);
+        }
+    }
+    size
+}
+
+/// Собирает почтовые хранилища, крупнейшие первыми
+pub fn collect_mail_stores(root: &DirNode) -> Vec<MailStore> {
+    fn walk(node: &DirNode, stores: &mut Vec<MailStore>) {
+        // Профиль Thunderbird - папка с prefs.js и папками почты; показываем его целиком
+        let is_thunderbird_profile = node.children.iter().any(|c| c.is_file && c.name == "prefs.js")
+            && node.children.iter().any(|c| !c.is_file && (c.name == "Mail" || c.name == "ImapMail"));
+        if is_thunderbird_profile {
+            let size = node
+                .children
+                .iter()
+                .filter(|c| !c.is_file && (c.name == "Mail" || c.name == "ImapMail"))
+                .map(thunderbird_mail_size)
+                .sum();
+            if size > 0 {
+                stores.push(MailStore {
+                    path: node.path.clone(),
+                    kind: MailKind::Thunderbird,
+                    size,
+                    orphaned: None,
+                });
+            }
+        }
+
+        for child in &node.children {
+            if !child.is_file {
+                walk(child, stores);
+                continue;
+            }
+            let extension = Path::new(&child.name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if let Some(kind) = MailKind::from_extension(&extension) {
+                stores.push(MailStore {
+                    path: child.path.clone(),
+                    kind,
+                    size: child.size,
+                    orphaned: None,
+                });
+            }
+        }
+    }
+
+    let mut stores = Vec::new();
+    walk(root, &mut stores);
+
+    // Профили читаются, только если в дереве есть что с ними сверять
+    if stores.iter().any(|s| matches!(s.kind, MailKind::OutlookPst | MailKind::OutlookOst)) {
+        if let Some(data_files) = crate::platform::outlook_data_files() {
+            for store in stores.iter_mut().filter(|s| matches!(s.kind, MailKind::OutlookPst | MailKind::OutlookOst)) {
+                store.orphaned = Some(!data_files.iter().any(|f| same_path(f, &store.path)));
+            }
+        }
+    }
+    if stores.iter().any(|s| s.kind == MailKind::Thunderbird) {
+        let profiles = thunderbird_profiles();
+        for store in stores.iter_mut().filter(|s| s.kind == MailKind::Thunderbird) {
+            store.orphaned = Some(!profiles.iter().any(|p| same_path(p, &store.path)));
+        }
+    }
+
+    stores.sort_unstable_by_key(|s| std::cmp::Reverse(s.size));
+    stores
+}
+
+impl CedarApp {
+    /// Окно отчёта о почтовых хранилищах
+    pub fn show_mail_report_window(&mut self, ctx: &egui::Context) {
+        if !self.show_mail_report {
+            return;
+        }
+
+        if self.mail_report.is_none() {
+            self.mail_report = self.root_node.as_ref().map(collect_mail_stores);
+        }
+
+        let mut open = self.show_mail_report;
+        let mut refresh = false;
+        let mut clicked_path = None;
+        let orphan_color = self.config.palette.status_text(crate::palette::Status::Warning, self.config.dark_mode);
+
+        egui::Window::new(format!("{} {}", regular::ENVELOPE, self.translations.get("mail_report")))
+            .open(&mut open)
+            .default_size([760.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.translations.get("mail_report_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    if let Some(stores) = &self.mail_report {
+                        let total: u64 = stores.iter().map(|s| s.size).sum();
+                        let orphaned: u64 = stores.iter().filter(|s| s.orphaned == Some(true)).map(|s| s.size).sum();
+                        ui.strong(self.translations.get_fmt(
+                            "mail_summary",
+                            &[&stores.len().to_string(), &format_size(total), &format_size(orphaned)],
+                        ));
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, self.translations.get("refresh"))).clicked() {
+                            refresh = true;
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                let Some(stores) = &self.mail_report else {
+                    return;
+                };
+
+                if stores.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("mail_none"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("mail_report_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                ui.strong(self.translations.get("mail_type"));
+                                ui.strong(self.translations.get("file"));
+                                ui.strong(self.translations.get("size"));
+                                ui.strong(self.translations.get("mail_profile"));
+                                ui.end_row();
+
+                                for store in stores {
+                                    ui.label(store.kind.name());
+                                    let response = ui.selectable_label(
+                                        self.selected_path.as_ref() == Some(&store.path),
+                                        store.path.display().to_string(),
+                                    );
+                                    if response.clicked() {
+                                        clicked_path = Some(store.path.clone());
+                                    }
+                                    ui.strong(format_size(store.size));
+                                    match store.orphaned {
+                                        Some(true) => {
+                                            ui.label(
+                                                egui::RichText::new(format!("{} {}", regular::WARNING, self.translations.get("mail_orphaned")))
+                                                    .color(orphan_color),
+                                            )
+                                            .on_hover_text(self.translations.get("mail_orphaned_hint"));
+                                        }
+                                        Some(false) => {
+                                            ui.label(self.translations.get("mail_in_use"));
+                                        }
+                                        None => {
+                                            ui.weak("—").on_hover_text(self.translations.get("mail_unknown_hint"));
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if refresh {
+            self.mail_report = None;
+        }
+        self.show_mail_report = open;
+    }
+}
@@ -4,41 +4,102 @@ use eframe::egui;
 use egui_phosphor::regular;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use sysinfo::Disks;
 
+mod a11y;
+mod app_paths;
+mod audit;
+mod basket;
+mod browsers;
+mod chart;
+mod checkpoint;
+mod clipboard;
+mod compare;
+mod confirm;
+mod csv_import;
+mod disk_probe;
+mod downloads;
+mod duplicates;
+mod exclusions;
+mod export;
+mod file_list;
+mod file_ops;
+mod filter;
+mod hash_cache;
+mod headless;
+mod help;
+mod html_report;
+mod heatmap;
 mod i18n;
+mod i18n_debug;
+mod icicle;
+mod icons;
+mod instance;
+mod jump_list;
+mod keymap;
+mod leftovers;
+mod mail;
+mod media;
+mod ncdu;
+mod notes;
+mod op_log;
+mod os_serde;
+mod palette;
+mod panels;
+mod pdf_report;
+mod pattern;
+mod platform;
+mod policy;
+mod reclaim;
+mod reports;
+mod rules;
+mod scan_path;
+mod search;
+mod settings;
+mod settings_file;
+mod shell_icons;
+mod snapshot;
+mod snapshot_diff;
+mod snapshot_store;
+mod smtp;
+mod status_bar;
+mod storage_sense;
+mod sunburst;
+mod taskbar;
+mod theme;
+mod throttle;
+#[cfg(test)]
+mod test_util;
+mod tree_columns;
+mod treemap;
+mod view_layouts;
+mod vm;
+mod watchlist;
+mod webhook;
+mod window;
 use i18n::{Language, Translations};
 
-// Встраиваем иконку приложения
-const ICON_CEDAR: &[u8] = include_bytes!("icons/cedar.svg");
+// Пределы масштаба интерфейса
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+/// Масштаб сохраняется, когда он столько времени не менялся
+const ZOOM_SAVE_DELAY: Duration = Duration::from_secs(1);
+/// Сколько элементов цели нераскрытой ссылки обходится для её размера.
+/// Ссылка на корень диска или сетевую папку иначе превратила бы сканирование в обход всего
+const LINK_TARGET_LIMIT: usize = 100_000;
 
-// Встраиваем SVG иконки для тёмной темы
-const ICON_FOLDER_DARK: &[u8] = include_bytes!("icons/dark/folder.svg");
-const ICON_FILE_DARK: &[u8] = include_bytes!("icons/dark/file.svg");
-const ICON_SEARCH_DARK: &[u8] = include_bytes!("icons/dark/search.svg");
-const ICON_STOP_DARK: &[u8] = include_bytes!("icons/dark/stop.svg");
-
-// Встраиваем SVG иконки для светлой темы
-const ICON_FOLDER_LIGHT: &[u8] = include_bytes!("icons/light/folder.svg");
-const ICON_FILE_LIGHT: &[u8] = include_bytes!("icons/light/file.svg");
-const ICON_SEARCH_LIGHT: &[u8] = include_bytes!("icons/light/search.svg");
-const ICON_STOP_LIGHT: &[u8] = include_bytes!("icons/light/stop.svg");
-
-// Функция для загрузки SVG как текстуры
-fn load_svg_as_texture(
-    ctx: &egui::Context,
-    svg_data: &[u8],
-    name: &str,
-    size: u32,
-) -> egui::TextureHandle {
+// Растеризация SVG в квадратное изображение заданного размера
+fn rasterize_svg(svg_data: &[u8], size: u32) -> Option<egui::ColorImage> {
     // Парсим SVG
     let opt = usvg::Options::default();
-    let tree = usvg::Tree::from_data(svg_data, &opt).expect("Failed to parse SVG");
+    let tree = usvg::Tree::from_data(svg_data, &opt).ok()?;
     
     // Получаем размеры SVG
     let svg_size = tree.size();
@@ -49,7 +110,7 @@ fn load_svg_as_texture(
     let scale = scale_x.min(scale_y); // Используем минимальный масштаб чтобы сохранить пропорции
     
     // Создаём pixmap для рендеринга
-    let mut pixmap = tiny_skia::Pixmap::new(size, size).expect("Failed to create pixmap");
+    let mut pixmap = tiny_skia::Pixmap::new(size, size)?;
     
     // Создаём трансформацию для масштабирования
     let transform = tiny_skia::Transform::from_scale(scale, scale);
@@ -62,68 +123,60 @@ fn load_svg_as_texture(
         size,
         size,
         pixmap.data().to_vec(),
-    ).expect("Failed to create image");
+    )?;
     
-    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+    Some(egui::ColorImage::from_rgba_unmultiplied(
         [size as usize, size as usize],
         &image_buffer,
-    );
-    
-    ctx.load_texture(name, color_image, egui::TextureOptions::LINEAR)
+    ))
 }
 
-// Функция для загрузки иконки приложения
-fn load_icon() -> egui::IconData {
-    // Парсим SVG
-    let opt = usvg::Options::default();
-    let tree = usvg::Tree::from_data(ICON_CEDAR, &opt).expect("Failed to parse SVG icon");
-    
-    // Размер иконки
-    let size = 256u32;
-    
-    // Получаем размеры SVG
-    let svg_size = tree.size();
-    
-    // Вычисляем масштаб
-    let scale_x = size as f32 / svg_size.width();
-    let scale_y = size as f32 / svg_size.height();
-    let scale = scale_x.min(scale_y);
-    
-    // Создаём pixmap для рендеринга
-    let mut pixmap = tiny_skia::Pixmap::new(size, size).expect("Failed to create pixmap");
-    
-    // Заполняем прозрачным фоном
-    pixmap.fill(tiny_skia::Color::TRANSPARENT);
+fn main() -> Result<(), eframe::Error> {
+    // Загрузка настроек, разданных администратором, при установке
+    if let Some(path) = settings_file::import_arg() {
+        platform::attach_parent_console();
+        let code = match path {
+            Ok(path) => settings_file::import_from_command_line(&path),
+            Err(error) => {
+                eprintln!("{}", error);
+                2
+            }
+        };
+        std::process::exit(code);
+    }
     
-    // Создаём трансформацию для масштабирования и центрирования
-    let offset_x = (size as f32 - svg_size.width() * scale) / 2.0;
-    let offset_y = (size as f32 - svg_size.height() * scale) / 2.0;
-    let transform = tiny_skia::Transform::from_translate(offset_x, offset_y)
-        .post_scale(scale, scale);
+    // Без окна: сканирование из командной строки или планировщика
+    if let Some(args) = headless::parse_args() {
+        platform::attach_parent_console();
+        let code = match args {
+            Ok(args) => headless::run(args),
+            Err(error) => {
+                eprintln!("{}", error);
+                2
+            }
+        };
+        std::process::exit(code);
+    }
     
-    // Рендерим SVG
-    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    // Снимок, открытый двойным щелчком, достаётся уже запущенному окну
+    let snapshot_file = instance::file_arg();
+    if snapshot_file.as_deref().is_some_and(instance::send) {
+        return Ok(());
+    }
     
-    // Конвертируем в RGBA
-    let rgba = pixmap.data().to_vec();
+    // Размещение окна, сохранённое для мониторов последнего запуска
+    let placements = CedarApp::load_config_from_file().window;
     
-    egui::IconData {
-        rgba,
-        width: size,
-        height: size,
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size([1200.0, 800.0])
+        .with_min_inner_size([1100.0, 600.0])
+        .with_title(window::TITLE);
+    // Иконка приложения
+    if let Some(icon) = icons::window_icon() {
+        viewport = viewport.with_icon(icon);
     }
-}
-
-fn main() -> Result<(), eframe::Error> {
-    // Загружаем иконку приложения
-    let icon_data = load_icon();
-    
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1200.0, 800.0])
-            .with_min_inner_size([1100.0, 600.0])
-            .with_title("Cedar Folder Size")
-            .with_icon(icon_data),
+        viewport: placements.apply_to(viewport),
         ..Default::default()
     };
     
@@ -156,71 +209,288 @@ fn main() -> Result<(), eframe::Error> {
             
             cc.egui_ctx.set_style(style);
             
-            Ok(Box::new(CedarApp::new(cc)))
+            let mut app = CedarApp::new(cc);
+            app.update_jump_list();
+            // Запуск из списка переходов панели задач
+            if let Some(path) = jump_list::launch_path() {
+                app.scan_path = path.clone();
+                app.start_scan(path);
+            }
+            if let Some(path) = snapshot_file {
+                app.open_snapshot_file(&path);
+            }
+            Ok(Box::new(app))
         }),
     )
 }
 
-#[derive(Clone)]
+/// Самый большой файл поддерева - подпись у свёрнутой папки
+#[derive(Clone, Serialize, Deserialize)]
+struct LargestFile {
+    #[serde(with = "os_serde")]
+    name: OsString,
+    size: u64,
+}
+
+/// Файлы и папки во всём содержимом папки
+fn item_counts(children: &[DirNode]) -> (u64, u64) {
+    children.iter().fold((0, 0), |(files, folders), child| {
+        if child.is_file {
+            (files + 1, folders)
+        } else {
+            (files + child.files, folders + child.folders + 1)
+        }
+    })
+}
+
+/// Самый большой файл среди детей и их поддеревьев; ссылки не в счёт
+fn largest_file(children: &[DirNode]) -> Option<Box<LargestFile>> {
+    children
+        .iter()
+        .filter_map(|child| {
+            if child.is_file {
+                child.link_target.is_none().then_some((child.name.as_os_str(), child.size))
+            } else {
+                child.largest_file.as_ref().map(|largest| (largest.name.as_os_str(), largest.size))
+            }
+        })
+        .max_by_key(|&(_, size)| size)
+        .map(|(name, size)| Box::new(LargestFile { name: name.to_os_string(), size }))
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct DirNode {
+    #[serde(with = "os_serde")]
     path: PathBuf,
-    name: String,
+    // Имя как в файловой системе; для показа - `display_name`
+    #[serde(with = "os_serde")]
+    name: OsString,
     size: u64,
+    allocated: u64, // место на диске с учётом округления до кластеров
+    skipped: u64,   // сколько элементов поддерева не удалось прочитать
+    modified: Option<SystemTime>, // для папок - самое свежее изменение в поддереве
+    #[serde(default)]
+    accessed: Option<SystemTime>, // для папок - самое свежее чтение файлов поддерева
+    #[serde(default)]
+    sparse: bool, // разреженный файл: занятое место меньше логического размера
+    #[serde(default)]
+    streams: u64, // размер альтернативных потоков NTFS, уже включён в size
+    #[serde(default)]
+    referenced: u64, // размер целей нераскрытых ссылок; в size и allocated не входит
+    #[serde(default, with = "os_serde::option")]
+    link_target: Option<PathBuf>, // для ссылки - куда она указывает
+    #[serde(default)]
+    dir_mtime: Option<SystemTime>, // для папки - время изменения её самой, для быстрого пересканирования
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    largest_file: Option<Box<LargestFile>>, // для папки - самый большой файл поддерева
+    #[serde(default)]
+    files: u64, // для папки - файлов во всём поддереве
+    #[serde(default)]
+    folders: u64, // для папки - вложенных папок во всём поддереве
     children: Vec<DirNode>,
+    #[serde(skip)]
     is_expanded: bool,
     is_file: bool,  // true если это файл, false если папка
 }
 
 impl DirNode {
-    fn new(path: PathBuf, name: String, size: u64, is_file: bool) -> Self {
+    fn new(path: PathBuf, name: OsString, size: u64, is_file: bool) -> Self {
         Self {
             path,
             name,
             size,
+            allocated: 0,
+            skipped: 0,
+            modified: None,
+            accessed: None,
+            sparse: false,
+            streams: 0,
+            referenced: 0,
+            link_target: None,
+            dir_mtime: None,
+            largest_file: None,
+            files: 0,
+            folders: 0,
             children: Vec::new(),
             is_expanded: false,
             is_file,
         }
     }
 
-    fn sort_by_size(&mut self) {
-        self.children.sort_by(|a, b| b.size.cmp(&a.size));
+    /// Имя для показа; неверные в UTF-8 символы заменяются на �
+    fn display_name(&self) -> std::borrow::Cow<'_, str> {
+        self.name.to_string_lossy()
+    }
+    
+    /// Потери на хвостах кластеров (занято на диске минус логический размер)
+    fn slack(&self) -> u64 {
+        self.allocated.saturating_sub(self.size)
+    }
+
+    /// Находит узел по пути, спускаясь только по предкам этого пути
+    fn find(&self, path: &Path) -> Option<&DirNode> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children
+            .iter()
+            .filter(|c| path.starts_with(&c.path))
+            .find_map(|c| c.find(path))
+    }
+
+    /// Удаляет потомка по пути и вычитает его размеры из всех предков
+    fn remove_descendant(&mut self, path: &Path) -> Option<DirNode> {
+        let removed = if let Some(pos) = self.children.iter().position(|c| c.path == path) {
+            self.children.remove(pos)
+        } else {
+            self.children
+                .iter_mut()
+                .filter(|c| path.starts_with(&c.path))
+                .find_map(|c| c.remove_descendant(path))?
+        };
+        
+        self.size = self.size.saturating_sub(removed.size);
+        self.allocated = self.allocated.saturating_sub(removed.allocated);
+        self.streams = self.streams.saturating_sub(removed.streams);
+        self.referenced = self.referenced.saturating_sub(removed.referenced);
+        self.skipped = self.skipped.saturating_sub(removed.skipped);
+        self.files = self.files.saturating_sub(removed.file_count());
+        self.folders = self.folders.saturating_sub(removed.folders + u64::from(!removed.is_file));
+        self.largest_file = largest_file(&self.children);
+        Some(removed)
+    }
+
+    /// Количество файлов в поддереве
+    fn file_count(&self) -> u64 {
+        if self.is_file { 1 } else { self.files }
+    }
+    
+    /// Пересчитывает число файлов и папок поддерева, например у дерева из старого снимка
+    fn update_counts(&mut self) {
         for child in &mut self.children {
-            child.sort_by_size();
+            if !child.is_file {
+                child.update_counts();
+            }
+        }
+        (self.files, self.folders) = item_counts(&self.children);
+    }
+
+    /// Раскрывает всех предков пути, чтобы он был виден в дереве
+    fn expand_to(&mut self, path: &Path) {
+        if self.path == path || !path.starts_with(&self.path) {
+            return;
+        }
+        self.is_expanded = true;
+        for child in self.children.iter_mut().filter(|c| path.starts_with(&c.path)) {
+            child.expand_to(path);
+        }
+    }
+
+    /// Собирает пути всех раскрытых папок
+    fn collect_expanded(&self, expanded: &mut HashSet<PathBuf>) {
+        if self.is_expanded {
+            expanded.insert(self.path.clone());
+        }
+        for child in self.children.iter().filter(|c| !c.is_file) {
+            child.collect_expanded(expanded);
+        }
+    }
+
+    /// Раскрывает папки, пути которых есть в наборе
+    fn restore_expanded(&mut self, expanded: &HashSet<PathBuf>) {
+        self.is_expanded = expanded.contains(&self.path);
+        for child in self.children.iter_mut().filter(|c| !c.is_file) {
+            child.restore_expanded(expanded);
         }
     }
 }
 
+/// Состояние дерева, переносимое на результат повторного сканирования того же корня
+struct TreeViewState {
+    root: PathBuf,
+    expanded: HashSet<PathBuf>,
+    scroll_offset: f32,
+}
+
 #[derive(Clone)]
 struct ScanProgress {
     message: String,
+    /// Папка, с которой начато сканирование
+    root: String,
     current_path: String,
     files_scanned: usize,
     dirs_scanned: usize,
     total_size: u64,
+    /// Просмотрено записей папок (включая отфильтрованные) - для процента
+    entries_seen: usize,
+    /// Сколько записей ожидается: по прошлому сканированию или быстрому подсчёту; 0 - неизвестно
+    expected_entries: usize,
+    /// Сканируется диск целиком
+    whole_disk: bool,
     disk_size: u64,
     disk_type: String,
     thread_count: usize,
+    /// Откуда взялось число потоков
+    tuning: disk_probe::Tuning,
+    /// Том, для которого запоминается скорость сканирования
+    volume_key: String,
+    /// Точка монтирования диска - для сообщения о его отключении
+    drive: String,
+    /// Пропущено исключениями
+    excluded: exclusions::ExclusionSavings,
+    cluster_size: u64,
+    /// Обработка после обхода: (обработано папок, всего); None - идёт обход
+    processing: Option<(usize, usize)>,
+    /// Уже просканированные приоритетные папки и их размер
+    scanned_first: Vec<(PathBuf, u64)>,
+}
+
+impl ScanProgress {
+    /// Доля выполненной работы; None - пока не известно, сколько всего
+    fn fraction(&self) -> Option<f32> {
+        if let Some((done, total)) = self.processing {
+            Some((done as f32 / total.max(1) as f32).min(1.0))
+        } else if self.expected_entries > 0 {
+            Some((self.entries_seen as f32 / self.expected_entries as f32).min(1.0))
+        } else if self.whole_disk && self.disk_size > 0 {
+            Some((self.total_size as f32 / self.disk_size as f32).min(1.0))
+        } else {
+            None
+        }
+    }
 }
 
 impl Default for ScanProgress {
     fn default() -> Self {
         Self {
             message: String::new(),
+            root: String::new(),
             current_path: String::new(),
             files_scanned: 0,
             dirs_scanned: 0,
             total_size: 0,
+            entries_seen: 0,
+            expected_entries: 0,
+            whole_disk: false,
             disk_size: 0,
             disk_type: String::new(),
             thread_count: 1,
+            tuning: disk_probe::Tuning::Detected,
+            volume_key: String::new(),
+            drive: String::new(),
+            excluded: Default::default(),
+            cluster_size: 0,
+            processing: None,
+            scanned_first: Vec::new(),
         }
     }
 }
 
 enum ScanResult {
-    InProgress,
-    Complete(DirNode),
+    /// Готовое дерево и, если просили, оно же, сжатое для автоматического снимка
+    Complete(DirNode, Option<snapshot::Encoded>),
+    /// Устройство отключилось во время сканирования; дерево неполное
+    Disconnected(DirNode),
     Cancelled,
     Error(String),
 }
@@ -234,102 +504,339 @@ struct DriveInfo {
     kind: String,
 }
 
+/// Представление результатов сканирования
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+enum ViewMode {
+    #[default]
+    Tree,
+    Icicle,
+    Treemap,
+    Sunburst,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AppConfig {
     dark_mode: bool,
+    /// Тема следует за системной; dark_mode тогда обновляется сам
+    #[serde(default)]
+    follow_system_theme: bool,
     language: Language,
     last_path: Option<String>,
+    #[serde(default)]
+    cleanup_rules: Vec<rules::CleanupRule>,
+    #[serde(default)]
+    view_mode: ViewMode,
+    /// Столбец и направление сортировки дерева
+    #[serde(default)]
+    tree_sort: tree_columns::TreeSort,
+    /// Представление и панели, запомненные для папок сканирования
+    #[serde(default)]
+    view_layouts: Vec<view_layouts::ViewLayout>,
+    #[serde(default = "default_true")]
+    remember_view_layouts: bool,
+    /// Папки, снимки которых сохраняются после каждого сканирования
+    #[serde(default)]
+    snapshot_policies: Vec<snapshot_store::RetentionPolicy>,
+    /// Последнее сканирование каждого диска сохраняется, чтобы открыть его без нового сканирования
+    #[serde(default = "default_true")]
+    remember_drive_scans: bool,
+    #[serde(default)]
+    watchlist: Vec<watchlist::WatchedFolder>,
+    #[serde(default)]
+    scan_filter: filter::ScanFilter,
+    #[serde(default)]
+    filter_presets: Vec<filter::FilterPreset>,
+    #[serde(default)]
+    color_by_age: bool,
+    #[serde(default)]
+    follow_links: bool,
+    /// Показывать у нераскрытых ссылок размер их цели
+    #[serde(default)]
+    link_targets: bool,
+    /// Не перечитывать папки, не менявшиеся с прошлого сканирования того же корня
+    #[serde(default)]
+    quick_rescan: bool,
+    /// Режим аудита: действия, меняющие файлы, отключены
+    #[serde(default)]
+    audit_mode: bool,
+    /// Куда сканирование без окна отправляет уведомления; пусто - никуда
+    #[serde(default)]
+    webhook_url: String,
+    /// Почтовый сервер для отчётов сканирования без окна
+    #[serde(default)]
+    smtp: smtp::SmtpSettings,
+    /// Папки, которые сканируются раньше остальных
+    #[serde(default, with = "os_serde::seq")]
+    scan_first: Vec<PathBuf>,
+    /// Предел скорости чтения при хешировании, МБ/с; 0 - без ограничения
+    #[serde(default)]
+    hashing_limit_mb: u32,
+    #[serde(default, with = "os_serde::map")]
+    notes: BTreeMap<PathBuf, String>,
+    #[serde(default)]
+    palette: palette::Palette,
+    #[serde(default)]
+    high_contrast: bool,
+    #[serde(default = "default_zoom")]
+    zoom: f32,
+    #[serde(default)]
+    panels: panels::PanelLayout,
+    #[serde(default)]
+    export: export::ExportSettings,
+    #[serde(default)]
+    exclusions: Vec<exclusions::Exclusion>,
+    #[serde(default)]
+    confirm: confirm::ConfirmSettings,
+    #[serde(default)]
+    window: window::WindowPlacements,
+    /// Стратегия сканирования, заданная вручную, по точке монтирования диска
+    #[serde(default)]
+    disk_strategies: BTreeMap<String, disk_probe::ScanStrategy>,
+    /// Скорость сканирования по томам - для выбора числа потоков
+    #[serde(default)]
+    scan_profiles: BTreeMap<String, disk_probe::ScanProfile>,
+    /// Размеры исключённых папок - для оценки сэкономленного времени
+    #[serde(default, with = "os_serde::map")]
+    excluded_sizes: BTreeMap<PathBuf, exclusions::SubtreeSize>,
+    /// Где в последний раз выбирали папку или файл в диалоге
+    #[serde(default, with = "os_serde::option")]
+    last_browse_dir: Option<PathBuf>,
+    /// Недавно сканированные папки, последняя - первой
+    #[serde(default, with = "os_serde::seq")]
+    recent_roots: Vec<PathBuf>,
+    /// Отчёты (PDF, письма) на английском независимо от языка интерфейса
+    #[serde(default)]
+    reports_in_english: bool,
+    /// Подпись самого большого файла у свёрнутых папок дерева
+    #[serde(default = "default_true")]
+    show_largest_file: bool,
+}
+
+fn default_zoom() -> f32 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             dark_mode: i18n::detect_system_theme(),
+            follow_system_theme: true,
             language: i18n::detect_system_language(),
             last_path: None,
+            cleanup_rules: Vec::new(),
+            view_mode: ViewMode::default(),
+            tree_sort: tree_columns::TreeSort::default(),
+            view_layouts: Vec::new(),
+            remember_view_layouts: true,
+            snapshot_policies: Vec::new(),
+            remember_drive_scans: true,
+            watchlist: Vec::new(),
+            scan_filter: filter::ScanFilter::default(),
+            filter_presets: Vec::new(),
+            color_by_age: false,
+            follow_links: false,
+            link_targets: false,
+            quick_rescan: false,
+            audit_mode: false,
+            webhook_url: String::new(),
+            smtp: smtp::SmtpSettings::default(),
+            scan_first: Vec::new(),
+            hashing_limit_mb: 0,
+            notes: BTreeMap::new(),
+            palette: palette::Palette::default(),
+            high_contrast: false,
+            zoom: default_zoom(),
+            panels: panels::PanelLayout::default(),
+            export: export::ExportSettings::default(),
+            exclusions: Vec::new(),
+            confirm: confirm::ConfirmSettings::default(),
+            window: window::WindowPlacements::default(),
+            disk_strategies: BTreeMap::new(),
+            scan_profiles: BTreeMap::new(),
+            excluded_sizes: BTreeMap::new(),
+            last_browse_dir: None,
+            recent_roots: Vec::new(),
+            reports_in_english: false,
+            show_largest_file: true,
         }
     }
 }
 
 struct CedarApp {
     root_node: Option<DirNode>,
+    /// Как собрано показанное дерево; None - сканирование неполное или дерево из файла
+    tree_origin: Option<TreeOrigin>,
+    /// Как собирается идущее сканирование
+    scan_origin: Option<TreeOrigin>,
     selected_path: Option<PathBuf>,
     scan_path: String,
+    /// Фоновая проверка введённого пути и подсказки к нему
+    path_lookup: scan_path::PathLookup,
+    taskbar: taskbar::Taskbar,
+    /// Окно покрытия переводов, открытое флагом запуска
+    translation_coverage: Option<i18n_debug::Coverage>,
     is_scanning: bool,
     scan_progress: Arc<Mutex<ScanProgress>>,
     scan_result: Arc<Mutex<Option<ScanResult>>>,
     scan_cancel: Arc<AtomicBool>,
     available_drives: Vec<DriveInfo>,
     last_scan_duration: Option<Duration>,
+    /// Пропущенное исключениями в последнем сканировании и примерно сэкономленное время
+    last_exclusion_savings: Option<(exclusions::ExclusionSavings, Duration)>,
+    /// Когда завершилось последнее сканирование
+    last_scan_finished: Option<SystemTime>,
     last_scan_size: u64,
+    /// Корень и число записей последнего полного сканирования - ожидаемый объём повторного
+    last_scan_entries: Option<(PathBuf, usize)>,
     scan_speed_mbps: f64,
     config: AppConfig,
     translations: Translations,
     show_about_window: bool,
+    show_help: bool,
+    show_diagnostics: bool,
+    /// Подсказка по сочетаниям клавиш поверх окна
+    show_shortcut_overlay: bool,
+    show_settings: bool,
+    show_slack_report: bool,
+    slack_report: Option<Vec<reports::SlackEntry>>,
+    show_untouched_report: bool,
+    untouched_report: Option<Vec<reports::UntouchedEntry>>,
+    show_streams_report: bool,
+    streams_report: Option<Vec<reports::StreamEntry>>,
+    show_mail_report: bool,
+    mail_report: Option<Vec<mail::MailStore>>,
+    show_vm_report: bool,
+    vm_report: Option<Vec<vm::VirtualDisk>>,
+    show_downloads_report: bool,
+    downloads_report: Option<downloads::DownloadsReport>,
+    /// Оценка реально освобождаемого места для папки из панели сведений
+    reclaim: Option<reclaim::ReclaimJob>,
+    panel_data: Option<panels::PanelData>,
+    chart_layers: chart::ChartLayers,
+    log: panels::EventLog,
+    untouched_years: u32,
+    basket: basket::Basket,
+    show_basket: bool,
+    show_rules: bool,
+    show_watchlist: bool,
+    preset_name: String,
+    file_list: Option<file_list::FileList>,
+    media_stats: Option<media::MediaStatsJob>,
+    compare: Option<compare::CompareWindow>,
+    snapshot_diff: Option<snapshot_diff::SnapshotDiffWindow>,
+    /// Фоновое сохранение автоматического снимка
+    auto_snapshot: Option<thread::JoinHandle<std::io::Result<snapshot_store::Saved>>>,
+    /// Прореживание хранилища снимков и подсчёт занятого им места
+    snapshot_store: snapshot_store::StoreTasks,
+    /// Последний сохранённый снимок каждого диска из списка; None - перечитать
+    drive_snapshots: Option<Vec<Option<(PathBuf, snapshot::SnapshotHeader)>>>,
+    /// Приём снимков, открытых двойным щелчком, пока окно уже запущено
+    instance: Option<instance::Listener>,
+    browser_scan: Option<browsers::BrowserScan>,
+    op_log: Option<op_log::OpLogView>,
+    duplicate_finder: Option<duplicates::DuplicateFinder>,
+    note_editor: Option<notes::NoteEditor>,
+    cleanup_plan: Option<Vec<rules::PlanItem>>,
+    leftovers: Option<leftovers::Leftovers>,
     show_delete_confirm: bool,
+    delete_free_space: Option<u64>,
+    /// Поместится ли удаляемый элемент в корзину, если её ёмкость известна
+    delete_trash: Option<(file_ops::TrashFit, platform::TrashCapacity)>,
     path_to_delete: Option<PathBuf>,
+    /// Режим аудита закреплён ключом запуска
+    audit_locked: bool,
+    /// Дерево импортировано из ncdu: пути в нём могут быть с другого компьютера
+    imported_tree: bool,
     status_message: Option<String>,
     status_message_time: Option<Instant>,
+    shell_icons: shell_icons::ShellIcons,
+    system_theme: theme::SystemTheme,
+    window_tracker: window::WindowTracker,
+    /// Когда масштаб последний раз изменился и ещё не сохранён
+    zoom_changed_at: Option<Instant>,
+    probe_cache: disk_probe::ProbeCache,
+    webhook_test: Option<webhook::WebhookTest>,
+    smtp_test: Option<smtp::SmtpTest>,
+    storage_sense: storage_sense::StorageSense,
+    view_focus: Option<PathBuf>,
+    tree_scroll_offset: f32,
+    /// Видимые строки дерева последнего кадра
+    tree_rows: Vec<TreeRow>,
+    /// Видимая область дерева - для снимка при экспорте
+    tree_view_rect: Option<egui::Rect>,
+    show_export: bool,
+    screenshot: Option<export::Screenshot>,
+    pending_scroll_offset: Option<f32>,
+    search: search::TreeSearch,
+    restore_view: Option<TreeViewState>,
+    checkpoint: Option<Arc<checkpoint::Checkpoint>>,
+    resumable: Option<checkpoint::CheckpointInfo>,
     // SVG иконки
-    icon_folder: egui::TextureHandle,
-    icon_file: egui::TextureHandle,
-    icon_search: egui::TextureHandle,
-    icon_stop: egui::TextureHandle,
-    icon_cedar: egui::TextureHandle,
+    icons: icons::Icons,
 }
 
 impl CedarApp {
     fn save_config_to_file(&self) {
+        if let Err(e) = Self::write_config_file(&self.config) {
+            eprintln!("Failed to save config: {}", e);
+        }
+    }
+    
+    /// Записывает конфигурацию в файл; без окна - при загрузке настроек из командной строки
+    fn write_config_file(config: &AppConfig) -> std::io::Result<()> {
         // Определяем путь к файлу конфигурации
-        if let Some(data_dir) = dirs::data_dir() {
-            let config_dir = data_dir.join("cedar-folder-size-analyzer");
-            
-            // Создаем директорию, если её нет
-            if let Err(e) = std::fs::create_dir_all(&config_dir) {
-                eprintln!("Failed to create config directory: {}", e);
-                return;
-            }
-            
-            let config_file = config_dir.join("config.json");
-            
-            // Сохраняем конфигурацию в файл
-            if let Ok(json) = serde_json::to_string_pretty(&self.config) {
-                if let Err(e) = std::fs::write(&config_file, json) {
-                    eprintln!("Failed to save config: {}", e);
-                }
-            }
+        let Some(config_file) = app_paths::config_file() else {
+            return Ok(());
+        };
+        
+        // Создаем директорию, если её нет
+        if let Some(config_dir) = config_file.parent() {
+            std::fs::create_dir_all(config_dir)?;
         }
+        
+        // Сохраняем конфигурацию в файл
+        std::fs::write(&config_file, serde_json::to_string_pretty(config)?)
     }
     
     fn load_config_from_file() -> AppConfig {
         // Определяем путь к файлу конфигурации
-        if let Some(data_dir) = dirs::data_dir() {
-            let config_file = data_dir
-                .join("cedar-folder-size-analyzer")
-                .join("config.json");
-            
+        if let Some(config_file) = app_paths::config_file() {
             // Загружаем конфигурацию из файла
             if let Ok(json) = std::fs::read_to_string(&config_file) {
-                if let Ok(config) = serde_json::from_str(&json) {
+                if let Ok(mut config) = serde_json::from_str(&json) {
+                    policy::apply(&mut config);
                     return config;
                 }
             }
         }
         
-        AppConfig::default()
+        let mut config = AppConfig::default();
+        policy::apply(&mut config);
+        config
     }
     
     fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // Загружаем конфигурацию из файла (новый способ) или из хранилища (старый способ для совместимости)
-        let config: AppConfig = if let Some(storage) = cc.storage {
+        let mut config: AppConfig = if let Some(storage) = cc.storage {
             storage.get_string("config")
                 .and_then(|s| serde_json::from_str(&s).ok())
-                .unwrap_or_else(|| Self::load_config_from_file())
+                .unwrap_or_else(Self::load_config_from_file)
         } else {
             Self::load_config_from_file()
         };
+        policy::apply(&mut config);
         
         let translations = Translations::load(config.language);
+        throttle::HASHING.set_limit_mb(config.hashing_limit_mb);
+        // Правила хранения могли ужесточить с прошлого запуска
+        let mut snapshot_store = snapshot_store::StoreTasks::default();
+        snapshot_store.prune(config.snapshot_policies.clone());
+        checkpoint::move_from_config_dir();
+        cc.egui_ctx.set_zoom_factor(config.zoom.clamp(MIN_ZOOM, MAX_ZOOM));
         
         let mut drives = Vec::new();
         let disks = Disks::new_with_refreshed_list();
@@ -353,100 +860,282 @@ impl CedarApp {
             .or_else(|| drives.first().map(|d| d.path.clone()))
             .unwrap_or_else(|| String::from("C:\\"));
         
-        // Загружаем SVG иконки как текстуры в зависимости от темы
-        let (folder_data, file_data, search_data, stop_data) = if config.dark_mode {
-            (ICON_FOLDER_DARK, ICON_FILE_DARK, ICON_SEARCH_DARK, ICON_STOP_DARK)
-        } else {
-            (ICON_FOLDER_LIGHT, ICON_FILE_LIGHT, ICON_SEARCH_LIGHT, ICON_STOP_LIGHT)
-        };
-        
-        let icon_folder = load_svg_as_texture(&cc.egui_ctx, folder_data, "icon_folder", 20);
-        let icon_file = load_svg_as_texture(&cc.egui_ctx, file_data, "icon_file", 20);
-        let icon_search = load_svg_as_texture(&cc.egui_ctx, search_data, "icon_search", 20);
-        let icon_stop = load_svg_as_texture(&cc.egui_ctx, stop_data, "icon_stop", 20);
-        let icon_cedar = load_svg_as_texture(&cc.egui_ctx, ICON_CEDAR, "icon_cedar", 80);
-        
         Self {
             root_node: None,
+            tree_origin: None,
+            scan_origin: None,
             selected_path: None,
             scan_path: default_path,
+            path_lookup: Default::default(),
+            taskbar: Default::default(),
+            translation_coverage: i18n_debug::Coverage::requested(),
             is_scanning: false,
             scan_progress: Arc::new(Mutex::new(ScanProgress::default())),
             scan_result: Arc::new(Mutex::new(None)),
             scan_cancel: Arc::new(AtomicBool::new(false)),
             available_drives: drives,
             last_scan_duration: None,
+            last_exclusion_savings: None,
+            last_scan_finished: None,
             last_scan_size: 0,
+            last_scan_entries: None,
             scan_speed_mbps: 0.0,
             config,
             translations,
             show_about_window: false,
+            show_help: false,
+            show_diagnostics: false,
+            show_shortcut_overlay: false,
+            show_settings: false,
+            show_slack_report: false,
+            slack_report: None,
+            show_untouched_report: false,
+            untouched_report: None,
+            show_streams_report: false,
+            streams_report: None,
+            show_mail_report: false,
+            mail_report: None,
+            show_vm_report: false,
+            vm_report: None,
+            show_downloads_report: false,
+            downloads_report: None,
+            reclaim: None,
+            panel_data: None,
+            chart_layers: chart::ChartLayers::default(),
+            log: panels::EventLog::default(),
+            untouched_years: 2,
+            basket: basket::Basket::default(),
+            show_basket: false,
+            show_rules: false,
+            show_watchlist: false,
+            preset_name: String::new(),
+            file_list: None,
+            media_stats: None,
+            compare: None,
+            snapshot_diff: None,
+            auto_snapshot: None,
+            snapshot_store,
+            drive_snapshots: None,
+            instance: instance::Listener::start(&cc.egui_ctx),
+            browser_scan: None,
+            op_log: None,
+            duplicate_finder: None,
+            note_editor: None,
+            cleanup_plan: None,
+            leftovers: None,
             show_delete_confirm: false,
+            delete_free_space: None,
+            delete_trash: None,
             path_to_delete: None,
+            audit_locked: audit::locked_by_command_line() || policy::get().disable_delete,
+            imported_tree: false,
             status_message: None,
             status_message_time: None,
-            icon_folder,
-            icon_file,
-            icon_search,
-            icon_stop,
-            icon_cedar,
+            shell_icons: shell_icons::ShellIcons::default(),
+            system_theme: theme::SystemTheme::default(),
+            window_tracker: window::WindowTracker::default(),
+            zoom_changed_at: None,
+            probe_cache: disk_probe::ProbeCache::default(),
+            webhook_test: None,
+            smtp_test: None,
+            storage_sense: storage_sense::StorageSense::default(),
+            view_focus: None,
+            tree_scroll_offset: 0.0,
+            tree_rows: Vec::new(),
+            tree_view_rect: None,
+            show_export: false,
+            screenshot: None,
+            pending_scroll_offset: None,
+            search: search::TreeSearch::default(),
+            restore_view: None,
+            checkpoint: None,
+            resumable: checkpoint::load_info(),
+            icons: icons::Icons::new(),
         }
     }
     
     fn set_language(&mut self, lang: Language) {
         self.config.language = lang;
         self.translations = Translations::load(lang);
+        self.update_jump_list();
     }
     
-    fn update_icons(&mut self, ctx: &egui::Context) {
-        // Обновляем иконки при смене темы
-        let (folder_data, file_data, search_data, stop_data) = if self.config.dark_mode {
-            (ICON_FOLDER_DARK, ICON_FILE_DARK, ICON_SEARCH_DARK, ICON_STOP_DARK)
-        } else {
-            (ICON_FOLDER_LIGHT, ICON_FILE_LIGHT, ICON_SEARCH_LIGHT, ICON_STOP_LIGHT)
-        };
-        
-        self.icon_folder = load_svg_as_texture(ctx, folder_data, "icon_folder", 20);
-        self.icon_file = load_svg_as_texture(ctx, file_data, "icon_file", 20);
-        self.icon_search = load_svg_as_texture(ctx, search_data, "icon_search", 20);
-        self.icon_stop = load_svg_as_texture(ctx, stop_data, "icon_stop", 20);
+    /// Передаёт недавние папки в список переходов панели задач
+    fn update_jump_list(&self) {
+        jump_list::update(self.config.recent_roots.clone(), &self.translations);
     }
 }
 
 impl CedarApp {
-    fn remove_from_tree(&mut self, path: &PathBuf) {
-        fn remove_recursive(node: &mut DirNode, path: &PathBuf) -> bool {
-            // Удаляем из детей
-            node.children.retain(|child| &child.path != path);
-            
-            // Рекурсивно проверяем детей
-            for child in &mut node.children {
-                if remove_recursive(child, path) {
-                    return true;
-                }
-            }
-            
-            false
-        }
-        
+    /// Новое дерево вместо прежнего (снимок): отчёты по старому больше не верны
+    fn replace_tree(&mut self, mut node: DirNode) {
+        // Снимки и импортированные отчёты упорядочены по размеру
+        self.config.tree_sort.sort_tree(&mut node, &AtomicUsize::new(0));
+        self.root_node = Some(node);
+        self.tree_origin = None;
+        self.imported_tree = false;
+        self.slack_report = None;
+        self.untouched_report = None;
+        self.streams_report = None;
+        self.mail_report = None;
+        self.vm_report = None;
+        self.downloads_report = None;
+        self.reclaim = None;
+        self.panel_data = None;
+        self.search.invalidate();
+        self.chart_layers.invalidate();
+        self.cleanup_plan = None;
+        self.leftovers = None;
+    }
+
+    fn remove_from_tree(&mut self, path: &Path) {
         if let Some(root) = &mut self.root_node {
             // Проверяем, не удаляем ли корневую папку
-            if &root.path == path {
+            if root.path == path {
                 self.root_node = None;
                 self.selected_path = None;
             } else {
-                remove_recursive(root, path);
-                // Если удалённый элемент был выбран, снимаем выделение
-                if self.selected_path.as_ref() == Some(path) {
+                root.remove_descendant(path);
+                // Если удалённый элемент (или его содержимое) был выбран, снимаем выделение
+                if self.selected_path.as_ref().is_some_and(|p| p.starts_with(path)) {
                     self.selected_path = None;
                 }
             }
+            self.slack_report = None;
+            self.untouched_report = None;
+            self.streams_report = None;
+            self.mail_report = None;
+            self.vm_report = None;
+            self.downloads_report = None;
+            self.reclaim = None;
+            self.panel_data = None;
+            self.chart_layers.invalidate();
+            self.search.invalidate();
+        }
+    }
+    
+    /// Удаляет элемент в корзину или безвозвратно и убирает его из дерева
+    fn delete_item(&mut self, path: &Path, permanent: bool) {
+        if self.audit_blocked() {
+            return;
+        }
+        match self.delete_logged(path, permanent) {
+            Ok(_) if permanent => {
+                self.remove_from_tree(path);
+                self.set_status(self.translations.get_fmt("deleted_permanently", &[&path.display().to_string()]));
+            }
+            Ok(_) => {
+                self.remove_from_tree(path);
+                self.set_status(format!("✓ Удалено в корзину: {}", path.display()));
+            }
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(format!("✗ Ошибка удаления: {}", e));
+            }
+        }
+    }
+    
+    /// Выполняет действия, выбранные в контекстном меню элемента
+    fn apply_node_actions(&mut self, actions: NodeActions) {
+        if let Some(path) = actions.watch_toggle {
+            watchlist::toggle(&mut self.config.watchlist, path, self.root_node.as_ref());
+            self.save_config_to_file();
+        }
+        if let Some(path) = actions.scan_first_toggle {
+            match self.config.scan_first.iter().position(|p| *p == path) {
+                Some(pos) => {
+                    self.config.scan_first.remove(pos);
+                }
+                None => self.config.scan_first.push(path),
+            }
+            self.save_config_to_file();
+        }
+        if let Some(path) = actions.edit_note {
+            self.edit_note(path);
+        }
+        if let Some(path) = actions.list_files {
+            self.file_list = self
+                .root_node
+                .as_ref()
+                .and_then(|root| root.find(&path))
+                .map(file_list::FileList::new);
+        }
+        if let Some(path) = actions.media_stats {
+            self.media_stats = self
+                .root_node
+                .as_ref()
+                .and_then(|root| root.find(&path))
+                .map(media::MediaStatsJob::start);
+        }
+        if let Some(path) = actions.find_duplicates {
+            self.duplicate_finder = self
+                .root_node
+                .as_ref()
+                .and_then(|root| root.find(&path))
+                .map(duplicates::DuplicateFinder::new);
+        }
+        if let Some(exclusion) = actions.exclude {
+            self.add_exclusion(exclusion);
         }
     }
     
+    /// Сообщение в строке состояния; оно же попадает в журнал
+    fn set_status(&mut self, text: String) {
+        self.log.info(text.clone());
+        self.status_message = Some(text);
+        self.status_message_time = Some(Instant::now());
+    }
+    
     fn start_scan(&mut self, path: String) {
+        let path = scan_path::normalize(&path);
+        self.start_scan_with(path, self.config.scan_filter.clone(), false);
+    }
+    
+    /// Продолжает прерванное сканирование с сохранённой контрольной точки
+    fn resume_scan(&mut self) {
+        if let Some(info) = self.resumable.take() {
+            // Продолжаем с тем же фильтром, с которым сканирование начиналось
+            self.start_scan_with(info.root.to_string_lossy().to_string(), info.filter, true);
+        }
+    }
+    
+    fn start_scan_with(&mut self, path: String, filter: filter::ScanFilter, resume: bool) {
         self.is_scanning = true;
-        self.root_node = None;
+        self.resumable = None;
+        self.imported_tree = false;
+        // Сообщение об отключённом диске висит до следующего сканирования
+        if self.status_message_time.is_none() {
+            self.status_message = None;
+        }
+        self.log.info(self.translations.get_fmt("log_scan_started", &[&path]));
+        self.last_exclusion_savings = None;
+        // Исключения, добавленные после прошлого сканирования, ещё видны в его дереве
+        if let Some(root) = &self.root_node {
+            exclusions::remember_sizes(root, &policy::exclusions(&self.config), &mut self.config.excluded_sizes);
+        }
+        // При пересканировании того же корня запоминаем раскрытые папки и прокрутку
+        let previous = self.root_node.take().filter(|root| root.path == Path::new(&path));
+        self.restore_view = previous.as_ref().map(|root| {
+            let mut expanded = HashSet::new();
+            root.collect_expanded(&mut expanded);
+            TreeViewState {
+                root: root.path.clone(),
+                expanded,
+                scroll_offset: self.tree_scroll_offset,
+            }
+        });
+        // Прошлое дерево того же корня - источник неизменившихся папок, если оно собрано
+        // так же. Продолжение берёт своё из контрольной точки
+        let origin = TreeOrigin {
+            filter: filter.clone(),
+            follow_links: self.config.follow_links,
+            link_targets: self.config.link_targets,
+            exclusions: policy::exclusions(&self.config),
+        };
+        let built = self.tree_origin.take();
+        let previous = previous
+            .filter(|_| self.config.quick_rescan && !resume && built.is_some_and(|built| built.can_reuse_for(&origin)))
+            .map(Arc::new);
         self.scan_cancel.store(false, Ordering::Relaxed);
         
         let progress = self.scan_progress.clone();
@@ -457,43 +1146,101 @@ impl CedarApp {
         *result.lock().unwrap() = None;
         
         // Получаем информацию о диске
-        let (disk_size, disk_type, is_ssd) = get_disk_info(&path);
+        let disk = get_disk_info(&path);
+        let strategy = self.config.disk_strategies.get(&*disk.mount.to_string_lossy()).copied().unwrap_or_default();
+        let profile = self.config.scan_profiles.get(&disk.volume_key).cloned();
+        let cluster_size = platform::cluster_size(Path::new(&path));
+        
+        // Процент по числу записей: известному с прошлого раза или подсчитанному заново.
+        // Для целого диска хватает доли его объёма, подсчёт там был бы слишком долгим
+        let known_entries = self.last_scan_entries.as_ref().filter(|(root, _)| root == Path::new(&path)).map(|(_, n)| *n);
+        let is_drive = self.available_drives.iter().any(|d| Path::new(&d.path) == Path::new(&path));
         
         {
             let mut prog = progress.lock().unwrap();
             prog.message = "Starting scan...".to_string();
+            prog.root = path.clone();
             prog.current_path.clear();
             prog.files_scanned = 0;
             prog.dirs_scanned = 0;
             prog.total_size = 0;
-            prog.disk_size = disk_size;
-            prog.disk_type = disk_type.clone();
-            prog.thread_count = if is_ssd {
-                rayon::current_num_threads()
-            } else {
-                1
-            };
+            prog.entries_seen = 0;
+            prog.expected_entries = known_entries.unwrap_or(0);
+            prog.whole_disk = is_drive;
+            prog.disk_size = disk.size;
+            prog.disk_type = disk.kind_label.clone();
+            prog.cluster_size = cluster_size;
+            prog.thread_count = 1;
+            prog.tuning = disk_probe::Tuning::Detected;
+            prog.volume_key = disk.volume_key.clone();
+            prog.drive = disk.mount.display().to_string();
+            prog.excluded = Default::default();
+            prog.processing = None;
+            prog.scanned_first.clear();
         }
         
+        let checkpoint = Arc::new(checkpoint::Checkpoint::new(PathBuf::from(&path), filter.clone(), resume));
+        self.checkpoint = Some(checkpoint.clone());
+        
+        let mut options = ScanOptions {
+            threads: 1,
+            cluster_size,
+            filter,
+            follow_links: origin.follow_links,
+            link_targets: origin.link_targets,
+            exclusions: origin.exclusions.clone(),
+            excluded_sizes: Arc::new(self.config.excluded_sizes.clone()),
+            previous,
+            scan_first: self.config.scan_first.clone(),
+            sort: self.config.tree_sort,
+            encode_snapshot: self.auto_snapshot_policy(Path::new(&path)).is_some(),
+        };
+        self.scan_origin = Some(origin);
+        let scan_done = Arc::new(AtomicBool::new(false));
+        if known_entries.is_none() && !is_drive {
+            let root = PathBuf::from(&path);
+            let exclusions = exclusions::Matcher::new(&options.exclusions);
+            let (progress, cancel, scan_done) = (progress.clone(), cancel.clone(), scan_done.clone());
+            thread::spawn(move || {
+                let stopped = || cancel.load(Ordering::Relaxed) || scan_done.load(Ordering::Relaxed);
+                if let Some(count) = count_entries(&root, &exclusions, &stopped) {
+                    let mut prog = progress.lock().unwrap();
+                    if !stopped() {
+                        prog.expected_entries = count;
+                    }
+                }
+            });
+        }
+        let probe_cache = self.probe_cache.clone();
         thread::spawn(move || {
-            scan_directory(&path, progress.clone(), result.clone(), cancel.clone(), is_ssd)
+            // Замер диска может занять долю секунды - делаем его уже в потоке сканирования
+            let (threads, tuning) = disk_probe::choose_threads(strategy, profile.as_ref(), || {
+                disk_probe::use_parallel(strategy, disk.kind, || probe_cache.latency(&disk.mount, Path::new(&path)))
+            });
+            options.threads = threads;
+            {
+                let mut prog = progress.lock().unwrap();
+                prog.thread_count = threads;
+                prog.tuning = tuning;
+            }
+            scan_directory(&path, progress.clone(), result.clone(), cancel.clone(), options, checkpoint);
+            scan_done.store(true, Ordering::Relaxed);
         });
     }
     
     fn stop_scan(&mut self) {
         self.scan_cancel.store(true, Ordering::Relaxed);
         self.is_scanning = false;
+        // Поток сканирования сохранит контрольную точку сам, предложение продолжить показываем сразу
+        self.resumable = self.checkpoint.take().map(|c| c.info()).filter(|info| info.scanned_size > 0);
         
         let mut prog = self.scan_progress.lock().unwrap();
         prog.message = "Scan cancelled".to_string();
     }
 }
 
-fn get_disk_info(path: &str) -> (u64, String, bool) {
-    let disks = Disks::new_with_refreshed_list();
-    let path_buf = PathBuf::from(path);
-    
-    // Находим диск, на котором находится путь
+/// Находит диск, на котором находится путь (с самой длинной точкой монтирования)
+fn find_disk<'a>(disks: &'a Disks, path: &Path) -> Option<&'a sysinfo::Disk> {
     let mut best_match: Option<&sysinfo::Disk> = None;
     let mut best_match_len = 0;
     
@@ -501,7 +1248,7 @@ fn get_disk_info(path: &str) -> (u64, String, bool) {
         let mount_point = disk.mount_point();
         
         // Проверяем, начинается ли путь с точки монтирования
-        if path_buf.starts_with(mount_point) {
+        if path.starts_with(mount_point) {
             let mount_len = mount_point.as_os_str().len();
             if mount_len > best_match_len {
                 best_match = Some(disk);
@@ -510,129 +1257,497 @@ fn get_disk_info(path: &str) -> (u64, String, bool) {
         }
     }
     
-    if let Some(disk) = best_match {
-        let size = disk.total_space();
-        let disk_type = format!("{:?}", disk.kind());
-        
-        // Определяем, является ли диск SSD
-        let is_ssd = matches!(disk.kind(), sysinfo::DiskKind::SSD);
-        
-        (size, disk_type, is_ssd)
-    } else {
-        (0, "Unknown".to_string(), false)
-    }
+    best_match
 }
 
-fn get_disk_size(path: &str) -> u64 {
-    get_disk_info(path).0
+/// Свободное место на диске, где находится путь
+fn get_free_space(path: &Path) -> Option<u64> {
+    let disks = Disks::new_with_refreshed_list();
+    find_disk(&disks, path).map(|disk| disk.available_space())
 }
 
-const MAX_VISIBLE_CHILDREN: usize = 200;
+/// Диск, на котором находится сканируемый путь
+struct DiskInfo {
+    size: u64,
+    kind_label: String,
+    kind: sysinfo::DiskKind,
+    mount: PathBuf,
+    /// Ключ тома для профиля скорости: серийный номер, а где его нет -
+    /// имя устройства, файловая система и размер
+    volume_key: String,
+}
 
-fn render_tree_node_static(
-    ui: &mut egui::Ui,
-    node: &mut DirNode,
-    depth: usize,
-    selected_path: &mut Option<PathBuf>,
-    path_to_delete: &mut Option<PathBuf>,
-    icon_folder: &egui::TextureHandle,
-    icon_file: &egui::TextureHandle,
-) {
-    let indent = depth as f32 * 24.0; // Увеличили отступ для лучшей читаемости
+fn get_disk_info(path: &str) -> DiskInfo {
+    let disks = Disks::new_with_refreshed_list();
     
-    ui.horizontal(|ui| {
-        ui.add_space(indent);
-        
-        let has_children = !node.children.is_empty();
-        
-        // Кнопка раскрытия только для папок с детьми
-        if !node.is_file && has_children {
-            // Используем иконки phosphor
-            let expand_icon = if node.is_expanded { regular::CARET_DOWN } else { regular::CARET_RIGHT };
-            
-            // Обычная кнопка вместо small_button для большего размера
-            if ui.button(expand_icon).clicked() {
-                node.is_expanded = !node.is_expanded;
-            }
-        } else {
-            ui.add_space(24.0);
+    if let Some(disk) = find_disk(&disks, Path::new(path)) {
+        let volume_key = match platform::volume_serial(disk.mount_point()) {
+            Some(serial) => format!("{:08X}", serial),
+            None => format!(
+                "{}|{}|{}",
+                disk.name().to_string_lossy(),
+                disk.file_system().to_string_lossy(),
+                disk.total_space()
+            ),
+        };
+        DiskInfo {
+            size: disk.total_space(),
+            kind_label: format!("{:?}", disk.kind()),
+            kind: disk.kind(),
+            mount: disk.mount_point().to_path_buf(),
+            volume_key,
         }
-        
-        // Иконка: всегда папка для папок, файл для файлов
-        let icon_texture = if node.is_file { 
-            icon_file
-        } else { 
-            icon_folder
+    } else {
+        DiskInfo {
+            size: 0,
+            kind_label: "Unknown".to_string(),
+            kind: sysinfo::DiskKind::Unknown(-1),
+            mount: PathBuf::from(path),
+            volume_key: path.to_string(),
+        }
+    }
+}
+
+/// Быстрый подсчёт записей в поддереве без чтения метаданных - ожидаемый объём
+/// сканирования. Ссылки не раскрываются, исключённые папки не обходятся.
+/// None, если подсчёт остановлен
+fn count_entries(root: &Path, exclusions: &exclusions::Matcher, stopped: &dyn Fn() -> bool) -> Option<usize> {
+    let mut count = 0;
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        if stopped() {
+            return None;
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
         };
+        for entry in entries.flatten() {
+            count += 1;
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dir && !exclusions.matches(&entry.path(), &entry.file_name().to_string_lossy(), false) {
+                stack.push(entry.path());
+            }
+        }
+    }
+    Some(count)
+}
+
+/// Действия из контекстного меню, которые выполняются после отрисовки
+#[derive(Default)]
+struct NodeActions {
+    watch_toggle: Option<PathBuf>,
+    scan_first_toggle: Option<PathBuf>,
+    edit_note: Option<PathBuf>,
+    list_files: Option<PathBuf>,
+    media_stats: Option<PathBuf>,
+    find_duplicates: Option<PathBuf>,
+    exclude: Option<exclusions::Exclusion>,
+}
+
+/// Контекстное меню элемента, общее для дерева и карты
+struct NodeMenu<'a> {
+    path_to_delete: &'a mut Option<PathBuf>,
+    /// Режим аудита или импортированное дерево: удаление недоступно
+    read_only: bool,
+    basket: &'a mut basket::Basket,
+    watchlist: &'a [watchlist::WatchedFolder],
+    scan_first: &'a [PathBuf],
+    notes: &'a BTreeMap<PathBuf, String>,
+    translations: &'a Translations,
+    actions: &'a mut NodeActions,
+}
+
+impl NodeMenu<'_> {
+    fn show(&mut self, ui: &mut egui::Ui, node: &DirNode) {
+        if ui.add_enabled(!self.read_only, egui::Button::new(format!("{} Удалить в корзину", regular::TRASH))).clicked() {
+            *self.path_to_delete = Some(node.path.clone());
+            ui.close_menu();
+        }
         
-        let size_str = format_size(node.size);
-        
-        // Отображаем иконку как изображение с фиксированным размером
-        ui.add(egui::Image::new(icon_texture).max_size(egui::vec2(16.0, 16.0)));
-        
-        let label = format!("{} - {}", node.name, size_str);
-        
-        let response = ui.selectable_label(
-            selected_path.as_ref() == Some(&node.path),
-            label,
-        );
-        
-        // Одиночный клик - выбор
-        if response.clicked() {
-            *selected_path = Some(node.path.clone());
+        if self.basket.contains(&node.path) {
+            if ui.button(format!("{} {}", regular::BASKET, self.translations.get("remove_from_basket"))).clicked() {
+                self.basket.remove(&node.path);
+                ui.close_menu();
+            }
+        } else if ui.button(format!("{} {}", regular::BASKET, self.translations.get("add_to_basket"))).clicked() {
+            self.basket.add(node.path.clone());
+            ui.close_menu();
         }
         
-        // Двойной клик - раскрытие/свёртывание (только для папок с детьми)
-        if !node.is_file && has_children && response.double_clicked() {
-            node.is_expanded = !node.is_expanded;
+        let note_key = if self.notes.contains_key(&node.path) { "edit_note" } else { "add_note" };
+        if ui.button(format!("{} {}", regular::NOTE_PENCIL, self.translations.get(note_key))).clicked() {
+            self.actions.edit_note = Some(node.path.clone());
+            ui.close_menu();
         }
         
-        // Контекстное меню (правый клик)
-        response.context_menu(|ui| {
-            if ui.button(format!("{} Удалить в корзину", regular::TRASH)).clicked() {
-                *path_to_delete = Some(node.path.clone());
+        if !node.is_file {
+            let (icon, key) = if self.watchlist.iter().any(|w| w.path == node.path) {
+                (regular::EYE_SLASH, "unwatch_folder")
+            } else {
+                (regular::EYE, "watch_folder")
+            };
+            if ui.button(format!("{} {}", icon, self.translations.get(key))).clicked() {
+                self.actions.watch_toggle = Some(node.path.clone());
                 ui.close_menu();
             }
             
-            if ui.button(format!("{} Открыть в проводнике", regular::FOLDER_OPEN)).clicked() {
-                if let Err(e) = open::that(&node.path) {
-                    eprintln!("Failed to open path: {}", e);
-                }
+            let mut first = self.scan_first.contains(&node.path);
+            if ui.checkbox(&mut first, self.translations.get("scan_first"))
+                .on_hover_text(self.translations.get("scan_first_hint"))
+                .clicked()
+            {
+                self.actions.scan_first_toggle = Some(node.path.clone());
                 ui.close_menu();
             }
             
-            if ui.button(format!("{} Копировать путь", regular::COPY)).clicked() {
-                ui.output_mut(|o| o.copied_text = node.path.display().to_string());
+            if ui.button(format!("{} {}", regular::LIST_BULLETS, self.translations.get("show_all_files"))).clicked() {
+                self.actions.list_files = Some(node.path.clone());
                 ui.close_menu();
             }
-        });
+            
+            if ui.button(format!("{} {}", regular::FILM_STRIP, self.translations.get("media_stats"))).clicked() {
+                self.actions.media_stats = Some(node.path.clone());
+                ui.close_menu();
+            }
+            
+            if ui.button(format!("{} {}", regular::COPY_SIMPLE, self.translations.get("find_duplicates"))).clicked() {
+                self.actions.find_duplicates = Some(node.path.clone());
+                ui.close_menu();
+            }
+        }
         
-        response.on_hover_text(node.path.display().to_string());
-    });
-    
-    if node.is_expanded {
-        let total_children = node.children.len();
+        ui.menu_button(format!("{} {}", regular::PROHIBIT, self.translations.get("exclude_from_scan")), |ui| {
+            let key = if node.is_file { "exclude_this_file" } else { "exclude_this_folder" };
+            if ui.button(self.translations.get(key)).clicked() {
+                self.actions.exclude = Some(exclusions::Exclusion::Path(node.path.clone()));
+                ui.close_menu();
+            }
+            if let Some(pattern) = node.is_file.then(|| exclusions::Exclusion::extension_of(&node.display_name())).flatten() {
+                if ui.button(self.translations.get_fmt("exclude_pattern", &[&pattern.label()])).clicked() {
+                    self.actions.exclude = Some(pattern);
+                    ui.close_menu();
+                }
+            }
+        });
         
-        // Показываем только первые MAX_VISIBLE_CHILDREN элементов
-        for child in node.children.iter_mut().take(MAX_VISIBLE_CHILDREN) {
-            render_tree_node_static(ui, child, depth + 1, selected_path, path_to_delete, icon_folder, icon_file);
+        // Файл показываем выделенным в его папке - открыть его "как папку" нельзя
+        let open_key = if node.is_file { "show_in_folder" } else { "open_in_file_manager" };
+        if ui.button(format!("{} {}", regular::FOLDER_OPEN, self.translations.get(open_key))).clicked() {
+            let result = if node.is_file {
+                platform::reveal_in_file_manager(&node.path)
+            } else {
+                open::that(&node.path)
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to open path: {}", e);
+            }
+            ui.close_menu();
         }
         
-        // Если элементов больше, показываем индикатор
-        if total_children > MAX_VISIBLE_CHILDREN {
-            let hidden_count = total_children - MAX_VISIBLE_CHILDREN;
-            let child_indent = (depth + 1) as f32 * 20.0;
-            
-            ui.horizontal(|ui| {
-                ui.add_space(child_indent);
-                ui.add_space(20.0); // Вместо стрелки
-                ui.label(
-                    egui::RichText::new(format!("... еще {} элементов", hidden_count))
-                        .italics()
-                        .color(ui.visuals().weak_text_color())
+        if ui.button(format!("{} {}", regular::COPY, self.translations.get("copy_path"))).clicked() {
+            ui.output_mut(|o| o.copied_text = node.path.display().to_string());
+            ui.close_menu();
+        }
+    }
+}
+
+/// Общее состояние для отрисовки строк дерева
+struct TreeView<'a> {
+    selected_path: &'a mut Option<PathBuf>,
+    menu: NodeMenu<'a>,
+    shell_icons: &'a mut shell_icons::ShellIcons,
+    icons: &'a icons::Icons,
+    translations: &'a Translations,
+    /// Момент отсчёта для раскраски по возрасту; None - раскраска выключена
+    age_now: Option<SystemTime>,
+    dark_mode: bool,
+    palette: palette::Palette,
+    /// Подписывать у свёрнутых папок самый большой файл
+    show_largest_file: bool,
+    search: &'a mut search::TreeSearch,
+}
+
+/// Строка дерева в порядке отображения: узел задан строкой родителя и номером среди его детей
+#[derive(Clone, Copy)]
+struct TreeRow {
+    parent: Option<usize>,
+    child: usize,
+    depth: usize,
+}
+
+/// Собирает видимые строки дерева - корень и содержимое развёрнутых папок.
+/// `visit` получает номер строки и узел
+fn collect_tree_rows(root: &DirNode, rows: &mut Vec<TreeRow>, visit: &mut impl FnMut(usize, &DirNode)) {
+    fn walk(node: &DirNode, row: usize, depth: usize, rows: &mut Vec<TreeRow>, visit: &mut impl FnMut(usize, &DirNode)) {
+        visit(row, node);
+        if !node.is_expanded {
+            return;
+        }
+        for (child, child_node) in node.children.iter().enumerate() {
+            let child_row = rows.len();
+            rows.push(TreeRow { parent: Some(row), child, depth: depth + 1 });
+            walk(child_node, child_row, depth + 1, rows, visit);
+        }
+    }
+    
+    rows.clear();
+    rows.push(TreeRow { parent: None, child: 0, depth: 0 });
+    walk(root, 0, 0, rows, visit);
+}
+
+/// Узел строки и размер его родителя; путь от корня восстанавливается по строкам родителей
+fn tree_row_node<'a>(root: &'a mut DirNode, rows: &[TreeRow], row: usize) -> (&'a mut DirNode, u64) {
+    let mut children = Vec::new();
+    let mut current = row;
+    while let Some(parent) = rows[current].parent {
+        children.push(rows[current].child);
+        current = parent;
+    }
+    let mut parent_size = root.size;
+    let mut node = root;
+    for &child in children.iter().rev() {
+        parent_size = node.size;
+        node = &mut node.children[child];
+    }
+    (node, parent_size)
+}
+
+/// Высота строки дерева: по кнопке раскрытия, самому высокому элементу строки
+fn tree_row_height(ui: &egui::Ui) -> f32 {
+    ui.text_style_height(&egui::TextStyle::Button) + 2.0 * ui.spacing().button_padding.y
+}
+
+fn render_tree_row(
+    ui: &mut egui::Ui,
+    node: &mut DirNode,
+    parent_size: u64,
+    depth: usize,
+    view: &mut TreeView,
+) {
+    let indent = depth as f32 * 24.0; // Увеличили отступ для лучшей читаемости
+    let row_height = tree_row_height(ui);
+    
+    // Место под фон строки: его размер известен только после отрисовки
+    let background = ui.painter().add(egui::Shape::Noop);
+    
+    let size_str = format_size(node.size);
+    let percent = if parent_size > 0 { node.size as f64 / parent_size as f64 * 100.0 } else { 100.0 };
+    
+    // Имя с отметками слева, столбцы справа
+    let row_rect = egui::Rect::from_min_size(ui.cursor().min, egui::vec2(ui.available_width(), row_height));
+    let (name_rect, cells) = tree_columns::split_row(ui, row_rect);
+    
+    // Строки создаются только на экране; свой id сохраняет открытое меню при прокрутке
+    let row_id = egui::Id::new(&node.path);
+    let row = ui.push_id(row_id, |ui| {
+        ui.allocate_new_ui(
+            egui::UiBuilder::new().max_rect(name_rect).layout(egui::Layout::left_to_right(egui::Align::Center)),
+            |ui| {
+                ui.set_min_height(row_height);
+                ui.set_clip_rect(name_rect.intersect(ui.clip_rect()));
+                ui.add_space(indent);
+                
+                let has_children = !node.children.is_empty();
+                
+                // Кнопка раскрытия только для папок с детьми
+                if !node.is_file && has_children {
+                    // Используем иконки phosphor
+                    let expand_icon = if node.is_expanded { regular::CARET_DOWN } else { regular::CARET_RIGHT };
+                    
+                    // Обычная кнопка вместо small_button для большего размера
+                    let expand_response = ui.button(expand_icon);
+                    let expand_key = if node.is_expanded { "collapse" } else { "expand" };
+                    a11y::set_name(&expand_response, egui::WidgetType::Button, &view.translations.get(expand_key));
+                    if expand_response.clicked() {
+                        node.is_expanded = !node.is_expanded;
+                    }
+                } else {
+                    ui.add_space(24.0);
+                }
+                
+                // Иконка: системная по расширению, иначе встроенная папка/файл
+                let native_icon = view.shell_icons.get(ui.ctx(), node);
+                let builtin = if node.is_file { icons::Icon::File } else { icons::Icon::Folder };
+                let icon_image = match &native_icon {
+                    Some(texture) => Some(egui::Image::new(texture)),
+                    None => view.icons.image(builtin).map(|image| image.tint(ui.visuals().text_color())),
+                };
+                
+                // Отображаем иконку как изображение с фиксированным размером
+                match icon_image {
+                    Some(image) => ui.add(image.max_size(egui::vec2(16.0, 16.0))),
+                    None => ui.add_sized([16.0, 16.0], egui::Label::new(builtin.glyph())),
+                };
+                
+                let selected = view.selected_path.as_ref() == Some(&node.path);
+                let response = ui.selectable_label(selected, node.display_name());
+                
+                // Для диктора: имя, тип, размер и доля в родительской папке
+                let accessible_name = view.translations.get_fmt(
+                    "tree_row_accessible",
+                    &[
+                        &node.display_name(),
+                        &view.translations.get(if node.is_file { "file" } else { "folder" }),
+                        &size_str,
+                        &format!("{:.1}", percent),
+                    ],
                 );
-            });
+                let enabled = response.enabled();
+                response.widget_info(|| {
+                    egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, enabled, selected, &accessible_name)
+                });
+                
+                // Одиночный клик - выбор
+                if response.clicked() {
+                    *view.selected_path = Some(node.path.clone());
+                }
+                
+                // Двойной клик - раскрытие/свёртывание (только для папок с детьми)
+                if !node.is_file && has_children && response.double_clicked() {
+                    node.is_expanded = !node.is_expanded;
+                }
+                
+                // Контекстное меню (правый клик)
+                response.context_menu(|ui| view.menu.show(ui, node));
+                
+                clipboard::path_tooltip(response, &node.path, &view.translations.get("copy_path"));
+                
+                // Свёрнутая папка: часто её размер объясняет один файл
+                if view.show_largest_file && !node.is_file && !node.is_expanded {
+                    if let Some(largest) = &node.largest_file {
+                        ui.label(
+                            egui::RichText::new(view.translations.get_fmt(
+                                "largest_file_badge",
+                                &[&largest.name.to_string_lossy(), &format_size(largest.size)],
+                            ))
+                            .small()
+                            .color(ui.visuals().weak_text_color())
+                        );
+                    }
+                }
+                
+                // Разреженный файл (или папка с такими файлами): занято меньше логического размера.
+                // У обычных папок занятое место из-за кластеров всегда не меньше размера.
+                let sparse_folder = !node.is_file && node.allocated < node.size;
+                if node.sparse || sparse_folder {
+                    let hint = if node.is_file { "sparse_hint" } else { "sparse_folder_hint" };
+                    ui.label(
+                        egui::RichText::new(view.translations.get_fmt("sparse_badge", &[&format_size(node.allocated)]))
+                            .small()
+                            .color(ui.visuals().selection.stroke.color)
+                    )
+                    .on_hover_text(view.translations.get_fmt(
+                        hint,
+                        &[&format_size(node.size), &format_size(node.allocated)],
+                    ));
+                }
+                
+                if node.referenced > 0 {
+                    let hint = match &node.link_target {
+                        Some(target) => view.translations.get_fmt("referenced_hint", &[&target.display().to_string()]),
+                        None => view.translations.get("referenced_folder_hint"),
+                    };
+                    ui.label(
+                        egui::RichText::new(view.translations.get_fmt("referenced_badge", &[&format_size(node.referenced)]))
+                            .small()
+                            .italics()
+                            .color(ui.visuals().weak_text_color())
+                    )
+                    .on_hover_text(hint);
+                }
+                
+                if node.streams > 0 {
+                    a11y::icon_label(
+                        ui,
+                        egui::RichText::new(regular::STACK).color(ui.visuals().weak_text_color()),
+                        &view.translations.get_fmt("streams_hint", &[&format_size(node.streams)]),
+                    );
+                }
+                
+                if view.menu.basket.contains(&node.path) {
+                    a11y::icon_label(
+                        ui,
+                        egui::RichText::new(regular::BASKET).color(ui.visuals().weak_text_color()),
+                        &view.translations.get("basket"),
+                    );
+                }
+                
+                if let Some(note) = view.menu.notes.get(&node.path) {
+                    a11y::icon_label(
+                        ui,
+                        egui::RichText::new(regular::NOTE).color(ui.visuals().selection.stroke.color),
+                        note,
+                    );
+                }
+                
+                if view.menu.watchlist.iter().any(|w| w.path == node.path) {
+                    a11y::icon_label(
+                        ui,
+                        egui::RichText::new(regular::EYE).color(ui.visuals().weak_text_color()),
+                        &view.translations.get("watchlist"),
+                    );
+                }
+                
+                // Значок для узлов, часть содержимого которых не удалось прочитать
+                if node.skipped > 0 {
+                    let warning_color = view.palette.status_text(palette::Status::Warning, view.dark_mode);
+                    a11y::icon_label(
+                        ui,
+                        egui::RichText::new(regular::WARNING).color(warning_color),
+                        &view.translations.get("items_skipped_hint"),
+                    );
+                    ui.label(
+                        egui::RichText::new(view.translations.get_fmt("items_skipped", &[&node.skipped.to_string()]))
+                            .small()
+                            .color(ui.visuals().weak_text_color())
+                    );
+                }
+            },
+        );
+        
+        let weak = ui.visuals().weak_text_color();
+        for ((column, _), rect) in tree_columns::COLUMNS.iter().zip(cells) {
+            match column {
+                tree_columns::TreeColumn::Size => {
+                    tree_columns::cell(ui, rect, size_str.as_str());
+                }
+                tree_columns::TreeColumn::Percent => {
+                    let text = format!("{}%", view.translations.format_decimal(percent, 1));
+                    tree_columns::cell(ui, rect, egui::RichText::new(text).color(weak));
+                }
+                tree_columns::TreeColumn::Files => {
+                    let text = if node.is_file { String::new() } else { view.translations.format_count(node.files) };
+                    tree_columns::cell(ui, rect, egui::RichText::new(text).color(weak));
+                }
+                tree_columns::TreeColumn::Folders => {
+                    let text = if node.is_file { String::new() } else { view.translations.format_count(node.folders) };
+                    tree_columns::cell(ui, rect, egui::RichText::new(text).color(weak));
+                }
+                tree_columns::TreeColumn::Modified => {
+                    let text = node.modified.map_or_else(|| "—".to_string(), |modified| view.translations.format_day(modified));
+                    let response = tree_columns::cell(ui, rect, egui::RichText::new(text).color(weak));
+                    if let Some(modified) = node.modified {
+                        response.on_hover_text(view.translations.format_date(modified));
+                    }
+                }
+                tree_columns::TreeColumn::Name => {}
+            }
         }
+    });
+    
+    // Подсветка совпадений поиска важнее раскраски по давности изменения
+    let hit = view.search.hit(node);
+    let accent = ui.visuals().selection.bg_fill;
+    let search_color = match hit {
+        search::Hit::Current => Some(accent),
+        search::Hit::Match => Some(accent.gamma_multiply(0.45)),
+        search::Hit::Contains | search::Hit::None => None,
+    };
+    if let Some(color) = search_color {
+        ui.painter().set(background, egui::Shape::rect_filled(row.response.rect, 2.0, color));
+    } else if let Some(now) = view.age_now {
+        let color = heatmap::age_color(node.modified, now, view.palette, view.dark_mode).gamma_multiply(0.35);
+        ui.painter().set(background, egui::Shape::rect_filled(row.response.rect, 2.0, color));
     }
 }
 
@@ -649,14 +1764,74 @@ impl eframe::App for CedarApp {
         // Также сохраняем в файл (основной способ)
         self.save_config_to_file();
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if self.zoom_changed_at.take().is_some() {
+            self.save_config_to_file();
+        }
+    }
     
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // В режиме "как в системе" переключаемся вслед за ОС
+        if self.config.follow_system_theme {
+            let dark = self.system_theme.is_dark(ctx);
+            if dark != self.config.dark_mode {
+                self.config.dark_mode = dark;
+                self.shell_icons.clear();
+                self.save_config_to_file();
+            }
+        }
+        
+        self.track_window(ctx);
+        self.poll_auto_snapshot();
+        self.poll_basket();
+        let received = self.instance.as_ref().map(instance::Listener::received).unwrap_or_default();
+        for path in received {
+            self.open_snapshot_file(&path);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        self.refresh_icons(ctx);
+        self.update_title(ctx);
+        self.update_taskbar(ctx, frame);
+        
         // Применяем тему
-        if self.config.dark_mode {
-            ctx.set_visuals(egui::Visuals::dark());
+        let mut visuals = if self.config.dark_mode {
+            egui::Visuals::dark()
         } else {
-            ctx.set_visuals(egui::Visuals::light());
+            egui::Visuals::light()
+        };
+        if self.config.high_contrast {
+            visuals = a11y::high_contrast(visuals, self.config.dark_mode);
+        }
+        ctx.set_visuals(visuals);
+        
+        // Ctrl+колесо мыши (и жест масштабирования) меняет масштаб интерфейса
+        let zoom_delta = ctx.input(|i| i.zoom_delta());
+        if zoom_delta != 1.0 {
+            ctx.set_zoom_factor((ctx.zoom_factor() * zoom_delta).clamp(MIN_ZOOM, MAX_ZOOM));
+        }
+        // Сохраняем масштаб, в том числе изменённый с клавиатуры (Ctrl+плюс/минус/0),
+        // когда жест закончился; несохранённый масштаб записывается при выходе
+        if ctx.zoom_factor() != self.config.zoom {
+            self.config.zoom = ctx.zoom_factor();
+            self.zoom_changed_at = Some(Instant::now());
+        }
+        if let Some(changed_at) = self.zoom_changed_at {
+            let elapsed = changed_at.elapsed();
+            if elapsed >= ZOOM_SAVE_DELAY {
+                self.zoom_changed_at = None;
+                self.save_config_to_file();
+            } else {
+                ctx.request_repaint_after(ZOOM_SAVE_DELAY - elapsed);
+            }
+        }
+        
+        // Поиск по дереву: до отрисовки, чтобы прокрутка к совпадению сработала в этом же кадре
+        if self.config.view_mode == ViewMode::Tree {
+            self.handle_search_keys(ctx);
         }
+        self.handle_copy_keys(ctx);
+        self.handle_help_keys(ctx);
         
         // Меню-бар
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
@@ -666,8 +1841,24 @@ impl eframe::App for CedarApp {
                 let home_page_text = self.translations.get("home_page");
                 let light_theme_text = self.translations.get("light_theme");
                 let dark_theme_text = self.translations.get("dark_theme");
+                let system_theme_text = self.translations.get("system_theme");
+                let theme_text = self.translations.get("theme");
                 let language_text = self.translations.get("language");
                 let about_text = self.translations.get("about");
+                let settings_text = self.translations.get("settings");
+                let reports_text = self.translations.get("reports");
+                let slack_report_text = self.translations.get("slack_report");
+                let untouched_report_text = self.translations.get("untouched_report");
+                let streams_report_text = self.translations.get("streams_report");
+                let mail_report_text = self.translations.get("mail_report");
+                let vm_report_text = self.translations.get("vm_report");
+                let pdf_report_text = self.translations.get("pdf_report");
+                let rules_text = self.translations.get("cleanup_rules");
+                let follow_links_text = self.translations.get("follow_links");
+                let watchlist_text = self.translations.get("watchlist");
+                let panels_text = self.translations.get("panels");
+                let basket_text = format!("{} ({})", self.translations.get("basket"), self.basket.items.len());
+                let has_tree = self.root_node.is_some();
                 let current_lang = self.config.language;
                 let is_dark = self.config.dark_mode;
                 
@@ -679,40 +1870,228 @@ impl eframe::App for CedarApp {
                         ui.close_menu();
                     }
                     
+                    // Снимки сканирования
+                    ui.add_enabled_ui(!self.is_scanning, |ui| {
+                        if ui.button(format!("{} {}", regular::FOLDER_OPEN, self.translations.get("open_snapshot"))).clicked() {
+                            ui.close_menu();
+                            self.open_snapshot();
+                        }
+                    });
+                    ui.add_enabled_ui(has_tree && !self.is_scanning, |ui| {
+                        if ui.button(format!("{} {}", regular::FLOPPY_DISK, self.translations.get("save_snapshot"))).clicked() {
+                            ui.close_menu();
+                            self.save_snapshot();
+                        }
+                    });
+                    ui.menu_button(format!("{} {}", regular::TERMINAL_WINDOW, self.translations.get("ncdu_menu")), |ui| {
+                        ui.add_enabled_ui(!self.is_scanning, |ui| {
+                            if ui.button(self.translations.get("ncdu_import")).clicked() {
+                                ui.close_menu();
+                                self.import_ncdu();
+                            }
+                        });
+                        ui.add_enabled_ui(has_tree && !self.is_scanning, |ui| {
+                            if ui.button(self.translations.get("ncdu_export")).clicked() {
+                                ui.close_menu();
+                                self.export_ncdu();
+                            }
+                        });
+                    });
+                    ui.add_enabled_ui(!self.is_scanning, |ui| {
+                        if ui.button(format!("{} {}", regular::FILE_CSV, self.translations.get("csv_import"))).clicked() {
+                            ui.close_menu();
+                            self.import_csv_report();
+                        }
+                    });
+                    
                     ui.separator();
                     
                     // Выбор темы
-                    if ui.button(if is_dark { 
-                        format!("{} {}", regular::SUN, light_theme_text)
-                    } else { 
-                        format!("{} {}", regular::MOON_STARS, dark_theme_text)
-                    }).clicked() {
-                        self.config.dark_mode = !self.config.dark_mode;
-                        self.update_icons(ctx);
-                        // Принудительно сохраняем настройки в файл
-                        self.save_config_to_file();
+                    let theme_icon = if is_dark { regular::MOON_STARS } else { regular::SUN };
+                    ui.menu_button(format!("{} {}", theme_icon, theme_text), |ui| {
+                        let follow = self.config.follow_system_theme;
+                        let choices = [
+                            (follow, regular::CIRCLE_HALF, &system_theme_text),
+                            (!follow && !is_dark, regular::SUN, &light_theme_text),
+                            (!follow && is_dark, regular::MOON_STARS, &dark_theme_text),
+                        ];
+                        for (index, (selected, icon, text)) in choices.into_iter().enumerate() {
+                            if ui.selectable_label(selected, format!("{} {}", icon, text)).clicked() {
+                                self.config.follow_system_theme = index == 0;
+                                if index > 0 {
+                                    self.config.dark_mode = index == 2;
+                                }
+                                self.shell_icons.clear();
+                                // Принудительно сохраняем настройки в файл
+                                self.save_config_to_file();
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    
+                    ui.separator();
+                    
+                    // Выбор языка
+                    let language_locked = policy::get().language().is_some();
+                    ui.add_enabled_ui(!language_locked, |ui| {
+                        ui.menu_button(format!("{} {}", regular::TRANSLATE, language_text), |ui| {
+                            for lang in Language::all() {
+                                if ui.selectable_label(
+                                    current_lang == lang,
+                                    lang.name()
+                                ).clicked() {
+                                    self.set_language(lang);
+                                    // Принудительно сохраняем настройки в файл
+                                    self.save_config_to_file();
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    })
+                    .response
+                    .on_disabled_hover_text(self.translations.get("policy_locked"));
+                    
+                    ui.separator();
+                    
+                    if ui.button(format!("{} {}", regular::BASKET, basket_text)).clicked() {
+                        self.show_basket = true;
                         ui.close_menu();
                     }
                     
+                    // Раскрытие ссылок действует со следующего сканирования
+                    if ui.checkbox(&mut self.config.follow_links, follow_links_text)
+                        .on_hover_text(self.translations.get("follow_links_hint"))
+                        .changed()
+                    {
+                        self.save_config_to_file();
+                    }
+                    
+                    if ui.checkbox(&mut self.config.link_targets, self.translations.get("link_targets"))
+                        .on_hover_text(self.translations.get("link_targets_hint"))
+                        .changed()
+                    {
+                        self.save_config_to_file();
+                    }
+                    
+                    if ui.checkbox(&mut self.config.quick_rescan, self.translations.get("quick_rescan"))
+                        .on_hover_text(self.translations.get("quick_rescan_hint"))
+                        .changed()
+                    {
+                        self.save_config_to_file();
+                    }
+                    
+                    self.show_audit_toggle(ui);
+                    
                     ui.separator();
                     
-                    // Выбор языка
-                    ui.menu_button(format!("{} {}", regular::TRANSLATE, language_text), |ui| {
-                        for lang in Language::all() {
-                            if ui.selectable_label(
-                                current_lang == lang,
-                                lang.name()
-                            ).clicked() {
-                                self.set_language(lang);
-                                // Принудительно сохраняем настройки в файл
-                                self.save_config_to_file();
+                    if ui.button(format!("{} {}", regular::LIST_CHECKS, rules_text)).clicked() {
+                        self.show_rules = true;
+                        ui.close_menu();
+                    }
+                    
+                    let leftovers_button = ui.add_enabled(
+                        self.root_node.is_some(),
+                        egui::Button::new(format!("{} {}", regular::BROOM, self.translations.get("leftovers"))),
+                    );
+                    if leftovers_button.clicked() {
+                        self.leftovers = self.root_node.as_ref().map(leftovers::Leftovers::find);
+                        ui.close_menu();
+                    }
+                    
+                    if ui.button(format!("{} {}", regular::EYE, watchlist_text)).clicked() {
+                        self.show_watchlist = true;
+                        ui.close_menu();
+                    }
+                    
+                    if ui.button(format!("{} {}", regular::SCALES, self.translations.get("compare_folders"))).clicked() {
+                        let root = self.root_node.as_ref().map(|r| r.path.as_path());
+                        self.compare.get_or_insert_with(|| compare::CompareWindow::new(root));
+                        ui.close_menu();
+                    }
+                    
+                    if ui.button(format!("{} {}", regular::GIT_DIFF, self.translations.get("diff_snapshots"))).clicked() {
+                        self.snapshot_diff.get_or_insert_with(snapshot_diff::SnapshotDiffWindow::new);
+                        ui.close_menu();
+                    }
+                    
+                    if ui.button(format!("{} {}", regular::GLOBE, self.translations.get("browser_data"))).clicked() {
+                        self.browser_scan = Some(browsers::BrowserScan::start());
+                        ui.close_menu();
+                    }
+                    
+                    if ui.button(format!("{} {}", regular::SCROLL, self.translations.get("op_log"))).clicked() {
+                        self.op_log = Some(op_log::OpLogView::open());
+                        ui.close_menu();
+                    }
+                    
+                    ui.menu_button(format!("{} {}", regular::SIDEBAR, panels_text), |ui| {
+                        for panel in panels::Panel::all() {
+                            let label = format!("{} {}", panel.icon(), self.translations.get(panel.translation_key()));
+                            ui.menu_button(label, |ui| {
+                                if panels::dock_menu(ui, self.config.panels.dock_mut(panel), &self.translations) {
+                                    self.view_layout_changed();
+                                }
+                            });
+                        }
+                    });
+                    
+                    // Отчёты по текущему сканированию
+                    ui.add_enabled_ui(has_tree, |ui| {
+                        ui.menu_button(format!("{} {}", regular::CHART_BAR, reports_text), |ui| {
+                            if ui.button(format!("{} {}", regular::PUZZLE_PIECE, slack_report_text)).clicked() {
+                                self.show_slack_report = true;
                                 ui.close_menu();
                             }
-                        }
+                            if ui.button(format!("{} {}", regular::SNOWFLAKE, untouched_report_text)).clicked() {
+                                self.show_untouched_report = true;
+                                ui.close_menu();
+                            }
+                            // Альтернативные потоки бывают только на NTFS
+                            if cfg!(windows) && ui.button(format!("{} {}", regular::STACK, streams_report_text)).clicked() {
+                                self.show_streams_report = true;
+                                ui.close_menu();
+                            }
+                            if ui.button(format!("{} {}", regular::ENVELOPE, mail_report_text)).clicked() {
+                                self.show_mail_report = true;
+                                ui.close_menu();
+                            }
+                            if ui.button(format!("{} {}", regular::DESKTOP_TOWER, vm_report_text)).clicked() {
+                                self.show_vm_report = true;
+                                ui.close_menu();
+                            }
+                            if ui.button(format!("{} {}", regular::DOWNLOAD_SIMPLE, self.translations.get("downloads_report"))).clicked() {
+                                self.show_downloads_report = true;
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            if ui.button(format!("{} {}", regular::FILE_PDF, pdf_report_text)).clicked() {
+                                ui.close_menu();
+                                self.export_pdf_report();
+                            }
+                            if ui.button(format!("{} {}", regular::FILE_HTML, self.translations.get("html_report"))).clicked() {
+                                ui.close_menu();
+                                self.export_html_report();
+                            }
+                        });
                     });
                     
                     ui.separator();
                     
+                    if ui.button(format!("{} {}", regular::GEAR, settings_text)).clicked() {
+                        self.show_settings = true;
+                        ui.close_menu();
+                    }
+                    
+                    if ui.button(format!("{} {}", regular::QUESTION, self.translations.get("help"))).clicked() {
+                        self.show_help = true;
+                        ui.close_menu();
+                    }
+                    
+                    if ui.button(format!("{} {}", regular::STETHOSCOPE, self.translations.get("diagnostics"))).clicked() {
+                        self.show_diagnostics = true;
+                        ui.close_menu();
+                    }
+                    
                     if ui.button(format!("{} {}", regular::INFO, about_text)).clicked() {
                         self.show_about_window = true;
                         ui.close_menu();
@@ -726,7 +2105,6 @@ impl eframe::App for CedarApp {
         
         // Копируем все необходимые переводы до использования в замыканиях
         let path_label = self.translations.get("path");
-        let browse_label = self.translations.get("browse");
         let scan_label = self.translations.get("scan");
         let stop_label = self.translations.get("stop");
         let files_label = self.translations.get("files");
@@ -740,7 +2118,7 @@ impl eframe::App for CedarApp {
         let available_drives_label = self.translations.get("available_drives");
         let selected_label = self.translations.get("selected");
         let no_selection_label = self.translations.get("no_selection");
-        let total_size_label = self.translations.get("total_size");
+        let copy_path_label = self.translations.get("copy_path");
         
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.add_space(5.0);
@@ -768,21 +2146,19 @@ impl eframe::App for CedarApp {
                         }
                     });
                 
-                ui.text_edit_singleline(&mut self.scan_path);
+                self.show_scan_path_field(ui);
                 
-                if ui.button(format!("{} {}", regular::FOLDER_OPEN, &browse_label)).clicked() {
-                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                        self.scan_path = path.display().to_string();
-                    }
-                }
+                self.show_browse_buttons(ui);
+                
+                // Фильтр файлов для следующего сканирования
+                self.show_filter_menu(ui);
                 
                 // Кнопка сканирования с SVG иконкой
-                ui.add_enabled_ui(!self.is_scanning, |ui| {
-                    let button = egui::Button::image_and_text(
-                        egui::Image::new(&self.icon_search).max_size(egui::vec2(16.0, 16.0)),
-                        &scan_label
-                    )
-                    .min_size(egui::vec2(140.0, 0.0));
+                let path_problem = self.scan_path_problem();
+                let can_scan = path_problem.is_none() && !self.scan_path.trim().is_empty();
+                ui.add_enabled_ui(!self.is_scanning && can_scan, |ui| {
+                    let button = self.icons.button(ui, icons::Icon::Search, &scan_label)
+                        .min_size(egui::vec2(140.0, 0.0));
                     let response = ui.add(button);
                     if response.hovered() {
                         ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
@@ -794,11 +2170,8 @@ impl eframe::App for CedarApp {
                 
                 // Кнопка остановки с SVG иконкой
                 ui.add_enabled_ui(self.is_scanning, |ui| {
-                    let button = egui::Button::image_and_text(
-                        egui::Image::new(&self.icon_stop).max_size(egui::vec2(16.0, 16.0)),
-                        &stop_label
-                    )
-                    .min_size(egui::vec2(140.0, 0.0));
+                    let button = self.icons.button(ui, icons::Icon::Stop, &stop_label)
+                        .min_size(egui::vec2(140.0, 0.0));
                     let response = ui.add(button);
                     if response.hovered() {
                         ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
@@ -809,13 +2182,26 @@ impl eframe::App for CedarApp {
                 });
             });
             
+            // Подсказка под полем пути, пока по нему нельзя сканировать
+            if let Some(problem) = self.scan_path_problem().filter(|_| !self.is_scanning) {
+                let mut text = format!("{} {}", regular::WARNING_CIRCLE, self.translations.get(problem.translation_key()));
+                if let scan_path::Problem::Unreadable(error) = &problem {
+                    text = format!("{}: {}", text, error);
+                }
+                ui.colored_label(self.config.palette.status_text(palette::Status::Critical, self.config.dark_mode), text);
+            }
+            
             if self.is_scanning {
                 if let Ok(progress) = self.scan_progress.lock() {
                     ui.separator();
                     
                     ui.horizontal(|ui| {
                         ui.spinner();
-                        ui.label(&progress.message);
+                        if progress.processing.is_some() {
+                            ui.label(self.translations.get("scan_processing"));
+                        } else {
+                            ui.label(&progress.message);
+                        }
                     });
                     
                     // Progress details
@@ -837,34 +2223,52 @@ impl eframe::App for CedarApp {
                             ui.separator();
                         }
                         ui.label(format!("{} {}: {}", regular::CPU, &threads_label, progress.thread_count));
+                        let tuning_key = match progress.tuning {
+                            disk_probe::Tuning::Tuned => Some("tuned_profile"),
+                            disk_probe::Tuning::Trial => Some("tuning_trial"),
+                            disk_probe::Tuning::Detected => None,
+                        };
+                        if let Some(key) = tuning_key {
+                            ui.label(
+                                egui::RichText::new(format!("({})", self.translations.get(key)))
+                                    .color(ui.visuals().weak_text_color()),
+                            )
+                            .on_hover_text(self.translations.get("tuned_profile_hint"));
+                        }
                     });
                     
                     // Current path
-                    if !progress.current_path.is_empty() {
+                    if !progress.current_path.is_empty() && progress.processing.is_none() {
                         ui.horizontal(|ui| {
                             ui.label(format!("{} {}:", regular::FOLDER, &scanning_label));
-                            ui.label(&progress.current_path);
+                            clipboard::path_label(ui, &progress.current_path, &copy_path_label);
                         });
                     }
                     
+                    // Приоритетные папки готовы раньше всего дерева
+                    if !progress.scanned_first.is_empty() {
+                        ui.label(self.translations.get("scanned_first"));
+                        for (path, size) in &progress.scanned_first {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{} {}", regular::CHECK, format_size(*size)));
+                                clipboard::path_label(ui, &path.display().to_string(), &copy_path_label);
+                            });
+                        }
+                    }
+                    
                     // Visual progress bar with real percentage
                     let available_width = ui.available_width();
-                    let progress_value = if progress.disk_size > 0 {
-                        (progress.total_size as f32 / progress.disk_size as f32).min(1.0)
-                    } else {
-                        0.0
-                    };
-                    
-                    let progress_text = if progress.disk_size > 0 {
-                        format!("{:.1}%", progress_value * 100.0)
-                    } else {
-                        calculating_label.clone()
+                    let fraction = progress.fraction();
+                    let progress_text = match fraction {
+                        Some(value) => format!("{:.1}%", value * 100.0),
+                        None => calculating_label.clone(),
                     };
                     
                     ui.add(
-                        egui::ProgressBar::new(progress_value)
+                        egui::ProgressBar::new(fraction.unwrap_or(0.0))
                             .text(progress_text)
                             .desired_width(available_width)
+                            .animate(fraction.is_none())
                     );
                 }
             }
@@ -872,27 +2276,218 @@ impl eframe::App for CedarApp {
             ui.add_space(5.0);
         });
         
+        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
+            ui.separator();
+            ui.horizontal(|ui| {
+                // Показываем статусное сообщение или выбранный путь
+                if let Some(status) = &self.status_message {
+                    ui.label(status);
+                } else if let Some(path) = &self.selected_path {
+                    ui.label(format!("{}:", &selected_label));
+                    let text = path.display().to_string();
+                    if clipboard::path_label(ui, &text, &copy_path_label) {
+                        self.set_status(self.translations.get_fmt("path_copied", &[&text]));
+                    }
+                } else {
+                    ui.label(&no_selection_label);
+                }
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    // Корзина подготовки к очистке
+                    if !self.basket.items.is_empty()
+                        && ui.button(format!("{} {}", regular::BASKET, self.basket.items.len())).clicked()
+                    {
+                        self.show_basket = true;
+                    }
+                    
+                    // Сводка сканирования
+                    self.show_status_chips(ui);
+                });
+            });
+        });
+        
+        // Пристыкованные панели занимают место до центральной
+        self.show_panels(ctx);
+        
         egui::CentralPanel::default().show(ctx, |ui| {
             if self.root_node.is_some() {
+                // Переключатель представлений; выделение и фокус у них общие
+                ui.horizontal(|ui| {
+                    let before = self.config.view_mode;
+                    ui.selectable_value(
+                        &mut self.config.view_mode,
+                        ViewMode::Tree,
+                        format!("{} {}", regular::TREE_STRUCTURE, self.translations.get("view_tree")),
+                    );
+                    ui.selectable_value(
+                        &mut self.config.view_mode,
+                        ViewMode::Icicle,
+                        format!("{} {}", regular::FLAME, self.translations.get("view_icicle")),
+                    );
+                    ui.selectable_value(
+                        &mut self.config.view_mode,
+                        ViewMode::Treemap,
+                        format!("{} {}", regular::SQUARES_FOUR, self.translations.get("view_treemap")),
+                    );
+                    ui.selectable_value(
+                        &mut self.config.view_mode,
+                        ViewMode::Sunburst,
+                        format!("{} {}", regular::CHART_DONUT, self.translations.get("view_sunburst")),
+                    );
+                    if self.config.view_mode != before {
+                        self.view_layout_changed();
+                    }
+                    
+                    ui.separator();
+                    if ui.toggle_value(
+                        &mut self.config.color_by_age,
+                        format!("{} {}", regular::THERMOMETER, self.translations.get("color_by_age")),
+                    ).changed() {
+                        self.save_config_to_file();
+                    }
+                    if self.config.color_by_age {
+                        heatmap::legend(
+                            ui,
+                            &self.translations.get("age_recent"),
+                            &self.translations.get("age_old"),
+                            self.config.palette,
+                            self.config.dark_mode,
+                        );
+                    }
+                    
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button(format!("{} {}", regular::IMAGE, self.translations.get("export_image"))).clicked() {
+                            self.show_export = true;
+                        }
+                        if self.config.view_mode == ViewMode::Tree {
+                            ui.separator();
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                                self.show_search_bar(ui);
+                            });
+                        }
+                    });
+                });
+                self.show_filter_chips(ui);
+                ui.separator();
+                
+                match self.config.view_mode {
+                    ViewMode::Tree => {
+                        let mut actions = NodeActions::default();
+                        self.show_tree_header(ui);
+                        let row_height = tree_row_height(ui);
+                        let row_pitch = row_height + ui.spacing().item_spacing.y;
+                        
+                        // Строки собираются заново каждый кадр - это дёшево, а виджеты создаются только для видимых
+                        self.begin_search_rows();
+                        let mut scroll_to_row = None;
+                        if let Some(root) = &self.root_node {
+                            let search = &mut self.search;
+                            collect_tree_rows(root, &mut self.tree_rows, &mut |row, node| {
+                                let hit = search.hit(node);
+                                search.mark_row(row as f32 * row_pitch, hit);
+                                if search.scroll_to.as_ref() == Some(&node.path) {
+                                    scroll_to_row = Some(row);
+                                }
+                            });
+                        }
+                        
+                        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false; 2]);
+                        if let Some(offset) = self.pending_scroll_offset.take() {
+                            scroll_area = scroll_area.vertical_scroll_offset(offset);
+                        }
+                        if let Some(row) = scroll_to_row {
+                            self.search.scroll_to = None;
+                            let offset = row as f32 * row_pitch - (ui.available_height() - row_height) / 2.0;
+                            scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+                        }
+                        let read_only = self.read_only();
+                        let rows = &self.tree_rows;
+                        let output = scroll_area
+                            .show_rows(ui, row_height, rows.len(), |ui, visible| {
+                                if let Some(root) = &mut self.root_node {
+                                    let mut view = TreeView {
+                                        selected_path: &mut self.selected_path,
+                                        menu: NodeMenu {
+                                            path_to_delete: &mut self.path_to_delete,
+                                            read_only,
+                                            basket: &mut self.basket,
+                                            watchlist: &self.config.watchlist,
+                                            scan_first: &self.config.scan_first,
+                                            notes: &self.config.notes,
+                                            translations: &self.translations,
+                                            actions: &mut actions,
+                                        },
+                                        shell_icons: &mut self.shell_icons,
+                                        icons: &self.icons,
+                                        translations: &self.translations,
+                                        age_now: self.config.color_by_age.then(SystemTime::now),
+                                        dark_mode: self.config.dark_mode,
+                                        palette: self.config.palette,
+                                        show_largest_file: self.config.show_largest_file,
+                                        search: &mut self.search,
+                                    };
+                                    for row in visible {
+                                        let (node, parent_size) = tree_row_node(root, rows, row);
+                                        render_tree_row(ui, node, parent_size, rows[row].depth, &mut view);
+                                    }
+                                }
+                            });
+                        self.tree_scroll_offset = output.state.offset.y;
+                        self.tree_view_rect = Some(output.inner_rect);
+                        self.paint_search_markers(ui, output.inner_rect, output.content_size.y);
+                        self.apply_node_actions(actions);
+                    }
+                    ViewMode::Icicle => self.show_icicle_view(ui),
+                    ViewMode::Treemap => self.show_treemap_view(ui),
+                    ViewMode::Sunburst => self.show_sunburst_view(ui),
+                }
+            } else if !self.is_scanning {
                 egui::ScrollArea::vertical()
                     .auto_shrink([false; 2])
                     .show(ui, |ui| {
-                        if let Some(root) = &mut self.root_node {
-                            render_tree_node_static(ui, root, 0, &mut self.selected_path, &mut self.path_to_delete, &self.icon_folder, &self.icon_file);
+                        // Предложение продолжить прерванное сканирование
+                        if let Some(info) = &self.resumable {
+                            ui.add_space(10.0);
+                            let mut resume = false;
+                            let mut discard = false;
+                            egui::Frame::group(ui.style()).show(ui, |ui| {
+                                let label = ui.label(self.translations.get_fmt(
+                                    "resume_scan_available",
+                                    &[&info.root.display().to_string(), &format_size(info.scanned_size)],
+                                ));
+                                if let Some(saved_at) = info.saved_at {
+                                    label.on_hover_text(self.translations.format_date(saved_at));
+                                }
+                                ui.horizontal(|ui| {
+                                    resume = ui.button(format!("{} {}", regular::PLAY, self.translations.get("resume_scan"))).clicked();
+                                    discard = ui.button(format!("{} {}", regular::X, self.translations.get("discard"))).clicked();
+                                });
+                            });
+                            if resume {
+                                self.resume_scan();
+                                return;
+                            }
+                            if discard {
+                                checkpoint::discard();
+                                self.resumable = None;
+                            }
                         }
-                    });
-            } else if !self.is_scanning {
-                egui::ScrollArea::vertical()
-                    .auto_shrink([false; 2])
-                    .show(ui, |ui| {
+                        
                         ui.add_space(20.0);
                         ui.heading(format!("{} {}", regular::HARD_DRIVE, &available_drives_label));
                         ui.add_space(10.0);
                         
                         // Отображаем диски в виде блоков (карточек) вертикально
                         let drives_clone = self.available_drives.clone();
+                        if self.drive_snapshots.as_ref().is_none_or(|snapshots| snapshots.len() != drives_clone.len()) {
+                            self.drive_snapshots = Some(
+                                drives_clone.iter().map(|drive| snapshot_store::latest(Path::new(&drive.path))).collect(),
+                            );
+                        }
+                        let drive_snapshots = self.drive_snapshots.clone().unwrap_or_default();
+                        let mut open_last = None;
                         
-                        for drive in drives_clone.iter() {
+                        for (drive, last_scan) in drives_clone.iter().zip(&drive_snapshots) {
                             // Карточка для диска
                             let used_space = drive.total_space.saturating_sub(drive.available_space);
                             let usage_percent = if drive.total_space > 0 {
@@ -911,17 +2506,14 @@ impl eframe::App for CedarApp {
                             };
                             
                             // Цвета для progressbar
-                            let progress_color = if usage_percent > 0.9 {
-                                egui::Color32::from_rgb(136, 0, 21)  // Красный #880015
+                            let usage_status = if usage_percent > 0.9 {
+                                palette::Status::Critical
                             } else if usage_percent > 0.75 {
-                                egui::Color32::from_rgb(255, 127, 39)  // Оранжевый #FF7F27
+                                palette::Status::Warning
                             } else {
-                                if self.config.dark_mode {
-                                    egui::Color32::from_rgb(60, 130, 80)  // Приглушенный зелёный
-                                } else {
-                                    egui::Color32::from_rgb(80, 150, 100)
-                                }
+                                palette::Status::Good
                             };
+                            let progress_color = self.config.palette.status_fill(usage_status, self.config.dark_mode);
                             
                             // Рамка для карточки - более компактная
                             egui::Frame::none()
@@ -959,6 +2551,25 @@ impl eframe::App for CedarApp {
                                             ui.label(egui::RichText::new(format!("{} {}", regular::DISC, &drive.kind))
                                                 .size(11.0)
                                                 .color(ui.visuals().weak_text_color()));
+                                            
+                                            // Последнее сканирование: открыть сохранённое вместо нового
+                                            if let Some((snapshot_path, header)) = last_scan {
+                                                ui.horizontal(|ui| {
+                                                    let text = self.translations.get_fmt(
+                                                        "drive_last_scan",
+                                                        &[&self.scan_age_text(header.scanned_at), &format_size(header.size)],
+                                                    );
+                                                    ui.label(egui::RichText::new(format!("{} {}", regular::CLOCK_COUNTER_CLOCKWISE, text))
+                                                        .size(11.0)
+                                                        .color(ui.visuals().weak_text_color()));
+                                                    if ui.small_button(self.translations.get("drive_open_last"))
+                                                        .on_hover_text(self.translations.get("drive_open_last_hint"))
+                                                        .clicked()
+                                                    {
+                                                        open_last = Some(snapshot_path.clone());
+                                                    }
+                                                });
+                                            }
                                         });
                                         
                                         ui.add_space(20.0);
@@ -1073,11 +2684,7 @@ impl eframe::App for CedarApp {
                                                         .color(ui.visuals().weak_text_color()));
                                                     ui.label(egui::RichText::new(format!("{} {}", format_size(drive.available_space), self.translations.get("available")))
                                                         .size(11.0)
-                                                        .color(if self.config.dark_mode {
-                                                            egui::Color32::from_rgb(100, 180, 120)
-                                                        } else {
-                                                            egui::Color32::from_rgb(60, 140, 80)
-                                                        }));
+                                                        .color(self.config.palette.status_text(palette::Status::Good, self.config.dark_mode)));
                                                 });
                                             });
                                         });
@@ -1086,86 +2693,73 @@ impl eframe::App for CedarApp {
                             
                             ui.add_space(8.0);
                         }
+                        if let Some(path) = open_last {
+                            self.open_snapshot_file(&path);
+                        }
                         
                         ui.add_space(20.0);
                     });
             }
         });
         
-        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
-            ui.separator();
-            ui.horizontal(|ui| {
-                // Показываем статусное сообщение или выбранный путь
-                if let Some(status) = &self.status_message {
-                    ui.label(status);
-                } else if let Some(path) = &self.selected_path {
-                    ui.label(format!("{}: {}", &selected_label, path.display()));
-                } else {
-                    ui.label(&no_selection_label);
-                }
-                
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    // Анализ производительности
-                    if self.scan_speed_mbps > 0.0 {
-                        ui.separator();
-                        
-                        // Типичные скорости SSD для сравнения
-                        let typical_ssd_speed = 500.0; // MB/s типичный SATA SSD
-                        let nvme_speed = 3500.0; // MB/s NVMe SSD
-                        
-                        let efficiency_percent = (self.scan_speed_mbps / typical_ssd_speed * 100.0).min(100.0);
-                        
-                        let speed_color = if self.scan_speed_mbps > 200.0 {
-                            egui::Color32::GREEN
-                        } else if self.scan_speed_mbps > 100.0 {
-                            egui::Color32::YELLOW
-                        } else {
-                            egui::Color32::LIGHT_RED
-                        };
-                        
-                        ui.colored_label(
-                            speed_color,
-                            format!("⚡ {:.1} MB/s", self.scan_speed_mbps)
-                        );
-                        
-                        // Показываем эффективность
-                        ui.label(format!("(~{:.0}% of SATA SSD)", efficiency_percent))
-                            .on_hover_text(format!(
-                                "Scan speed: {:.1} MB/s\n\
-                                Typical SATA SSD: ~{} MB/s\n\
-                                Typical NVMe SSD: ~{} MB/s\n\
-                                \n\
-                                Note: Scan speed limited by:\n\
-                                - Metadata reading (not sequential)\n\
-                                - File system overhead\n\
-                                - Small file processing\n\
-                                - CPU processing time",
-                                self.scan_speed_mbps, typical_ssd_speed, nvme_speed
-                            ));
-                    }
-                    
-                    if let Some(duration) = self.last_scan_duration {
-                        ui.separator();
-                        ui.label(format!("⏱ {:.2}s", duration.as_secs_f64()));
-                    }
-                    
-                    if let Some(root) = &self.root_node {
-                        ui.separator();
-                        ui.label(format!("{}: {}", &total_size_label, format_size(root.size)));
-                    }
-                });
-            });
-        });
-        
         // Check if scan is complete
         if self.is_scanning {
             if let Ok(mut result) = self.scan_result.try_lock() {
                 if let Some(scan_result) = result.take() {
                     match scan_result {
-                        ScanResult::Complete(node) => {
+                        ScanResult::Complete(mut node, encoded) => {
                             self.is_scanning = false;
+                            self.checkpoint = None;
                             self.last_scan_size = node.size;
+                            let entries = self.scan_progress.lock().unwrap().entries_seen;
+                            self.last_scan_entries = Some((node.path.clone(), entries));
+                            
+                            // Возвращаем раскрытие, выделение и прокрутку прежнего дерева
+                            match self.restore_view.take().filter(|view| view.root == node.path) {
+                                Some(view) => {
+                                    node.restore_expanded(&view.expanded);
+                                    self.pending_scroll_offset = Some(view.scroll_offset);
+                                }
+                                None => self.pending_scroll_offset = Some(0.0),
+                            }
+                            if self.selected_path.as_ref().is_some_and(|p| node.find(p).is_none()) {
+                                self.selected_path = None;
+                            }
+                            view_layouts::restore(&mut self.config, &node.path);
+                            if let (Some(policy), Some(encoded)) = (self.auto_snapshot_policy(&node.path), encoded) {
+                                self.auto_snapshot = Some(snapshot_store::save_in_background(policy, encoded, SystemTime::now()));
+                            }
+                            // Сравниваем наблюдаемые папки с прошлым сканированием
+                            if watchlist::record_scan(&mut self.config.watchlist, &node) {
+                                self.save_config_to_file();
+                                self.show_watchlist = true;
+                            }
+                            if self.config.recent_roots.first() != Some(&node.path) {
+                                jump_list::remember(&mut self.config.recent_roots, &node.path);
+                                self.update_jump_list();
+                            }
+                            self.log.info(self.translations.get_fmt(
+                                "log_scan_complete",
+                                &[&node.path.display().to_string(), &format_size(node.size)],
+                            ));
+                            if node.skipped > 0 {
+                                self.log.error(self.translations.get_fmt("items_skipped", &[&node.skipped.to_string()]));
+                            }
                             self.root_node = Some(node);
+                            self.tree_origin = self.scan_origin.take();
+                            self.last_scan_finished = Some(SystemTime::now());
+                            self.slack_report = None;
+                            self.untouched_report = None;
+                            self.streams_report = None;
+                            self.mail_report = None;
+                            self.vm_report = None;
+                            self.downloads_report = None;
+                            self.reclaim = None;
+                            self.panel_data = None;
+                            self.search.invalidate();
+                            self.chart_layers.invalidate();
+                            self.cleanup_plan = None;
+                            self.leftovers = None;
                             
                             // Получаем время сканирования из прогресса
                             if let Ok(prog) = self.scan_progress.lock() {
@@ -1180,27 +2774,79 @@ impl eframe::App for CedarApp {
                                                 let size_mb = self.last_scan_size as f64 / (1024.0 * 1024.0);
                                                 self.scan_speed_mbps = size_mb / secs;
                                             }
+                                            
+                                            // Запоминаем скорость для выбора числа потоков в следующий раз
+                                            self.config
+                                                .scan_profiles
+                                                .entry(prog.volume_key.clone())
+                                                .or_default()
+                                                .record(prog.thread_count, prog.entries_seen, Duration::from_secs_f64(secs));
+                                            
+                                            let savings = prog.excluded;
+                                            if savings.entries > 0 {
+                                                let saved = savings.time_saved(prog.entries_seen, Duration::from_secs_f64(secs));
+                                                self.last_exclusion_savings = Some((savings, saved));
+                                            }
                                         }
                                     }
                                 }
                             }
+                            if let Some(text) = self.exclusion_savings_text() {
+                                self.log.info(text);
+                            }
+                            self.save_config_to_file();
+                        }
+                        ScanResult::Disconnected(node) => {
+                            self.is_scanning = false;
+                            self.last_scan_duration = None;
+                            self.last_scan_size = node.size;
+                            self.scan_speed_mbps = 0.0;
+                            self.restore_view = None;
+                            self.pending_scroll_offset = Some(0.0);
+                            // Продолжить можно будет, когда диск вернётся
+                            self.resumable = self.checkpoint.take().map(|c| c.info()).filter(|info| info.scanned_size > 0);
+                            let drive = self.scan_progress.lock().unwrap().drive.clone();
+                            let drive = drive.trim_end_matches(['\\', '/']);
+                            let drive = if drive.is_empty() { "/" } else { drive };
+                            let message = self.translations.get_fmt("drive_disconnected", &[drive]);
+                            self.taskbar.fail();
+                            self.log.error(message.clone());
+                            self.status_message = Some(message);
+                            self.status_message_time = None;
+                            self.root_node = Some(node);
+                            // Недочитанные папки нельзя брать в следующее сканирование
+                            self.tree_origin = None;
+                            self.last_scan_finished = Some(SystemTime::now());
+                            self.slack_report = None;
+                            self.untouched_report = None;
+                            self.streams_report = None;
+                            self.mail_report = None;
+                            self.vm_report = None;
+                            self.downloads_report = None;
+                            self.reclaim = None;
+                            self.panel_data = None;
+                            self.search.invalidate();
+                            self.chart_layers.invalidate();
+                            self.cleanup_plan = None;
+                            self.leftovers = None;
                         }
                         ScanResult::Cancelled => {
                             self.is_scanning = false;
                             self.last_scan_duration = None;
                             self.last_scan_size = 0;
                             self.scan_speed_mbps = 0.0;
+                            self.restore_view = None;
+                            self.log.info(self.translations.get("log_scan_cancelled"));
                         }
                         ScanResult::Error(err) => {
                             self.is_scanning = false;
                             self.last_scan_duration = None;
                             self.last_scan_size = 0;
                             self.scan_speed_mbps = 0.0;
-                            eprintln!("Scan error: {}", err);
-                        }
-                        ScanResult::InProgress => {
-                            // Возвращаем обратно
-                            *result = Some(ScanResult::InProgress);
+                            self.restore_view = None;
+                            self.checkpoint = None;
+                            self.taskbar.fail();
+                            self.log.error(self.translations.get_fmt("log_scan_error", &[&err]));
                         }
                     }
                 }
@@ -1209,16 +2855,48 @@ impl eframe::App for CedarApp {
         }
         
         // Проверяем, нужно ли показать диалог удаления
-        if self.path_to_delete.is_some() && !self.show_delete_confirm {
-            self.show_delete_confirm = true;
+        if let Some(path) = self.path_to_delete.clone() {
+            if !self.show_delete_confirm {
+                let size = self.root_node.as_ref().and_then(|r| r.find(&path)).map_or(0, |n| n.size);
+                // Не помещающийся в корзину элемент подтверждается всегда
+                let trash = platform::trash_capacity(&path).map(|c| (file_ops::trash_fit(&c, size, size), c));
+                let fits = trash.is_none_or(|(fit, _)| fit == file_ops::TrashFit::Fits);
+                if self.config.confirm.needs_confirmation(false, 1, size) || !fits {
+                    self.show_delete_confirm = true;
+                    // Свободное место запрашиваем один раз при открытии диалога
+                    self.delete_free_space = get_free_space(&path);
+                    self.delete_trash = trash;
+                } else {
+                    self.path_to_delete = None;
+                    self.delete_item(&path, false);
+                }
+            }
         }
         
         // Диалог подтверждения удаления
         if self.show_delete_confirm {
             if let Some(path) = self.path_to_delete.clone() {
                 let path_display = path.display().to_string();
+                let node = self.root_node.as_ref().and_then(|r| r.find(&path));
+                let item_size = node.map(|n| n.size);
+                let size_text = item_size.map(|size| match node {
+                    Some(n) if !n.is_file => self.translations.get_fmt(
+                        "delete_item_size_files",
+                        &[&format_size(size), &n.file_count().to_string()],
+                    ),
+                    _ => self.translations.get_fmt("delete_item_size", &[&format_size(size)]),
+                });
+                // Корзина на том же диске: место освободится только после её очистки
+                let free_space_text = self.delete_free_space.map(|free| self.translations.get_fmt(
+                    "delete_free_space",
+                    &[&format_size(free), &format_size(free + item_size.unwrap_or(0))],
+                ));
+                
+                let trash_fit = self.delete_trash.map(|(fit, _)| fit);
+                let trash_warning = self.delete_trash.and_then(|(fit, capacity)| self.trash_warning(fit, &capacity));
                 
                 let mut delete_confirmed = false;
+                let mut permanent_confirmed = false;
                 let mut cancelled = false;
                 
                 let red_color = egui::Color32::from_rgb(198, 61, 53); // #c63d35
@@ -1234,10 +2912,21 @@ impl eframe::App for CedarApp {
                             ui.label("Вы действительно хотите удалить в корзину:");
                             ui.add_space(5.0);
                             ui.label(egui::RichText::new(&path_display).strong());
+                            if let Some(text) = &size_text {
+                                ui.label(format!("{} {}", regular::SCALES, text));
+                            }
+                            if let Some(text) = &free_space_text {
+                                ui.label(format!("{} {}", regular::HARD_DRIVE, text));
+                            }
                             ui.add_space(10.0);
                             
-                            ui.label("⚠ Элемент будет перемещён в корзину Windows.");
-                            ui.label("Вы сможете восстановить его из корзины.");
+                            if let Some((text, color)) = &trash_warning {
+                                ui.label(egui::RichText::new(format!("{} {}", regular::WARNING, text)).color(*color));
+                            }
+                            if trash_fit != Some(file_ops::TrashFit::TooLarge) {
+                                ui.label("⚠ Элемент будет перемещён в корзину Windows.");
+                                ui.label("Вы сможете восстановить его из корзины.");
+                            }
                             
                             ui.add_space(15.0);
                             
@@ -1249,10 +2938,22 @@ impl eframe::App for CedarApp {
                                 )
                                 .fill(red_color);
                                 
-                                if ui.add(delete_button).clicked() {
+                                if trash_fit != Some(file_ops::TrashFit::TooLarge) && ui.add(delete_button).clicked() {
                                     delete_confirmed = true;
                                 }
                                 
+                                // Корзина переполнится: безвозвратное удаление предлагается явно
+                                if trash_warning.is_some() {
+                                    let permanent_button = egui::Button::new(
+                                        egui::RichText::new(format!("{} {}", regular::WARNING, self.translations.get("delete_permanently")))
+                                            .color(egui::Color32::WHITE)
+                                    )
+                                    .fill(red_color);
+                                    if ui.add(permanent_button).clicked() {
+                                        permanent_confirmed = true;
+                                    }
+                                }
+                                
                                 if ui.button(format!("{} Отмена", regular::X)).clicked() {
                                     cancelled = true;
                                 }
@@ -1262,20 +2963,8 @@ impl eframe::App for CedarApp {
                         });
                     });
                 
-                if delete_confirmed {
-                    // Выполняем удаление
-                    match trash::delete(&path) {
-                        Ok(_) => {
-                            // Удаляем из дерева
-                            self.remove_from_tree(&path);
-                            self.status_message = Some(format!("✓ Удалено в корзину: {}", path_display));
-                            self.status_message_time = Some(Instant::now());
-                        }
-                        Err(e) => {
-                            self.status_message = Some(format!("✗ Ошибка удаления: {}", e));
-                            self.status_message_time = Some(Instant::now());
-                        }
-                    }
+                if delete_confirmed || permanent_confirmed {
+                    self.delete_item(&path, permanent_confirmed);
                     self.show_delete_confirm = false;
                     self.path_to_delete = None;
                 }
@@ -1287,6 +2976,32 @@ impl eframe::App for CedarApp {
             }
         }
         
+        self.show_slack_report_window(ctx);
+        self.show_untouched_report_window(ctx);
+        self.show_streams_report_window(ctx);
+        self.show_mail_report_window(ctx);
+        self.show_vm_report_window(ctx);
+        self.show_downloads_report_window(ctx);
+        self.show_basket_window(ctx);
+        self.show_rules_window(ctx);
+        self.show_watchlist_window(ctx);
+        self.show_file_list_window(ctx);
+        self.show_media_stats_window(ctx);
+        self.show_compare_window(ctx);
+        self.show_snapshot_diff_window(ctx);
+        self.show_browser_data_window(ctx);
+        self.show_op_log_window(ctx);
+        self.show_duplicates_window(ctx);
+        self.show_note_editor_window(ctx);
+        self.show_settings_window(ctx);
+        self.show_translation_coverage(ctx);
+        self.show_help_window(ctx);
+        self.show_diagnostics_window(ctx);
+        self.show_shortcut_overlay(ctx);
+        self.show_export_window(ctx);
+        self.show_leftovers_window(ctx);
+        self.handle_screenshot(ctx);
+        
         // Автоматически скрываем статусное сообщение через 5 секунд
         if let Some(time) = self.status_message_time {
             if time.elapsed().as_secs() > 5 {
@@ -1306,7 +3021,10 @@ impl eframe::App for CedarApp {
                         ui.add_space(10.0);
                         
                         // Иконка кедра
-                        ui.add(egui::Image::new(&self.icon_cedar).max_size(egui::vec2(80.0, 80.0)));
+                        match self.icons.image(icons::Icon::Cedar) {
+                            Some(image) => ui.add(image.max_size(egui::vec2(80.0, 80.0))),
+                            None => ui.label(egui::RichText::new(icons::Icon::Cedar.glyph()).size(64.0)),
+                        };
                         ui.add_space(5.0);
                         
                         ui.heading("Cedar Folder Size Analyzer");
@@ -1357,13 +3075,72 @@ impl eframe::App for CedarApp {
     }
 }
 
+/// Настройки, с которыми собрано дерево сканирования
+#[derive(Clone, PartialEq)]
+struct TreeOrigin {
+    filter: filter::ScanFilter,
+    follow_links: bool,
+    link_targets: bool,
+    exclusions: Vec<exclusions::Exclusion>,
+}
+
+impl TreeOrigin {
+    /// Можно ли брать неизменившиеся папки из этого дерева в сканирование с настройками `next`.
+    /// С фильтром в папках не всё содержимое; добавленные исключения применяются к взятым
+    /// папкам, а снятые вернули бы то, чего в дереве нет
+    fn can_reuse_for(&self, next: &TreeOrigin) -> bool {
+        !self.filter.is_active()
+            && !next.filter.is_active()
+            && self.follow_links == next.follow_links
+            && self.link_targets == next.link_targets
+            && self.exclusions.iter().all(|exclusion| next.exclusions.contains(exclusion))
+    }
+}
+
+/// Параметры одного сканирования
+struct ScanOptions {
+    /// Число потоков; 1 - однопоточный обход
+    threads: usize,
+    cluster_size: u64,
+    filter: filter::ScanFilter,
+    /// Раскрывать символические ссылки и точки соединения
+    follow_links: bool,
+    /// Учитывать размер целей нераскрытых ссылок отдельно от физического
+    link_targets: bool,
+    exclusions: Vec<exclusions::Exclusion>,
+    excluded_sizes: Arc<BTreeMap<PathBuf, exclusions::SubtreeSize>>,
+    /// Прошлое дерево того же корня для быстрого пересканирования
+    previous: Option<Arc<DirNode>>,
+    /// Папки, результаты которых нужны раньше остальных
+    scan_first: Vec<PathBuf>,
+    /// Порядок детей в готовом дереве
+    sort: tree_columns::TreeSort,
+    /// Сжать готовое дерево для автоматического снимка
+    encode_snapshot: bool,
+}
+
 fn scan_directory(
     path: &str,
     progress: Arc<Mutex<ScanProgress>>,
     result: Arc<Mutex<Option<ScanResult>>>,
     cancel: Arc<AtomicBool>,
-    use_parallel: bool,
+    options: ScanOptions,
+    checkpoint: Arc<checkpoint::Checkpoint>,
 ) {
+    let ScanOptions {
+        threads,
+        cluster_size,
+        filter,
+        follow_links,
+        link_targets,
+        exclusions,
+        excluded_sizes,
+        previous,
+        scan_first,
+        sort,
+        encode_snapshot,
+    } = options;
+    let use_parallel = threads > 1;
     let start_time = Instant::now();
     let path_buf = PathBuf::from(path);
     
@@ -1376,7 +3153,9 @@ fn scan_directory(
     
     {
         let mut prog = progress.lock().unwrap();
-        prog.message = if use_parallel {
+        prog.message = if checkpoint.is_resuming() {
+            "Resuming scan...".to_string()
+        } else if use_parallel {
             "Scanning (parallel mode)...".to_string()
         } else {
             "Scanning (single-threaded mode)...".to_string()
@@ -1387,206 +3166,622 @@ fn scan_directory(
     let file_count = Arc::new(AtomicUsize::new(0));
     let dir_count = Arc::new(AtomicUsize::new(0));
     let total_size = Arc::new(AtomicUsize::new(0));
+    let entries_seen = Arc::new(AtomicUsize::new(0));
+    let disconnected = Arc::new(AtomicBool::new(false));
+    checkpoint.load_resumed();
     
-    // Однопоточная рекурсивная функция для глубоких уровней
-    fn scan_recursive_single(
+    // Общее состояние рекурсивного обхода
+    struct ScanContext {
+        cancel: Arc<AtomicBool>,
+        file_count: Arc<AtomicUsize>,
+        dir_count: Arc<AtomicUsize>,
+        total_size: Arc<AtomicUsize>,
+        entries_seen: Arc<AtomicUsize>,
+        /// Устройство пропало - сканирование остановлено через `cancel`
+        disconnected: Arc<AtomicBool>,
+        cluster_size: u64,
+        filter: filter::ScanFilter,
+        now: SystemTime,
+        checkpoint: Arc<checkpoint::Checkpoint>,
+        follow_links: bool,
+        link_targets: bool,
+        exclusions: exclusions::Matcher,
+        excluded_sizes: Arc<BTreeMap<PathBuf, exclusions::SubtreeSize>>,
+        /// Пропущенное исключениями
+        excluded: Mutex<exclusions::ExclusionSavings>,
+        previous: Option<Arc<DirNode>>,
+        scan_first: Vec<PathBuf>,
+        /// Сюда попадают приоритетные папки по мере готовности
+        progress: Arc<Mutex<ScanProgress>>,
+        /// Корень сканирования без ссылок - для ссылок внутрь него
+        canonical_root: PathBuf,
+        /// Уже пройденные объекты (только при раскрытии ссылок): защита от
+        /// циклов и повторного учёта bind-монтирований и петель точек соединения
+        visited: Mutex<HashSet<(u64, u64)>>,
+        /// Размеры целей нераскрытых ссылок: несколько ссылок на одну папку обходят её один раз
+        link_sizes: Mutex<HashMap<(u64, u64), u64>>,
+    }
+    
+    let ctx = ScanContext {
+        cancel: cancel.clone(),
+        file_count: file_count.clone(),
+        dir_count: dir_count.clone(),
+        total_size: total_size.clone(),
+        entries_seen: entries_seen.clone(),
+        disconnected: disconnected.clone(),
+        cluster_size,
+        filter,
+        now: SystemTime::now(),
+        checkpoint: checkpoint.clone(),
+        follow_links,
+        link_targets,
+        exclusions: exclusions::Matcher::new(&exclusions),
+        excluded_sizes,
+        excluded: Mutex::new(Default::default()),
+        previous,
+        scan_first,
+        progress: progress.clone(),
+        canonical_root: std::fs::canonicalize(&path_buf).unwrap_or_else(|_| path_buf.clone()),
+        visited: Mutex::new(HashSet::new()),
+        link_sizes: Mutex::new(HashMap::new()),
+    };
+    
+    // Первое ли посещение объекта по этому пути. Без раскрытия ссылок дерево
+    // обходится без повторов, и проверка не нужна.
+    fn first_visit(path: &Path, ctx: &ScanContext) -> bool {
+        if !ctx.follow_links {
+            return true;
+        }
+        match platform::file_id(path) {
+            Some(id) => ctx.visited.lock().unwrap().insert(id),
+            None => true,
+        }
+    }
+    
+    // Раскрывается ли ссылка. Цели внутри корня и так будут учтены по своему
+    // настоящему пути, поэтому раскрываются только ссылки наружу.
+    fn follows(entry: &std::fs::DirEntry, ctx: &ScanContext) -> bool {
+        ctx.follow_links
+            && entry.file_type().is_ok_and(|t| t.is_symlink())
+            && std::fs::canonicalize(entry.path()).is_ok_and(|target| !target.starts_with(&ctx.canonical_root))
+    }
+    
+    // Есть ли внутри папки приоритетные папки
+    fn has_first_below(path: &Path, ctx: &ScanContext) -> bool {
+        ctx.scan_first.iter().any(|first| first != path && first.starts_with(path))
+    }
+    
+    // Ведёт ли путь к приоритетной папке (или это она сама)
+    fn leads_to_first(path: &Path, ctx: &ScanContext) -> bool {
+        ctx.scan_first.iter().any(|first| first.starts_with(path))
+    }
+    
+    // Записи папки; ведущие к приоритетным папкам идут первыми. Rayon разбирает
+    // список с головы, поэтому они сразу попадают в работу, а свободные потоки
+    // тем временем забирают остальное
+    fn order_first(entries: &mut [std::fs::DirEntry], path: &Path, ctx: &ScanContext) {
+        if has_first_below(path, ctx) {
+            entries.sort_by_key(|entry| !leads_to_first(&entry.path(), ctx));
+        }
+    }
+    
+    // Готовая приоритетная папка сразу показывается в прогрессе
+    fn report_first(node: &DirNode, ctx: &ScanContext) {
+        if !ctx.cancel.load(Ordering::Relaxed) && ctx.scan_first.contains(&node.path) {
+            ctx.progress.lock().unwrap().scanned_first.push((node.path.clone(), node.size));
+        }
+    }
+    
+    // Тип элемента; раскрываемая ссылка заменяется типом цели
+    fn entry_type(entry: &std::fs::DirEntry, ctx: &ScanContext) -> std::io::Result<std::fs::FileType> {
+        if follows(entry, ctx) {
+            return Ok(std::fs::metadata(entry.path())?.file_type());
+        }
+        entry.file_type()
+    }
+    
+    // Метаданные элемента (для раскрываемой ссылки - её цели)
+    fn entry_metadata(entry: &std::fs::DirEntry, ctx: &ScanContext) -> std::io::Result<std::fs::Metadata> {
+        if follows(entry, ctx) {
+            return std::fs::metadata(entry.path());
+        }
+        entry.metadata()
+    }
+    
+    // Узел дерева для файла: логический размер и место на диске.
+    // None - файл не проходит фильтр сканирования.
+    fn file_node(
+        entry: &std::fs::DirEntry,
+        metadata: &std::fs::Metadata,
+        ctx: &ScanContext,
+    ) -> Option<DirNode> {
+        let file_name = entry.file_name();
+        let modified = metadata.modified().ok();
+        if !ctx.filter.matches(&file_name.to_string_lossy(), metadata.len(), modified, ctx.now) {
+            return None;
+        }
+        // Несколько ссылок на один файл учитываются один раз
+        let is_link = entry.file_type().is_ok_and(|t| t.is_symlink());
+        if is_link && !first_visit(&entry.path(), ctx) {
+            return None;
+        }
+        let mut node = DirNode::new(entry.path(), file_name, metadata.len(), true);
+        node.allocated = platform::allocated_size(&node.path, metadata, ctx.cluster_size);
+        node.sparse = platform::is_sparse(metadata, node.allocated);
+        
+        // Альтернативные потоки NTFS входят в размер файла, каждый занимает свои кластеры
+        for stream in platform::alternate_streams(&node.path) {
+            node.streams += stream.size;
+            node.allocated += stream.size.div_ceil(ctx.cluster_size.max(1)) * ctx.cluster_size.max(1);
+        }
+        node.size += node.streams;
+        node.modified = modified;
+        node.accessed = metadata.accessed().ok();
+        Some(node)
+    }
+    
+    // Нераскрытая ссылка: узел без собственного размера, с размером цели в `referenced`.
+    // Цель считается отдельно и не попадает в физические итоги, даже если лежит внутри
+    // корня. Обход цели ограничен LINK_TARGET_LIMIT элементами, у больших целей размер неполный
+    fn link_node(entry: &std::fs::DirEntry, ctx: &ScanContext) -> Option<DirNode> {
+        fn walk(path: &Path, ctx: &ScanContext, budget: &mut usize) -> u64 {
+            if *budget == 0 || ctx.cancel.load(Ordering::Relaxed) {
+                return 0;
+            }
+            *budget -= 1;
+            let Ok(metadata) = std::fs::symlink_metadata(path) else {
+                return 0;
+            };
+            if metadata.is_dir() {
+                let mut size = 0;
+                for entry in std::fs::read_dir(path).into_iter().flatten().flatten() {
+                    size += walk(&entry.path(), ctx, budget);
+                }
+                size
+            } else if metadata.is_file() {
+                metadata.len()
+            } else {
+                0
+            }
+        }
+
+        fn target_size(path: &Path, ctx: &ScanContext) -> u64 {
+            let id = platform::file_id(path);
+            if let Some(size) = id.and_then(|id| ctx.link_sizes.lock().unwrap().get(&id).copied()) {
+                return size;
+            }
+            let mut budget = LINK_TARGET_LIMIT;
+            let size = walk(path, ctx, &mut budget);
+            if let Some(id) = id {
+                ctx.link_sizes.lock().unwrap().insert(id, size);
+            }
+            size
+        }
+        
+        if !ctx.link_targets || ctx.filter.is_active() {
+            return None;
+        }
+        let path = entry.path();
+        // Цель должна существовать: висячие ссылки не показываются
+        std::fs::metadata(&path).ok()?;
+        let target = std::fs::read_link(&path).ok()?;
+        let target = match path.parent() {
+            Some(parent) if target.is_relative() => parent.join(target),
+            _ => target,
+        };
+        let mut node = DirNode::new(path, entry.file_name(), 0, true);
+        node.referenced = target_size(&target, ctx);
+        node.link_target = Some(target);
+        Some(node)
+    }
+    
+    // Исключённые пользователем элементы пропускаются, не считаясь ошибками,
+    // и учитываются в сводке сэкономленного
+    fn excluded(entry: &std::fs::DirEntry, file_type: &std::fs::FileType, ctx: &ScanContext) -> bool {
+        let path = entry.path();
+        let excluded = !ctx.exclusions.is_empty()
+            && ctx.exclusions.matches(&path, &entry.file_name().to_string_lossy(), file_type.is_file());
+        if excluded {
+            let file_size = file_type.is_file().then(|| entry.metadata().map_or(0, |m| m.len()));
+            ctx.excluded.lock().unwrap().add(&path, file_size, &ctx.excluded_sizes);
+        }
+        excluded
+    }
+    
+    // Ошибка чтения папки: если пропало само устройство, дальше сканировать нечего
+    fn read_failed(error: &std::io::Error, ctx: &ScanContext) {
+        if platform::is_device_gone(error, &ctx.canonical_root) {
+            ctx.disconnected.store(true, Ordering::Relaxed);
+            ctx.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+    
+    // С активным фильтром папки без подходящих файлов в дерево не попадают
+    fn keep_dir(node: &DirNode, ctx: &ScanContext) -> bool {
+        !ctx.filter.is_active() || !node.children.is_empty() || node.skipped > 0
+    }
+    
+    // Поддерево, готовое с прошлого прерванного сеанса: учитываем его в прогрессе
+    fn take_resumed(path: &Path, ctx: &ScanContext, depth: usize) -> Option<DirNode> {
+        fn count(node: &DirNode, ctx: &ScanContext) {
+            ctx.entries_seen.fetch_add(node.children.len(), Ordering::Relaxed);
+            for child in &node.children {
+                if child.is_file {
+                    ctx.file_count.fetch_add(1, Ordering::Relaxed);
+                    ctx.total_size.fetch_add(child.size as usize, Ordering::Relaxed);
+                } else {
+                    ctx.dir_count.fetch_add(1, Ordering::Relaxed);
+                    count(child, ctx);
+                }
+            }
+        }
+        
+        let node = ctx.checkpoint.take_resumed(path)?;
+        count(&node, ctx);
+        ctx.checkpoint.complete(&node, depth);
+        Some(node)
+    }
+    
+    // Быстрое пересканирование: время изменения папки (и число подпапок, где ОС его
+    // сообщает) то же, что в прошлом дереве, значит её состав прежний. Файлы берутся
+    // из прошлого дерева без чтения папки, подпапки проверяются так же через `recurse`.
+    // Изменение размера файла на месте время папки не меняет - его такой обход пропустит
+    fn reuse_unchanged(
         path: &Path,
-        cancel: &Arc<AtomicBool>,
-        file_count: &Arc<AtomicUsize>,
-        dir_count: &Arc<AtomicUsize>,
-        total_size: &Arc<AtomicUsize>,
+        metadata: Option<&std::fs::Metadata>,
+        ctx: &ScanContext,
+        depth: usize,
+        recurse: fn(&Path, &ScanContext, usize) -> Option<DirNode>,
     ) -> Option<DirNode> {
+        let previous = ctx.previous.as_ref()?.find(path)?;
+        let modified = metadata?.modified().ok()?;
+        // Нечитаемую в прошлый раз папку перечитываем: доступ могли открыть
+        if previous.is_file || previous.dir_mtime != Some(modified) || (previous.children.is_empty() && previous.skipped > 0) {
+            return None;
+        }
+        let subdirs = previous.children.iter().filter(|c| !c.is_file).count() as u64;
+        if metadata.and_then(platform::subdir_count).is_some_and(|count| count != subdirs) {
+            return None;
+        }
+        
+        let mut node = DirNode::new(path.to_path_buf(), previous.name.clone(), 0, false);
+        node.dir_mtime = previous.dir_mtime;
+        let mut children = Vec::new();
+        ctx.entries_seen.fetch_add(previous.children.len(), Ordering::Relaxed);
+        for child in &previous.children {
+            if ctx.cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            // Исключения могли добавить после прошлого сканирования
+            if !ctx.exclusions.is_empty() && ctx.exclusions.matches(&child.path, &child.display_name(), child.is_file) {
+                continue;
+            }
+            if child.is_file {
+                if child.link_target.is_none() {
+                    ctx.file_count.fetch_add(1, Ordering::Relaxed);
+                    ctx.total_size.fetch_add(child.size as usize, Ordering::Relaxed);
+                }
+                children.push(child.clone());
+            } else if let Some(child_node) = recurse(&child.path, ctx, depth + 1).filter(|n| keep_dir(n, ctx)) {
+                ctx.dir_count.fetch_add(1, Ordering::Relaxed);
+                children.push(child_node);
+            }
+        }
+        
+        node.size = children.iter().map(|c| c.size).sum();
+        node.allocated = children.iter().map(|c| c.allocated).sum();
+        node.streams = children.iter().map(|c| c.streams).sum();
+        node.referenced = children.iter().map(|c| c.referenced).sum();
+        node.skipped = children.iter().map(|c| c.skipped).sum();
+        node.modified = children.iter().filter_map(|c| c.modified).max();
+        node.accessed = children.iter().filter_map(|c| c.accessed).max();
+        node.largest_file = largest_file(&children);
+        (node.files, node.folders) = item_counts(&children);
+        node.children = children;
+        
+        if !ctx.cancel.load(Ordering::Relaxed) {
+            ctx.checkpoint.complete(&node, depth);
+        }
+        
+        report_first(&node, ctx);
+        Some(node)
+    }
+    
+    // Однопоточная рекурсивная функция для глубоких уровней
+    fn scan_recursive_single(path: &Path, ctx: &ScanContext, depth: usize) -> Option<DirNode> {
         // Проверка отмены
-        if cancel.load(Ordering::Relaxed) {
+        if ctx.cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        
+        if let Some(node) = take_resumed(path, ctx, depth) {
+            return Some(node);
+        }
+        
+        // Папка, уже пройденная по другому пути (цикл ссылок, bind-монтирование)
+        if !first_visit(path, ctx) {
             return None;
         }
         
-        let name = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or_else(|| path.to_str().unwrap_or("Unknown"))
-            .to_string();
+        let metadata = std::fs::metadata(path).ok();
+        if let Some(node) = reuse_unchanged(path, metadata.as_ref(), ctx, depth, scan_recursive_single) {
+            return Some(node);
+        }
+        
+        // У корня диска имени нет - показывается весь путь
+        let name = path.file_name().unwrap_or(path.as_os_str()).to_os_string();
         
         let mut node = DirNode::new(path.to_path_buf(), name, 0, false);
+        node.dir_mtime = metadata.and_then(|m| m.modified().ok());
         let mut dir_size = 0u64;
+        let mut dir_allocated = 0u64;
+        let mut skipped = 0u64;
         
-        // Читаем содержимое директории
-        let entries = match std::fs::read_dir(path) {
-            Ok(entries) => entries,
-            Err(_) => return Some(node),
+        // Читаем содержимое директории; список собирается, только если его надо упорядочить
+        let entries: Box<dyn Iterator<Item = std::io::Result<std::fs::DirEntry>>> = match std::fs::read_dir(path) {
+            Ok(entries) if has_first_below(path, ctx) => {
+                let mut entries: Vec<_> = entries.collect();
+                entries.sort_by_key(|entry| !entry.as_ref().is_ok_and(|e| leads_to_first(&e.path(), ctx)));
+                Box::new(entries.into_iter())
+            }
+            Ok(entries) => Box::new(entries),
+            Err(e) => {
+                // Содержимое папки недоступно - её размер занижен
+                read_failed(&e, ctx);
+                node.skipped = 1;
+                return Some(node);
+            }
         };
         
         let mut children = Vec::new();
         
         for entry in entries {
-            if cancel.load(Ordering::Relaxed) {
+            if ctx.cancel.load(Ordering::Relaxed) {
                 break;
             }
+            ctx.entries_seen.fetch_add(1, Ordering::Relaxed);
             
             let entry = match entry {
                 Ok(e) => e,
-                Err(_) => continue,
+                Err(_) => {
+                    skipped += 1;
+                    continue;
+                }
             };
             
             // Используем file_type() - не следует символическим ссылкам
-            let file_type = match entry.file_type() {
+            let file_type = match entry_type(&entry, ctx) {
                 Ok(ft) => ft,
-                Err(_) => continue,
+                Err(_) => {
+                    skipped += 1;
+                    continue;
+                }
             };
+            if excluded(&entry, &file_type, ctx) {
+                continue;
+            }
             
             if file_type.is_dir() {
                 // Рекурсивно сканируем подпапку
-                if let Some(child_node) = scan_recursive_single(
-                    &entry.path(),
-                    cancel,
-                    file_count,
-                    dir_count,
-                    total_size,
-                ) {
+                if let Some(child_node) = scan_recursive_single(&entry.path(), ctx, depth + 1)
+                    .filter(|n| keep_dir(n, ctx))
+                {
                     dir_size += child_node.size;
+                    dir_allocated += child_node.allocated;
+                    skipped += child_node.skipped;
                     children.push(child_node);
-                    dir_count.fetch_add(1, Ordering::Relaxed);
+                    ctx.dir_count.fetch_add(1, Ordering::Relaxed);
                 }
             } else if file_type.is_file() {
                 // Добавляем файл как узел дерева
-                if let Ok(metadata) = entry.metadata() {
-                    let file_size = metadata.len();
-                    let file_name = entry.file_name().to_string_lossy().to_string();
-                    let file_node = DirNode::new(entry.path(), file_name, file_size, true);
-                    
-                    dir_size += file_size;
-                    children.push(file_node);
-                    file_count.fetch_add(1, Ordering::Relaxed);
-                    total_size.fetch_add(file_size as usize, Ordering::Relaxed);
+                match entry_metadata(&entry, ctx) {
+                    Ok(metadata) => {
+                        if let Some(file_node) = file_node(&entry, &metadata, ctx) {
+                            dir_size += file_node.size;
+                            dir_allocated += file_node.allocated;
+                            ctx.file_count.fetch_add(1, Ordering::Relaxed);
+                            ctx.total_size.fetch_add(file_node.size as usize, Ordering::Relaxed);
+                            children.push(file_node);
+                        }
+                    }
+                    Err(_) => skipped += 1,
                 }
+            } else if file_type.is_symlink() {
+                children.extend(link_node(&entry, ctx));
             }
         }
         
         node.size = dir_size;
+        node.allocated = dir_allocated;
+        node.streams = children.iter().map(|c| c.streams).sum();
+        node.referenced = children.iter().map(|c| c.referenced).sum();
+        node.skipped = skipped;
+        node.modified = children.iter().filter_map(|c| c.modified).max();
+        node.accessed = children.iter().filter_map(|c| c.accessed).max();
+        node.largest_file = largest_file(&children);
+        (node.files, node.folders) = item_counts(&children);
         node.children = children;
         
+        // Прерванная отменой папка неполна - в контрольную точку не попадает
+        if !ctx.cancel.load(Ordering::Relaxed) {
+            ctx.checkpoint.complete(&node, depth);
+        }
+        
+        report_first(&node, ctx);
         Some(node)
     }
     
     // Параллельная функция для первого уровня (использует rayon)
-    fn scan_recursive_parallel(
-        path: &Path,
-        cancel: &Arc<AtomicBool>,
-        file_count: &Arc<AtomicUsize>,
-        dir_count: &Arc<AtomicUsize>,
-        total_size: &Arc<AtomicUsize>,
-        depth: usize,
-    ) -> Option<DirNode> {
-        if cancel.load(Ordering::Relaxed) {
+    fn scan_recursive_parallel(path: &Path, ctx: &ScanContext, depth: usize) -> Option<DirNode> {
+        if ctx.cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        
+        if let Some(node) = take_resumed(path, ctx, depth) {
+            return Some(node);
+        }
+        
+        // Папка, уже пройденная по другому пути (цикл ссылок, bind-монтирование)
+        if !first_visit(path, ctx) {
             return None;
         }
         
-        let name = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or_else(|| path.to_str().unwrap_or("Unknown"))
-            .to_string();
+        let metadata = std::fs::metadata(path).ok();
+        if let Some(node) = reuse_unchanged(path, metadata.as_ref(), ctx, depth, scan_recursive_parallel) {
+            return Some(node);
+        }
+        
+        // У корня диска имени нет - показывается весь путь
+        let name = path.file_name().unwrap_or(path.as_os_str()).to_os_string();
         
         let mut node = DirNode::new(path.to_path_buf(), name, 0, false);
+        node.dir_mtime = metadata.and_then(|m| m.modified().ok());
         
         let entries = match std::fs::read_dir(path) {
             Ok(entries) => entries,
-            Err(_) => return Some(node),
+            Err(e) => {
+                read_failed(&e, ctx);
+                node.skipped = 1;
+                return Some(node);
+            }
         };
         
-        // Собираем все записи
-        let entries_vec: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+        // Собираем все записи, считая нечитаемые
+        let mut entries_vec = Vec::new();
+        let mut skipped = 0u64;
+        for entry in entries {
+            match entry {
+                Ok(e) => entries_vec.push(e),
+                Err(_) => skipped += 1,
+            }
+        }
         
-        let mut dir_size = 0u64;
+        ctx.entries_seen.fetch_add(entries_vec.len(), Ordering::Relaxed);
+        order_first(&mut entries_vec, path, ctx);
         let mut children = Vec::new();
         
         // На первых 2 уровнях используем параллелизм
         if depth < 2 {
-            let results: Vec<_> = entries_vec
+            let unreadable = AtomicUsize::new(0);
+            let results: Vec<DirNode> = entries_vec
                 .par_iter()
                 .filter_map(|entry| {
-                    if cancel.load(Ordering::Relaxed) {
+                    if ctx.cancel.load(Ordering::Relaxed) {
                         return None;
                     }
                     
-                    let file_type = entry.file_type().ok()?;
+                    let Ok(file_type) = entry_type(entry, ctx) else {
+                        unreadable.fetch_add(1, Ordering::Relaxed);
+                        return None;
+                    };
+                    if excluded(entry, &file_type, ctx) {
+                        return None;
+                    }
                     
                     if file_type.is_dir() {
-                        let child = scan_recursive_parallel(
-                            &entry.path(),
-                            cancel,
-                            file_count,
-                            dir_count,
-                            total_size,
-                            depth + 1,
-                        )?;
-                        dir_count.fetch_add(1, Ordering::Relaxed);
-                        Some((child.size, Some(child)))
+                        let child = scan_recursive_parallel(&entry.path(), ctx, depth + 1)
+                            .filter(|n| keep_dir(n, ctx))?;
+                        ctx.dir_count.fetch_add(1, Ordering::Relaxed);
+                        Some(child)
                     } else if file_type.is_file() {
-                        let metadata = entry.metadata().ok()?;
-                        let file_size = metadata.len();
-                        file_count.fetch_add(1, Ordering::Relaxed);
-                        total_size.fetch_add(file_size as usize, Ordering::Relaxed);
-                        Some((file_size, None))
+                        let Ok(metadata) = entry_metadata(entry, ctx) else {
+                            unreadable.fetch_add(1, Ordering::Relaxed);
+                            return None;
+                        };
+                        let file_node = file_node(entry, &metadata, ctx)?;
+                        ctx.file_count.fetch_add(1, Ordering::Relaxed);
+                        ctx.total_size.fetch_add(file_node.size as usize, Ordering::Relaxed);
+                        Some(file_node)
+                    } else if file_type.is_symlink() {
+                        link_node(entry, ctx)
                     } else {
                         None
                     }
                 })
                 .collect();
             
-            for (size, child_opt) in results {
-                dir_size += size;
-                if let Some(child) = child_opt {
-                    children.push(child);
-                }
-            }
+            skipped += unreadable.load(Ordering::Relaxed) as u64;
+            children.extend(results);
         } else {
             // Глубже 2 уровней - однопоточно
             for entry in entries_vec {
-                if cancel.load(Ordering::Relaxed) {
+                if ctx.cancel.load(Ordering::Relaxed) {
                     break;
                 }
                 
-                let file_type = match entry.file_type() {
+                let file_type = match entry_type(&entry, ctx) {
                     Ok(ft) => ft,
-                    Err(_) => continue,
+                    Err(_) => {
+                        skipped += 1;
+                        continue;
+                    }
                 };
+                if excluded(&entry, &file_type, ctx) {
+                    continue;
+                }
                 
                 if file_type.is_dir() {
-                    if let Some(child_node) = scan_recursive_single(
-                        &entry.path(),
-                        cancel,
-                        file_count,
-                        dir_count,
-                        total_size,
-                    ) {
-                        dir_size += child_node.size;
+                    if let Some(child_node) = scan_recursive_single(&entry.path(), ctx, depth + 1)
+                        .filter(|n| keep_dir(n, ctx))
+                    {
                         children.push(child_node);
-                        dir_count.fetch_add(1, Ordering::Relaxed);
+                        ctx.dir_count.fetch_add(1, Ordering::Relaxed);
                     }
                 } else if file_type.is_file() {
-                    if let Ok(metadata) = entry.metadata() {
-                        let file_size = metadata.len();
-                        dir_size += file_size;
-                        file_count.fetch_add(1, Ordering::Relaxed);
-                        total_size.fetch_add(file_size as usize, Ordering::Relaxed);
+                    match entry_metadata(&entry, ctx) {
+                        Ok(metadata) => {
+                            if let Some(file_node) = file_node(&entry, &metadata, ctx) {
+                                ctx.file_count.fetch_add(1, Ordering::Relaxed);
+                                ctx.total_size.fetch_add(file_node.size as usize, Ordering::Relaxed);
+                                children.push(file_node);
+                            }
+                        }
+                        Err(_) => skipped += 1,
                     }
+                } else if file_type.is_symlink() {
+                    children.extend(link_node(&entry, ctx));
                 }
             }
         }
         
-        node.size = dir_size;
+        node.size = children.iter().map(|c| c.size).sum();
+        node.allocated = children.iter().map(|c| c.allocated).sum();
+        node.streams = children.iter().map(|c| c.streams).sum();
+        node.referenced = children.iter().map(|c| c.referenced).sum();
+        node.skipped = skipped + children.iter().map(|c| c.skipped).sum::<u64>();
+        node.modified = children.iter().filter_map(|c| c.modified).max();
+        node.accessed = children.iter().filter_map(|c| c.accessed).max();
+        node.largest_file = largest_file(&children);
+        (node.files, node.folders) = item_counts(&children);
         node.children = children;
         
+        if !ctx.cancel.load(Ordering::Relaxed) {
+            ctx.checkpoint.complete(&node, depth);
+        }
+        
+        report_first(&node, ctx);
         Some(node)
     }
     
-    // Сортировка после сканирования
-    fn sort_tree(node: &mut DirNode) {
-        node.children.sort_unstable_by(|a, b| b.size.cmp(&a.size));
-        for child in &mut node.children {
-            sort_tree(child);
-        }
+    // Обработка после обхода - сортировка по столбцу дерева. На дереве в миллионы
+    // узлов она заметна, поэтому в прогрессе у неё своя фаза с долей обработанных папок
+    fn process_tree(root: &mut DirNode, progress: &Mutex<ScanProgress>, dirs: usize, sort: tree_columns::TreeSort) {
+        let done = AtomicUsize::new(0);
+        let finished = AtomicBool::new(false);
+        progress.lock().unwrap().processing = Some((0, dirs + 1));
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                while !finished.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(100));
+                    if let Some((processed, _)) = &mut progress.lock().unwrap().processing {
+                        *processed = done.load(Ordering::Relaxed);
+                    }
+                }
+            });
+            sort.sort_tree(root, &done);
+            finished.store(true, Ordering::Relaxed);
+        });
+        root.is_expanded = true;
     }
     
     // Поток для обновления прогресса
@@ -1594,7 +3789,11 @@ fn scan_directory(
     let file_count_clone = file_count.clone();
     let dir_count_clone = dir_count.clone();
     let total_size_clone = total_size.clone();
+    let entries_seen_clone = entries_seen.clone();
+    let disconnected_clone = disconnected.clone();
+    let root_clone = path_buf.clone();
     let cancel_clone = cancel.clone();
+    let checkpoint_clone = checkpoint.clone();
     
     let progress_thread = thread::spawn(move || {
         while !cancel_clone.load(Ordering::Relaxed) {
@@ -1604,30 +3803,34 @@ fn scan_directory(
             prog.files_scanned = file_count_clone.load(Ordering::Relaxed);
             prog.dirs_scanned = dir_count_clone.load(Ordering::Relaxed);
             prog.total_size = total_size_clone.load(Ordering::Relaxed) as u64;
+            prog.entries_seen = entries_seen_clone.load(Ordering::Relaxed);
+            drop(prog);
+            
+            // Ошибки отключённого устройства бывают и неотличимы от обычных -
+            // тогда его выдаёт пропавший корень сканирования
+            if std::fs::metadata(&root_clone).is_err() {
+                disconnected_clone.store(true, Ordering::Relaxed);
+                cancel_clone.store(true, Ordering::Relaxed);
+                break;
+            }
+            
+            checkpoint_clone.save_if_due();
         }
     });
     
     // Выбираем режим сканирования в зависимости от типа диска
     let root_result = if use_parallel {
-        scan_recursive_parallel(
-            &path_buf,
-            &cancel,
-            &file_count,
-            &dir_count,
-            &total_size,
-            0,
-        )
+        // Свой пул, чтобы число потоков соответствовало выбранному для тома
+        match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            Ok(pool) => pool.install(|| scan_recursive_parallel(&path_buf, &ctx, 0)),
+            Err(_) => scan_recursive_parallel(&path_buf, &ctx, 0),
+        }
     } else {
-        scan_recursive_single(
-            &path_buf,
-            &cancel,
-            &file_count,
-            &dir_count,
-            &total_size,
-        )
+        scan_recursive_single(&path_buf, &ctx, 0)
     };
     
     // Останавливаем поток прогресса
+    let cancelled = cancel.load(Ordering::Relaxed);
     cancel.store(true, Ordering::Relaxed);
     let _ = progress_thread.join();
     cancel.store(false, Ordering::Relaxed);
@@ -1635,6 +3838,28 @@ fn scan_directory(
     // Отправляем результат
     let elapsed = start_time.elapsed();
     
+    // Устройство отключилось: показываем то, что успели прочитать, и сохраняем
+    // прогресс, чтобы продолжить после подключения
+    if disconnected.load(Ordering::Relaxed) {
+        checkpoint.save();
+        let partial = root_result.map(|mut root| {
+            process_tree(&mut root, &progress, dir_count.load(Ordering::Relaxed), sort);
+            root
+        });
+        *result.lock().unwrap() = Some(match partial {
+            Some(root) => ScanResult::Disconnected(root),
+            None => ScanResult::Cancelled,
+        });
+        return;
+    }
+    
+    // При отмене дерево неполное - сохраняем прогресс, чтобы продолжить позже
+    if cancelled {
+        checkpoint.save();
+        *result.lock().unwrap() = Some(ScanResult::Cancelled);
+        return;
+    }
+    
     match root_result {
         Some(mut root) => {
             // Обновляем финальную статистику
@@ -1643,18 +3868,21 @@ fn scan_directory(
                 prog.files_scanned = file_count.load(Ordering::Relaxed);
                 prog.dirs_scanned = dir_count.load(Ordering::Relaxed);
                 prog.total_size = total_size.load(Ordering::Relaxed) as u64;
-                prog.message = "Sorting...".to_string();
+                prog.entries_seen = entries_seen.load(Ordering::Relaxed);
+                prog.excluded = *ctx.excluded.lock().unwrap();
             }
             
-            // Сортируем дерево после сканирования
-            sort_tree(&mut root);
-            
-            root.is_expanded = true;
+            process_tree(&mut root, &progress, dir_count.load(Ordering::Relaxed), sort);
+            checkpoint.finish();
+            // Снимок сжимается здесь, пока окно показывает обработку: копия дерева
+            // для фоновой записи удвоила бы память на большом диске
+            let encoded = encode_snapshot.then(|| snapshot::encode(&root).ok()).flatten();
             
             let mut prog = progress.lock().unwrap();
+            prog.processing = None;
             prog.message = format!("Complete in {:.2}s", elapsed.as_secs_f64());
             
-            *result.lock().unwrap() = Some(ScanResult::Complete(root));
+            *result.lock().unwrap() = Some(ScanResult::Complete(root, encoded));
         }
         None => {
             *result.lock().unwrap() = Some(ScanResult::Cancelled);
@@ -0,0 +1,494 @@
+// Статистика медиатеки: сколько места занимают видео, фото и музыка и в каком
+// они разрешении и кодеке - чтобы решить, что перекодировать или удалить.
+// Разрешение и кодек читаются из заголовков файлов, без декодирования.
+
+use crate::{format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Больший `moov` не читаем целиком - такие файлы встречаются редко
+const MAX_MOOV_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    Video,
+    Image,
+    Audio,
+}
+
+impl MediaKind {
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "mp4" | "m4v" | "mov" | "mkv" | "webm" | "avi" | "wmv" | "flv" | "mpg" | "mpeg" | "ts" | "m2ts"
+            | "3gp" => Some(Self::Video),
+            "jpg" | "jpeg" | "png" | "gif" | "webp" | "heic" | "heif" | "bmp" | "tif" | "tiff" | "dng" | "cr2"
+            | "nef" | "arw" => Some(Self::Image),
+            "mp3" | "flac" | "wav" | "aac" | "m4a" | "ogg" | "opus" | "wma" | "ape" => Some(Self::Audio),
+            _ => None,
+        }
+    }
+
+    fn translation_key(self) -> &'static str {
+        match self {
+            Self::Video => "media_video",
+            Self::Image => "media_image",
+            Self::Audio => "media_audio",
+        }
+    }
+}
+
+/// Что удалось узнать из заголовка файла
+#[derive(Default)]
+struct MediaInfo {
+    resolution: Option<(u32, u32)>,
+    codec: Option<String>,
+}
+
+/// Группа файлов в разбивке (по типу, разрешению или кодеку)
+pub struct MediaGroup {
+    pub kind: MediaKind,
+    /// Разрешение или кодек; None - не удалось определить
+    pub label: Option<String>,
+    pub files: u64,
+    pub size: u64,
+}
+
+pub struct MediaStats {
+    pub files: u64,
+    pub size: u64,
+    pub by_kind: Vec<MediaGroup>,
+    pub by_resolution: Vec<MediaGroup>,
+    pub by_codec: Vec<MediaGroup>,
+}
+
+/// Подсчёт статистики для папки; заголовки читаются в фоновом потоке
+pub struct MediaStatsJob {
+    pub root: PathBuf,
+    /// Полный размер папки - для доли медиа
+    pub folder_size: u64,
+    pub stats: Option<MediaStats>,
+    result: Arc<Mutex<Option<MediaStats>>>,
+}
+
+impl MediaStatsJob {
+    pub fn start(node: &DirNode) -> Self {
+        fn walk(node: &DirNode, files: &mut Vec<(PathBuf, u64, MediaKind)>) {
+            for child in &node.children {
+                if child.is_file {
+                    if let Some(kind) = extension(&child.path).as_deref().and_then(MediaKind::from_extension) {
+                        files.push((child.path.clone(), child.size, kind));
+                    }
+                } else {
+                    walk(child, files);
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        walk(node, &mut files);
+
+        let result = Arc::new(Mutex::new(None));
+        let result_clone = result.clone();
+        thread::spawn(move || {
+            let stats = collect_stats(files);
+            *result_clone.lock().unwrap() = Some(stats);
+        });
+
+        Self {
+            root: node.path.clone(),
+            folder_size: node.size,
+            stats: None,
+            result,
+        }
+    }
+
+    /// Забирает результат фонового потока, если он уже готов
+    fn poll(&mut self) {
+        if self.stats.is_none() {
+            if let Ok(mut result) = self.result.try_lock() {
+                self.stats = result.take();
+            }
+        }
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension().map(|e| e.to_string_lossy().to_lowercase())
+}
+
+fn collect_stats(files: Vec<(PathBuf, u64, MediaKind)>) -> MediaStats {
+    let infos: Vec<MediaInfo> = files
+        .par_iter()
+        .map(|(path, _, kind)| probe(path, *kind))
+        .collect();
+
+    let mut by_kind: HashMap<MediaKind, (u64, u64)> = HashMap::new();
+    let mut by_resolution: HashMap<(MediaKind, Option<String>), (u64, u64)> = HashMap::new();
+    let mut by_codec: HashMap<(MediaKind, Option<String>), (u64, u64)> = HashMap::new();
+
+    for ((_, size, kind), info) in files.iter().zip(infos) {
+        let add = |entry: &mut (u64, u64)| {
+            entry.0 += 1;
+            entry.1 += size;
+        };
+        add(by_kind.entry(*kind).or_default());
+        // У музыки разрешения нет
+        if *kind != MediaKind::Audio {
+            let class = info.resolution.map(|(w, h)| resolution_class(*kind, w, h));
+            add(by_resolution.entry((*kind, class)).or_default());
+        }
+        add(by_codec.entry((*kind, info.codec)).or_default());
+    }
+
+    let into_groups = |map: HashMap<(MediaKind, Option<String>), (u64, u64)>| {
+        let mut groups: Vec<MediaGroup> = map
+            .into_iter()
+            .map(|((kind, label), (files, size))| MediaGroup { kind, label, files, size })
+            .collect();
+        groups.sort_unstable_by_key(|g| std::cmp::Reverse(g.size));
+        groups
+    };
+
+    let mut kinds: Vec<MediaGroup> = by_kind
+        .into_iter()
+        .map(|(kind, (files, size))| MediaGroup { kind, label: None, files, size })
+        .collect();
+    kinds.sort_unstable_by_key(|g| std::cmp::Reverse(g.size));
+
+    MediaStats {
+        files: files.len() as u64,
+        size: files.iter().map(|(_, size, _)| size).sum(),
+        by_kind: kinds,
+        by_resolution: into_groups(by_resolution),
+        by_codec: into_groups(by_codec),
+    }
+}
+
+/// Класс разрешения: для видео - по меньшей стороне, для фото - в мегапикселях
+fn resolution_class(kind: MediaKind, width: u32, height: u32) -> String {
+    if kind == MediaKind::Video {
+        let side = width.min(height);
+        let class = match side {
+            2160.. => "2160p",
+            1440.. => "1440p",
+            1080.. => "1080p",
+            720.. => "720p",
+            480.. => "480p",
+            _ => "< 480p",
+        };
+        return class.to_string();
+    }
+
+    let megapixels = width as u64 * height as u64 / 1_000_000;
+    let class = match megapixels {
+        24.. => "24+ MP",
+        16.. => "16–24 MP",
+        8.. => "8–16 MP",
+        2.. => "2–8 MP",
+        _ => "< 2 MP",
+    };
+    class.to_string()
+}
+
+fn probe(path: &Path, kind: MediaKind) -> MediaInfo {
+    let ext = extension(path).unwrap_or_default();
+    let Ok(file) = File::open(path) else {
+        return MediaInfo::default();
+    };
+    let mut reader = BufReader::new(file);
+
+    match kind {
+        MediaKind::Video => match ext.as_str() {
+            "mp4" | "m4v" | "mov" | "3gp" => probe_mp4(&mut reader).unwrap_or_default(),
+            _ => MediaInfo::default(),
+        },
+        MediaKind::Image => {
+            let resolution = match ext.as_str() {
+                "png" => png_size(&mut reader),
+                "jpg" | "jpeg" => jpeg_size(&mut reader),
+                "gif" => gif_size(&mut reader),
+                _ => None,
+            };
+            let codec = match ext.as_str() {
+                "jpg" | "jpeg" => "JPEG".to_string(),
+                "tif" => "TIFF".to_string(),
+                "heif" => "HEIC".to_string(),
+                other => other.to_uppercase(),
+            };
+            MediaInfo { resolution, codec: Some(codec) }
+        }
+        // Формат музыки однозначно задаётся расширением
+        MediaKind::Audio => MediaInfo { resolution: None, codec: Some(ext.to_uppercase()) },
+    }
+}
+
+fn read_u16_be(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32_be(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn png_size(reader: &mut impl Read) -> Option<(u32, u32)> {
+    // Сигнатура (8), длина и тип чанка IHDR (8), затем ширина и высота
+    let mut header = [0u8; 24];
+    reader.read_exact(&mut header).ok()?;
+    if &header[1..4] != b"PNG" || &header[12..16] != b"IHDR" {
+        return None;
+    }
+    Some((read_u32_be(&header, 16)?, read_u32_be(&header, 20)?))
+}
+
+fn gif_size(reader: &mut impl Read) -> Option<(u32, u32)> {
+    let mut header = [0u8; 10];
+    reader.read_exact(&mut header).ok()?;
+    if &header[..3] != b"GIF" {
+        return None;
+    }
+    let width = u16::from_le_bytes([header[6], header[7]]);
+    let height = u16::from_le_bytes([header[8], header[9]]);
+    Some((width as u32, height as u32))
+}
+
+/// Размер из маркера SOF; остальные сегменты (EXIF, миниатюры) пропускаются
+fn jpeg_size(reader: &mut (impl Read + Seek)) -> Option<(u32, u32)> {
+    let mut marker = [0u8; 2];
+    reader.read_exact(&mut marker).ok()?;
+    if marker != [0xFF, 0xD8] {
+        return None;
+    }
+
+    loop {
+        reader.read_exact(&mut marker).ok()?;
+        if marker[0] != 0xFF {
+            return None;
+        }
+        match marker[1] {
+            // Заполнители и маркеры без длины
+            0xFF => {
+                reader.seek(SeekFrom::Current(-1)).ok()?;
+                continue;
+            }
+            0x01 | 0xD0..=0xD7 => continue,
+            0xD9 | 0xDA => return None,
+            _ => {}
+        }
+
+        let mut length = [0u8; 2];
+        reader.read_exact(&mut length).ok()?;
+        let length = u16::from_be_bytes(length) as i64;
+
+        let is_sof = matches!(marker[1], 0xC0..=0xCF) && !matches!(marker[1], 0xC4 | 0xC8 | 0xCC);
+        if is_sof {
+            let mut sof = [0u8; 5];
+            reader.read_exact(&mut sof).ok()?;
+            let height = read_u16_be(&sof, 1)?;
+            let width = read_u16_be(&sof, 3)?;
+            return Some((width as u32, height as u32));
+        }
+        reader.seek(SeekFrom::Current(length - 2)).ok()?;
+    }
+}
+
+/// Ищет среди боксов на верхнем уровне `bytes` бокс с типом `kind`
+fn find_box<'a>(bytes: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(bytes).find(|(k, _)| k == kind).map(|(_, body)| body)
+}
+
+/// Итератор по дочерним боксам MP4: (тип, содержимое без заголовка)
+fn boxes(mut bytes: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        let size = read_u32_be(bytes, 0)? as usize;
+        let kind: [u8; 4] = bytes.get(4..8)?.try_into().ok()?;
+        // 64-битные размеры внутри moov не встречаются на практике
+        if size < 8 || size > bytes.len() {
+            return None;
+        }
+        let body = &bytes[8..size];
+        bytes = &bytes[size..];
+        Some((kind, body))
+    })
+}
+
+/// Кодек и разрешение первой видеодорожки MP4/MOV
+fn probe_mp4(reader: &mut (impl Read + Seek)) -> Option<MediaInfo> {
+    // Ищем moov среди боксов верхнего уровня; он бывает и в конце файла
+    let mut position = reader.stream_position().ok()?;
+    let moov = loop {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header).ok()?;
+        let mut size = read_u32_be(&header, 0)? as u64;
+        let mut header_size = 8;
+        if size == 1 {
+            let mut large = [0u8; 8];
+            reader.read_exact(&mut large).ok()?;
+            size = u64::from_be_bytes(large);
+            header_size = 16;
+        }
+        if size < header_size {
+            return None;
+        }
+
+        if &header[4..8] == b"moov" {
+            if size > MAX_MOOV_SIZE {
+                return None;
+            }
+            let mut moov = vec![0u8; (size - header_size) as usize];
+            reader.read_exact(&mut moov).ok()?;
+            break moov;
+        }
+        // Размер из повреждённого файла может не влезть в смещение; каждый бокс
+        // обязан продвигать чтение вперёд, иначе цикл не закончится
+        let skip = i64::try_from(size - header_size).ok()?;
+        let next = reader.seek(SeekFrom::Current(skip)).ok()?;
+        if next <= position {
+            return None;
+        }
+        position = next;
+    };
+
+    for (kind, trak) in boxes(&moov) {
+        if &kind != b"trak" {
+            continue;
+        }
+        let Some(mdia) = find_box(trak, b"mdia") else {
+            continue;
+        };
+        // hdlr: версия и флаги (4), pre_defined (4), тип обработчика
+        let handler = find_box(mdia, b"hdlr").and_then(|h| h.get(8..12));
+        if handler != Some(b"vide".as_slice()) {
+            continue;
+        }
+
+        let stbl = find_box(find_box(mdia, b"minf")?, b"stbl")?;
+        // stsd: версия и флаги (4), число записей (4), затем первая запись
+        let entry = find_box(stbl, b"stsd")?.get(8..)?;
+        let fourcc: [u8; 4] = entry.get(4..8)?.try_into().ok()?;
+        // VisualSampleEntry: заголовок (8), reserved и индекс (8), pre_defined (16)
+        let width = read_u16_be(entry, 32)?;
+        let height = read_u16_be(entry, 34)?;
+
+        let codec = match &fourcc {
+            b"avc1" | b"avc3" => "H.264".to_string(),
+            b"hvc1" | b"hev1" => "H.265".to_string(),
+            b"av01" => "AV1".to_string(),
+            b"vp09" => "VP9".to_string(),
+            b"mp4v" => "MPEG-4".to_string(),
+            b"apch" | b"apcn" | b"apcs" | b"apco" | b"ap4h" => "ProRes".to_string(),
+            other => String::from_utf8_lossy(other).trim().to_string(),
+        };
+        return Some(MediaInfo {
+            resolution: Some((width as u32, height as u32)),
+            codec: Some(codec),
+        });
+    }
+    None
+}
+
+impl CedarApp {
+    /// Окно статистики медиафайлов папки
+    pub fn show_media_stats_window(&mut self, ctx: &egui::Context) {
+        let Some(job) = &mut self.media_stats else {
+            return;
+        };
+        job.poll();
+
+        let mut open = true;
+        let unknown = self.translations.get("unknown");
+
+        egui::Window::new(format!(
+            "{} {} - {}",
+            regular::FILM_STRIP,
+            self.translations.get("media_stats"),
+            job.root.display()
+        ))
+        .id(egui::Id::new("media_stats_window"))
+        .open(&mut open)
+        .default_size([560.0, 480.0])
+        .show(ctx, |ui| {
+            let Some(stats) = &job.stats else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(self.translations.get("media_stats_reading"));
+                });
+                ctx.request_repaint();
+                return;
+            };
+
+            if stats.files == 0 {
+                ui.label(
+                    egui::RichText::new(self.translations.get("media_none"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                return;
+            }
+
+            let share = if job.folder_size > 0 {
+                stats.size as f64 / job.folder_size as f64 * 100.0
+            } else {
+                0.0
+            };
+            ui.strong(self.translations.get_fmt(
+                "media_summary",
+                &[&stats.files.to_string(), &format_size(stats.size), &format!("{:.0}", share)],
+            ));
+            ui.label(
+                egui::RichText::new(self.translations.get("media_stats_hint"))
+                    .color(ui.visuals().weak_text_color()),
+            );
+            ui.separator();
+
+            let group_grid = |ui: &mut egui::Ui, id: &str, groups: &[MediaGroup], with_label: bool| {
+                egui::Grid::new(id)
+                    .striped(true)
+                    .num_columns(if with_label { 5 } else { 4 })
+                    .show(ui, |ui| {
+                        ui.strong(self.translations.get("type"));
+                        if with_label {
+                            ui.label("");
+                        }
+                        ui.strong(self.translations.get("files"));
+                        ui.strong(self.translations.get("size"));
+                        ui.strong(self.translations.get("average_size"));
+                        ui.end_row();
+
+                        for group in groups {
+                            ui.label(self.translations.get(group.kind.translation_key()));
+                            if with_label {
+                                ui.label(group.label.as_deref().unwrap_or(&unknown));
+                            }
+                            ui.label(group.files.to_string());
+                            ui.strong(format_size(group.size));
+                            ui.label(format_size(group.size / group.files.max(1)));
+                            ui.end_row();
+                        }
+                    });
+            };
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    group_grid(ui, "media_kind_grid", &stats.by_kind, false);
+                    ui.add_space(8.0);
+                    egui::CollapsingHeader::new(self.translations.get("media_by_resolution"))
+                        .default_open(true)
+                        .show(ui, |ui| group_grid(ui, "media_resolution_grid", &stats.by_resolution, true));
+                    egui::CollapsingHeader::new(self.translations.get("media_by_codec"))
+                        .default_open(true)
+                        .show(ui, |ui| group_grid(ui, "media_codec_grid", &stats.by_codec, true));
+                });
+        });
+
+        if !open {
+            self.media_stats = None;
+        }
+    }
+}
@@ -0,0 +1,295 @@
+// Файлы ncdu (`ncdu -o`): дерево, отсканированное на сервере без графики,
+// открывается здесь, а наше сканирование выгружается для `ncdu -f`. Формат -
+// JSON-массив [1, 2, {сведения}, папка], где папка - массив из описания самой
+// папки и её элементов, а файл - объект. Размеры папок ncdu хранит без
+// содержимого; здесь, как и при сканировании, папка - сумма её элементов
+
+use crate::snapshot::from_reader;
+use crate::{item_counts, largest_file, CedarApp, DirNode};
+use serde_json::Value;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const EXTENSION: &str = "json";
+
+const MAJOR_VERSION: u64 = 1;
+const MINOR_VERSION: u64 = 2;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Читает элемент; None - элемент исключён при сканировании ncdu и не учитывается
+fn read_entry(value: &Value, parent: &Path) -> io::Result<Option<DirNode>> {
+    let (info, children) = match value {
+        Value::Array(items) => match items.split_first() {
+            Some((info, children)) => (info, Some(children)),
+            None => return Err(invalid("empty directory entry")),
+        },
+        info => (info, None),
+    };
+    let field = |name: &str| info.get(name);
+    let number = |name: &str| field(name).and_then(Value::as_u64).unwrap_or(0);
+    let flag = |name: &str| field(name).and_then(Value::as_bool).unwrap_or(false);
+    if field("excluded").is_some() {
+        return Ok(None);
+    }
+    let name = field("name").and_then(Value::as_str).ok_or_else(|| invalid("entry without a name"))?;
+    // У корня имя - полный путь
+    let path = if parent.as_os_str().is_empty() { PathBuf::from(name) } else { parent.join(name) };
+    let mut node = DirNode::new(path, OsString::from(name), number("asize"), children.is_none());
+    node.modified = field("mtime").and_then(Value::as_u64).map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+
+    match children {
+        None => {
+            node.allocated = number("dsize");
+            node.skipped = u64::from(flag("read_error"));
+        }
+        Some(children) => {
+            for child in children {
+                node.children.extend(read_entry(child, &node.path)?);
+            }
+            node.children.sort_unstable_by_key(|c| std::cmp::Reverse(c.size));
+            node.size = node.children.iter().map(|c| c.size).sum();
+            node.allocated = node.children.iter().map(|c| c.allocated).sum();
+            node.skipped = node.children.iter().map(|c| c.skipped).sum::<u64>() + u64::from(flag("read_error"));
+            node.modified = node.children.iter().filter_map(|c| c.modified).max().or(node.modified);
+            node.largest_file = largest_file(&node.children);
+            (node.files, node.folders) = item_counts(&node.children);
+        }
+    }
+    Ok(Some(node))
+}
+
+/// Открывает файл ncdu: дерево и время сканирования
+pub fn import(path: &Path) -> io::Result<(DirNode, Option<SystemTime>)> {
+    let document: Value = from_reader(BufReader::new(File::open(path)?))?;
+    let items = document.as_array().ok_or_else(|| invalid("not an ncdu export"))?;
+    if items.first().and_then(Value::as_u64) != Some(MAJOR_VERSION) || items.len() < 4 {
+        return Err(invalid("not an ncdu export"));
+    }
+    let timestamp = items[2].get("timestamp").and_then(Value::as_u64).map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+    let mut root = read_entry(&items[3], Path::new(""))?.ok_or_else(|| invalid("root is excluded"))?;
+    if root.is_file {
+        return Err(invalid("root is not a directory"));
+    }
+    root.is_expanded = true;
+    Ok((root, timestamp))
+}
+
+fn write_entry(writer: &mut impl Write, node: &DirNode, name: &str) -> io::Result<()> {
+    let name = serde_json::to_string(name)?;
+    if node.is_file {
+        write!(writer, r#"{{"name":{},"asize":{},"dsize":{}"#, name, node.size, node.allocated)?;
+        if let Some(modified) = node.modified {
+            write!(writer, r#","mtime":{}"#, seconds(modified))?;
+        }
+        if node.link_target.is_some() {
+            write!(writer, r#","notreg":true"#)?;
+        }
+        return write!(writer, "}}");
+    }
+
+    // Ошибки чтения самой папки, а не её содержимого
+    let own_errors = node.skipped.saturating_sub(node.children.iter().map(|c| c.skipped).sum());
+    write!(writer, r#"[{{"name":{},"asize":0,"dsize":0"#, name)?;
+    if own_errors > 0 {
+        write!(writer, r#","read_error":true"#)?;
+    }
+    write!(writer, "}}")?;
+    for child in &node.children {
+        writeln!(writer, ",")?;
+        write_entry(writer, child, &child.display_name())?;
+    }
+    write!(writer, "]")
+}
+
+/// Записывает дерево в формате ncdu
+pub fn export(path: &Path, root: &DirNode, scanned_at: SystemTime) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(
+        writer,
+        r#"[{},{},{{"progname":"{}","progver":"{}","timestamp":{}}},"#,
+        MAJOR_VERSION,
+        MINOR_VERSION,
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        seconds(scanned_at),
+    )?;
+    writeln!(writer)?;
+    write_entry(&mut writer, root, &root.path.display().to_string())?;
+    writeln!(writer, "]")?;
+    writer.flush()
+}
+
+impl CedarApp {
+    /// Выгружает текущее дерево в файл ncdu по выбору пользователя
+    pub fn export_ncdu(&mut self) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("ncdu", &[EXTENSION])
+            .set_file_name(format!("{}.ncdu.{}", root.display_name(), EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        let scanned_at = self.last_scan_finished.unwrap_or_else(SystemTime::now);
+        match export(&path, root, scanned_at) {
+            Ok(()) => self.set_status(self.translations.get_fmt("ncdu_exported", &[&path.display().to_string()])),
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("ncdu_export_failed", &[&e.to_string()]));
+            }
+        }
+    }
+
+    /// Открывает файл ncdu вместо текущего дерева
+    pub fn import_ncdu(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("ncdu", &[EXTENSION]).pick_file() else {
+            return;
+        };
+        match import(&path) {
+            Ok((root, scanned_at)) => {
+                let text = self.translations.get_fmt("ncdu_imported", &[&root.path.display().to_string()]);
+                self.open_tree(root, scanned_at.unwrap_or_else(SystemTime::now));
+                // Пути из ncdu обычно сняты на другой машине: "/srv/..." в Windows
+                // указал бы на папку текущего диска, поэтому удалять из такого дерева нельзя
+                self.imported_tree = true;
+                self.set_status(text);
+            }
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("ncdu_import_failed", &[&e.to_string()]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{child, TempFile};
+
+    fn import_str(name: &str, contents: &str) -> io::Result<(DirNode, Option<SystemTime>)> {
+        import(&TempFile::with_contents(&format!("ncdu-{}.json", name), contents).0)
+    }
+
+    #[test]
+    fn nested_directories_sum_their_contents() {
+        let (root, timestamp) = import_str(
+            "nested",
+            r#"[1,2,{"progname":"ncdu","progver":"2.3","timestamp":1700000000},
+            [{"name":"/srv","asize":4096,"dsize":4096},
+                {"name":"a.log","asize":100,"dsize":4096,"mtime":1600000000},
+                [{"name":"data","asize":4096},
+                    {"name":"b.bin","asize":1000,"dsize":4096,"mtime":1650000000},
+                    [{"name":"empty"}]
+                ]
+            ]]"#,
+        )
+        .unwrap();
+
+        assert_eq!(timestamp, Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+        assert_eq!(root.path, PathBuf::from("/srv"));
+        assert!(!root.is_file && root.is_expanded);
+        assert_eq!(root.size, 1100);
+        assert_eq!(root.allocated, 8192);
+        assert_eq!((root.files, root.folders), (2, 2));
+        // Дети упорядочены по размеру
+        assert_eq!(root.children[0].name, "data");
+
+        let data = child(&root, "data");
+        assert_eq!(data.path, PathBuf::from("/srv/data"));
+        assert_eq!(data.size, 1000);
+        assert_eq!(data.modified, Some(UNIX_EPOCH + Duration::from_secs(1_650_000_000)));
+        assert_eq!(child(data, "empty").size, 0);
+        assert_eq!(root.largest_file.as_ref().map(|f| f.size), Some(1000));
+    }
+
+    #[test]
+    fn hard_links_count_every_name_like_a_scan() {
+        let (root, _) = import_str(
+            "hardlinks",
+            r#"[1,2,{},
+            [{"name":"/home"},
+                {"name":"one","asize":500,"dsize":512,"ino":42,"hlnkc":true,"nlink":2},
+                {"name":"two","asize":500,"dsize":512,"ino":42,"hlnkc":true,"nlink":2}
+            ]]"#,
+        )
+        .unwrap();
+
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.size, 1000);
+        assert_eq!(root.file_count(), 2);
+    }
+
+    #[test]
+    fn excluded_entries_are_skipped() {
+        let (root, _) = import_str(
+            "excluded",
+            r#"[1,2,{},
+            [{"name":"/"},
+                {"name":"kept","asize":10},
+                {"name":"proc","excluded":"pattern"},
+                [{"name":"mnt","excluded":"otherfs"}],
+                {"name":"broken","read_error":true}
+            ]]"#,
+        )
+        .unwrap();
+
+        let names: Vec<_> = root.children.iter().map(|c| c.display_name()).collect();
+        assert_eq!(names, ["kept", "broken"]);
+        assert_eq!(root.size, 10);
+        assert_eq!(root.skipped, 1);
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        let cases = [
+            ("not-json", "ncdu"),
+            ("truncated", r#"[1,2,{},[{"name":"/"},"#),
+            ("object", r#"{"name":"/"}"#),
+            ("short", r#"[1,2,{}]"#),
+            ("version", r#"[2,0,{},[{"name":"/"}]]"#),
+            ("root-file", r#"[1,2,{},{"name":"/etc/passwd","asize":1}]"#),
+            ("root-excluded", r#"[1,2,{},[{"name":"/","excluded":"pattern"}]]"#),
+            ("empty-dir", r#"[1,2,{},[{"name":"/"},[]]]"#),
+            ("no-name", r#"[1,2,{},[{"name":"/"},{"asize":1}]]"#),
+            ("name-not-string", r#"[1,2,{},[{"name":"/"},{"name":7}]]"#),
+            ("entry-not-object", r#"[1,2,{},[{"name":"/"},3]]"#),
+        ];
+        for (name, contents) in cases {
+            assert!(import_str(name, contents).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn export_reads_back() {
+        let (root, _) = import_str(
+            "source",
+            r#"[1,2,{},
+            [{"name":"/srv"},
+                {"name":"quote\"d","asize":7,"dsize":4096,"mtime":1600000000},
+                [{"name":"sub"},{"name":"x","asize":3,"dsize":4096}]
+            ]]"#,
+        )
+        .unwrap();
+        let exported = TempFile::new("ncdu-export.json");
+        export(&exported.0, &root, UNIX_EPOCH + Duration::from_secs(1_700_000_000)).unwrap();
+        let (back, timestamp) = import(&exported.0).unwrap();
+
+        assert_eq!(timestamp, Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+        assert_eq!((back.path.clone(), back.size, back.allocated), (root.path.clone(), root.size, root.allocated));
+        assert_eq!(child(&back, "quote\"d").modified, child(&root, "quote\"d").modified);
+        assert_eq!(child(child(&back, "sub"), "x").size, 3);
+    }
+}
@@ -0,0 +1,81 @@
+// Заметки к путям ("можно удалить после 3 квартала", "папка бухгалтерии"):
+// хранятся в конфигурации и видны значком в дереве
+
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::PathBuf;
+
+/// Заметка, открытая в редакторе
+pub struct NoteEditor {
+    pub path: PathBuf,
+    pub text: String,
+    /// Фокус на поле ввода ставится один раз, при открытии
+    focused: bool,
+}
+
+impl CedarApp {
+    /// Открывает редактор заметки для пути
+    pub fn edit_note(&mut self, path: PathBuf) {
+        let text = self.config.notes.get(&path).cloned().unwrap_or_default();
+        self.note_editor = Some(NoteEditor { path, text, focused: false });
+    }
+
+    /// Окно редактирования заметки
+    pub fn show_note_editor_window(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.note_editor else {
+            return;
+        };
+
+        let mut open = true;
+        let mut save = false;
+        let mut close = false;
+
+        egui::Window::new(format!("{} {}", regular::NOTE_PENCIL, self.translations.get("note")))
+            .id(egui::Id::new("note_editor_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(editor.path.display().to_string()).strong());
+                ui.add_space(5.0);
+
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut editor.text)
+                        .hint_text(self.translations.get("note_hint"))
+                        .desired_rows(3)
+                        .desired_width(360.0),
+                );
+                if !editor.focused {
+                    response.request_focus();
+                    editor.focused = true;
+                }
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    if ui.button(format!("{} {}", regular::FLOPPY_DISK, self.translations.get("save"))).clicked() {
+                        save = true;
+                    }
+                    if ui.button(self.translations.get("cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if save {
+            // Пустая заметка - это удаление
+            let text = editor.text.trim().to_string();
+            if text.is_empty() {
+                self.config.notes.remove(&editor.path);
+            } else {
+                self.config.notes.insert(editor.path.clone(), text);
+            }
+            self.save_config_to_file();
+            close = true;
+        }
+        if close || !open {
+            self.note_editor = None;
+        }
+    }
+}
@@ -0,0 +1,264 @@
+// Журнал файловых операций: каждое удаление через приложение дописывается в файл
+// operations.log построчно в JSON и никогда не переписывается. Журнал можно
+// просмотреть и выгрузить в CSV, чтобы после уборки ответить, кто и что удалил
+
+use crate::{file_ops, format_size, CedarApp};
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Trash,
+    Delete,
+}
+
+impl Action {
+    fn translation_key(self) -> &'static str {
+        match self {
+            Action::Trash => "op_trash",
+            Action::Delete => "op_delete",
+        }
+    }
+}
+
+/// Запись журнала
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Operation {
+    /// Секунды от начала эпохи Unix
+    pub time: u64,
+    pub user: String,
+    pub host: String,
+    pub action: Action,
+    #[serde(with = "crate::os_serde")]
+    pub path: PathBuf,
+    pub size: u64,
+    /// Текст ошибки; `None` - операция выполнена
+    pub error: Option<String>,
+}
+
+pub fn log_path() -> Option<PathBuf> {
+    crate::app_paths::data_path("operations.log")
+}
+
+/// Дописывает операцию в конец журнала
+fn append(operation: &Operation) -> std::io::Result<()> {
+    let Some(path) = log_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let line = serde_json::to_string(operation).map_err(std::io::Error::other)?;
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Все записи журнала; повреждённые строки пропускаются
+fn read_all() -> Vec<Operation> {
+    let Some(text) = log_path().and_then(|p| std::fs::read_to_string(p).ok()) else {
+        return Vec::new();
+    };
+    text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn to_csv(operations: &[Operation]) -> String {
+    let mut csv = String::from("time,user,host,action,path,size,result\n");
+    for op in operations {
+        // В CSV время в UTC, чтобы журналы разных компьютеров сравнивались напрямую
+        let time = i64::try_from(op.time)
+            .ok()
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default();
+        let action = match op.action {
+            Action::Trash => "trash",
+            Action::Delete => "delete",
+        };
+        let fields = [
+            time,
+            op.user.clone(),
+            op.host.clone(),
+            action.to_string(),
+            op.path.display().to_string(),
+            op.size.to_string(),
+            op.error.clone().unwrap_or_else(|| "ok".to_string()),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Окно просмотра журнала
+pub struct OpLogView {
+    operations: Vec<Operation>,
+    filter: String,
+}
+
+impl OpLogView {
+    pub fn open() -> Self {
+        let mut operations = read_all();
+        operations.reverse();
+        Self {
+            operations,
+            filter: String::new(),
+        }
+    }
+}
+
+/// Удаляет путь размером `size` и записывает операцию в журнал, в том числе
+/// неудачную. Годится для фоновых потоков; вторая часть результата - ошибка записи журнала
+pub fn delete_and_record(path: &Path, size: u64, permanent: bool) -> (Result<(), String>, std::io::Result<()>) {
+    let result = file_ops::delete_path(path, permanent);
+    let operation = Operation {
+        time: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        user: std::env::var("USERNAME").or_else(|_| std::env::var("USER")).unwrap_or_default(),
+        host: sysinfo::System::host_name().unwrap_or_default(),
+        action: if permanent { Action::Delete } else { Action::Trash },
+        path: path.to_path_buf(),
+        size,
+        error: result.as_ref().err().cloned(),
+    };
+    let written = append(&operation);
+    (result, written)
+}
+
+impl CedarApp {
+    /// Удаляет путь и записывает операцию в журнал, в том числе неудачную
+    pub fn delete_logged(&mut self, path: &Path, permanent: bool) -> Result<(), String> {
+        let size = self.root_node.as_ref().and_then(|r| r.find(path)).map_or(0, |n| n.size);
+        let (result, written) = delete_and_record(path, size, permanent);
+        if let Err(e) = written {
+            self.log.error(self.translations.get_fmt("op_log_write_failed", &[&e.to_string()]));
+        }
+        result
+    }
+
+    /// Окно журнала файловых операций
+    pub fn show_op_log_window(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.op_log else {
+            return;
+        };
+
+        let mut open = true;
+        let mut export = false;
+        let ok_color = self.config.palette.status_text(crate::palette::Status::Good, self.config.dark_mode);
+        let error_color = self.config.palette.status_text(crate::palette::Status::Critical, self.config.dark_mode);
+
+        egui::Window::new(format!("{} {}", regular::SCROLL, self.translations.get("op_log")))
+            .open(&mut open)
+            .default_size([820.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.translations.get("op_log_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(regular::MAGNIFYING_GLASS);
+                    ui.add(
+                        egui::TextEdit::singleline(&mut view.filter)
+                            .hint_text(self.translations.get("op_log_filter"))
+                            .desired_width(260.0),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_enabled_ui(!view.operations.is_empty(), |ui| {
+                            if ui.button(format!("{} {}", regular::EXPORT, self.translations.get("op_log_export"))).clicked() {
+                                export = true;
+                            }
+                        });
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, self.translations.get("refresh"))).clicked() {
+                            *view = OpLogView::open();
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                if view.operations.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("op_log_empty"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                let filter = view.filter.to_lowercase();
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("op_log_grid")
+                            .striped(true)
+                            .num_columns(5)
+                            .show(ui, |ui| {
+                                ui.strong(self.translations.get("op_log_time"));
+                                ui.strong(self.translations.get("op_log_user"));
+                                ui.strong(self.translations.get("op_log_action"));
+                                ui.strong(self.translations.get("size"));
+                                ui.strong(self.translations.get("op_log_path"));
+                                ui.end_row();
+
+                                for op in view.operations.iter().filter(|op| {
+                                    filter.is_empty()
+                                        || op.path.to_string_lossy().to_lowercase().contains(&filter)
+                                        || op.user.to_lowercase().contains(&filter)
+                                }) {
+                                    let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(op.time);
+                                    ui.label(self.translations.format_date(time));
+                                    ui.label(format!("{}@{}", op.user, op.host));
+                                    let (icon, color) = match &op.error {
+                                        None => (regular::CHECK, ok_color),
+                                        Some(_) => (regular::X, error_color),
+                                    };
+                                    let response = ui.label(
+                                        egui::RichText::new(format!("{} {}", icon, self.translations.get(op.action.translation_key())))
+                                            .color(color),
+                                    );
+                                    if let Some(error) = &op.error {
+                                        response.on_hover_text(error);
+                                    }
+                                    ui.label(format_size(op.size));
+                                    ui.label(op.path.display().to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if export {
+            let path = rfd::FileDialog::new()
+                .add_filter("CSV", &["csv"])
+                .set_file_name("cedar-operations.csv")
+                .save_file();
+            if let Some(path) = path {
+                // Выгружаем в хронологическом порядке, как в самом журнале
+                let mut operations = view.operations.clone();
+                operations.reverse();
+                match std::fs::write(&path, to_csv(&operations)) {
+                    Ok(()) => self.set_status(self.translations.get_fmt("op_log_exported", &[&path.display().to_string()])),
+                    Err(e) => self.log.error(format!("{}: {}", path.display(), e)),
+                }
+            }
+        }
+        if !open {
+            self.op_log = None;
+        }
+    }
+}
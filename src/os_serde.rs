@@ -0,0 +1,197 @@
+// Имена и пути в JSON (экспорт, контрольные точки, настройки, журнал). Обычные
+// имена пишутся строками, а имена не в UTF-8 - массивом исходных единиц кода
+// (байтов в Unix, 16-битных слов в Windows), чтобы такие файлы не терялись при сохранении
+
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::marker::PhantomData;
+
+#[cfg(unix)]
+type Unit = u8;
+#[cfg(windows)]
+type Unit = u16;
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Repr {
+    Text(String),
+    Raw(Vec<Unit>),
+}
+
+#[cfg(unix)]
+fn to_units(value: &OsStr) -> Vec<Unit> {
+    use std::os::unix::ffi::OsStrExt;
+    value.as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+fn from_units(units: Vec<Unit>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(units)
+}
+
+#[cfg(windows)]
+fn to_units(value: &OsStr) -> Vec<Unit> {
+    use std::os::windows::ffi::OsStrExt;
+    value.encode_wide().collect()
+}
+
+#[cfg(windows)]
+fn from_units(units: Vec<Unit>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    OsString::from_wide(&units)
+}
+
+fn repr(value: &OsStr) -> Repr {
+    match value.to_str() {
+        Some(text) => Repr::Text(text.to_string()),
+        None => Repr::Raw(to_units(value)),
+    }
+}
+
+fn from_repr(repr: Repr) -> OsString {
+    match repr {
+        Repr::Text(text) => text.into(),
+        Repr::Raw(units) => from_units(units),
+    }
+}
+
+pub fn serialize<T: AsRef<OsStr>, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    repr(value.as_ref()).serialize(serializer)
+}
+
+pub fn deserialize<'de, T: From<OsString>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    Repr::deserialize(deserializer).map(|repr| from_repr(repr).into())
+}
+
+/// То же для необязательных значений
+pub mod option {
+    use super::*;
+
+    pub fn serialize<T: AsRef<OsStr>, S: Serializer>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.as_ref().map(|v| repr(v.as_ref())).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: From<OsString>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+        Option::<Repr>::deserialize(deserializer).map(|repr| repr.map(|r| from_repr(r).into()))
+    }
+}
+
+/// То же для списков
+pub mod seq {
+    use super::*;
+
+    pub fn serialize<T: AsRef<OsStr>, S: Serializer>(values: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter().map(|v| repr(v.as_ref())))
+    }
+
+    pub fn deserialize<'de, T: From<OsString>, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> {
+        Vec::<Repr>::deserialize(deserializer).map(|reprs| reprs.into_iter().map(|r| from_repr(r).into()).collect())
+    }
+}
+
+/// Словари с путями в ключах. Ключ объекта JSON бывает только строкой, поэтому
+/// словарь с ключом не в UTF-8 пишется списком пар; читаются оба вида
+pub mod map {
+    use super::*;
+
+    pub fn serialize<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: AsRef<OsStr> + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        // Пока все ключи в UTF-8, пишется обычный объект - его читают и прежние версии
+        if map.into_iter().all(|(key, _)| key.as_ref().to_str().is_some()) {
+            serializer.collect_map(map.into_iter().map(|(key, value)| (key.as_ref().to_string_lossy(), value)))
+        } else {
+            serializer.collect_seq(map.into_iter().map(|(key, value)| (repr(key.as_ref()), value)))
+        }
+    }
+
+    pub fn deserialize<'de, M, K, V, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        M: Default + Extend<(K, V)>,
+        K: From<OsString>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(Entries(PhantomData))
+    }
+
+    struct Entries<M, K, V>(PhantomData<(M, K, V)>);
+
+    impl<'de, M, K, V> Visitor<'de> for Entries<M, K, V>
+    where
+        M: Default + Extend<(K, V)>,
+        K: From<OsString>,
+        V: Deserialize<'de>,
+    {
+        type Value = M;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map or a list of key-value pairs")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<M, A::Error> {
+            let mut map = M::default();
+            while let Some((key, value)) = access.next_entry::<String, V>()? {
+                map.extend([(K::from(key.into()), value)]);
+            }
+            Ok(map)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut access: A) -> Result<M, A::Error> {
+            let mut map = M::default();
+            while let Some((key, value)) = access.next_element::<(Repr, V)>()? {
+                map.extend([(K::from(from_repr(key)), value)]);
+            }
+            Ok(map)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::os::unix::ffi::OsStringExt;
+    use std::path::PathBuf;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Paths {
+        #[serde(with = "super::seq")]
+        list: Vec<PathBuf>,
+        #[serde(with = "super::map")]
+        sizes: BTreeMap<PathBuf, u64>,
+    }
+
+    fn round_trip(paths: &Paths) -> String {
+        let json = serde_json::to_string(paths).unwrap();
+        assert_eq!(&serde_json::from_str::<Paths>(&json).unwrap(), paths);
+        json
+    }
+
+    #[test]
+    fn utf8_keys_stay_an_object() {
+        let paths = Paths {
+            list: vec![PathBuf::from("/data")],
+            sizes: BTreeMap::from([(PathBuf::from("/data/a"), 1)]),
+        };
+        assert_eq!(round_trip(&paths), r#"{"list":["/data"],"sizes":{"/data/a":1}}"#);
+    }
+
+    #[test]
+    fn non_utf8_paths_survive() {
+        let raw = PathBuf::from(OsString::from_vec(b"/data/\xff".to_vec()));
+        let paths = Paths {
+            list: vec![raw.clone(), PathBuf::from("/data")],
+            sizes: BTreeMap::from([(raw, 1), (PathBuf::from("/data/a"), 2)]),
+        };
+        let json = round_trip(&paths);
+        assert!(json.contains(r#""sizes":[["/data/a",2],[[47,100,97,116,97,47,255],1]]"#), "{}", json);
+    }
+}
@@ -0,0 +1,128 @@
+// Палитры цветового кодирования: стандартная и различимая при дейтеранопии и
+// протанопии (без противопоставления красного и зелёного, на основе набора Окабе - Ито)
+
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Для красно-зелёной слепоты: синий - оранжевый вместо зелёного - красного
+    ColorBlind,
+}
+
+/// Уровень состояния: заполненность диска, скорость сканирования, предупреждения
+#[derive(Clone, Copy, PartialEq)]
+pub enum Status {
+    Good,
+    Warning,
+    Critical,
+}
+
+/// Категориальный набор Окабе - Ито (без чёрного)
+const OKABE_ITO: [Color32; 7] = [
+    Color32::from_rgb(230, 159, 0),
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(0, 158, 115),
+    Color32::from_rgb(240, 228, 66),
+    Color32::from_rgb(0, 114, 178),
+    Color32::from_rgb(213, 94, 0),
+    Color32::from_rgb(204, 121, 167),
+];
+
+/// Концы шкалы "новое - старое" для палитры без красного и зелёного
+const RAMP_HOT: Color32 = Color32::from_rgb(230, 97, 1);
+const RAMP_COLD: Color32 = Color32::from_rgb(33, 102, 172);
+
+/// Оттенок самых старых данных в стандартной шкале (синий); самые свежие - красный
+const STANDARD_COLDEST_HUE: f32 = 0.66;
+
+fn lerp(a: Color32, b: Color32, t: f32) -> Color32 {
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    Color32::from_rgb(mix(a.r(), b.r()), mix(a.g(), b.g()), mix(a.b(), b.b()))
+}
+
+/// Затемняет цвет, не трогая прозрачность
+fn darken(color: Color32, factor: f32) -> Color32 {
+    let scale = |c: u8| (c as f32 * factor).round() as u8;
+    Color32::from_rgb(scale(color.r()), scale(color.g()), scale(color.b()))
+}
+
+impl Palette {
+    pub fn all() -> [Palette; 2] {
+        [Palette::Standard, Palette::ColorBlind]
+    }
+
+    pub fn translation_key(self) -> &'static str {
+        match self {
+            Palette::Standard => "palette_standard",
+            Palette::ColorBlind => "palette_color_blind",
+        }
+    }
+
+    /// Заливка индикатора состояния (полосы заполненности дисков)
+    pub fn status_fill(self, status: Status, dark_mode: bool) -> Color32 {
+        match (self, status) {
+            (Palette::Standard, Status::Good) if dark_mode => Color32::from_rgb(60, 130, 80),
+            (Palette::Standard, Status::Good) => Color32::from_rgb(80, 150, 100),
+            (Palette::Standard, Status::Warning) => Color32::from_rgb(255, 127, 39),
+            (Palette::Standard, Status::Critical) => Color32::from_rgb(136, 0, 21),
+            (Palette::ColorBlind, Status::Good) if dark_mode => Color32::from_rgb(0, 94, 150),
+            (Palette::ColorBlind, Status::Good) => Color32::from_rgb(0, 114, 178),
+            (Palette::ColorBlind, Status::Warning) => Color32::from_rgb(230, 159, 0),
+            (Palette::ColorBlind, Status::Critical) => Color32::from_rgb(170, 60, 0),
+        }
+    }
+
+    /// Цвет текста с оценкой состояния
+    pub fn status_text(self, status: Status, dark_mode: bool) -> Color32 {
+        match (self, status) {
+            (Palette::Standard, Status::Good) if dark_mode => Color32::from_rgb(100, 180, 120),
+            (Palette::Standard, Status::Good) => Color32::from_rgb(60, 140, 80),
+            (Palette::Standard, Status::Warning) => Color32::from_rgb(255, 127, 39),
+            (Palette::Standard, Status::Critical) => Color32::LIGHT_RED,
+            (Palette::ColorBlind, Status::Good) if dark_mode => Color32::from_rgb(86, 180, 233),
+            (Palette::ColorBlind, Status::Good) => Color32::from_rgb(0, 114, 178),
+            (Palette::ColorBlind, Status::Warning) => Color32::from_rgb(230, 159, 0),
+            (Palette::ColorBlind, Status::Critical) => Color32::from_rgb(213, 94, 0),
+        }
+    }
+
+    /// Шкала от 0 (новое, "горячее") до 1 (старое, "холодное")
+    pub fn ramp(self, fraction: f32, dark_mode: bool) -> Color32 {
+        match self {
+            Palette::Standard => {
+                let value = if dark_mode { 0.6 } else { 0.9 };
+                egui::ecolor::Hsva::new(fraction * STANDARD_COLDEST_HUE, 0.65, value, 1.0).into()
+            }
+            Palette::ColorBlind => {
+                let color = lerp(RAMP_HOT, RAMP_COLD, fraction);
+                if dark_mode { darken(color, 0.75) } else { color }
+            }
+        }
+    }
+
+    /// Цвет категории (ветки графического представления) по оттенку `hue` из [0, 1)
+    pub fn category(self, hue: f32, depth: usize, is_file: bool, dark_mode: bool) -> Color32 {
+        let shade = (depth as f32 * 0.04).min(0.2);
+        match self {
+            Palette::Standard => {
+                let saturation = if is_file { 0.15 } else { 0.45 };
+                let value = if dark_mode { 0.55 - shade } else { 0.85 - shade };
+                egui::ecolor::Hsva::new(hue, saturation, value, 1.0).into()
+            }
+            Palette::ColorBlind => {
+                let index = (hue * OKABE_ITO.len() as f32) as usize % OKABE_ITO.len();
+                let mut color = OKABE_ITO[index];
+                // Файлы бледнее папок: смешиваем с фоном
+                if is_file {
+                    let background = if dark_mode { Color32::from_gray(60) } else { Color32::from_gray(225) };
+                    color = lerp(color, background, 0.6);
+                }
+                let brightness = if dark_mode { 0.7 } else { 1.0 };
+                darken(color, brightness - shade)
+            }
+        }
+    }
+}
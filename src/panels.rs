@@ -0,0 +1,572 @@
+// Пристыковываемые панели: сведения о выбранном элементе, крупнейшие файлы,
+// папки с ошибками чтения, журнал событий и рекомендации по очистке. Каждую панель можно разместить
+// слева, справа или снизу либо скрыть; размещение и размеры доков хранятся в конфигурации
+
+use crate::palette::Status;
+use crate::{a11y, format_size, throttle, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Сколько крупнейших файлов показывать
+const TOP_FILES_LIMIT: usize = 100;
+/// Сколько последних событий хранить в журнале
+const LOG_LIMIT: usize = 500;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Dock {
+    Left,
+    Right,
+    Bottom,
+    Hidden,
+}
+
+impl Dock {
+    fn all() -> [Dock; 4] {
+        [Dock::Left, Dock::Right, Dock::Bottom, Dock::Hidden]
+    }
+
+    fn translation_key(self) -> &'static str {
+        match self {
+            Dock::Left => "dock_left",
+            Dock::Right => "dock_right",
+            Dock::Bottom => "dock_bottom",
+            Dock::Hidden => "dock_hidden",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Panel {
+    Details,
+    TopFiles,
+    Errors,
+    Log,
+    Recommendations,
+}
+
+impl Panel {
+    pub fn all() -> [Panel; 5] {
+        [Panel::Details, Panel::TopFiles, Panel::Errors, Panel::Log, Panel::Recommendations]
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Panel::Details => regular::INFO,
+            Panel::TopFiles => regular::SORT_DESCENDING,
+            Panel::Errors => regular::WARNING,
+            Panel::Log => regular::SCROLL,
+            Panel::Recommendations => regular::BROOM,
+        }
+    }
+
+    pub fn translation_key(self) -> &'static str {
+        match self {
+            Panel::Details => "panel_details",
+            Panel::TopFiles => "panel_top_files",
+            Panel::Errors => "panel_errors",
+            Panel::Log => "panel_log",
+            Panel::Recommendations => "panel_recommendations",
+        }
+    }
+}
+
+/// Размещение панелей. Панели в одном доке показываются вкладками
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    details: Dock,
+    top_files: Dock,
+    errors: Dock,
+    log: Dock,
+    recommendations: Dock,
+    left_tab: Panel,
+    right_tab: Panel,
+    bottom_tab: Panel,
+    left_width: f32,
+    right_width: f32,
+    bottom_height: f32,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            details: Dock::Right,
+            top_files: Dock::Hidden,
+            errors: Dock::Hidden,
+            log: Dock::Hidden,
+            recommendations: Dock::Hidden,
+            left_tab: Panel::Details,
+            right_tab: Panel::Details,
+            bottom_tab: Panel::Log,
+            left_width: 280.0,
+            right_width: 280.0,
+            bottom_height: 180.0,
+        }
+    }
+}
+
+impl PanelLayout {
+    pub fn dock_mut(&mut self, panel: Panel) -> &mut Dock {
+        match panel {
+            Panel::Details => &mut self.details,
+            Panel::TopFiles => &mut self.top_files,
+            Panel::Errors => &mut self.errors,
+            Panel::Log => &mut self.log,
+            Panel::Recommendations => &mut self.recommendations,
+        }
+    }
+
+    fn dock(&self, panel: Panel) -> Dock {
+        match panel {
+            Panel::Details => self.details,
+            Panel::TopFiles => self.top_files,
+            Panel::Errors => self.errors,
+            Panel::Log => self.log,
+            Panel::Recommendations => self.recommendations,
+        }
+    }
+
+    /// Показывает панель открытой вкладкой; скрытая панель встаёт в правый док
+    pub fn open(&mut self, panel: Panel) {
+        if self.dock(panel) == Dock::Hidden {
+            *self.dock_mut(panel) = Dock::Right;
+        }
+        let dock = self.dock(panel);
+        *self.tab_mut(dock) = panel;
+    }
+
+    fn tab_mut(&mut self, dock: Dock) -> &mut Panel {
+        match dock {
+            Dock::Left => &mut self.left_tab,
+            Dock::Bottom => &mut self.bottom_tab,
+            Dock::Right | Dock::Hidden => &mut self.right_tab,
+        }
+    }
+
+    fn size_mut(&mut self, dock: Dock) -> &mut f32 {
+        match dock {
+            Dock::Left => &mut self.left_width,
+            Dock::Bottom => &mut self.bottom_height,
+            Dock::Right | Dock::Hidden => &mut self.right_width,
+        }
+    }
+}
+
+/// Выбор дока для панели; возвращает true, если размещение изменилось
+pub fn dock_menu(ui: &mut egui::Ui, dock: &mut Dock, translations: &crate::i18n::Translations) -> bool {
+    let mut changed = false;
+    for target in Dock::all() {
+        if ui.radio(*dock == target, translations.get(target.translation_key())).clicked() {
+            *dock = target;
+            changed = true;
+            ui.close_menu();
+        }
+    }
+    changed
+}
+
+/// Данные панелей, которые дорого пересчитывать каждый кадр
+pub struct PanelData {
+    top_files: Vec<(PathBuf, u64)>,
+    /// Папки, часть содержимого которых не удалось прочитать, и число таких элементов
+    pub errors: Vec<(PathBuf, u64)>,
+    pub files: u64,
+    /// Папки без корня
+    pub folders: u64,
+}
+
+impl PanelData {
+    pub fn collect(root: &DirNode) -> Self {
+        let mut top_files = BinaryHeap::new();
+        let mut errors = Vec::new();
+        let (mut files, mut folders) = (0u64, 0u64);
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if node.is_file {
+                files += 1;
+                top_files.push(Reverse((node.size, node.path.clone())));
+                if top_files.len() > TOP_FILES_LIMIT {
+                    top_files.pop();
+                }
+                continue;
+            }
+            // skipped включает ошибки поддерева; оставляем только те, что случились в самой папке
+            let own = node.skipped.saturating_sub(node.children.iter().map(|c| c.skipped).sum());
+            if own > 0 {
+                errors.push((node.path.clone(), own));
+            }
+            folders += 1;
+            stack.extend(&node.children);
+        }
+
+        let top_files = top_files
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((size, path))| (path, size))
+            .collect();
+        errors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Self {
+            top_files,
+            errors,
+            files,
+            folders: folders.saturating_sub(1),
+        }
+    }
+}
+
+struct LogEntry {
+    at: Duration,
+    text: String,
+    error: bool,
+}
+
+/// Журнал событий сеанса: сканирования, удаления, ошибки
+pub struct EventLog {
+    started: Instant,
+    entries: VecDeque<LogEntry>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+impl EventLog {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(text.into(), false);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(text.into(), true);
+    }
+
+    fn push(&mut self, text: String, error: bool) {
+        if self.entries.len() == LOG_LIMIT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            at: self.started.elapsed(),
+            text,
+            error,
+        });
+    }
+}
+
+impl CedarApp {
+    /// Доки с панелями; вызывается до центральной панели, чтобы та заняла оставшееся место
+    pub fn show_panels(&mut self, ctx: &egui::Context) {
+        let mut changed = false;
+
+        for dock in [Dock::Left, Dock::Right, Dock::Bottom] {
+            let panels: Vec<Panel> = Panel::all()
+                .into_iter()
+                .filter(|panel| self.config.panels.dock(*panel) == dock)
+                .collect();
+            if panels.is_empty() {
+                continue;
+            }
+
+            let size = *self.config.panels.size_mut(dock);
+            let rect = match dock {
+                Dock::Left | Dock::Right => {
+                    let panel = if dock == Dock::Left {
+                        egui::SidePanel::left("dock_left")
+                    } else {
+                        egui::SidePanel::right("dock_right")
+                    };
+                    panel
+                        .resizable(true)
+                        .default_width(size)
+                        .width_range(160.0..=900.0)
+                        .show(ctx, |ui| changed |= self.show_dock(ui, dock, &panels))
+                        .response
+                        .rect
+                }
+                _ => {
+                    egui::TopBottomPanel::bottom("dock_bottom")
+                        .resizable(true)
+                        .default_height(size)
+                        .height_range(80.0..=800.0)
+                        .show(ctx, |ui| changed |= self.show_dock(ui, dock, &panels))
+                        .response
+                        .rect
+                }
+            };
+
+            // Размер запоминаем, когда пользователь отпустил границу дока
+            let new_size = if dock == Dock::Bottom { rect.height() } else { rect.width() };
+            if !ctx.input(|i| i.pointer.any_down()) && (new_size - size).abs() >= 1.0 {
+                *self.config.panels.size_mut(dock) = new_size;
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.view_layout_changed();
+        }
+    }
+
+    /// Вкладки дока и содержимое открытой; возвращает true, если изменилось размещение
+    fn show_dock(&mut self, ui: &mut egui::Ui, dock: Dock, panels: &[Panel]) -> bool {
+        let mut changed = false;
+        let mut tab = *self.config.panels.tab_mut(dock);
+        if !panels.contains(&tab) {
+            tab = panels[0];
+        }
+
+        ui.horizontal(|ui| {
+            for &panel in panels {
+                let label = format!("{} {}", panel.icon(), self.translations.get(panel.translation_key()));
+                changed |= ui.selectable_value(&mut tab, panel, label).changed();
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let dock_label = self.translations.get("dock_panel");
+                let menu = ui.menu_button(regular::DOTS_THREE_VERTICAL, |ui| {
+                    changed |= dock_menu(ui, self.config.panels.dock_mut(tab), &self.translations);
+                });
+                let response = menu.response.on_hover_text(&dock_label);
+                a11y::set_name(&response, egui::WidgetType::Button, &dock_label);
+            });
+        });
+        *self.config.panels.tab_mut(dock) = tab;
+        ui.separator();
+
+        match tab {
+            Panel::Details => self.show_details_panel(ui),
+            Panel::TopFiles => self.show_top_files_panel(ui),
+            Panel::Errors => self.show_errors_panel(ui),
+            Panel::Log => self.show_log_panel(ui),
+            Panel::Recommendations => self.show_recommendations_panel(ui),
+        }
+        changed
+    }
+
+    fn show_details_panel(&mut self, ui: &mut egui::Ui) {
+        if let Some(job) = &mut self.reclaim {
+            job.poll();
+        }
+        let Some(node) = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| self.root_node.as_ref()?.find(path))
+        else {
+            ui.weak(self.translations.get("no_selection"));
+            return;
+        };
+
+        let icon = if node.is_file { regular::FILE } else { regular::FOLDER };
+        ui.heading(format!("{} {}", icon, node.display_name()));
+        ui.label(egui::RichText::new(node.path.display().to_string()).weak());
+        ui.add_space(4.0);
+
+        let now = SystemTime::now();
+        let age = |time: Option<SystemTime>| {
+            time.map(|time| {
+                let years = now.duration_since(time).unwrap_or_default().as_secs_f64() / SECONDS_PER_YEAR;
+                format!("{} ({})", self.translations.format_date(time), self.translations.format_years_ago(years))
+            })
+            .unwrap_or_else(|| "—".to_string())
+        };
+
+        let mut estimate_reclaim = false;
+        egui::Grid::new("details_grid").num_columns(2).striped(true).show(ui, |ui| {
+            ui.label(self.translations.get("type"));
+            ui.label(self.translations.get(if node.is_file { "file" } else { "folder" }));
+            ui.end_row();
+
+            ui.label(self.translations.get("size"));
+            ui.label(format_size(node.size));
+            ui.end_row();
+
+            ui.label(self.translations.get("size_on_disk"));
+            ui.label(format_size(node.allocated));
+            ui.end_row();
+
+            if !node.is_file {
+                ui.label(self.translations.get("files"));
+                ui.label(node.file_count().to_string());
+                ui.end_row();
+
+                ui.label(self.translations.get("reclaimable"));
+                match self.reclaim.as_ref().filter(|job| job.path == node.path) {
+                    Some(job) => match job.estimate {
+                        Some(estimate) => {
+                            let hint = self.translations.get_fmt("reclaim_hint", &[
+                                &format_size(estimate.linked_elsewhere),
+                                &format_size(estimate.copied_elsewhere),
+                            ]);
+                            ui.horizontal(|ui| {
+                                ui.label(format_size(estimate.frees));
+                                if estimate.copied_elsewhere > 0 {
+                                    ui.weak(self.translations.get_fmt("reclaim_copies", &[&format_size(estimate.copied_elsewhere)]));
+                                }
+                            })
+                            .response
+                            .on_hover_text(hint);
+                        }
+                        None => {
+                            let (done, total) = job.progress();
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.weak(self.translations.get_fmt("reclaim_progress", &[&done.to_string(), &total.to_string()]));
+                                ui.weak(throttle::rate_label(&throttle::HASHING));
+                            });
+                            ui.ctx().request_repaint();
+                        }
+                    },
+                    None => {
+                        estimate_reclaim = ui
+                            .small_button(format!("{} {}", regular::CALCULATOR, self.translations.get("reclaim_estimate")))
+                            .on_hover_text(self.translations.get("reclaim_estimate_hint"))
+                            .clicked();
+                    }
+                }
+                ui.end_row();
+            }
+
+            ui.label(self.translations.get("modified"));
+            ui.label(age(node.modified));
+            ui.end_row();
+
+            ui.label(self.translations.get("accessed"));
+            ui.label(age(node.accessed));
+            ui.end_row();
+
+            if node.referenced > 0 {
+                ui.label(regular::ARROW_BEND_UP_RIGHT);
+                ui.label(self.translations.get_fmt("referenced_badge", &[&format_size(node.referenced)]));
+                ui.end_row();
+            }
+
+            if let Some(target) = &node.link_target {
+                ui.label(self.translations.get("link_target"));
+                ui.label(target.display().to_string());
+                ui.end_row();
+            }
+
+            if node.streams > 0 {
+                ui.label(regular::STACK);
+                ui.label(self.translations.get_fmt("streams_hint", &[&format_size(node.streams)]));
+                ui.end_row();
+            }
+
+            if node.skipped > 0 {
+                ui.label(regular::WARNING);
+                ui.label(self.translations.get_fmt("items_skipped", &[&node.skipped.to_string()]));
+                ui.end_row();
+            }
+        });
+
+        if let Some(note) = self.config.notes.get(&node.path) {
+            ui.add_space(4.0);
+            ui.label(egui::RichText::new(format!("{} {}", regular::NOTE, note)).italics());
+        }
+
+        if estimate_reclaim {
+            let path = node.path.clone();
+            if let Some(root) = &self.root_node {
+                self.reclaim = Some(crate::reclaim::ReclaimJob::start(root, &path));
+            }
+        }
+    }
+
+    /// Список путей с числовым значением; щелчок выделяет элемент в дереве
+    fn show_path_list(&mut self, ui: &mut egui::Ui, id: &str, rows: &[(PathBuf, String)]) {
+        let mut clicked_path = None;
+        let row_height = ui.spacing().interact_size.y;
+        let copy_label = self.translations.get("copy_path");
+        egui::ScrollArea::vertical()
+            .id_salt(id)
+            .auto_shrink([false; 2])
+            .show_rows(ui, row_height, rows.len(), |ui, range| {
+                for (path, value) in &rows[range] {
+                    ui.horizontal(|ui| {
+                        let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+                        let selected = self.selected_path.as_ref() == Some(path);
+                        let response = ui.selectable_label(selected, name);
+                        if crate::clipboard::path_tooltip(response, path, &copy_label).clicked() {
+                            clicked_path = Some(path.clone());
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(value);
+                        });
+                    });
+                }
+            });
+
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+    }
+
+    pub fn panel_data(&mut self) -> Option<&PanelData> {
+        let root = self.root_node.as_ref()?;
+        Some(self.panel_data.get_or_insert_with(|| PanelData::collect(root)))
+    }
+
+    fn show_top_files_panel(&mut self, ui: &mut egui::Ui) {
+        let rows: Vec<(PathBuf, String)> = match self.panel_data() {
+            Some(data) => data.top_files.iter().map(|(path, size)| (path.clone(), format_size(*size))).collect(),
+            None => Vec::new(),
+        };
+        if rows.is_empty() {
+            ui.weak(self.translations.get("panel_empty"));
+            return;
+        }
+        self.show_path_list(ui, "top_files_panel", &rows);
+    }
+
+    fn show_errors_panel(&mut self, ui: &mut egui::Ui) {
+        let rows: Vec<(PathBuf, String)> = match self.panel_data() {
+            Some(data) => data.errors.iter().map(|(path, count)| (path.clone(), count.to_string())).collect(),
+            None => Vec::new(),
+        };
+        if rows.is_empty() {
+            ui.weak(self.translations.get("panel_no_errors"));
+            return;
+        }
+        ui.label(self.translations.get("items_skipped_hint"));
+        self.show_path_list(ui, "errors_panel", &rows);
+    }
+
+    fn show_log_panel(&mut self, ui: &mut egui::Ui) {
+        let error_color = self.config.palette.status_text(Status::Critical, self.config.dark_mode);
+
+        if ui.small_button(format!("{} {}", regular::BROOM, self.translations.get("clear"))).clicked() {
+            self.log.entries.clear();
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("log_panel")
+            .auto_shrink([false; 2])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for entry in &self.log.entries {
+                    let secs = entry.at.as_secs();
+                    let time = format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.monospace(time);
+                        if entry.error {
+                            ui.colored_label(error_color, &entry.text);
+                        } else {
+                            ui.label(&entry.text);
+                        }
+                    });
+                }
+            });
+    }
+}
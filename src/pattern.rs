@@ -0,0 +1,171 @@
+// Сопоставление имён с шаблонами вида `*.log`, `~$*`, `file?.tmp` и сравнение
+// путей. Одно и то же имя может быть записано составными символами (NFC, как
+// обычно в Windows и Linux) или разложенными (NFD, как в файлах с macOS) -
+// перед сравнением имена приводятся к NFC. Регистр различается только там,
+// где его различает файловая система
+
+use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+
+/// Имя в NFC и нижнем регистре - для поиска и шаблонов
+pub fn fold(name: &str) -> String {
+    name.nfc().collect::<String>().to_lowercase()
+}
+
+/// Ключ для сравнения имён файлов: в Windows регистр не различается
+pub fn name_key(name: &str) -> String {
+    if cfg!(windows) {
+        fold(name)
+    } else {
+        name.nfc().collect()
+    }
+}
+
+/// Одно ли это имя; для имён из ASCII обходится без выделения памяти
+fn same_name(a: &str, b: &str) -> bool {
+    if a.is_ascii() && b.is_ascii() {
+        return if cfg!(windows) { a.eq_ignore_ascii_case(b) } else { a == b };
+    }
+    name_key(a) == name_key(b)
+}
+
+/// Один ли это путь с учётом нормализации и регистра. Проверяется при
+/// сканировании для каждого элемента, поэтому сначала сравниваются последние имена
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    if a == b {
+        return true;
+    }
+    let (name_a, name_b) = (a.file_name().unwrap_or_default(), b.file_name().unwrap_or_default());
+    if !same_name(&name_a.to_string_lossy(), &name_b.to_string_lossy()) {
+        return false;
+    }
+    let (mut a, mut b) = (a.components(), b.components());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(x), Some(y)) if same_name(&x.as_os_str().to_string_lossy(), &y.as_os_str().to_string_lossy()) => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Лежит ли `path` внутри `prefix` или совпадает с ним, с учётом нормализации и регистра
+pub fn path_starts_with(path: &Path, prefix: &Path) -> bool {
+    if path.starts_with(prefix) {
+        return true;
+    }
+    let mut path = path.components();
+    prefix.components().all(|p| {
+        path.next()
+            .is_some_and(|c| same_name(&c.as_os_str().to_string_lossy(), &p.as_os_str().to_string_lossy()))
+    })
+}
+
+/// Шаблон с `*` (любая строка) и `?` (один символ), приведённый к NFC и нижнему
+/// регистру один раз - для проверки множества имён при сканировании
+pub struct Wildcard(Vec<char>);
+
+impl Wildcard {
+    pub fn new(pattern: &str) -> Self {
+        Self(fold(pattern).chars().collect())
+    }
+
+    /// Подходит ли имя; регистр не учитывается
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = fold(name).chars().collect();
+        match_folded(&self.0, &name)
+    }
+}
+
+/// Проверяет имя на соответствие шаблону с `*` (любая строка) и `?` (один символ).
+/// Регистр не учитывается.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    Wildcard::new(pattern).matches(name)
+}
+
+fn match_folded(pattern: &[char], name: &[char]) -> bool {
+    // Классический жадный алгоритм с откатом к последней звёздочке
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<usize> = None;
+    let mut star_match = 0;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            star_match = n;
+            p += 1;
+        } else if let Some(star_pos) = star {
+            p = star_pos + 1;
+            star_match += 1;
+            n = star_match;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Проверяет имя на соответствие хотя бы одному шаблону из списка через `;`.
+/// Пустой список совпадает с любым именем.
+pub fn matches_any(patterns: &str, name: &str) -> bool {
+    let mut has_patterns = false;
+    for pattern in patterns.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        has_patterns = true;
+        if wildcard_match(pattern, name) {
+            return true;
+        }
+    }
+    !has_patterns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "é" одним символом и буквой с комбинируемым ударением
+    const NFC: &str = "caf\u{e9}";
+    const NFD: &str = "cafe\u{301}";
+
+    #[test]
+    fn composed_and_decomposed_names_are_the_same() {
+        assert!(same_path(Path::new(&format!("/docs/{}", NFC)), Path::new(&format!("/docs/{}", NFD))));
+        assert!(path_starts_with(Path::new(&format!("/{}/menu.txt", NFD)), Path::new(&format!("/{}", NFC))));
+        assert!(wildcard_match(&format!("{}*", NFD), &format!("{}.txt", NFC)));
+        assert!(!same_path(Path::new("/docs/cafe"), Path::new(&format!("/docs/{}", NFC))));
+    }
+
+    #[test]
+    fn stars_and_question_marks_at_the_edges() {
+        assert!(wildcard_match("*.log", "server.log"));
+        assert!(wildcard_match("*.log", ".log"));
+        assert!(!wildcard_match("*.log", "server.log.1"));
+        assert!(wildcard_match("~$*", "~$report.docx"));
+        assert!(!wildcard_match("~$*", "report~$"));
+        assert!(wildcard_match("file?", "file1"));
+        assert!(!wildcard_match("file?", "file"));
+        assert!(wildcard_match("?ile", "file"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("**a**", "a"));
+    }
+
+    #[test]
+    fn patterns_ignore_case_and_paths_follow_the_file_system() {
+        assert!(wildcard_match("*.ISO", "Disk.iso"));
+        assert!(Wildcard::new("Backup?.*").matches("backup1.ZIP"));
+        let (lower, upper) = (Path::new("/data/Photos"), Path::new("/data/photos"));
+        assert_eq!(same_path(lower, upper), cfg!(windows));
+        assert_eq!(path_starts_with(Path::new("/DATA/photos/a.jpg"), upper), cfg!(windows));
+        assert_eq!(name_key("ÄB") == name_key("äb"), cfg!(windows));
+    }
+
+    #[test]
+    fn lists_of_patterns() {
+        assert!(matches_any("", "anything"));
+        assert!(matches_any("*.tmp; *.bak", "old.BAK"));
+        assert!(!matches_any("*.tmp;;", "old.bak"));
+    }
+}
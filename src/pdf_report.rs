@@ -0,0 +1,532 @@
+// Печатный отчёт в PDF: сводка со сведениями о диске и сканировании, карта,
+// таблицы крупнейших папок и файлов и разбивка по типам файлов.
+// Страницы собираются как SVG и растеризуются тем же resvg, что и экспорт изображений,
+// поэтому любые алфавиты в именах выводятся системными шрифтами. Отчёты пишутся
+// на языке интерфейса с его форматом чисел и дат или, по настройке, на английском
+
+use crate::chart::ChartStyle;
+use crate::i18n::Translations;
+use crate::palette::Palette;
+use crate::i18n::Language;
+use crate::{export, treemap, AppConfig, CedarApp, DirNode};
+use eframe::egui;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// A4 в пунктах PDF
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 40.0;
+/// Плотность растеризации относительно 72 точек на дюйм (2 - около 150 dpi)
+const RENDER_SCALE: f32 = 2.0;
+const ROW_HEIGHT: f32 = 15.0;
+const CHART_HEIGHT: f32 = 300.0;
+const TOP_FOLDERS: usize = 20;
+const TOP_FILES: usize = 20;
+const TOP_EXTENSIONS: usize = 15;
+
+/// Сведения о сканировании, по которому строится отчёт
+#[derive(Clone, Copy, Default)]
+pub struct ScanStats {
+    pub finished: Option<SystemTime>,
+    pub duration: Option<Duration>,
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Колонка таблицы: заголовок, левый край, ширина, выравнивание по правому краю
+struct Column<'a> {
+    title: &'a str,
+    x: f32,
+    width: f32,
+    right: bool,
+}
+
+/// Постраничная раскладка: когда место кончается, начинается новая страница
+struct Pages {
+    pages: Vec<String>,
+    body: String,
+    y: f32,
+}
+
+impl Pages {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            body: String::new(),
+            y: MARGIN,
+        }
+    }
+
+    fn bottom() -> f32 {
+        PAGE_HEIGHT - MARGIN - ROW_HEIGHT
+    }
+
+    fn break_page(&mut self) {
+        self.pages.push(std::mem::take(&mut self.body));
+        self.y = MARGIN;
+    }
+
+    /// Гарантирует `height` пунктов на текущей странице; возвращает true, если начата новая
+    fn reserve(&mut self, height: f32) -> bool {
+        if self.y + height > Self::bottom() && !self.body.is_empty() {
+            self.break_page();
+            return true;
+        }
+        false
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, bold: bool, anchor: &str, text: &str) {
+        let weight = if bold { "bold" } else { "normal" };
+        let _ = writeln!(
+            self.body,
+            r#"<text x="{:.1}" y="{:.1}" font-size="{}" font-weight="{}" text-anchor="{}">{}</text>"#,
+            x,
+            y,
+            size,
+            weight,
+            anchor,
+            escape(text),
+        );
+    }
+
+    fn heading(&mut self, text: &str, size: f32) {
+        self.reserve(size * 2.0 + ROW_HEIGHT * 3.0);
+        self.y += size;
+        self.text(MARGIN, self.y, size, true, "start", text);
+        self.y += size * 0.8;
+    }
+
+    fn rule(&mut self) {
+        let _ = writeln!(
+            self.body,
+            r##"<line x1="{}" y1="{:.1}" x2="{}" y2="{:.1}" stroke="#999999" stroke-width="0.5"/>"##,
+            MARGIN,
+            self.y,
+            PAGE_WIDTH - MARGIN,
+            self.y,
+        );
+    }
+
+    fn table_header(&mut self, columns: &[Column]) {
+        self.y += ROW_HEIGHT;
+        for column in columns {
+            let (x, anchor) = if column.right { (column.x + column.width, "end") } else { (column.x, "start") };
+            self.text(x, self.y - 4.0, 9.0, true, anchor, column.title);
+        }
+        self.rule();
+    }
+
+    /// Таблица; при переносе на новую страницу заголовок повторяется
+    fn table(&mut self, columns: &[Column], rows: &[Vec<String>]) {
+        self.reserve(ROW_HEIGHT * 3.0);
+        self.table_header(columns);
+        for (index, row) in rows.iter().enumerate() {
+            if self.reserve(ROW_HEIGHT) {
+                self.table_header(columns);
+            }
+            if index % 2 == 1 {
+                let _ = writeln!(
+                    self.body,
+                    r##"<rect x="{}" y="{:.1}" width="{}" height="{}" fill="#f2f2f2"/>"##,
+                    MARGIN,
+                    self.y,
+                    PAGE_WIDTH - MARGIN * 2.0,
+                    ROW_HEIGHT,
+                );
+            }
+            self.y += ROW_HEIGHT;
+            for (column, cell) in columns.iter().zip(row) {
+                let (x, anchor) = if column.right { (column.x + column.width, "end") } else { (column.x, "start") };
+                let _ = writeln!(
+                    self.body,
+                    r#"<text x="{:.1}" y="{:.1}" font-size="9" text-anchor="{}" clip-path="url(#col{:.0})">{}</text>"#,
+                    x,
+                    self.y - 4.0,
+                    anchor,
+                    column.x,
+                    escape(cell),
+                );
+            }
+        }
+        self.y += ROW_HEIGHT;
+    }
+
+    /// Готовые SVG-страницы с колонтитулом "страница N из M"
+    fn finish(mut self, columns: &[&[Column]], footer: &str) -> Vec<String> {
+        if !self.body.is_empty() {
+            self.pages.push(self.body);
+        }
+        // Области обрезки ячеек: id по левому краю колонки, зазор со стороны соседней колонки
+        let mut clips = String::new();
+        for column in columns.iter().flat_map(|c| c.iter()) {
+            let left = if column.right { column.x + 4.0 } else { column.x };
+            let _ = write!(
+                clips,
+                r#"<clipPath id="col{:.0}"><rect x="{:.1}" y="0" width="{:.1}" height="{}"/></clipPath>"#,
+                column.x,
+                left,
+                column.width - 4.0,
+                PAGE_HEIGHT,
+            );
+        }
+
+        let count = self.pages.len();
+        self.pages
+            .iter()
+            .enumerate()
+            .map(|(index, body)| {
+                format!(
+                    concat!(
+                        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {vw} {vh}" font-family="sans-serif" fill="#202020">"##,
+                        r##"<defs>{clips}</defs><rect width="100%" height="100%" fill="white"/>{body}"##,
+                        r##"<text x="{fx}" y="{fy}" font-size="8" text-anchor="end" fill="#808080">{footer}</text></svg>"##,
+                    ),
+                    w = PAGE_WIDTH * RENDER_SCALE,
+                    h = PAGE_HEIGHT * RENDER_SCALE,
+                    vw = PAGE_WIDTH,
+                    vh = PAGE_HEIGHT,
+                    clips = clips,
+                    body = body,
+                    fx = PAGE_WIDTH - MARGIN,
+                    fy = PAGE_HEIGHT - MARGIN / 2.0,
+                    footer = escape(&footer.replace("%1", &(index + 1).to_string()).replace("%2", &count.to_string())),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Строка PDF в UTF-16BE - для метаданных с любыми символами
+fn pdf_text(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        let _ = write!(hex, "{:04X}", unit);
+    }
+    hex.push('>');
+    hex
+}
+
+/// Минимальный PDF: каждая страница - одно сжатое RGB-изображение во весь лист
+fn write_pdf(pages: &[tiny_skia::Pixmap], title: &str) -> Vec<u8> {
+    let mut pdf: Vec<u8> = Vec::new();
+    let mut offsets = Vec::new();
+    pdf.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+
+    // Объекты: 1 - каталог, 2 - дерево страниц, 3 - сведения, далее по три на страницу
+    let page_id = |index: usize| 4 + index * 3;
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", page_id(i))).collect();
+
+    let mut object = |pdf: &mut Vec<u8>, body: &[u8]| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", offsets.len()).as_bytes());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendobj\n");
+    };
+
+    object(&mut pdf, b"<< /Type /Catalog /Pages 2 0 R >>");
+    object(
+        &mut pdf,
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).as_bytes(),
+    );
+    object(
+        &mut pdf,
+        format!("<< /Title {} /Producer {} >>", pdf_text(title), pdf_text("Cedar Folder Size")).as_bytes(),
+    );
+
+    for (index, pixmap) in pages.iter().enumerate() {
+        let id = page_id(index);
+        object(
+            &mut pdf,
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                id + 2,
+                id + 1,
+            )
+            .as_bytes(),
+        );
+
+        let content = format!("q {} 0 0 {} 0 0 cm /Im0 Do Q", PAGE_WIDTH, PAGE_HEIGHT);
+        object(
+            &mut pdf,
+            format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content).as_bytes(),
+        );
+
+        // Фон страницы непрозрачный, поэтому альфа-канал просто отбрасываем
+        let rgb: Vec<u8> = pixmap.data().chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect();
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::new(6));
+        // Запись в память не завершается ошибкой
+        let data = encoder.write_all(&rgb).and_then(|_| encoder.finish()).unwrap_or_default();
+        let mut image = format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>\nstream\n",
+            pixmap.width(),
+            pixmap.height(),
+            data.len(),
+        )
+        .into_bytes();
+        image.extend_from_slice(&data);
+        image.extend_from_slice(b"\nendstream");
+        object(&mut pdf, &image);
+    }
+
+    let xref = pdf.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+    for offset in &offsets {
+        let _ = writeln!(table, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        table,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n",
+        offsets.len() + 1,
+        xref
+    );
+    pdf.extend_from_slice(table.as_bytes());
+    pdf
+}
+
+/// Путь относительно корня сканирования - короче и понятнее в таблице
+fn relative(root: &DirNode, path: &Path) -> String {
+    match path.strip_prefix(&root.path) {
+        Ok(rest) if !rest.as_os_str().is_empty() => rest.display().to_string(),
+        _ => path.display().to_string(),
+    }
+}
+
+fn percent(t: &Translations, size: u64, total: u64) -> String {
+    t.format_percent(size as f64 / total.max(1) as f64)
+}
+
+/// Переводы для отчётов: язык интерфейса или английский, если отчёты передаются другим командам
+pub fn report_translations(config: &AppConfig) -> Translations {
+    Translations::load(if config.reports_in_english { Language::English } else { config.language })
+}
+
+/// Собирает отчёт по дереву `root` в PDF
+pub fn report_pdf(root: &DirNode, stats: ScanStats, t: &Translations, palette: Palette) -> Result<Vec<u8>, String> {
+    let now = SystemTime::now();
+
+    let mut folders = 0u64;
+    let mut files = Vec::new();
+    // Размер и число файлов по расширению в нижнем регистре; "" - без расширения
+    let mut extensions: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.is_file {
+            let extension = node.path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+            let entry = extensions.entry(extension).or_default();
+            entry.0 += node.size;
+            entry.1 += 1;
+            files.push(node);
+        } else {
+            folders += 1;
+            stack.extend(&node.children);
+        }
+    }
+    let file_count = files.len();
+    if files.len() > TOP_FILES {
+        files.select_nth_unstable_by(TOP_FILES, |a, b| b.size.cmp(&a.size));
+        files.truncate(TOP_FILES);
+    }
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+    let mut pages = Pages::new();
+    pages.heading(&t.get("pdf_report_title"), 18.0);
+    pages.y += 6.0;
+
+    // Сводка
+    let mut summary = vec![
+        (t.get("pdf_report_folder"), root.path.display().to_string()),
+        (t.get("pdf_report_date"), t.format_date(now)),
+        (t.get("total_size"), t.format_size(root.size)),
+        (t.get("size_on_disk"), t.format_size(root.allocated)),
+        (t.get("files"), t.format_count(file_count as u64)),
+        (t.get("directories"), t.format_count(folders.saturating_sub(1))),
+    ];
+    if let Some(finished) = stats.finished {
+        summary.push((t.get("pdf_report_scanned"), t.format_date(finished)));
+    }
+    if let Some(duration) = stats.duration.filter(|d| !d.is_zero()) {
+        let speed = root.size as f64 / (1024.0 * 1024.0) / duration.as_secs_f64();
+        summary.push((
+            t.get("scan_time"),
+            t.get_fmt("pdf_report_scan_speed", &[&t.format_decimal(duration.as_secs_f64(), 1), &t.format_decimal(speed, 1)]),
+        ));
+    }
+    if root.skipped > 0 {
+        summary.push((t.get("pdf_report_unreadable"), t.get_fmt("items_skipped", &[&t.format_count(root.skipped)])));
+    }
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    if let Some(disk) = crate::find_disk(&disks, &root.path) {
+        summary.push((
+            t.get("disk"),
+            t.get_fmt(
+                "pdf_report_disk",
+                &[&t.format_size(disk.total_space()), &t.format_size(disk.available_space())],
+            ),
+        ));
+    }
+    for (label, value) in &summary {
+        pages.y += ROW_HEIGHT;
+        pages.text(MARGIN, pages.y, 10.0, true, "start", label);
+        pages.text(MARGIN + 130.0, pages.y, 10.0, false, "start", value);
+    }
+    pages.y += ROW_HEIGHT;
+
+    // Карта корня в светлом оформлении, пригодном для печати
+    pages.heading(&t.get("view_treemap"), 13.0);
+    pages.y += 4.0;
+    let style = ChartStyle {
+        palette,
+        dark_mode: false,
+        age_now: None,
+    };
+    let chart_rect = egui::Rect::from_min_size(
+        egui::Pos2::ZERO,
+        egui::vec2(PAGE_WIDTH - MARGIN * 2.0, CHART_HEIGHT),
+    );
+    let blocks = treemap::layout(root, chart_rect, &style);
+    let _ = write!(pages.body, r#"<g transform="translate({}, {:.1})" font-size="8">"#, MARGIN, pages.y);
+    export::write_blocks(&mut pages.body, &blocks, &style);
+    pages.body.push_str("</g>\n");
+    pages.y += CHART_HEIGHT + ROW_HEIGHT;
+
+    let width = PAGE_WIDTH - MARGIN * 2.0;
+    let size_title = t.get("size");
+    let share_title = t.get("pdf_report_share");
+    let files_title = t.get("files");
+
+    // Крупнейшие папки верхнего уровня
+    let folder_title = t.get("folder");
+    let folder_columns = [
+        Column { title: &folder_title, x: MARGIN, width: width - 210.0, right: false },
+        Column { title: &size_title, x: MARGIN + width - 210.0, width: 80.0, right: true },
+        Column { title: &share_title, x: MARGIN + width - 130.0, width: 60.0, right: true },
+        Column { title: &files_title, x: MARGIN + width - 70.0, width: 70.0, right: true },
+    ];
+    let mut top_folders: Vec<&DirNode> = root.children.iter().filter(|c| !c.is_file).collect();
+    top_folders.sort_by_key(|c| std::cmp::Reverse(c.size));
+    let folder_rows: Vec<Vec<String>> = top_folders
+        .iter()
+        .take(TOP_FOLDERS)
+        .map(|node| {
+            vec![
+                relative(root, &node.path),
+                t.format_size(node.size),
+                percent(t, node.size, root.size),
+                t.format_count(node.file_count()),
+            ]
+        })
+        .collect();
+    if !folder_rows.is_empty() {
+        pages.heading(&t.get("pdf_report_top_folders"), 13.0);
+        pages.table(&folder_columns, &folder_rows);
+    }
+
+    // Крупнейшие файлы всего дерева
+    let file_title = t.get("file");
+    let age_title = t.get("modified");
+    let file_columns = [
+        Column { title: &file_title, x: MARGIN, width: width - 230.0, right: false },
+        Column { title: &size_title, x: MARGIN + width - 230.0, width: 80.0, right: true },
+        Column { title: &share_title, x: MARGIN + width - 150.0, width: 50.0, right: true },
+        Column { title: &age_title, x: MARGIN + width - 95.0, width: 95.0, right: true },
+    ];
+    let file_rows: Vec<Vec<String>> = files
+        .iter()
+        .map(|node| {
+            vec![
+                relative(root, &node.path),
+                t.format_size(node.size),
+                percent(t, node.size, root.size),
+                node.modified.map(|m| t.format_day(m)).unwrap_or_default(),
+            ]
+        })
+        .collect();
+    if !file_rows.is_empty() {
+        pages.heading(&t.get("pdf_report_top_files"), 13.0);
+        pages.table(&file_columns, &file_rows);
+    }
+
+    // Типы файлов по занятому месту; колонки те же, что у папок
+    let extension_title = t.get("pdf_report_extension");
+    let extension_columns = [
+        Column { title: &extension_title, ..folder_columns[0] },
+        Column { title: &size_title, ..folder_columns[1] },
+        Column { title: &share_title, ..folder_columns[2] },
+        Column { title: &files_title, ..folder_columns[3] },
+    ];
+    let mut extensions: Vec<(String, (u64, u64))> = extensions.into_iter().collect();
+    extensions.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then_with(|| a.0.cmp(&b.0)));
+    let no_extension = t.get("pdf_report_no_extension");
+    let extension_rows: Vec<Vec<String>> = extensions
+        .iter()
+        .take(TOP_EXTENSIONS)
+        .map(|(extension, (size, count))| {
+            vec![
+                if extension.is_empty() { no_extension.clone() } else { format!(".{}", extension) },
+                t.format_size(*size),
+                percent(t, *size, root.size),
+                t.format_count(*count),
+            ]
+        })
+        .collect();
+    if !extension_rows.is_empty() {
+        pages.heading(&t.get("pdf_report_extensions"), 13.0);
+        pages.table(&extension_columns, &extension_rows);
+    }
+
+    let svg_pages = pages.finish(&[&folder_columns, &file_columns, &extension_columns], &t.get("pdf_report_page"));
+
+    let mut options = usvg::Options::default();
+    export::load_fonts(&mut options);
+    let mut pixmaps = Vec::with_capacity(svg_pages.len());
+    for svg in &svg_pages {
+        let tree = usvg::Tree::from_str(svg, &options).map_err(|e| e.to_string())?;
+        let size = tree.size().to_int_size();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+            .ok_or_else(|| "page is too large".to_string())?;
+        resvg::render(&tree, tiny_skia::Transform::identity(), &mut pixmap.as_mut());
+        pixmaps.push(pixmap);
+    }
+
+    Ok(write_pdf(&pixmaps, &t.get("pdf_report_title")))
+}
+
+fn save_report(root: &DirNode, stats: ScanStats, t: &Translations, palette: Palette, path: &Path) -> Result<(), String> {
+    let pdf = report_pdf(root, stats, t, palette)?;
+    std::fs::File::create(path).and_then(|mut file| file.write_all(&pdf)).map_err(|e| e.to_string())
+}
+
+impl CedarApp {
+    /// Пункт меню "PDF-отчёт": выбор файла и сохранение
+    pub fn export_pdf_report(&mut self) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        let path = rfd::FileDialog::new()
+            .add_filter("PDF", &["pdf"])
+            .set_file_name(format!("{}.pdf", root.display_name()))
+            .save_file();
+        let Some(path) = path else {
+            return;
+        };
+        let stats = ScanStats {
+            finished: self.last_scan_finished,
+            duration: self.last_scan_duration,
+        };
+        match save_report(root, stats, &report_translations(&self.config), self.config.palette, &path) {
+            Ok(()) => self.set_status(self.translations.get_fmt("pdf_report_done", &[&path.display().to_string()])),
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("pdf_report_failed", &[&e]));
+            }
+        }
+    }
+}
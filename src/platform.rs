@@ -0,0 +1,754 @@
+// Платформенно-зависимые запросы к файловой системе
+
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+
+/// Размер кластера по умолчанию, если ОС не смогла его сообщить
+const DEFAULT_CLUSTER_SIZE: u64 = 4096;
+
+/// Размер кластера (единицы выделения) тома, на котором находится путь
+pub fn cluster_size(path: &Path) -> u64 {
+    #[cfg(windows)]
+    {
+        if let Some(size) = windows_cluster_size(path) {
+            return size;
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        if let Ok(metadata) = std::fs::metadata(path) {
+            if metadata.blksize() > 0 {
+                return metadata.blksize();
+            }
+        }
+    }
+
+    DEFAULT_CLUSTER_SIZE
+}
+
+/// Подключает стандартный вывод к консоли, из которой запущена программа. Сборка
+/// для Windows своей консоли не имеет, и без этого вывод запуска без окна пропал бы.
+/// Вывод, перенаправленный в файл, остаётся как есть
+pub fn attach_parent_console() {
+    #[cfg(windows)]
+    unsafe {
+        use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Серийный номер тома; вне Windows номера у тома нет
+pub fn volume_serial(path: &Path) -> Option<u32> {
+    #[cfg(windows)]
+    {
+        windows_volume_serial(path)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+#[cfg(windows)]
+fn windows_volume_serial(path: &Path) -> Option<u32> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetVolumeInformationW, GetVolumePathNameW};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut volume = [0u16; 261];
+    let mut serial = 0u32;
+
+    unsafe {
+        if GetVolumePathNameW(wide.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) == 0 {
+            return None;
+        }
+        if GetVolumeInformationW(
+            volume.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            &mut serial,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            0,
+        ) == 0
+        {
+            return None;
+        }
+    }
+
+    Some(serial)
+}
+
+#[cfg(windows)]
+fn windows_cluster_size(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetDiskFreeSpaceW, GetVolumePathNameW};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut volume = [0u16; 261];
+    let mut sectors_per_cluster = 0u32;
+    let mut bytes_per_sector = 0u32;
+    let mut free_clusters = 0u32;
+    let mut total_clusters = 0u32;
+
+    // Сначала находим корень тома (работает и для точек монтирования, и для UNC)
+    unsafe {
+        if GetVolumePathNameW(wide.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) == 0 {
+            return None;
+        }
+        if GetDiskFreeSpaceW(
+            volume.as_ptr(),
+            &mut sectors_per_cluster,
+            &mut bytes_per_sector,
+            &mut free_clusters,
+            &mut total_clusters,
+        ) == 0
+        {
+            return None;
+        }
+    }
+
+    let size = sectors_per_cluster as u64 * bytes_per_sector as u64;
+    (size > 0).then_some(size)
+}
+
+/// Корзина тома: сколько в неё помещается и сколько уже занято
+#[derive(Clone, Copy)]
+pub struct TrashCapacity {
+    pub max: u64,
+    pub used: u64,
+    /// Корзина отключена: удаление в неё сразу безвозвратное
+    pub disabled: bool,
+}
+
+/// Ёмкость корзины тома, на котором находится путь.
+/// `None` - ограничение неизвестно (вне Windows размер корзины не ограничивается)
+pub fn trash_capacity(path: &Path) -> Option<TrashCapacity> {
+    #[cfg(windows)]
+    {
+        windows_trash_capacity(path)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+#[cfg(windows)]
+fn windows_trash_capacity(path: &Path) -> Option<TrashCapacity> {
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetDiskFreeSpaceW, GetVolumeNameForVolumeMountPointW, GetVolumePathNameW,
+    };
+    use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+    use windows_sys::Win32::UI::Shell::{SHQueryRecycleBinW, SHQUERYRBINFO};
+    const MB: u64 = 1024 * 1024;
+    const GB: u64 = 1024 * MB;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut mount = [0u16; 261];
+    let mut volume = [0u16; 64];
+    let mut sectors_per_cluster = 0u32;
+    let mut bytes_per_sector = 0u32;
+    let mut free_clusters = 0u32;
+    let mut total_clusters = 0u32;
+    let mut recycled = SHQUERYRBINFO {
+        cbSize: std::mem::size_of::<SHQUERYRBINFO>() as u32,
+        i64Size: 0,
+        i64NumItems: 0,
+    };
+
+    unsafe {
+        if GetVolumePathNameW(wide.as_ptr(), mount.as_mut_ptr(), mount.len() as u32) == 0 {
+            return None;
+        }
+        if GetVolumeNameForVolumeMountPointW(mount.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) == 0 {
+            return None;
+        }
+        if GetDiskFreeSpaceW(
+            mount.as_ptr(),
+            &mut sectors_per_cluster,
+            &mut bytes_per_sector,
+            &mut free_clusters,
+            &mut total_clusters,
+        ) == 0
+        {
+            return None;
+        }
+        // Занятое корзиной тома; при ошибке считается пустой
+        if SHQueryRecycleBinW(mount.as_ptr(), &mut recycled) < 0 {
+            recycled.i64Size = 0;
+        }
+    }
+
+    // Имя тома вида \\?\Volume{GUID}\ - настройки корзины хранятся под {GUID}
+    let len = volume.iter().position(|&c| c == 0).unwrap_or(volume.len());
+    let volume = std::ffi::OsString::from_wide(&volume[..len]).to_string_lossy().to_string();
+    let guid = volume[volume.find('{')?..].trim_end_matches('\\').to_string();
+    let key: Vec<u16> = format!(r"Software\Microsoft\Windows\CurrentVersion\Explorer\BitBucket\Volume\{}", guid)
+        .encode_utf16()
+        .chain(Some(0))
+        .collect();
+    let value = |name: &str| {
+        let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+        let mut data = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ptr(),
+                RRF_RT_REG_DWORD,
+                std::ptr::null_mut(),
+                (&mut data as *mut u32).cast(),
+                &mut size,
+            )
+        };
+        (status == 0).then_some(u64::from(data))
+    };
+
+    // Без явной настройки Windows отводит под корзину 10% первых 40 ГБ тома и 5% остального
+    let total = total_clusters as u64 * sectors_per_cluster as u64 * bytes_per_sector as u64;
+    let default_max = total.min(40 * GB) / 10 + total.saturating_sub(40 * GB) / 20;
+    Some(TrashCapacity {
+        max: value("MaxCapacity").map_or(default_max, |mb| mb * MB),
+        used: u64::try_from(recycled.i64Size).unwrap_or(0),
+        disabled: value("NukeOnDelete") == Some(1),
+    })
+}
+
+/// Место, которое файл реально занимает на диске
+pub fn allocated_size(path: &Path, metadata: &Metadata, cluster_size: u64) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // blocks() всегда считается в 512-байтных секторах
+        let _ = (path, cluster_size);
+        metadata.blocks() * 512
+    }
+
+    #[cfg(not(unix))]
+    {
+        let cluster_size = cluster_size.max(1);
+
+        // У разреженных и сжатых файлов занятое место меньше логического размера -
+        // его сообщает только сама файловая система
+        #[cfg(windows)]
+        if is_sparse(metadata, 0) || is_compressed(metadata) {
+            if let Some(size) = windows_compressed_size(path) {
+                return size.div_ceil(cluster_size) * cluster_size;
+            }
+        }
+        #[cfg(not(windows))]
+        let _ = path;
+
+        // Округляем логический размер вверх до целого числа кластеров
+        metadata.len().div_ceil(cluster_size) * cluster_size
+    }
+}
+
+/// Число подпапок по счётчику жёстких ссылок папки; `None`, если ОС
+/// или файловая система его так не ведёт
+pub fn subdir_count(metadata: &Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // "." и ссылка из родителя плюс ".." каждой подпапки; btrfs и часть
+        // сетевых систем всегда сообщают 1
+        let links = metadata.nlink();
+        (links >= 2).then(|| links - 2)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// Разреженный файл: часть логического размера - "дыры", не занимающие места на диске
+pub fn is_sparse(metadata: &Metadata, allocated: u64) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_SPARSE_FILE;
+
+        let _ = allocated;
+        metadata.file_attributes() & FILE_ATTRIBUTE_SPARSE_FILE != 0
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // Отдельного признака нет: считаем разреженным файл, которому не хватает
+        // хотя бы одного блока до логического размера
+        allocated + metadata.blksize() <= metadata.len()
+    }
+
+    #[cfg(not(any(windows, unix)))]
+    {
+        let _ = (metadata, allocated);
+        false
+    }
+}
+
+#[cfg(windows)]
+fn is_compressed(metadata: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_COMPRESSED;
+
+    metadata.file_attributes() & FILE_ATTRIBUTE_COMPRESSED != 0
+}
+
+#[cfg(windows)]
+fn windows_compressed_size(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetCompressedFileSizeW, INVALID_FILE_SIZE};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high = 0u32;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    // INVALID_FILE_SIZE в младшей части без старшей - ошибка (файлы ровно 4 ГБ редки)
+    if low == INVALID_FILE_SIZE && high == 0 {
+        return None;
+    }
+    Some(((high as u64) << 32) | low as u64)
+}
+
+/// Альтернативный поток данных NTFS
+pub struct DataStream {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Альтернативные потоки данных файла (без основного); вне Windows их нет
+pub fn alternate_streams(path: &Path) -> Vec<DataStream> {
+    #[cfg(windows)]
+    {
+        windows_alternate_streams(path)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = path;
+        Vec::new()
+    }
+}
+
+#[cfg(windows)]
+fn windows_alternate_streams(path: &Path) -> Vec<DataStream> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard, WIN32_FIND_STREAM_DATA,
+    };
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut data: WIN32_FIND_STREAM_DATA = unsafe { std::mem::zeroed() };
+    let mut streams = Vec::new();
+
+    unsafe {
+        let handle = FindFirstStreamW(
+            wide.as_ptr(),
+            FindStreamInfoStandard,
+            &mut data as *mut _ as *mut std::ffi::c_void,
+            0,
+        );
+        if handle == INVALID_HANDLE_VALUE {
+            return streams;
+        }
+
+        loop {
+            let len = data.cStreamName.iter().position(|&c| c == 0).unwrap_or(data.cStreamName.len());
+            let full_name = String::from_utf16_lossy(&data.cStreamName[..len]);
+            // Имена вида ":имя:$DATA"; основной поток - "::$DATA"
+            let name = full_name
+                .trim_start_matches(':')
+                .trim_end_matches("$DATA")
+                .trim_end_matches(':');
+            if !name.is_empty() {
+                streams.push(DataStream {
+                    name: name.to_string(),
+                    size: data.StreamSize.max(0) as u64,
+                });
+            }
+
+            if FindNextStreamW(handle, &mut data as *mut _ as *mut std::ffi::c_void) == 0 {
+                break;
+            }
+        }
+        FindClose(handle);
+    }
+
+    streams
+}
+
+/// Идентификатор объекта файловой системы: (устройство, inode) или
+/// (серийный номер тома, индекс файла). Ссылки раскрываются.
+pub fn file_id(path: &Path) -> Option<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = std::fs::metadata(path).ok()?;
+        Some((metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(windows)]
+    {
+        windows_file_info(path).map(|info| windows_object_id(&info))
+    }
+
+    #[cfg(not(any(windows, unix)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Жёсткие ссылки файла: идентификатор объекта (как у `file_id`) и число его имён
+pub fn hard_links(path: &Path) -> Option<((u64, u64), u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = std::fs::symlink_metadata(path).ok()?;
+        Some(((metadata.dev(), metadata.ino()), metadata.nlink()))
+    }
+
+    #[cfg(windows)]
+    {
+        let info = windows_file_info(path)?;
+        Some((windows_object_id(&info), info.nNumberOfLinks as u64))
+    }
+
+    #[cfg(not(any(windows, unix)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+#[cfg(windows)]
+fn windows_object_id(info: &windows_sys::Win32::Storage::FileSystem::BY_HANDLE_FILE_INFORMATION) -> (u64, u64) {
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    (info.dwVolumeSerialNumber as u64, index)
+}
+
+#[cfg(windows)]
+fn windows_file_info(path: &Path) -> Option<windows_sys::Win32::Storage::FileSystem::BY_HANDLE_FILE_INFORMATION> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+    };
+
+    // Без прав на чтение содержимого; BACKUP_SEMANTICS нужен, чтобы открыть папку
+    let file = std::fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+        .ok()?;
+
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+        return None;
+    }
+    Some(info)
+}
+
+/// Открывает папку с файлом в файловом менеджере и выделяет в ней сам файл
+pub fn reveal_in_file_manager(path: &Path) -> std::io::Result<()> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // Проводник разбирает аргумент сам: путь в кавычках сразу после запятой.
+        // Код возврата у explorer бессмысленный, поэтому процесс только запускается
+        std::process::Command::new("explorer")
+            .raw_arg(format!("/select,\"{}\"", path.display()))
+            .spawn()
+            .map(|_| ())
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open").arg("-R").arg(path).spawn().map(|_| ())
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        // Nautilus, Dolphin, Nemo и другие поддерживают интерфейс FileManager1;
+        // без него открываем просто родительскую папку
+        let shown = std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", file_uri(path)))
+            .arg("string:")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if shown {
+            return Ok(());
+        }
+        open::that(path.parent().unwrap_or(path))
+    }
+}
+
+/// `file://` URI пути с экранированием всего, кроме безопасных символов
+#[cfg(not(any(windows, target_os = "macos")))]
+fn file_uri(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Папка текущего пользователя внутри общей папки `parent`, закрытая от остальных:
+/// называется по SID пользователя, доступ к ней только у него, системы и
+/// администраторов. Заранее созданная кем-то другим папка с тем же именем не берётся
+pub fn private_user_dir(parent: &Path) -> std::io::Result<PathBuf> {
+    #[cfg(windows)]
+    {
+        windows_private_user_dir(parent)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = parent;
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "private folders in shared data are Windows-only"))
+    }
+}
+
+#[cfg(windows)]
+fn windows_private_user_dir(parent: &Path) -> std::io::Result<PathBuf> {
+    use std::io::{Error, ErrorKind};
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::{null, null_mut};
+    use windows_sys::Win32::Foundation::{CloseHandle, LocalFree, ERROR_ALREADY_EXISTS, ERROR_SUCCESS};
+    use windows_sys::Win32::Security::Authorization::{
+        ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW, GetNamedSecurityInfoW,
+        SetNamedSecurityInfoW, SDDL_REVISION_1, SE_FILE_OBJECT,
+    };
+    use windows_sys::Win32::Security::{
+        EqualSid, GetSecurityDescriptorDacl, GetTokenInformation, TokenUser, DACL_SECURITY_INFORMATION,
+        OWNER_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION, SECURITY_ATTRIBUTES, TOKEN_QUERY, TOKEN_USER,
+    };
+    use windows_sys::Win32::Storage::FileSystem::CreateDirectoryW;
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    std::fs::create_dir_all(parent)?;
+
+    unsafe {
+        let mut token = null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return Err(Error::last_os_error());
+        }
+        // TOKEN_USER и следующий за ним SID; u64 - ради выравнивания структуры
+        let mut buffer = [0u64; 64];
+        let mut len = 0u32;
+        let ok = GetTokenInformation(
+            token,
+            TokenUser,
+            buffer.as_mut_ptr().cast(),
+            std::mem::size_of_val(&buffer) as u32,
+            &mut len,
+        );
+        CloseHandle(token);
+        if ok == 0 {
+            return Err(Error::last_os_error());
+        }
+        let user = (*buffer.as_ptr().cast::<TOKEN_USER>()).User.Sid;
+
+        let mut text = null_mut();
+        if ConvertSidToStringSidW(user, &mut text) == 0 {
+            return Err(Error::last_os_error());
+        }
+        let len = (0..).take_while(|&i| *text.add(i) != 0).count();
+        let sid = String::from_utf16_lossy(std::slice::from_raw_parts(text, len));
+        LocalFree(text.cast());
+
+        // Владелец - пользователь; наследование прав от ProgramData (чтение для всех) отключено
+        let sddl: Vec<u16> = format!("O:{0}D:P(A;OICI;FA;;;{0})(A;OICI;FA;;;SY)(A;OICI;FA;;;BA)", sid)
+            .encode_utf16()
+            .chain(Some(0))
+            .collect();
+        let mut descriptor = null_mut();
+        if ConvertStringSecurityDescriptorToSecurityDescriptorW(sddl.as_ptr(), SDDL_REVISION_1, &mut descriptor, null_mut()) == 0 {
+            return Err(Error::last_os_error());
+        }
+
+        let dir = parent.join(&sid);
+        let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(Some(0)).collect();
+        let result = (|| {
+            let attributes = SECURITY_ATTRIBUTES {
+                nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+                lpSecurityDescriptor: descriptor,
+                bInheritHandle: 0,
+            };
+            if CreateDirectoryW(wide.as_ptr(), &attributes) != 0 {
+                return Ok(());
+            }
+            let error = Error::last_os_error();
+            if error.raw_os_error() != Some(ERROR_ALREADY_EXISTS as i32) {
+                return Err(error);
+            }
+
+            let mut owner = null_mut();
+            let mut existing = null_mut();
+            let status = GetNamedSecurityInfoW(
+                wide.as_ptr(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION,
+                &mut owner,
+                null_mut(),
+                null_mut(),
+                null_mut(),
+                &mut existing,
+            );
+            if status != ERROR_SUCCESS {
+                return Err(Error::from_raw_os_error(status as i32));
+            }
+            let is_own = EqualSid(owner, user) != 0;
+            LocalFree(existing);
+            if !is_own {
+                return Err(Error::new(ErrorKind::PermissionDenied, "the folder belongs to another user"));
+            }
+
+            // Папка прежних версий наследовала права ProgramData - закрываем её вместе с содержимым
+            let (mut present, mut defaulted, mut dacl) = (0, 0, null_mut());
+            if GetSecurityDescriptorDacl(descriptor, &mut present, &mut dacl, &mut defaulted) == 0 {
+                return Err(Error::last_os_error());
+            }
+            let status = SetNamedSecurityInfoW(
+                wide.as_ptr(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                null_mut(),
+                null_mut(),
+                dacl,
+                null(),
+            );
+            if status != ERROR_SUCCESS {
+                return Err(Error::from_raw_os_error(status as i32));
+            }
+            Ok(())
+        })();
+        LocalFree(descriptor);
+        result.map(|()| dir)
+    }
+}
+
+/// Ошибка означает, что устройство с корнем сканирования `root` отключено
+/// (извлечённая флешка, пропавший сетевой диск)
+pub fn is_device_gone(error: &std::io::Error, root: &Path) -> bool {
+    let Some(code) = error.raw_os_error() else {
+        return false;
+    };
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::{
+            ERROR_BAD_NETPATH, ERROR_DEVICE_NOT_CONNECTED, ERROR_DEV_NOT_EXIST, ERROR_NETNAME_DELETED,
+            ERROR_NOT_READY,
+        };
+
+        let _ = root;
+        [ERROR_NOT_READY, ERROR_DEV_NOT_EXIST, ERROR_DEVICE_NOT_CONNECTED, ERROR_NETNAME_DELETED, ERROR_BAD_NETPATH]
+            .contains(&(code as u32))
+    }
+
+    #[cfg(unix)]
+    {
+        const EIO: i32 = 5;
+        const ENXIO: i32 = 6;
+        const ENODEV: i32 = 19;
+
+        // EIO даёт и выдернутое до размонтирования устройство, и просто сбойный сектор -
+        // отличаем по тому, читается ли ещё корень
+        match code {
+            ENXIO | ENODEV => true,
+            EIO => std::fs::read_dir(root).is_err(),
+            _ => false,
+        }
+    }
+
+    #[cfg(not(any(windows, unix)))]
+    {
+        let _ = (code, root);
+        false
+    }
+}
+
+/// Файлы данных Outlook (PST/OST), подключённые к почтовым профилям пользователя.
+/// `None` - профили прочитать не удалось или Outlook бывает только на Windows
+pub fn outlook_data_files() -> Option<Vec<PathBuf>> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+        // Пути к файлам данных хранятся в двоичных значениях профилей строками UTF-16
+        let keys = [
+            r"HKCU\Software\Microsoft\Office",
+            r"HKCU\Software\Microsoft\Windows NT\CurrentVersion\Windows Messaging Subsystem\Profiles",
+        ];
+        let mut files = Vec::new();
+        let mut any_read = false;
+        for key in keys {
+            let Ok(output) = std::process::Command::new("reg")
+                .args(["query", key, "/s"])
+                .creation_flags(CREATE_NO_WINDOW)
+                .output()
+            else {
+                continue;
+            };
+            any_read |= output.status.success();
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                let mut parts = line.split_whitespace();
+                let (Some(_), Some("REG_BINARY"), Some(hex)) = (parts.next(), parts.next(), parts.next()) else {
+                    continue;
+                };
+                let bytes: Vec<u8> = (0..hex.len() / 2)
+                    .filter_map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+                    .collect();
+                let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+                let text = String::from_utf16_lossy(&units);
+                let text = text.trim_end_matches('\0');
+                let lower = text.to_lowercase();
+                if lower.ends_with(".pst") || lower.ends_with(".ost") {
+                    files.push(PathBuf::from(text));
+                }
+            }
+        }
+        any_read.then_some(files)
+    }
+
+    #[cfg(not(windows))]
+    {
+        None
+    }
+}
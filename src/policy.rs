@@ -0,0 +1,169 @@
+// Политика организации: настройки для всего компьютера, которые пользователь не
+// может изменить. Файл policy.json лежит в общей папке (/etc/cedar-folder-size-analyzer,
+// %ProgramData%\cedar-folder-size-analyzer, /Library/Application Support/cedar-folder-size-analyzer),
+// а в Windows те же значения задаются групповой политикой в ключе
+// HKLM\SOFTWARE\Policies\Cedar Folder Size Analyzer; значения реестра важнее файла.
+// Политика запрещает удаление (закрепляет режим аудита), добавляет обязательные
+// исключения и задаёт язык
+
+use crate::exclusions::Exclusion;
+use crate::i18n::Language;
+use crate::AppConfig;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const POLICY_FILE: &str = "policy.json";
+
+#[cfg(windows)]
+const POLICY_KEY: &str = r"SOFTWARE\Policies\Cedar Folder Size Analyzer";
+
+/// Содержимое policy.json; те же имена у значений реестра
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    /// Удаление и другие изменения файлов запрещены
+    pub disable_delete: bool,
+    /// Пути и шаблоны имён (`*.iso`), которые исключаются всегда
+    pub exclusions: Vec<String>,
+    /// Код языка интерфейса и отчётов, например "de"
+    pub language: Option<String>,
+}
+
+impl Policy {
+    pub fn language(&self) -> Option<Language> {
+        self.language.as_deref().map(str::trim).filter(|code| !code.is_empty()).map(Language::from_code)
+    }
+
+    /// Обязательные исключения: строка с `*` или `?` и без разделителей - шаблон имени, иначе путь
+    pub fn exclusions(&self) -> Vec<Exclusion> {
+        self.exclusions
+            .iter()
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                if entry.contains(['*', '?']) && !entry.contains(['/', '\\']) {
+                    Exclusion::Pattern(entry.to_string())
+                } else {
+                    Exclusion::Path(PathBuf::from(entry))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Файл политики в общей папке программы
+pub fn policy_file() -> Option<PathBuf> {
+    crate::app_paths::machine_dir().map(|dir| dir.join(POLICY_FILE))
+}
+
+fn read_file() -> Policy {
+    let Some(path) = policy_file() else {
+        return Policy::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            Policy::default()
+        }),
+        Err(_) => Policy::default(),
+    }
+}
+
+#[cfg(windows)]
+fn read_registry(policy: &mut Policy) {
+    use windows_sys::Win32::System::Registry::{
+        RegGetValueW, HKEY_LOCAL_MACHINE, REG_ROUTINE_FLAGS, RRF_RT_REG_DWORD, RRF_RT_REG_MULTI_SZ, RRF_RT_REG_SZ,
+    };
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    let key = wide(POLICY_KEY);
+    let dword = |name: &str| -> Option<u32> {
+        let name = wide(name);
+        let mut value = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_LOCAL_MACHINE,
+                key.as_ptr(),
+                name.as_ptr(),
+                RRF_RT_REG_DWORD,
+                std::ptr::null_mut(),
+                (&mut value as *mut u32).cast(),
+                &mut size,
+            )
+        };
+        (status == 0).then_some(value)
+    };
+    // Строка или список строк; размер сначала запрашивается, затем читается значение
+    let strings = |name: &str, flags: REG_ROUTINE_FLAGS| -> Option<Vec<String>> {
+        let name = wide(name);
+        let mut size = 0u32;
+        let status = unsafe {
+            RegGetValueW(HKEY_LOCAL_MACHINE, key.as_ptr(), name.as_ptr(), flags, std::ptr::null_mut(), std::ptr::null_mut(), &mut size)
+        };
+        if status != 0 {
+            return None;
+        }
+        let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_LOCAL_MACHINE,
+                key.as_ptr(),
+                name.as_ptr(),
+                flags,
+                std::ptr::null_mut(),
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        if status != 0 {
+            return None;
+        }
+        buffer.truncate(size as usize / 2);
+        Some(buffer.split(|&c| c == 0).filter(|s| !s.is_empty()).map(String::from_utf16_lossy).collect())
+    };
+
+    if let Some(value) = dword("disable_delete") {
+        policy.disable_delete = value != 0;
+    }
+    if let Some(exclusions) = strings("exclusions", RRF_RT_REG_MULTI_SZ) {
+        policy.exclusions = exclusions;
+    }
+    if let Some(language) = strings("language", RRF_RT_REG_SZ).and_then(|values| values.into_iter().next()) {
+        policy.language = Some(language);
+    }
+}
+
+/// Политика компьютера; читается один раз за запуск
+pub fn get() -> &'static Policy {
+    static POLICY: OnceLock<Policy> = OnceLock::new();
+    POLICY.get_or_init(|| {
+        #[allow(unused_mut)]
+        let mut policy = read_file();
+        #[cfg(windows)]
+        read_registry(&mut policy);
+        policy
+    })
+}
+
+/// Переносит в настройки то, что политика задаёт; вызывается после каждой загрузки настроек
+pub fn apply(config: &mut AppConfig) {
+    if let Some(language) = get().language() {
+        config.language = language;
+    }
+}
+
+/// Исключения для сканирования: пользовательские и обязательные
+pub fn exclusions(config: &AppConfig) -> Vec<Exclusion> {
+    let mut exclusions = config.exclusions.clone();
+    for exclusion in get().exclusions() {
+        if !exclusions.contains(&exclusion) {
+            exclusions.push(exclusion);
+        }
+    }
+    exclusions
+}
@@ -0,0 +1,194 @@
+// Сколько на самом деле освободит удаление папки. Файл с жёсткими ссылками
+// за пределами папки места не освободит, а данные, побайтные копии которых
+// есть в другом месте дерева, освободят место, но ничего не потеряют
+
+use crate::duplicates::{hash_file, Progress, PARTIAL_HASH_SIZE};
+use crate::hash_cache::HashCache;
+use crate::{platform, DirNode};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Оценка для папки; все размеры - место на диске
+#[derive(Clone, Copy, Default)]
+pub struct Estimate {
+    /// Освободится при удалении
+    pub frees: u64,
+    /// Не освободится: у этих файлов есть жёсткие ссылки вне папки
+    pub linked_elsewhere: u64,
+    /// Часть освобождаемого, у которой есть побайтные копии вне папки
+    pub copied_elsewhere: u64,
+}
+
+struct File {
+    path: PathBuf,
+    size: u64,
+    allocated: u64,
+}
+
+/// Оценка, считающаяся в фоновом потоке
+pub struct ReclaimJob {
+    pub path: PathBuf,
+    progress: Progress,
+    result: Arc<Mutex<Option<Estimate>>>,
+    pub estimate: Option<Estimate>,
+}
+
+impl Drop for ReclaimJob {
+    // Оценка другой папки или новое сканирование останавливают старую
+    fn drop(&mut self) {
+        self.progress.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl ReclaimJob {
+    pub fn start(root: &DirNode, path: &Path) -> Self {
+        let mut inside = Vec::new();
+        let mut outside = Vec::new();
+        collect(root, path, &mut inside, &mut outside);
+
+        let progress = Progress::new();
+        let result = Arc::new(Mutex::new(None));
+        let thread_progress = progress.clone();
+        let thread_result = result.clone();
+        thread::spawn(move || {
+            // Кэш общий с поиском дубликатов: хеши, посчитанные там, не считаются заново
+            let cache = HashCache::load();
+            let estimate = estimate(inside, outside, &cache, &thread_progress);
+            cache.save();
+            if let Some(estimate) = estimate {
+                *thread_result.lock().unwrap() = Some(estimate);
+            }
+        });
+
+        Self {
+            path: path.to_path_buf(),
+            progress,
+            result,
+            estimate: None,
+        }
+    }
+
+    /// Забирает результат фонового потока
+    pub fn poll(&mut self) {
+        if self.estimate.is_none() {
+            self.estimate = self.result.try_lock().ok().and_then(|mut r| r.take());
+        }
+    }
+
+    /// (обработано, всего) в текущей фазе
+    pub fn progress(&self) -> (usize, usize) {
+        (self.progress.done.load(Ordering::Relaxed), self.progress.total.load(Ordering::Relaxed))
+    }
+}
+
+/// Делит файлы дерева на лежащие в папке и все остальные; ссылки не в счёт
+fn collect(node: &DirNode, folder: &Path, inside: &mut Vec<File>, outside: &mut Vec<File>) {
+    for child in &node.children {
+        if !child.is_file {
+            collect(child, folder, inside, outside);
+        } else if child.link_target.is_none() {
+            let list = if child.path.starts_with(folder) { &mut *inside } else { &mut *outside };
+            list.push(File {
+                path: child.path.clone(),
+                size: child.size,
+                allocated: child.allocated,
+            });
+        }
+    }
+}
+
+fn estimate(inside: Vec<File>, outside: Vec<File>, cache: &HashCache, progress: &Progress) -> Option<Estimate> {
+    // Объект с несколькими именами освобождается, только если все они в папке,
+    // и учитывается один раз
+    progress.start_phase(inside.len());
+    let links: Vec<Option<((u64, u64), u64)>> = inside
+        .par_iter()
+        .map(|file| {
+            if progress.is_cancelled() {
+                return None;
+            }
+            let links = platform::hard_links(&file.path);
+            progress.step();
+            links
+        })
+        .collect();
+    if progress.is_cancelled() {
+        return None;
+    }
+
+    let mut names: HashMap<(u64, u64), u64> = HashMap::new();
+    for (id, count) in links.iter().flatten() {
+        if *count > 1 {
+            *names.entry(*id).or_default() += 1;
+        }
+    }
+
+    let mut estimate = Estimate::default();
+    let mut counted = HashSet::new();
+    let mut freed = Vec::new();
+    for (file, link) in inside.into_iter().zip(links) {
+        if let Some((id, count)) = link.filter(|(_, count)| *count > 1) {
+            if !counted.insert(id) {
+                continue;
+            }
+            if names[&id] < count {
+                estimate.linked_elsewhere += file.allocated;
+                continue;
+            }
+        }
+        estimate.frees += file.allocated;
+        freed.push(file);
+    }
+
+    // Копии вне папки ищутся как в поиске дубликатов: размер, хеш начала, полный хеш
+    let sizes: HashSet<u64> = freed.iter().map(|f| f.size).filter(|size| *size > 0).collect();
+    let outside: Vec<&File> = outside.iter().filter(|f| sizes.contains(&f.size)).collect();
+    let sizes: HashSet<u64> = outside.iter().map(|f| f.size).collect();
+    let inside: Vec<&File> = freed.iter().filter(|f| sizes.contains(&f.size)).collect();
+
+    let (inside, outside) = matching(inside, outside, Some(PARTIAL_HASH_SIZE), cache, progress)?;
+    let (inside, _) = matching(inside, outside, None, cache, progress)?;
+    estimate.copied_elsewhere = inside.iter().map(|f| f.allocated).sum();
+    Some(estimate)
+}
+
+/// Оставляет с каждой стороны файлы, у которых на другой стороне есть файл
+/// того же размера и хеша
+fn matching<'a>(
+    inside: Vec<&'a File>,
+    outside: Vec<&'a File>,
+    limit: Option<u64>,
+    cache: &HashCache,
+    progress: &Progress,
+) -> Option<(Vec<&'a File>, Vec<&'a File>)> {
+    progress.start_phase(inside.len() + outside.len());
+    let hash = |files: Vec<&'a File>| -> Vec<(&'a File, (u64, [u8; 20]))> {
+        files
+            .into_par_iter()
+            .filter_map(|file| {
+                if progress.is_cancelled() {
+                    return None;
+                }
+                let hash = cache.hash(&file.path, limit, || hash_file(&file.path, limit));
+                progress.step();
+                Some((file, (file.size, hash?)))
+            })
+            .collect()
+    };
+    let inside = hash(inside);
+    let outside = hash(outside);
+    if progress.is_cancelled() {
+        return None;
+    }
+
+    let inside_keys: HashSet<_> = inside.iter().map(|(_, key)| *key).collect();
+    let outside_keys: HashSet<_> = outside.iter().map(|(_, key)| *key).collect();
+    Some((
+        inside.into_iter().filter(|(_, key)| outside_keys.contains(key)).map(|(f, _)| f).collect(),
+        outside.into_iter().filter(|(_, key)| inside_keys.contains(key)).map(|(f, _)| f).collect(),
+    ))
+}
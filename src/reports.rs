@@ -0,0 +1,446 @@
+// Отчёты по результатам сканирования
+
+use crate::platform::{self, DataStream};
+use crate::{format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Сколько папок показывать в отчёте о потерях на кластерах
+const SLACK_REPORT_LIMIT: usize = 200;
+
+/// Сколько папок показывать в отчёте о нетронутых данных
+const UNTOUCHED_REPORT_LIMIT: usize = 200;
+
+/// Сколько файлов показывать в отчёте об альтернативных потоках
+const STREAMS_REPORT_LIMIT: usize = 200;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// Строка отчёта о потерях на кластерах
+#[derive(Clone)]
+pub struct SlackEntry {
+    pub path: PathBuf,
+    pub files: u64,
+    pub size: u64,
+    pub allocated: u64,
+}
+
+impl SlackEntry {
+    fn slack(&self) -> u64 {
+        self.allocated.saturating_sub(self.size)
+    }
+}
+
+/// Собирает папки, теряющие больше всего места на хвостах кластеров.
+///
+/// Если почти весь slack папки приходится на одну подпапку, в отчёт попадает
+/// только подпапка — иначе список забился бы цепочкой родителей до корня.
+pub fn collect_slack(root: &DirNode) -> Vec<SlackEntry> {
+    fn walk(node: &DirNode, entries: &mut Vec<SlackEntry>) -> u64 {
+        let mut files = 0u64;
+        let mut largest_child_slack = 0u64;
+
+        for child in &node.children {
+            if child.is_file {
+                files += 1;
+            } else {
+                files += walk(child, entries);
+                largest_child_slack = largest_child_slack.max(child.slack());
+            }
+        }
+
+        let slack = node.slack();
+        if slack > 0 && (largest_child_slack as f64) < slack as f64 * 0.9 {
+            entries.push(SlackEntry {
+                path: node.path.clone(),
+                files,
+                size: node.size,
+                allocated: node.allocated,
+            });
+        }
+
+        files
+    }
+
+    let mut entries = Vec::new();
+    walk(root, &mut entries);
+    entries.sort_unstable_by_key(|e| std::cmp::Reverse(e.slack()));
+    entries.truncate(SLACK_REPORT_LIMIT);
+    entries
+}
+
+/// Строка отчёта о нетронутых данных
+#[derive(Clone)]
+pub struct UntouchedEntry {
+    pub path: PathBuf,
+    pub files: u64,
+    pub size: u64,
+    /// Сколько лет назад читался самый свежий файл папки
+    pub years: f64,
+}
+
+/// Собирает папки, ни один файл которых не читался дольше `years` лет.
+///
+/// В отчёт попадают только верхние такие папки: их подпапки нетронуты тем более.
+/// Папки без времени доступа (файловая система его не хранит) пропускаются.
+pub fn collect_untouched(root: &DirNode, years: u32, now: SystemTime) -> Vec<UntouchedEntry> {
+    fn walk(node: &DirNode, years: f64, now: SystemTime, entries: &mut Vec<UntouchedEntry>) {
+        for child in node.children.iter().filter(|c| !c.is_file) {
+            let age = child
+                .accessed
+                .map(|a| now.duration_since(a).unwrap_or_default().as_secs_f64() / SECONDS_PER_YEAR);
+            match age {
+                Some(age) if age >= years => entries.push(UntouchedEntry {
+                    path: child.path.clone(),
+                    files: child.file_count(),
+                    size: child.size,
+                    years: age,
+                }),
+                _ => walk(child, years, now, entries),
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    walk(root, years as f64, now, &mut entries);
+    entries.sort_unstable_by_key(|e| std::cmp::Reverse(e.size));
+    entries.truncate(UNTOUCHED_REPORT_LIMIT);
+    entries
+}
+
+/// Строка отчёта об альтернативных потоках NTFS
+pub struct StreamEntry {
+    pub path: PathBuf,
+    pub streams: Vec<DataStream>,
+}
+
+/// Собирает файлы с альтернативными потоками, крупнейшие первыми.
+/// Имена потоков в дереве не хранятся, поэтому перечисляются заново.
+pub fn collect_streams(root: &DirNode) -> Vec<StreamEntry> {
+    fn walk<'a>(node: &'a DirNode, files: &mut Vec<&'a DirNode>) {
+        for child in node.children.iter().filter(|c| c.streams > 0) {
+            if child.is_file {
+                files.push(child);
+            } else {
+                walk(child, files);
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(root, &mut files);
+    files.sort_unstable_by_key(|f| std::cmp::Reverse(f.streams));
+    files.truncate(STREAMS_REPORT_LIMIT);
+
+    files
+        .into_iter()
+        .map(|file| StreamEntry {
+            path: file.path.clone(),
+            streams: platform::alternate_streams(&file.path),
+        })
+        .collect()
+}
+
+impl CedarApp {
+    /// Окно отчёта о папках, которые давно никто не читал
+    pub fn show_untouched_report_window(&mut self, ctx: &egui::Context) {
+        if !self.show_untouched_report {
+            return;
+        }
+
+        if self.untouched_report.is_none() {
+            self.untouched_report = self
+                .root_node
+                .as_ref()
+                .map(|root| collect_untouched(root, self.untouched_years, SystemTime::now()));
+        }
+
+        let mut open = self.show_untouched_report;
+        let mut refresh = false;
+        let mut clicked_path = None;
+
+        egui::Window::new(format!("{} {}", regular::SNOWFLAKE, self.translations.get("untouched_report")))
+            .open(&mut open)
+            .default_size([760.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.translations.get("untouched_report_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(self.translations.get("untouched_for"));
+                    refresh |= ui.add(
+                        egui::DragValue::new(&mut self.untouched_years)
+                            .range(1..=50)
+                            .suffix(format!(" {}", self.translations.get("years")))
+                    ).changed();
+
+                    if let Some(entries) = &self.untouched_report {
+                        ui.separator();
+                        let total: u64 = entries.iter().map(|e| e.size).sum();
+                        ui.strong(self.translations.get_fmt(
+                            "untouched_summary",
+                            &[&entries.len().to_string(), &format_size(total)],
+                        ));
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, self.translations.get("refresh"))).clicked() {
+                            refresh = true;
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                let Some(entries) = &self.untouched_report else {
+                    return;
+                };
+
+                if entries.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("no_matches"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("untouched_report_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                ui.strong(self.translations.get("folder"));
+                                ui.strong(self.translations.get("files"));
+                                ui.strong(self.translations.get("size"));
+                                ui.strong(self.translations.get("last_read"));
+                                ui.end_row();
+
+                                for entry in entries {
+                                    let response = ui.selectable_label(
+                                        self.selected_path.as_ref() == Some(&entry.path),
+                                        entry.path.display().to_string(),
+                                    );
+                                    if response.clicked() {
+                                        clicked_path = Some(entry.path.clone());
+                                    }
+                                    ui.label(entry.files.to_string());
+                                    ui.strong(format_size(entry.size));
+                                    ui.label(self.translations.format_years_ago(entry.years));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = clicked_path {
+            self.selected_path = Some(path);
+        }
+        if refresh {
+            self.untouched_report = None;
+        }
+        self.show_untouched_report = open;
+    }
+
+    /// Окно отчёта о потерях на кластерах
+    pub fn show_slack_report_window(&mut self, ctx: &egui::Context) {
+        if !self.show_slack_report {
+            return;
+        }
+
+        if self.slack_report.is_none() {
+            self.slack_report = self.root_node.as_ref().map(collect_slack);
+        }
+
+        let cluster_size = self.scan_progress.lock().map(|p| p.cluster_size).unwrap_or(0);
+        let (total_size, total_allocated) = self
+            .root_node
+            .as_ref()
+            .map(|r| (r.size, r.allocated))
+            .unwrap_or((0, 0));
+
+        let mut open = self.show_slack_report;
+        let mut refresh = false;
+        let mut clicked_path = None;
+
+        egui::Window::new(format!("{} {}", regular::PUZZLE_PIECE, self.translations.get("slack_report")))
+            .open(&mut open)
+            .default_size([760.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.translations.get("slack_report_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}: {}", self.translations.get("cluster_size"), format_size(cluster_size)));
+                    ui.separator();
+                    ui.label(format!(
+                        "{}: {}",
+                        self.translations.get("total_slack"),
+                        self.translations.get_fmt(
+                            "slack_summary",
+                            &[
+                                &format_size(total_allocated.saturating_sub(total_size)),
+                                &format_size(total_allocated),
+                            ],
+                        )
+                    ));
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, self.translations.get("refresh"))).clicked() {
+                            refresh = true;
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                let Some(entries) = &self.slack_report else {
+                    return;
+                };
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("slack_report_grid")
+                            .striped(true)
+                            .num_columns(5)
+                            .show(ui, |ui| {
+                                ui.strong(self.translations.get("folder"));
+                                ui.strong(self.translations.get("files"));
+                                ui.strong(self.translations.get("size"));
+                                ui.strong(self.translations.get("on_disk"));
+                                ui.strong(self.translations.get("slack"));
+                                ui.end_row();
+
+                                for entry in entries {
+                                    let response = ui.selectable_label(
+                                        self.selected_path.as_ref() == Some(&entry.path),
+                                        entry.path.display().to_string(),
+                                    );
+                                    if response.clicked() {
+                                        clicked_path = Some(entry.path.clone());
+                                    }
+                                    ui.label(entry.files.to_string());
+                                    ui.label(format_size(entry.size));
+                                    ui.label(format_size(entry.allocated));
+                                    ui.strong(format_size(entry.slack()));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = clicked_path {
+            self.selected_path = Some(path);
+        }
+        if refresh {
+            self.slack_report = None;
+        }
+        self.show_slack_report = open;
+    }
+
+    /// Окно отчёта об альтернативных потоках NTFS
+    pub fn show_streams_report_window(&mut self, ctx: &egui::Context) {
+        if !self.show_streams_report {
+            return;
+        }
+
+        if self.streams_report.is_none() {
+            self.streams_report = self.root_node.as_ref().map(collect_streams);
+        }
+
+        let total_streams = self.root_node.as_ref().map_or(0, |r| r.streams);
+        let mut open = self.show_streams_report;
+        let mut refresh = false;
+        let mut clicked_path = None;
+
+        egui::Window::new(format!("{} {}", regular::STACK, self.translations.get("streams_report")))
+            .open(&mut open)
+            .default_size([760.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.translations.get("streams_report_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.strong(self.translations.get_fmt("streams_summary", &[&format_size(total_streams)]));
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, self.translations.get("refresh"))).clicked() {
+                            refresh = true;
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                let Some(entries) = &self.streams_report else {
+                    return;
+                };
+
+                if entries.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("no_matches"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("streams_report_grid")
+                            .striped(true)
+                            .num_columns(3)
+                            .show(ui, |ui| {
+                                ui.strong(self.translations.get("file"));
+                                ui.strong(self.translations.get("stream"));
+                                ui.strong(self.translations.get("size"));
+                                ui.end_row();
+
+                                for entry in entries {
+                                    for (index, stream) in entry.streams.iter().enumerate() {
+                                        // Путь файла - только в первой строке его потоков
+                                        if index == 0 {
+                                            let response = ui.selectable_label(
+                                                self.selected_path.as_ref() == Some(&entry.path),
+                                                entry.path.display().to_string(),
+                                            );
+                                            if response.clicked() {
+                                                clicked_path = Some(entry.path.clone());
+                                            }
+                                        } else {
+                                            ui.label("");
+                                        }
+                                        ui.label(&stream.name);
+                                        ui.strong(format_size(stream.size));
+                                        ui.end_row();
+                                    }
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if refresh {
+            self.streams_report = None;
+        }
+        self.show_streams_report = open;
+    }
+}
@@ -0,0 +1,276 @@
+// Правила автоматической очистки: оцениваются по результатам сканирования
+// и превращаются в план, который пользователь просматривает перед удалением
+
+use crate::{a11y, format_size, pattern, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// К чему применяется правило
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RuleTarget {
+    Files,
+    Folders,
+}
+
+/// Правило вида "*.log старше 90 дней в D:\Logs"
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CleanupRule {
+    pub enabled: bool,
+    pub target: RuleTarget,
+    /// Шаблоны имён через `;`
+    pub pattern: String,
+    /// Ограничение по расположению; пустая строка - где угодно
+    pub under: String,
+    /// Минимальный возраст последнего изменения в днях
+    pub min_age_days: u32,
+}
+
+impl Default for CleanupRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target: RuleTarget::Files,
+            pattern: "*.log".to_string(),
+            under: String::new(),
+            min_age_days: 90,
+        }
+    }
+}
+
+impl CleanupRule {
+    fn matches(&self, node: &DirNode, now: SystemTime) -> bool {
+        if !self.enabled || self.pattern.trim().is_empty() {
+            return false;
+        }
+        if node.is_file != (self.target == RuleTarget::Files) {
+            return false;
+        }
+        if !self.under.trim().is_empty() && !pattern::path_starts_with(&node.path, Path::new(self.under.trim())) {
+            return false;
+        }
+        if !pattern::matches_any(&self.pattern, &node.display_name()) {
+            return false;
+        }
+        // Пустые папки без времени изменения считаем давно нетронутыми
+        age_days(node, now).is_none_or(|days| days >= self.min_age_days as u64)
+    }
+}
+
+/// Возраст последнего изменения узла в днях
+fn age_days(node: &DirNode, now: SystemTime) -> Option<u64> {
+    let modified = node.modified?;
+    let age = now.duration_since(modified).unwrap_or_default();
+    Some(age.as_secs() / SECONDS_PER_DAY)
+}
+
+/// Элемент плана очистки
+pub struct PlanItem {
+    pub path: PathBuf,
+    pub size: u64,
+    pub age_days: Option<u64>,
+    pub rule: usize,
+    pub selected: bool,
+}
+
+/// Применяет правила к дереву. Совпавшая папка попадает в план целиком,
+/// внутрь неё правила уже не заглядывают.
+pub fn evaluate(root: &DirNode, rules: &[CleanupRule], now: SystemTime) -> Vec<PlanItem> {
+    fn walk(node: &DirNode, rules: &[CleanupRule], now: SystemTime, plan: &mut Vec<PlanItem>) {
+        for child in &node.children {
+            if let Some(rule) = rules.iter().position(|r| r.matches(child, now)) {
+                plan.push(PlanItem {
+                    path: child.path.clone(),
+                    size: child.size,
+                    age_days: age_days(child, now),
+                    rule,
+                    selected: true,
+                });
+            } else if !child.is_file {
+                walk(child, rules, now, plan);
+            }
+        }
+    }
+
+    let mut plan = Vec::new();
+    walk(root, rules, now, &mut plan);
+    plan.sort_unstable_by_key(|item| std::cmp::Reverse(item.size));
+    plan
+}
+
+impl CedarApp {
+    /// Окно правил очистки и плана по ним
+    pub fn show_rules_window(&mut self, ctx: &egui::Context) {
+        if !self.show_rules {
+            return;
+        }
+
+        let mut open = self.show_rules;
+        let mut changed = false;
+        let mut remove_rule = None;
+        let mut evaluate_now = false;
+        let mut add_to_basket = false;
+        let read_only = self.read_only();
+        let read_only_hint = self.read_only_hint();
+
+        let files_text = self.translations.get("rule_files");
+        let folders_text = self.translations.get("rule_folders");
+
+        egui::Window::new(format!("{} {}", regular::LIST_CHECKS, self.translations.get("cleanup_rules")))
+            .open(&mut open)
+            .default_size([820.0, 520.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.translations.get("rules_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.add_space(5.0);
+
+                egui::Grid::new("cleanup_rules_grid")
+                    .striped(true)
+                    .num_columns(6)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.strong(self.translations.get("rule_target"));
+                        ui.strong(self.translations.get("rule_pattern"));
+                        ui.strong(self.translations.get("rule_under"));
+                        ui.strong(self.translations.get("rule_min_age"));
+                        ui.label("");
+                        ui.end_row();
+
+                        for (index, rule) in self.config.cleanup_rules.iter_mut().enumerate() {
+                            changed |= ui.checkbox(&mut rule.enabled, "").changed();
+
+                            egui::ComboBox::from_id_salt(("rule_target", index))
+                                .selected_text(match rule.target {
+                                    RuleTarget::Files => files_text.as_str(),
+                                    RuleTarget::Folders => folders_text.as_str(),
+                                })
+                                .show_ui(ui, |ui| {
+                                    changed |= ui.selectable_value(&mut rule.target, RuleTarget::Files, &files_text).changed();
+                                    changed |= ui.selectable_value(&mut rule.target, RuleTarget::Folders, &folders_text).changed();
+                                });
+
+                            changed |= ui.add(egui::TextEdit::singleline(&mut rule.pattern).desired_width(160.0)).changed();
+                            changed |= ui.add(
+                                egui::TextEdit::singleline(&mut rule.under)
+                                    .hint_text(self.translations.get("rule_anywhere"))
+                                    .desired_width(200.0)
+                            ).changed();
+                            changed |= ui.add(
+                                egui::DragValue::new(&mut rule.min_age_days)
+                                    .range(0..=36500)
+                                    .suffix(format!(" {}", self.translations.get("days")))
+                            ).changed();
+
+                            if a11y::icon_button(ui, regular::TRASH, &self.translations.get("remove_rule")).clicked() {
+                                remove_rule = Some(index);
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui.button(format!("{} {}", regular::PLUS, self.translations.get("add_rule"))).clicked() {
+                        self.config.cleanup_rules.push(CleanupRule::default());
+                        changed = true;
+                    }
+
+                    ui.add_enabled_ui(self.root_node.is_some(), |ui| {
+                        if ui.button(format!("{} {}", regular::PLAY, self.translations.get("evaluate_rules"))).clicked() {
+                            evaluate_now = true;
+                        }
+                    });
+                });
+
+                let Some(plan) = &mut self.cleanup_plan else {
+                    return;
+                };
+
+                ui.separator();
+
+                let selected: Vec<&PlanItem> = plan.iter().filter(|i| i.selected).collect();
+                let selected_size: u64 = selected.iter().map(|i| i.size).sum();
+                ui.horizontal(|ui| {
+                    ui.strong(self.translations.get_fmt(
+                        "cleanup_plan_summary",
+                        &[&plan.len().to_string(), &selected.len().to_string(), &format_size(selected_size)],
+                    ));
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_enabled_ui(!selected.is_empty() && !read_only, |ui| {
+                            if ui.button(format!("{} {}", regular::BASKET, self.translations.get("add_selected_to_basket"))).clicked() {
+                                add_to_basket = true;
+                            }
+                        })
+                        .response
+                        .on_disabled_hover_text(&read_only_hint);
+                    });
+                });
+
+                if plan.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("no_matches"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.small_button(self.translations.get("select_all")).clicked() {
+                        plan.iter_mut().for_each(|i| i.selected = true);
+                    }
+                    if ui.small_button(self.translations.get("select_none")).clicked() {
+                        plan.iter_mut().for_each(|i| i.selected = false);
+                    }
+                });
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("cleanup_plan_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                for item in plan.iter_mut() {
+                                    ui.checkbox(&mut item.selected, item.path.display().to_string());
+                                    ui.label(format_size(item.size));
+                                    ui.label(match item.age_days {
+                                        Some(days) => format!("{} {}", self.translations.format_count(days), self.translations.get("days")),
+                                        None => "—".to_string(),
+                                    });
+                                    ui.label(format!("#{}", item.rule + 1));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(index) = remove_rule {
+            self.config.cleanup_rules.remove(index);
+            changed = true;
+        }
+        if changed {
+            self.save_config_to_file();
+        }
+        if evaluate_now {
+            if let Some(root) = &self.root_node {
+                self.cleanup_plan = Some(evaluate(root, &self.config.cleanup_rules, SystemTime::now()));
+            }
+        }
+        if add_to_basket {
+            if let Some(plan) = &self.cleanup_plan {
+                for item in plan.iter().filter(|i| i.selected) {
+                    self.basket.add(item.path.clone());
+                }
+            }
+            self.cleanup_plan = None;
+            self.show_basket = true;
+        }
+        self.show_rules = open;
+    }
+}
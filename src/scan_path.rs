@@ -0,0 +1,469 @@
+// Путь для сканирования, введённый вручную: кавычки после "Копировать как путь",
+// переменные окружения (%USERPROFILE% в Windows, $HOME и ~ в Unix), прямые
+// слэши в Windows и лишние разделители в конце приводятся к обычному виду,
+// а поле ввода сразу показывает, если по пути нечего сканировать. По мере
+// ввода под полем предлагаются подходящие подпапки. Диалог выбора папки
+// открывается там, где уже стоит путь, и умеет найти в дереве отдельный файл.
+// Проверка пути и подсказки читают диск в фоне: сетевой путь может отвечать
+// десятки секунд, и окно не должно замирать на каждой нажатой клавише
+
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Как часто перепроверяется тот же путь: папку могли создать или диск подключить
+const RECHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Пауза после последней нажатой клавиши перед обращением к диску
+const TYPING_PAUSE: Duration = Duration::from_millis(250);
+
+/// Сколько подпапок предлагается сразу
+const MAX_SUGGESTIONS: usize = 12;
+
+/// Почему по пути нельзя сканировать
+#[derive(Clone, PartialEq)]
+pub enum Problem {
+    NotFound,
+    NotADirectory,
+    /// Папка есть, но её содержимое не читается
+    Unreadable(String),
+}
+
+impl Problem {
+    pub fn translation_key(&self) -> &'static str {
+        match self {
+            Problem::NotFound => "path_not_found",
+            Problem::NotADirectory => "path_not_directory",
+            Problem::Unreadable(_) => "path_unreadable",
+        }
+    }
+}
+
+/// Подставляет значения переменных окружения; неизвестные остаются как есть
+fn expand_variables(input: &str) -> String {
+    let mut result = String::new();
+    let mut rest = input;
+    if cfg!(windows) {
+        // %ИМЯ%
+        while let Some(start) = rest.find('%') {
+            let Some(len) = rest[start + 1..].find('%') else {
+                break;
+            };
+            let name = &rest[start + 1..start + 1 + len];
+            result.push_str(&rest[..start]);
+            match std::env::var(name).ok().filter(|_| !name.is_empty()) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..start + len + 2]),
+            }
+            rest = &rest[start + len + 2..];
+        }
+    } else {
+        // $ИМЯ и ${ИМЯ}
+        while let Some(start) = rest.find('$') {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let (name, consumed) = match after.strip_prefix('{').and_then(|a| a.find('}').map(|end| (&a[..end], end + 2))) {
+                Some(braced) => braced,
+                None => {
+                    let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+                    (&after[..end], end)
+                }
+            };
+            match std::env::var(name).ok().filter(|_| !name.is_empty()) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..start + 1 + consumed]),
+            }
+            rest = &after[consumed..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Приводит введённый путь к виду, в котором его покажет файловый менеджер
+pub fn normalize(input: &str) -> String {
+    let trimmed = input.trim();
+    let unquoted = trimmed
+        .strip_prefix('"')
+        .and_then(|p| p.strip_suffix('"'))
+        .unwrap_or(trimmed);
+    let mut path = expand_variables(unquoted);
+
+    if !cfg!(windows) {
+        if let Some(home) = dirs::home_dir() {
+            if path == "~" {
+                path = home.display().to_string();
+            } else if let Some(rest) = path.strip_prefix("~/") {
+                path = home.join(rest).display().to_string();
+            }
+        }
+    } else {
+        path = path.replace('/', "\\");
+        // "C:" - это текущая папка диска, а не его корень
+        if path.len() == 2 && path.ends_with(':') {
+            path.push('\\');
+        }
+    }
+
+    // Разделители в конце убираются, кроме корня ("/", "C:\")
+    while path.len() > 1 && path.ends_with(MAIN_SEPARATOR) && !path.ends_with(&format!(":{}", MAIN_SEPARATOR)) {
+        path.pop();
+    }
+    path
+}
+
+/// Проверяет, можно ли сканировать путь (уже нормализованный)
+pub fn validate(path: &str) -> Result<(), Problem> {
+    let metadata = std::fs::metadata(Path::new(path)).map_err(|_| Problem::NotFound)?;
+    if !metadata.is_dir() {
+        return Err(Problem::NotADirectory);
+    }
+    std::fs::read_dir(path).map(|_| ()).map_err(|e| Problem::Unreadable(e.to_string()))
+}
+
+/// Проверка одного ввода, сделанная в фоне
+struct Lookup {
+    input: String,
+    problem: Option<Problem>,
+    /// None - подсказки для этого ввода не собирались
+    suggestions: Option<Vec<String>>,
+    at: Instant,
+}
+
+/// Проверка пути и подсказки подпапок для поля ввода. Одновременно идёт
+/// не больше одной фоновой проверки, следующая начинается после паузы в наборе
+#[derive(Default)]
+pub struct PathLookup {
+    /// Последняя готовая проверка
+    ready: Option<Lookup>,
+    /// Сюда фоновый поток кладёт готовый результат
+    incoming: Arc<Mutex<Option<Lookup>>>,
+    running: bool,
+    /// Текущий ввод и когда он последний раз менялся
+    typed: Option<(String, Instant)>,
+    /// Подсказки последней проверки, где они собирались
+    suggestions: Vec<String>,
+    selected: usize,
+}
+
+impl PathLookup {
+    /// Забирает готовый результат и при необходимости запускает проверку `input`
+    fn poll(&mut self, ctx: &egui::Context, input: &str, want_suggestions: bool) {
+        if let Some(lookup) = self.incoming.lock().unwrap().take() {
+            self.running = false;
+            if let Some(suggestions) = &lookup.suggestions {
+                if self.ready.as_ref().map(|r| &r.input) != Some(&lookup.input) {
+                    self.selected = 0;
+                }
+                self.suggestions = suggestions.clone();
+            }
+            self.ready = Some(lookup);
+        }
+        if self.typed.as_ref().map(|(typed, _)| typed.as_str()) != Some(input) {
+            self.typed = Some((input.to_string(), Instant::now()));
+        }
+        if normalize(input).is_empty() {
+            self.suggestions.clear();
+            return;
+        }
+
+        let fresh = self.ready.as_ref().is_some_and(|r| {
+            r.input == input && r.at.elapsed() < RECHECK_INTERVAL && (r.suggestions.is_some() || !want_suggestions)
+        });
+        if fresh || self.running {
+            return;
+        }
+        let since_typed = self.typed.as_ref().map_or(TYPING_PAUSE, |(_, at)| at.elapsed());
+        if since_typed < TYPING_PAUSE {
+            ctx.request_repaint_after(TYPING_PAUSE - since_typed);
+            return;
+        }
+
+        self.running = true;
+        let incoming = self.incoming.clone();
+        let input = input.to_string();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let problem = validate(&normalize(&input)).err();
+            let suggestions = want_suggestions.then(|| suggestions(&input));
+            *incoming.lock().unwrap() = Some(Lookup { input, problem, suggestions, at: Instant::now() });
+            ctx.request_repaint();
+        });
+    }
+
+    /// Что не так с путём по последней проверке именно этого ввода
+    fn problem(&self, input: &str) -> Option<Problem> {
+        self.ready.as_ref().filter(|r| r.input == input).and_then(|r| r.problem.clone())
+    }
+}
+
+/// Подпапки, имя которых начинается с последней части введённого пути
+fn suggestions(input: &str) -> Vec<String> {
+    let path = normalize(input);
+    if path.is_empty() {
+        return Vec::new();
+    }
+    // После разделителя предлагается всё содержимое папки
+    let ends_with_separator = input.trim_end().ends_with(MAIN_SEPARATOR) || input.trim_end().ends_with('/');
+    let (dir, prefix) = if ends_with_separator {
+        (Path::new(&path), String::new())
+    } else {
+        let path = Path::new(&path);
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => (parent, crate::pattern::fold(&name.to_string_lossy())),
+            _ => return Vec::new(),
+        }
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<(String, String)> = entries
+        .flatten()
+        // Ссылки на папки тоже подходят
+        .filter(|entry| std::fs::metadata(entry.path()).is_ok_and(|m| m.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        // Скрытые папки - только если их начали вводить
+        .filter(|name| !name.starts_with('.') || prefix.starts_with('.'))
+        .map(|name| (crate::pattern::fold(&name), name))
+        .filter(|(folded, _)| folded.starts_with(&prefix))
+        .collect();
+    names.sort();
+    names
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| dir.join(name).display().to_string())
+        .collect()
+}
+
+impl CedarApp {
+    /// Поле ввода пути с подсказками подпапок: стрелки выбирают, Enter или щелчок подставляют
+    pub fn show_scan_path_field(&mut self, ui: &mut egui::Ui) {
+        let popup_id = ui.make_persistent_id("scan_path_completion");
+        let popup_open = ui.memory(|m| m.is_popup_open(popup_id));
+        let count = self.path_lookup.suggestions.len();
+        // Стрелки забираются до поля ввода, иначе оно двигает по ним курсор
+        if popup_open && count > 0 {
+            let completion = &mut self.path_lookup;
+            ui.input_mut(|i| {
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+                    completion.selected = (completion.selected + 1) % count;
+                }
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+                    completion.selected = (completion.selected + count - 1) % count;
+                }
+            });
+        }
+
+        let response = ui.text_edit_singleline(&mut self.scan_path);
+        let mut accepted = None;
+        if response.has_focus() {
+            if self.path_lookup.suggestions.is_empty() {
+                ui.memory_mut(|m| m.close_popup());
+            } else if !popup_open {
+                ui.memory_mut(|m| m.open_popup(popup_id));
+            }
+        } else if response.lost_focus() {
+            if popup_open && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                accepted = Some(self.path_lookup.selected);
+            } else if !ui.input(|i| i.pointer.any_down()) {
+                // Нажатая кнопка мыши - щелчок по подсказке, список пока нужен
+                ui.memory_mut(|m| m.close_popup());
+            }
+            self.scan_path = normalize(&self.scan_path);
+        }
+        self.path_lookup.poll(ui.ctx(), &self.scan_path, response.has_focus());
+
+        let completion = &self.path_lookup;
+        egui::popup_below_widget(ui, popup_id, &response, egui::PopupCloseBehavior::CloseOnClickOutside, |ui| {
+            ui.set_min_width(response.rect.width());
+            for (index, suggestion) in completion.suggestions.iter().enumerate() {
+                if ui.selectable_label(index == completion.selected, suggestion).clicked() {
+                    accepted = Some(index);
+                }
+            }
+        });
+
+        let Some(suggestion) = accepted.and_then(|index| self.path_lookup.suggestions.get(index)) else {
+            return;
+        };
+        // Подставленная папка сразу раскрывается: ввод продолжается внутри неё
+        self.scan_path = format!("{}{}", suggestion, MAIN_SEPARATOR);
+        response.request_focus();
+        if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), response.id) {
+            let end = egui::text::CCursor::new(self.scan_path.chars().count());
+            state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+            state.store(ui.ctx(), response.id);
+        }
+    }
+
+    /// Кнопки выбора папки для сканирования и поиска файла в текущем дереве
+    pub fn show_browse_buttons(&mut self, ui: &mut egui::Ui) {
+        if ui.button(format!("{} {}", regular::FOLDER_OPEN, self.translations.get("browse"))).clicked() {
+            let mut dialog = rfd::FileDialog::new();
+            if let Some(start) = self.browse_start() {
+                dialog = dialog.set_directory(start);
+            }
+            if let Some(path) = dialog.pick_folder() {
+                self.scan_path = path.display().to_string();
+                self.remember_browsed(path);
+            }
+        }
+
+        let locate = ui
+            .add_enabled(self.root_node.is_some(), egui::Button::new(regular::FILE_MAGNIFYING_GLASS))
+            .on_hover_text(self.translations.get("locate_file"));
+        if locate.clicked() {
+            self.locate_file();
+        }
+    }
+
+    /// Где открыть диалог: у введённого пути, иначе там, где выбирали в прошлый раз
+    fn browse_start(&self) -> Option<PathBuf> {
+        let typed = normalize(&self.scan_path);
+        if !typed.is_empty() && Path::new(&typed).is_dir() {
+            return Some(PathBuf::from(typed));
+        }
+        self.config.last_browse_dir.clone().filter(|dir| dir.is_dir())
+    }
+
+    fn remember_browsed(&mut self, dir: PathBuf) {
+        if self.config.last_browse_dir.as_ref() != Some(&dir) {
+            self.config.last_browse_dir = Some(dir);
+            self.save_config_to_file();
+        }
+    }
+
+    /// Выбор файла в диалоге и переход к нему в дереве последнего сканирования
+    fn locate_file(&mut self) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        // Диалог открывается у выделенной папки или у корня сканирования
+        let start = self
+            .selected_path
+            .as_ref()
+            .and_then(|path| root.find(path))
+            .map(|node| if node.is_file { node.path.parent().unwrap_or(&node.path).to_path_buf() } else { node.path.clone() })
+            .unwrap_or_else(|| root.path.clone());
+        let Some(path) = rfd::FileDialog::new().set_directory(&start).pick_file() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            self.config.last_browse_dir = Some(parent.to_path_buf());
+            self.save_config_to_file();
+        }
+
+        let Some(root) = &mut self.root_node else {
+            return;
+        };
+        if root.find(&path).is_none() {
+            let text = self.translations.get_fmt("locate_not_scanned", &[&path.display().to_string()]);
+            self.set_status(text);
+            return;
+        }
+        root.expand_to(&path);
+        self.selected_path = Some(path.clone());
+        self.search.scroll_to = Some(path);
+    }
+
+    /// Что не так с путём в поле ввода; пока путь проверяется, препятствий нет
+    pub fn scan_path_problem(&self) -> Option<Problem> {
+        self.path_lookup.problem(&self.scan_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            assert_eq!(normalize(input), *expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn quotes_spaces_and_trailing_separators() {
+        let root = if cfg!(windows) { "C:\\" } else { "/" };
+        let data = if cfg!(windows) { "C:\\Data" } else { "/data" };
+        let spaced = if cfg!(windows) { "C:\\My Files" } else { "/my files" };
+        check(&[
+            (&format!("  {}  ", data), data),
+            (&format!("\"{}\"", spaced), spaced),
+            (&format!(" \"{}{}\" ", spaced, MAIN_SEPARATOR), spaced),
+            (&format!("{}{}{}", data, MAIN_SEPARATOR, MAIN_SEPARATOR), data),
+            (root, root),
+            // Кавычка без пары - часть пути
+            (&format!("\"{}", data), &format!("\"{}", data)),
+            ("\"\"", ""),
+            ("", ""),
+        ]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_forms() {
+        check(&[
+            ("C:", "C:\\"),
+            ("d:", "d:\\"),
+            ("C:\\", "C:\\"),
+            ("C:/", "C:\\"),
+            ("c:/Users/x/", "c:\\Users\\x"),
+            ("C:\\Users\\\\", "C:\\Users"),
+            ("\\\\server\\share\\", "\\\\server\\share"),
+            ("//server/share/dir/", "\\\\server\\share\\dir"),
+            ("\\\\?\\C:\\", "\\\\?\\C:\\"),
+            ("\\\\?\\C:\\Data\\", "\\\\?\\C:\\Data"),
+            ("\\\\?\\UNC\\server\\share\\", "\\\\?\\UNC\\server\\share"),
+        ]);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn unix_forms() {
+        check(&[
+            ("///", "/"),
+            ("/data//sub/", "/data//sub"),
+            // Обратный слэш в Unix - обычный символ имени
+            ("/data\\", "/data\\"),
+        ]);
+        if let Some(home) = dirs::home_dir() {
+            let home = home.display().to_string();
+            check(&[
+                ("~", &home),
+                ("~/", &home),
+                ("~/Documents/", &format!("{}/Documents", home.trim_end_matches('/'))),
+                ("/srv/~", "/srv/~"),
+            ]);
+        }
+    }
+
+    #[test]
+    fn environment_variables() {
+        std::env::set_var("CEDAR_SCAN_PATH_TEST", "base");
+        std::env::remove_var("CEDAR_SCAN_PATH_UNSET");
+        if cfg!(windows) {
+            check(&[
+                ("%CEDAR_SCAN_PATH_TEST%\\x", "base\\x"),
+                ("%CEDAR_SCAN_PATH_UNSET%\\x", "%CEDAR_SCAN_PATH_UNSET%\\x"),
+                ("100%", "100%"),
+                ("%%\\x", "%%\\x"),
+                ("\"%CEDAR_SCAN_PATH_TEST%\"", "base"),
+            ]);
+        } else {
+            check(&[
+                ("$CEDAR_SCAN_PATH_TEST/x", "base/x"),
+                ("${CEDAR_SCAN_PATH_TEST}x/", "basex"),
+                ("/a/$CEDAR_SCAN_PATH_UNSET/b", "/a/$CEDAR_SCAN_PATH_UNSET/b"),
+                ("/price$", "/price$"),
+                ("${unclosed", "${unclosed"),
+                ("\"$CEDAR_SCAN_PATH_TEST\"", "base"),
+            ]);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// Поиск по имени в дереве: подсветка совпадений, отметки на полосе прокрутки
+// и переход между совпадениями по F3 / Shift+F3
+
+use crate::keymap::{self, Command};
+use crate::{CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Ширина отметок на полосе прокрутки
+const MARKER_WIDTH: f32 = 6.0;
+
+/// Отношение строки дерева к поиску
+#[derive(Clone, Copy, PartialEq)]
+pub enum Hit {
+    None,
+    /// Свёрнутая папка, внутри которой есть совпадения
+    Contains,
+    Match,
+    /// Совпадение, на котором стоит навигация
+    Current,
+}
+
+#[derive(Default)]
+pub struct TreeSearch {
+    pub query: String,
+    /// Совпадения в порядке отрисовки дерева
+    matches: Vec<PathBuf>,
+    /// Предки совпадений - чтобы отмечать свёрнутые папки
+    containers: HashSet<PathBuf>,
+    current: Option<usize>,
+    /// Запрос, по которому собраны совпадения; None - собрать заново
+    indexed: Option<String>,
+    /// Строки с отметками в последнем кадре: отступ от начала содержимого и вид
+    rows: Vec<(f32, Hit)>,
+    /// Строка, к которой нужно прокрутить дерево
+    pub scroll_to: Option<PathBuf>,
+    focus_field: bool,
+}
+
+/// Совпадает ли имя с запросом: с `*` и `?` - как шаблон, иначе по подстроке.
+/// `query` уже приведён через `pattern::fold`.
+fn name_matches(query: &str, name: &str) -> bool {
+    if query.contains(['*', '?']) {
+        crate::pattern::wildcard_match(query, name)
+    } else {
+        crate::pattern::fold(name).contains(query)
+    }
+}
+
+fn collect(node: &DirNode, query: &str, matches: &mut Vec<PathBuf>) {
+    if name_matches(query, &node.display_name()) {
+        matches.push(node.path.clone());
+    }
+    for child in &node.children {
+        collect(child, query, matches);
+    }
+}
+
+impl TreeSearch {
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+    }
+
+    /// Сбрасывает найденное после изменения дерева
+    pub fn invalidate(&mut self) {
+        self.indexed = None;
+    }
+
+    /// Пересобирает совпадения, если изменился запрос или дерево
+    fn refresh(&mut self, root: Option<&DirNode>) {
+        let query = crate::pattern::fold(self.query.trim());
+        if self.indexed.as_deref() == Some(query.as_str()) {
+            return;
+        }
+        let current = self.current.and_then(|i| self.matches.get(i)).cloned();
+        self.matches.clear();
+        self.containers.clear();
+        if let (Some(root), false) = (root, query.is_empty()) {
+            collect(root, &query, &mut self.matches);
+            for path in &self.matches {
+                let mut parent = path.parent();
+                while let Some(dir) = parent.filter(|dir| dir.starts_with(&root.path)) {
+                    if !self.containers.insert(dir.to_path_buf()) {
+                        break;
+                    }
+                    parent = dir.parent();
+                }
+            }
+        }
+        // Текущее совпадение сохраняется, если оно всё ещё подходит
+        self.current = current.and_then(|path| self.matches.iter().position(|p| *p == path));
+        self.indexed = Some(query);
+    }
+
+    /// Отношение узла к поиску; свёрнутые папки отмечаются, если внутри есть совпадения
+    pub fn hit(&self, node: &DirNode) -> Hit {
+        let Some(query) = self.indexed.as_deref().filter(|q| !q.is_empty()) else {
+            return Hit::None;
+        };
+        if self.current.and_then(|i| self.matches.get(i)) == Some(&node.path) {
+            Hit::Current
+        } else if name_matches(query, &node.display_name()) {
+            Hit::Match
+        } else if !node.is_expanded && self.containers.contains(&node.path) {
+            Hit::Contains
+        } else {
+            Hit::None
+        }
+    }
+
+    /// Запоминает строку с отметкой; `y` - от начала содержимого прокрутки
+    pub fn mark_row(&mut self, y: f32, hit: Hit) {
+        if hit != Hit::None {
+            self.rows.push((y, hit));
+        }
+    }
+
+    /// Следующее (или предыдущее) совпадение по кругу
+    fn step(&mut self, forward: bool) -> Option<PathBuf> {
+        let count = self.matches.len();
+        if count == 0 {
+            return None;
+        }
+        let next = match (self.current, forward) {
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+            (None, true) => 0,
+            (None, false) => count - 1,
+        };
+        self.current = Some(next);
+        self.matches.get(next).cloned()
+    }
+}
+
+impl CedarApp {
+    /// Поле поиска с числом совпадений и кнопками перехода
+    pub fn show_search_bar(&mut self, ui: &mut egui::Ui) {
+        self.search.refresh(self.root_node.as_ref());
+
+        let field = egui::TextEdit::singleline(&mut self.search.query)
+            .hint_text(format!("{} {}", regular::MAGNIFYING_GLASS, self.translations.get("search_hint")))
+            .desired_width(180.0);
+        let response = ui.add(field);
+        if std::mem::take(&mut self.search.focus_field) {
+            response.request_focus();
+        }
+        // Enter в поле - следующее совпадение, как F3
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let backward = ui.input(|i| i.modifiers.shift);
+            self.go_to_match(!backward);
+            response.request_focus();
+        }
+
+        if !self.search.is_active() {
+            return;
+        }
+
+        let count = self.search.matches.len();
+        let position = self.search.current.map_or("–".to_string(), |i| (i + 1).to_string());
+        ui.label(
+            egui::RichText::new(self.translations.get_fmt("search_count", &[&position, &count.to_string()]))
+                .color(ui.visuals().weak_text_color()),
+        );
+        ui.add_enabled_ui(count > 0, |ui| {
+            if crate::a11y::icon_button(ui, regular::ARROW_UP, &self.translations.get("search_previous")).clicked() {
+                self.go_to_match(false);
+            }
+            if crate::a11y::icon_button(ui, regular::ARROW_DOWN, &self.translations.get("search_next")).clicked() {
+                self.go_to_match(true);
+            }
+        });
+        if crate::a11y::icon_button(ui, regular::X, &self.translations.get("clear")).clicked() {
+            self.search.query.clear();
+        }
+    }
+
+    /// Ctrl+F - к полю поиска, F3 / Shift+F3 - следующее и предыдущее совпадение
+    pub fn handle_search_keys(&mut self, ctx: &egui::Context) {
+        if keymap::consume(ctx, Command::FocusSearch) {
+            self.search.focus_field = true;
+        }
+        if keymap::consume(ctx, Command::PreviousMatch) {
+            self.go_to_match(false);
+        }
+        if keymap::consume(ctx, Command::NextMatch) {
+            self.go_to_match(true);
+        }
+    }
+
+    /// Раскрывает дерево до совпадения, выделяет его и прокручивает к нему
+    fn go_to_match(&mut self, forward: bool) {
+        self.search.refresh(self.root_node.as_ref());
+        let Some(path) = self.search.step(forward) else {
+            return;
+        };
+        if let Some(root) = &mut self.root_node {
+            root.expand_to(&path);
+        }
+        self.selected_path = Some(path.clone());
+        self.search.scroll_to = Some(path);
+    }
+
+    /// Начало кадра дерева: отметки собираются заново при отрисовке строк
+    pub fn begin_search_rows(&mut self) {
+        self.search.refresh(self.root_node.as_ref());
+        self.search.rows.clear();
+    }
+
+    /// Отметки совпадений поверх полосы прокрутки дерева
+    pub fn paint_search_markers(&self, ui: &egui::Ui, inner_rect: egui::Rect, content_height: f32) {
+        if self.search.rows.is_empty() {
+            return;
+        }
+        // Пока содержимое помещается целиком, отметки стоят напротив самих строк
+        let scale = inner_rect.height() / content_height.max(inner_rect.height());
+        let accent = ui.visuals().selection.stroke.color;
+        let painter = ui.painter();
+        for &(y, hit) in &self.search.rows {
+            let color = match hit {
+                Hit::Current => ui.visuals().strong_text_color(),
+                Hit::Contains => accent.gamma_multiply(0.5),
+                _ => accent,
+            };
+            let top = inner_rect.top() + y * scale;
+            let marker = egui::Rect::from_min_max(
+                egui::pos2(inner_rect.right() - MARKER_WIDTH, top),
+                egui::pos2(inner_rect.right(), top + 2.0),
+            );
+            painter.rect_filled(marker, 0.0, color);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Окно настроек
+
+use crate::palette::{Palette, Status};
+use crate::CedarApp;
+use eframe::egui;
+use egui_phosphor::regular;
+
+/// Образец палитры: индикаторы состояния, шкала возраста и категории
+fn palette_preview(ui: &mut egui::Ui, palette: Palette, dark_mode: bool) {
+    const SWATCH: egui::Vec2 = egui::vec2(14.0, 14.0);
+
+    ui.horizontal(|ui| {
+        for status in [Status::Good, Status::Warning, Status::Critical] {
+            let (rect, _) = ui.allocate_exact_size(SWATCH, egui::Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, palette.status_fill(status, dark_mode));
+        }
+        ui.separator();
+
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(96.0, SWATCH.y), egui::Sense::hover());
+        const STEPS: usize = 24;
+        let step_width = rect.width() / STEPS as f32;
+        for step in 0..STEPS {
+            let cell = egui::Rect::from_min_size(
+                egui::pos2(rect.left() + step as f32 * step_width, rect.top()),
+                egui::vec2(step_width, rect.height()),
+            );
+            let fraction = step as f32 / (STEPS - 1) as f32;
+            ui.painter().rect_filled(cell, 0.0, palette.ramp(fraction, dark_mode));
+        }
+        ui.separator();
+
+        for index in 0..7 {
+            let (rect, _) = ui.allocate_exact_size(SWATCH, egui::Sense::hover());
+            let color = palette.category(index as f32 / 7.0, 0, false, dark_mode);
+            ui.painter().rect_filled(rect, 2.0, color);
+        }
+    });
+}
+
+impl CedarApp {
+    /// Окно настроек приложения
+    pub fn show_settings_window(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+
+        let mut open = self.show_settings;
+        let mut changed = false;
+
+        egui::Window::new(format!("{} {}", regular::GEAR, self.translations.get("settings")))
+            .open(&mut open)
+            .resizable(false)
+            .vscroll(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(format!("{} {}", regular::EXPORT, self.translations.get("export_settings"))).clicked() {
+                        self.export_settings();
+                    }
+                    if ui.button(format!("{} {}", regular::DOWNLOAD_SIMPLE, self.translations.get("import_settings"))).clicked() {
+                        self.import_settings();
+                    }
+                });
+                ui.separator();
+
+                ui.heading(self.translations.get("settings_appearance"));
+                ui.add_space(5.0);
+
+                ui.label(self.translations.get("palette"));
+                for palette in Palette::all() {
+                    changed |= ui
+                        .radio_value(&mut self.config.palette, palette, self.translations.get(palette.translation_key()))
+                        .changed();
+                    ui.indent(palette.translation_key(), |ui| {
+                        palette_preview(ui, palette, self.config.dark_mode);
+                    });
+                    ui.add_space(4.0);
+                }
+
+                ui.separator();
+                changed |= ui
+                    .checkbox(&mut self.config.high_contrast, self.translations.get("high_contrast"))
+                    .on_hover_text(self.translations.get("high_contrast_hint"))
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut self.config.show_largest_file, self.translations.get("show_largest_file"))
+                    .on_hover_text(self.translations.get("show_largest_file_hint"))
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut self.config.remember_view_layouts, self.translations.get("remember_view_layouts"))
+                    .on_hover_text(self.translations.get("remember_view_layouts_hint"))
+                    .changed();
+
+                ui.add_space(8.0);
+                changed |= self.show_confirm_settings(ui);
+
+                ui.add_space(8.0);
+                changed |= self.show_exclusions(ui);
+
+                ui.add_space(8.0);
+                changed |= self.show_disk_strategies(ui);
+
+                ui.add_space(8.0);
+                changed |= self.show_throttle_settings(ui);
+
+                ui.add_space(8.0);
+                changed |= self.show_snapshot_store_settings(ui);
+
+                ui.add_space(8.0);
+                ui.heading(self.translations.get("reports_language"));
+                changed |= ui
+                    .checkbox(&mut self.config.reports_in_english, self.translations.get("reports_in_english"))
+                    .on_hover_text(self.translations.get("reports_in_english_hint"))
+                    .changed();
+
+                ui.add_space(8.0);
+                changed |= self.show_webhook_settings(ui);
+                ui.add_space(4.0);
+                changed |= self.show_smtp_settings(ui);
+            });
+
+        if changed {
+            self.save_config_to_file();
+        }
+        self.show_settings = open;
+    }
+}
@@ -0,0 +1,166 @@
+// Файл настроек: настройки выгружаются в один файл и загружаются на других
+// компьютерах, в окне или ключом `--import-settings <файл>` при установке. Так
+// администратор раздаёт одинаковые исключения, правила очистки, фильтры и
+// уведомления. Состояние конкретного компьютера (окна, недавние папки, замеры
+// дисков, заметки) в файл не попадает и при загрузке остаётся прежним; пароль
+// почтового сервера тоже не выгружается
+
+use crate::{AppConfig, CedarApp};
+use serde_json::{json, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const EXTENSION: &str = "json";
+
+const FORMAT: &str = "cedar-settings";
+const VERSION: u64 = 1;
+const IMPORT_ARG: &str = "--import-settings";
+
+/// Поля, которые относятся к этому компьютеру или пользователю
+const LOCAL_FIELDS: &[&str] = &[
+    "last_path",
+    "view_layouts",
+    "notes",
+    "zoom",
+    "window",
+    "scan_profiles",
+    "excluded_sizes",
+    "last_browse_dir",
+    "recent_roots",
+];
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn settings_object(config: &AppConfig) -> io::Result<serde_json::Map<String, Value>> {
+    match serde_json::to_value(config)? {
+        Value::Object(settings) => Ok(settings),
+        _ => Err(invalid("settings are not an object")),
+    }
+}
+
+/// Записывает переносимую часть настроек
+pub fn export(path: &Path, config: &AppConfig) -> io::Result<()> {
+    let mut settings = settings_object(config)?;
+    for field in LOCAL_FIELDS {
+        settings.remove(*field);
+    }
+    if let Some(smtp) = settings.get_mut("smtp").and_then(Value::as_object_mut) {
+        smtp.insert("password".into(), Value::String(String::new()));
+    }
+    // Наблюдаемые папки переносятся с пределами, но без размеров с этого компьютера
+    if let Some(Value::Array(watchlist)) = settings.get_mut("watchlist") {
+        for folder in watchlist.iter_mut().filter_map(Value::as_object_mut) {
+            folder.insert("previous_size".into(), Value::Null);
+            folder.insert("current_size".into(), Value::Null);
+        }
+    }
+    let document = json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "program": env!("CARGO_PKG_VERSION"),
+        "settings": settings,
+    });
+    std::fs::write(path, serde_json::to_string_pretty(&document)?)
+}
+
+/// Настройки из файла поверх текущих; поля этого компьютера остаются прежними
+pub fn import(path: &Path, current: &AppConfig) -> io::Result<AppConfig> {
+    let document: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    if document.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err(invalid("not a Cedar settings file"));
+    }
+    if document.get("version").and_then(Value::as_u64).unwrap_or(0) > VERSION {
+        return Err(invalid("settings were exported by a newer version"));
+    }
+    let Some(Value::Object(imported)) = document.get("settings") else {
+        return Err(invalid("settings are missing"));
+    };
+
+    let mut settings = settings_object(current)?;
+    for (field, value) in imported {
+        if LOCAL_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let mut value = value.clone();
+        // Пароль не выгружается, поэтому пустой пароль в файле не стирает здешний
+        if field == "smtp" {
+            let password = settings.get("smtp").and_then(|smtp| smtp.get("password")).cloned();
+            if let (Some(smtp), Some(password)) = (value.as_object_mut(), password) {
+                if smtp.get("password").and_then(Value::as_str).is_none_or(str::is_empty) {
+                    smtp.insert("password".into(), password);
+                }
+            }
+        }
+        settings.insert(field.clone(), value);
+    }
+    Ok(serde_json::from_value(Value::Object(settings))?)
+}
+
+/// Файл из ключа `--import-settings`; None - ключа нет
+pub fn import_arg() -> Option<Result<PathBuf, String>> {
+    let mut args = std::env::args().skip(1);
+    args.find(|arg| arg == IMPORT_ARG)?;
+    Some(args.next().map(PathBuf::from).ok_or_else(|| format!("{} requires a value", IMPORT_ARG)))
+}
+
+/// Загружает настройки из файла в сохранённые без окна; возвращает код завершения процесса
+pub fn import_from_command_line(path: &Path) -> i32 {
+    match import(path, &CedarApp::load_config_from_file()) {
+        Ok(config) => match CedarApp::write_config_file(&config) {
+            Ok(()) => 0,
+            Err(error) => {
+                eprintln!("{}", error);
+                1
+            }
+        },
+        Err(error) => {
+            eprintln!("{}: {}", path.display(), error);
+            1
+        }
+    }
+}
+
+impl CedarApp {
+    /// Выгружает настройки в файл по выбору пользователя
+    pub fn export_settings(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Cedar", &[EXTENSION])
+            .set_file_name(format!("cedar-settings.{}", EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        match export(&path, &self.config) {
+            Ok(()) => self.set_status(self.translations.get_fmt("settings_exported", &[&path.display().to_string()])),
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("settings_export_failed", &[&e.to_string()]));
+            }
+        }
+    }
+
+    /// Загружает настройки из файла по выбору пользователя и сразу применяет их
+    pub fn import_settings(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("Cedar", &[EXTENSION]).pick_file() else {
+            return;
+        };
+        match import(&path, &self.config) {
+            Ok(config) => {
+                self.config = config;
+                crate::policy::apply(&mut self.config);
+                let language = self.config.language;
+                self.set_language(language);
+                crate::throttle::HASHING.set_limit_mb(self.config.hashing_limit_mb);
+                self.save_config_to_file();
+                self.log.info(self.translations.get_fmt("settings_imported", &[&path.display().to_string()]));
+                self.set_status(self.translations.get_fmt("settings_imported", &[&path.display().to_string()]));
+            }
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("settings_import_failed", &[&e.to_string()]));
+            }
+        }
+    }
+}
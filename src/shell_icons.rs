@@ -0,0 +1,276 @@
+// Системные иконки файлов и папок: SHGetFileInfo на Windows,
+// иконки темы freedesktop в Linux. Кэшируются по расширению.
+
+use crate::DirNode;
+use eframe::egui;
+use std::collections::HashMap;
+
+/// Размер иконок в дереве, в точках интерфейса
+const ICON_POINTS: f32 = 16.0;
+
+#[derive(Default)]
+pub struct ShellIcons {
+    // None - системной иконки нет, используем встроенную
+    cache: HashMap<String, Option<egui::TextureHandle>>,
+    /// Пикселей на точку, для которых загружены иконки
+    pixels_per_point: f32,
+    #[cfg(all(unix, not(target_os = "macos")))]
+    mime_types: Option<HashMap<String, String>>,
+}
+
+impl ShellIcons {
+    /// Сбрасывает кэш, например после смены темы: системные иконки могут от неё зависеть
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Размер иконок в пикселях под масштаб экрана; при смене масштаба кэш сбрасывается
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: f32) {
+        if (self.pixels_per_point - pixels_per_point).abs() > 0.01 {
+            self.pixels_per_point = pixels_per_point;
+            self.clear();
+        }
+    }
+
+    /// Нужный размер иконки в пикселях
+    #[cfg_attr(not(any(windows, all(unix, not(target_os = "macos")))), allow(dead_code))]
+    fn pixel_size(&self) -> u32 {
+        (ICON_POINTS * self.pixels_per_point.max(1.0)).ceil() as u32
+    }
+
+    /// Системная иконка для узла дерева, если её удалось получить
+    pub fn get(&mut self, ctx: &egui::Context, node: &DirNode) -> Option<egui::TextureHandle> {
+        let key = if node.is_file {
+            std::path::Path::new(&node.name)
+                .extension()
+                .map(|e| format!(".{}", e.to_string_lossy().to_lowercase()))
+                .unwrap_or_default()
+        } else {
+            "<folder>".to_string()
+        };
+
+        if let Some(cached) = self.cache.get(&key) {
+            return cached.clone();
+        }
+
+        let texture = self
+            .load_native(&key)
+            .map(|image| ctx.load_texture(format!("shell_icon{}", key), image, egui::TextureOptions::LINEAR));
+        self.cache.insert(key, texture.clone());
+        texture
+    }
+
+    #[cfg(windows)]
+    fn load_native(&mut self, key: &str) -> Option<egui::ColorImage> {
+        windows_icon(key, self.pixel_size())
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn load_native(&mut self, key: &str) -> Option<egui::ColorImage> {
+        let mime_types = self.mime_types.get_or_insert_with(load_mime_globs);
+
+        let mut names = Vec::new();
+        if key == "<folder>" {
+            names.push("folder".to_string());
+        } else {
+            if let Some(mime) = key.strip_prefix('.').and_then(|ext| mime_types.get(ext)) {
+                names.push(mime.replace('/', "-"));
+                if let Some(major) = mime.split('/').next() {
+                    names.push(format!("{}-x-generic", major));
+                }
+            }
+            names.push("text-x-generic".to_string());
+        }
+
+        let size = self.pixel_size();
+        names.iter().find_map(|name| freedesktop_icon(name, size))
+    }
+
+    #[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+    fn load_native(&mut self, _key: &str) -> Option<egui::ColorImage> {
+        None
+    }
+}
+
+#[cfg(windows)]
+fn windows_icon(key: &str, size: u32) -> Option<egui::ColorImage> {
+    use windows_sys::Win32::Storage::FileSystem::{FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_NORMAL};
+    use windows_sys::Win32::UI::Shell::{
+        SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON, SHGFI_LARGEICON, SHGFI_SMALLICON, SHGFI_USEFILEATTRIBUTES,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::DestroyIcon;
+
+    // С SHGFI_USEFILEATTRIBUTES файл не обязан существовать - хватает расширения
+    let (name, attributes) = if key == "<folder>" {
+        ("folder".to_string(), FILE_ATTRIBUTE_DIRECTORY)
+    } else {
+        (format!("file{}", key), FILE_ATTRIBUTE_NORMAL)
+    };
+    let wide: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+    // Маленькая иконка - 16 пикселей, большая - 32: на экранах от 125% маленькая размыта
+    let icon_size = if size > 20 { SHGFI_LARGEICON } else { SHGFI_SMALLICON };
+
+    unsafe {
+        let mut info: SHFILEINFOW = std::mem::zeroed();
+        let found = SHGetFileInfoW(
+            wide.as_ptr(),
+            attributes,
+            &mut info,
+            std::mem::size_of::<SHFILEINFOW>() as u32,
+            SHGFI_ICON | icon_size | SHGFI_USEFILEATTRIBUTES,
+        );
+        if found == 0 || info.hIcon.is_null() {
+            return None;
+        }
+
+        let image = icon_to_image(info.hIcon);
+        DestroyIcon(info.hIcon);
+        image
+    }
+}
+
+/// Переводит HICON в RGBA-изображение через GetDIBits
+#[cfg(windows)]
+unsafe fn icon_to_image(icon: windows_sys::Win32::UI::WindowsAndMessaging::HICON) -> Option<egui::ColorImage> {
+    use windows_sys::Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO, BITMAPINFOHEADER,
+        BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetIconInfo, ICONINFO};
+
+    let mut icon_info: ICONINFO = std::mem::zeroed();
+    if GetIconInfo(icon, &mut icon_info) == 0 {
+        return None;
+    }
+
+    let color = icon_info.hbmColor;
+    let mut image = None;
+
+    // У монохромных иконок нет цветного битмапа - для них оставляем встроенную
+    let mut bitmap: BITMAP = std::mem::zeroed();
+    if !color.is_null()
+        && GetObjectW(
+            color,
+            std::mem::size_of::<BITMAP>() as i32,
+            &mut bitmap as *mut BITMAP as *mut std::ffi::c_void,
+        ) != 0
+    {
+        let (width, height) = (bitmap.bmWidth, bitmap.bmHeight);
+        let mut header: BITMAPINFO = std::mem::zeroed();
+        header.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+        header.bmiHeader.biWidth = width;
+        header.bmiHeader.biHeight = -height; // строки сверху вниз
+        header.bmiHeader.biPlanes = 1;
+        header.bmiHeader.biBitCount = 32;
+        header.bmiHeader.biCompression = BI_RGB;
+
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        let dc = GetDC(std::ptr::null_mut());
+        let lines = GetDIBits(
+            dc,
+            color,
+            0,
+            height as u32,
+            pixels.as_mut_ptr() as *mut std::ffi::c_void,
+            &mut header,
+            DIB_RGB_COLORS,
+        );
+        ReleaseDC(std::ptr::null_mut(), dc);
+
+        if lines != 0 {
+            // BGRA -> RGBA; старые иконки без альфа-канала считаем непрозрачными
+            let has_alpha = pixels.chunks_exact(4).any(|p| p[3] != 0);
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+                if !has_alpha {
+                    pixel[3] = 255;
+                }
+            }
+            image = Some(egui::ColorImage::from_rgba_unmultiplied(
+                [width as usize, height as usize],
+                &pixels,
+            ));
+        }
+    }
+
+    if !color.is_null() {
+        DeleteObject(color);
+    }
+    if !icon_info.hbmMask.is_null() {
+        DeleteObject(icon_info.hbmMask);
+    }
+    image
+}
+
+/// Сопоставление расширений MIME-типам из базы shared-mime-info
+#[cfg(all(unix, not(target_os = "macos")))]
+fn load_mime_globs() -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let Ok(content) = std::fs::read_to_string("/usr/share/mime/globs2") else {
+        return map;
+    };
+
+    // Формат строки: вес:mime/type:*.ext
+    for line in content.lines().filter(|l| !l.starts_with('#')) {
+        let mut parts = line.splitn(3, ':');
+        let (Some(_), Some(mime), Some(glob)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        if let Some(ext) = glob.strip_prefix("*.") {
+            if !ext.contains(['*', '?', '[']) {
+                map.entry(ext.to_lowercase()).or_insert_with(|| mime.to_string());
+            }
+        }
+    }
+    map
+}
+
+/// Ищет иконку в распространённых темах freedesktop; `pixels` - нужный размер
+#[cfg(all(unix, not(target_os = "macos")))]
+fn freedesktop_icon(name: &str, pixels: u32) -> Option<egui::ColorImage> {
+    const THEMES: [&str; 5] = ["Adwaita", "breeze", "Papirus", "gnome", "hicolor"];
+    const SIZES: [u32; 6] = [16, 22, 24, 32, 48, 64];
+    const CONTEXTS: [&str; 2] = ["mimetypes", "places"];
+
+    // Сначала наименьший размер не меньше нужного, затем меньшие по убыванию
+    let mut sizes: Vec<u32> = SIZES.iter().copied().filter(|&s| s >= pixels).collect();
+    sizes.extend(SIZES.iter().rev().copied().filter(|&s| s < pixels));
+
+    let roots = ["/usr/share/icons", "/usr/local/share/icons"];
+    for root in roots {
+        for theme in THEMES {
+            for &size in &sizes {
+                let size = format!("{size}x{size}");
+                let size = size.as_str();
+                for context in CONTEXTS {
+                    // Встречаются обе раскладки: размер/контекст и контекст/размер
+                    for dir in [format!("{size}/{context}"), format!("{context}/{}", size.split('x').next().unwrap_or(size))] {
+                        let path = std::path::Path::new(root).join(theme).join(dir).join(format!("{name}.png"));
+                        if let Ok(data) = std::fs::read(&path) {
+                            if let Ok(image) = image::load_from_memory(&data) {
+                                let rgba = image.to_rgba8();
+                                let size = [rgba.width() as usize, rgba.height() as usize];
+                                return Some(egui::ColorImage::from_rgba_unmultiplied(size, &rgba));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Масштабируемые SVG-иконки
+            for context in CONTEXTS {
+                let path = std::path::Path::new(root)
+                    .join(theme)
+                    .join("scalable")
+                    .join(context)
+                    .join(format!("{name}.svg"));
+                if let Ok(data) = std::fs::read(&path) {
+                    if let Some(image) = crate::rasterize_svg(&data, pixels) {
+                        return Some(image);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
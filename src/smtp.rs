@@ -0,0 +1,254 @@
+// Отчёт по почте для сред без веб-хуков: сканирование без окна с `--email <адреса>`
+// после каждого сканирования отправляет письмо со сводкой в HTML и PDF-отчётом
+// во вложении. Сервер задаётся в настройках; пароль можно не хранить
+// в конфигурации, а передать переменной окружения CEDAR_SMTP_PASSWORD
+
+use crate::headless::Summary;
+use crate::i18n::Translations;
+use crate::palette::{Palette, Status};
+use crate::pdf_report::{self, escape};
+use crate::{CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+const PASSWORD_VAR: &str = "CEDAR_SMTP_PASSWORD";
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Защита соединения с сервером
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Security {
+    /// Обычный порт с переходом на TLS (587)
+    #[default]
+    StartTls,
+    /// TLS с самого начала (465)
+    Tls,
+    /// Без шифрования - только для серверов внутри сети
+    None,
+}
+
+impl Security {
+    fn all() -> [Security; 3] {
+        [Security::StartTls, Security::Tls, Security::None]
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Security::StartTls => "STARTTLS",
+            Security::Tls => "TLS",
+            Security::None => "—",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpSettings {
+    pub server: String,
+    pub port: u16,
+    pub security: Security,
+    /// Пустое имя - без входа на сервер
+    pub username: String,
+    pub password: String,
+    pub from: String,
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        Self {
+            server: String::new(),
+            port: 587,
+            security: Security::StartTls,
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+        }
+    }
+}
+
+fn transport(settings: &SmtpSettings) -> Result<SmtpTransport, String> {
+    let builder = match settings.security {
+        Security::StartTls => SmtpTransport::starttls_relay(&settings.server).map_err(|e| e.to_string())?,
+        Security::Tls => SmtpTransport::relay(&settings.server).map_err(|e| e.to_string())?,
+        Security::None => SmtpTransport::builder_dangerous(&settings.server),
+    };
+    let mut builder = builder.port(settings.port).timeout(Some(TIMEOUT));
+    if !settings.username.is_empty() {
+        let password = std::env::var(PASSWORD_VAR).unwrap_or_else(|_| settings.password.clone());
+        builder = builder.credentials(Credentials::new(settings.username.clone(), password));
+    }
+    Ok(builder.build())
+}
+
+fn send(settings: &SmtpSettings, to: &[String], subject: String, html: String, pdf: Option<(String, Vec<u8>)>) -> Result<(), String> {
+    if settings.server.is_empty() {
+        return Err("SMTP server is not set in the settings".to_string());
+    }
+    let from: Mailbox = settings.from.parse().map_err(|e| format!("{}: {}", settings.from, e))?;
+    let mut builder = Message::builder().from(from).subject(subject);
+    for address in to {
+        builder = builder.to(address.parse().map_err(|e| format!("{}: {}", address, e))?);
+    }
+
+    let body = SinglePart::html(html);
+    let message = match pdf {
+        Some((name, data)) => {
+            let pdf_type = ContentType::parse("application/pdf").map_err(|e| e.to_string())?;
+            builder.multipart(MultiPart::mixed().singlepart(body).singlepart(Attachment::new(name).body(data, pdf_type)))
+        }
+        None => builder.singlepart(body),
+    }
+    .map_err(|e| e.to_string())?;
+
+    transport(settings)?.send(&message).map(|_| ()).map_err(|e| format!("{}: {}", settings.server, e))
+}
+
+/// Сводка письмом: итоги и крупнейшие папки в теле, полный отчёт во вложении
+fn summary_html(summary: &Summary, t: &Translations) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<html><body style=\"font-family: sans-serif\"><h2>{}</h2><p><b>{}</b></p><table cellpadding=\"4\">",
+        escape(&t.get("pdf_report_title")),
+        escape(&summary.root.display().to_string()),
+    );
+    let rows = [
+        (t.get("total_size"), t.format_size(summary.size)),
+        (t.get("size_on_disk"), t.format_size(summary.allocated)),
+        (t.get("files"), t.format_count(summary.files)),
+        (t.get("directories"), t.format_count(summary.folders)),
+        (t.get("pdf_report_date"), t.format_date(std::time::UNIX_EPOCH + Duration::from_secs(summary.finished))),
+    ];
+    for (name, value) in rows {
+        let _ = write!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(&name), escape(&value));
+    }
+    html.push_str("</table>");
+    if summary.skipped > 0 {
+        let _ = write!(html, "<p>{}</p>", escape(&t.get_fmt("items_skipped", &[&t.format_count(summary.skipped)])));
+    }
+
+    let _ = write!(
+        html,
+        "<h3>{}</h3><table cellpadding=\"4\" border=\"1\" style=\"border-collapse: collapse\"><tr><th>{}</th><th>{}</th><th>{}</th></tr>",
+        escape(&t.get("pdf_report_top_folders")),
+        escape(&t.get("folder")),
+        escape(&t.get("size")),
+        escape(&t.get("files")),
+    );
+    for folder in &summary.top_folders {
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td align=\"right\">{}</td><td align=\"right\">{}</td></tr>",
+            escape(&folder.path.display().to_string()),
+            t.format_size(folder.size),
+            t.format_count(folder.files),
+        );
+    }
+    html.push_str("</table></body></html>");
+    html
+}
+
+/// Отправляет отчёт по сканированию на адреса `to`
+pub fn send_report(
+    settings: &SmtpSettings,
+    to: &[String],
+    root: &DirNode,
+    summary: &Summary,
+    t: &Translations,
+    palette: Palette,
+) -> Result<(), String> {
+    let stats = pdf_report::ScanStats {
+        finished: Some(UNIX_EPOCH + Duration::from_secs(summary.finished)),
+        duration: Some(Duration::from_secs_f64(summary.duration_secs)),
+    };
+    let pdf = pdf_report::report_pdf(root, stats, t, palette)?;
+    let subject = format!("{}: {} - {}", t.get("pdf_report_title"), root.path.display(), t.format_size(summary.size));
+    send(settings, to, subject, summary_html(summary, t), Some((format!("{}.pdf", root.display_name()), pdf)))
+}
+
+/// Пробное письмо из настроек, идущее в фоне
+pub type SmtpTest = Arc<Mutex<Option<Result<(), String>>>>;
+
+impl CedarApp {
+    /// Раздел настроек: почтовый сервер для отчётов; возвращает true, если что-то изменилось
+    pub fn show_smtp_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.label(egui::RichText::new(self.translations.get("smtp_hint")).weak());
+
+        let settings = &mut self.config.smtp;
+        let mut changed = false;
+        egui::Grid::new("smtp_settings").num_columns(2).show(ui, |ui| {
+            ui.label(self.translations.get("smtp_server"));
+            ui.horizontal(|ui| {
+                changed |= ui.add(egui::TextEdit::singleline(&mut settings.server).desired_width(180.0)).changed();
+                changed |= ui.add(egui::DragValue::new(&mut settings.port).range(1..=65535)).changed();
+                egui::ComboBox::from_id_salt("smtp_security")
+                    .selected_text(settings.security.label())
+                    .show_ui(ui, |ui| {
+                        for security in Security::all() {
+                            changed |= ui.selectable_value(&mut settings.security, security, security.label()).changed();
+                        }
+                    });
+            });
+            ui.end_row();
+
+            ui.label(self.translations.get("smtp_username"));
+            changed |= ui.text_edit_singleline(&mut settings.username).changed();
+            ui.end_row();
+
+            ui.label(self.translations.get("smtp_password"));
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut settings.password).password(true))
+                .on_hover_text(self.translations.get_fmt("smtp_password_hint", &[PASSWORD_VAR]))
+                .changed();
+            ui.end_row();
+
+            ui.label(self.translations.get("smtp_from"));
+            changed |= ui.text_edit_singleline(&mut settings.from).changed();
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui| {
+            let settings = self.config.smtp.clone();
+            let sending = self.smtp_test.as_ref().is_some_and(|t| t.lock().unwrap().is_none());
+            let button = egui::Button::new(format!("{} {}", regular::ENVELOPE_SIMPLE, self.translations.get("smtp_test")));
+            let response = ui
+                .add_enabled(!settings.server.is_empty() && !settings.from.is_empty() && !sending, button)
+                .on_hover_text(self.translations.get("smtp_test_hint"));
+            if response.clicked() {
+                let result: SmtpTest = Arc::new(Mutex::new(None));
+                let thread_result = result.clone();
+                let subject = self.translations.get("smtp_test_subject");
+                thread::spawn(move || {
+                    let to = [settings.from.clone()];
+                    let sent = send(&settings, &to, subject.clone(), format!("<p>{}</p>", escape(&subject)), None);
+                    *thread_result.lock().unwrap() = Some(sent);
+                });
+                self.smtp_test = Some(result);
+            }
+
+            if let Some(test) = &self.smtp_test {
+                match &*test.lock().unwrap() {
+                    None => {
+                        ui.spinner();
+                        ui.ctx().request_repaint();
+                    }
+                    Some(Ok(())) => {
+                        ui.label(self.translations.get("webhook_sent"));
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(self.config.palette.status_text(Status::Critical, self.config.dark_mode), error);
+                    }
+                }
+            }
+        });
+        changed
+    }
+}
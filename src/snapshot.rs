@@ -0,0 +1,324 @@
+// Снимки сканирования (.cedar): законченное дерево сохраняется в файл и
+// открывается позже без повторного сканирования. После сигнатуры идёт
+// несжатый заголовок (корень, время сканирования, итоги), а за ним - дерево
+// в JSON, сжатое zlib. Двоичные форматы serde здесь не подходят: имена не в
+// UTF-8 и необязательные поля DirNode требуют самоописываемого формата
+
+use crate::{format_size, os_serde, view_layouts, CedarApp, DirNode};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const EXTENSION: &str = "cedar";
+
+const MAGIC: &[u8; 8] = b"CEDARSNP";
+const VERSION: u16 = 1;
+
+/// Заголовок снимка; читается без распаковки дерева
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    #[serde(with = "os_serde")]
+    pub root: PathBuf,
+    pub scanned_at: SystemTime,
+    pub size: u64,
+    pub files: u64,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Дерево, уже сжатое для снимка, без заголовка. Сжимается в потоке сканирования,
+/// чтобы автоматический снимок не копировал готовое дерево в окне
+pub struct Encoded {
+    root: PathBuf,
+    size: u64,
+    files: u64,
+    body: Vec<u8>,
+}
+
+pub fn encode(root: &DirNode) -> io::Result<Encoded> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    serde_json::to_writer(&mut encoder, root)?;
+    Ok(Encoded {
+        root: root.path.clone(),
+        size: root.size,
+        files: root.file_count(),
+        body: encoder.finish()?,
+    })
+}
+
+/// Пишет сигнатуру и заголовок во временный файл рядом с `path`
+fn create_with_header(path: &Path, header: &SnapshotHeader) -> io::Result<(PathBuf, BufWriter<File>)> {
+    let header = serde_json::to_vec(header)?;
+    let tmp = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&(header.len() as u32).to_le_bytes())?;
+    writer.write_all(&header)?;
+    Ok((tmp, writer))
+}
+
+/// Сохраняет дерево; пишет во временный файл и переименовывает, чтобы сбой не оставил битый снимок
+pub fn save(path: &Path, root: &DirNode, scanned_at: SystemTime) -> io::Result<()> {
+    let header = SnapshotHeader {
+        root: root.path.clone(),
+        scanned_at,
+        size: root.size,
+        files: root.file_count(),
+    };
+    let (tmp, writer) = create_with_header(path, &header)?;
+    let mut encoder = ZlibEncoder::new(writer, flate2::Compression::default());
+    serde_json::to_writer(&mut encoder, root)?;
+    encoder.finish()?.flush()?;
+    std::fs::rename(&tmp, path)
+}
+
+/// То же для заранее сжатого дерева
+pub fn save_encoded(path: &Path, encoded: &Encoded, scanned_at: SystemTime) -> io::Result<()> {
+    let header = SnapshotHeader {
+        root: encoded.root.clone(),
+        scanned_at,
+        size: encoded.size,
+        files: encoded.files,
+    };
+    let (tmp, mut writer) = create_with_header(path, &header)?;
+    writer.write_all(&encoded.body)?;
+    writer.flush()?;
+    drop(writer);
+    std::fs::rename(&tmp, path)
+}
+
+fn read_header(reader: &mut impl Read) -> io::Result<SnapshotHeader> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a Cedar snapshot"));
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    if u16::from_le_bytes(version) > VERSION {
+        return Err(invalid("snapshot was saved by a newer version"));
+    }
+    let mut length = [0u8; 4];
+    reader.read_exact(&mut length)?;
+    let mut header = vec![0u8; u32::from_le_bytes(length) as usize];
+    reader.read_exact(&mut header)?;
+    Ok(serde_json::from_slice(&header)?)
+}
+
+/// Чтение JSON без ограничения вложенности: у глубоких деревьев уровней больше 128
+pub fn from_reader<T: for<'de> Deserialize<'de>>(reader: impl Read) -> io::Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    deserializer.disable_recursion_limit();
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Читает только заголовок снимка
+pub fn load_header(path: &Path) -> io::Result<SnapshotHeader> {
+    read_header(&mut BufReader::new(File::open(path)?))
+}
+
+/// Открывает снимок: заголовок и дерево
+pub fn load(path: &Path) -> io::Result<(SnapshotHeader, DirNode)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = read_header(&mut reader)?;
+    let mut root: DirNode = from_reader(BufReader::new(ZlibDecoder::new(reader)))?;
+    // В снимках прежних версий числа файлов и папок нет
+    root.update_counts();
+    Ok((header, root))
+}
+
+impl CedarApp {
+    /// Показывает дерево, прочитанное из файла, как результат сканирования
+    pub fn open_tree(&mut self, root: DirNode, scanned_at: SystemTime) {
+        self.scan_path = root.path.display().to_string();
+        self.selected_path = None;
+        self.view_focus = None;
+        self.restore_view = None;
+        self.pending_scroll_offset = Some(0.0);
+        self.last_scan_size = root.size;
+        self.last_scan_duration = None;
+        self.scan_speed_mbps = 0.0;
+        self.last_scan_finished = Some(scanned_at);
+        if view_layouts::restore(&mut self.config, &root.path) {
+            self.save_config_to_file();
+        }
+        self.replace_tree(root);
+    }
+
+    /// Сохраняет текущее дерево в снимок по выбору пользователя
+    pub fn save_snapshot(&mut self) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        let name = root.display_name().into_owned();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Cedar", &[EXTENSION])
+            .set_file_name(format!("{}.{}", name, EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        let scanned_at = self.last_scan_finished.unwrap_or_else(SystemTime::now);
+        match save(&path, root, scanned_at) {
+            Ok(()) => self.set_status(self.translations.get_fmt("snapshot_saved", &[&path.display().to_string()])),
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("snapshot_save_failed", &[&e.to_string()]));
+            }
+        }
+    }
+
+    /// Открывает снимок по выбору пользователя вместо текущего дерева
+    pub fn open_snapshot(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("Cedar", &[EXTENSION]).pick_file() else {
+            return;
+        };
+        self.open_snapshot_file(&path);
+    }
+
+    pub fn open_snapshot_file(&mut self, path: &Path) {
+        let (header, mut root) = match load(path) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                self.log.error(format!("{}: {}", path.display(), e));
+                self.set_status(self.translations.get_fmt("snapshot_open_failed", &[&e.to_string()]));
+                return;
+            }
+        };
+        root.is_expanded = true;
+        self.open_tree(root, header.scanned_at);
+
+        let age = self.scan_age_text(header.scanned_at);
+        self.set_status(self.translations.get_fmt(
+            "snapshot_opened",
+            &[
+                &header.root.display().to_string(),
+                &format_size(header.size),
+                &self.translations.format_count(header.files),
+                &age,
+            ],
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::largest_file;
+    use crate::test_util::TempFile;
+    use std::ffi::OsString;
+    use std::time::Duration;
+
+    fn temp_snapshot(name: &str) -> TempFile {
+        TempFile::new(&format!("snapshot-{}.{}", name, EXTENSION))
+    }
+
+    fn file(parent: &Path, name: OsString, size: u64) -> DirNode {
+        let mut node = DirNode::new(parent.join(&name), name, size, true);
+        node.allocated = size.div_ceil(4096) * 4096;
+        node.modified = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + size));
+        node
+    }
+
+    fn folder(path: PathBuf, children: Vec<DirNode>) -> DirNode {
+        let name = path.file_name().map(OsString::from).unwrap_or_else(|| path.clone().into_os_string());
+        let mut node = DirNode::new(path, name, children.iter().map(|c| c.size).sum(), false);
+        node.allocated = children.iter().map(|c| c.allocated).sum();
+        node.modified = children.iter().filter_map(|c| c.modified).max();
+        node.largest_file = largest_file(&children);
+        node.children = children;
+        node
+    }
+
+    fn sample_tree() -> DirNode {
+        let root = PathBuf::from("/data");
+        let inner = root.join("inner");
+        let mut link = file(&inner, "link".into(), 0);
+        link.link_target = Some(PathBuf::from("/elsewhere"));
+        link.referenced = 123;
+        let mut sparse = file(&inner, "disk.img".into(), 1 << 30);
+        sparse.sparse = true;
+        sparse.allocated = 4096;
+        let mut unreadable = folder(root.join("locked"), Vec::new());
+        unreadable.skipped = 1;
+        let mut root = folder(
+            root.clone(),
+            vec![
+                folder(inner.clone(), vec![sparse, link, file(&inner, "ünïcode ☃.txt".into(), 7)]),
+                file(&root, "a.log".into(), 100),
+                unreadable,
+            ],
+        );
+        root.skipped = 1;
+        root.update_counts();
+        root
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let root = sample_tree();
+        let scanned_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_123);
+        let snapshot = temp_snapshot("round-trip");
+        save(&snapshot.0, &root, scanned_at).unwrap();
+
+        let header = load_header(&snapshot.0).unwrap();
+        assert_eq!(header.root, root.path);
+        assert_eq!(header.scanned_at, scanned_at);
+        assert_eq!((header.size, header.files), (root.size, 4));
+
+        let (_, loaded) = load(&snapshot.0).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&root).unwrap());
+        assert_eq!((loaded.files, loaded.folders), (4, 2));
+    }
+
+    #[test]
+    fn encoded_tree_matches_direct_save() {
+        let root = sample_tree();
+        let scanned_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_123);
+        let (direct, encoded) = (temp_snapshot("direct"), temp_snapshot("encoded"));
+        save(&direct.0, &root, scanned_at).unwrap();
+        save_encoded(&encoded.0, &encode(&root).unwrap(), scanned_at).unwrap();
+        assert_eq!(std::fs::read(&direct.0).unwrap(), std::fs::read(&encoded.0).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn names_outside_utf8_survive() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let name = OsString::from_vec(vec![b'b', 0xff, b'.', b'b', b'i', b'n']);
+        let root = folder(PathBuf::from("/raw"), vec![file(Path::new("/raw"), name.clone(), 5)]);
+        let snapshot = temp_snapshot("raw-names");
+        save(&snapshot.0, &root, SystemTime::UNIX_EPOCH).unwrap();
+
+        let (_, loaded) = load(&snapshot.0).unwrap();
+        assert_eq!(loaded.children[0].name, name);
+        assert_eq!(loaded.children[0].path, Path::new("/raw").join(&name));
+    }
+
+    #[test]
+    fn damaged_files_are_errors() {
+        let snapshot = temp_snapshot("damaged");
+        save(&snapshot.0, &sample_tree(), SystemTime::UNIX_EPOCH).unwrap();
+        let bytes = std::fs::read(&snapshot.0).unwrap();
+
+        // Обрезанное сжатое дерево
+        std::fs::write(&snapshot.0, &bytes[..bytes.len() - 20]).unwrap();
+        assert!(load(&snapshot.0).is_err());
+        // Чужой файл
+        std::fs::write(&snapshot.0, b"PK\x03\x04 not a snapshot").unwrap();
+        assert!(load_header(&snapshot.0).is_err());
+        // Снимок более новой версии
+        let mut newer = bytes.clone();
+        newer[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        std::fs::write(&snapshot.0, &newer).unwrap();
+        assert!(load_header(&snapshot.0).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+// Сравнение двух снимков одной папки: дерево изменений с приростом и
+// убылью по каждой папке, новыми и удалёнными элементами. Отвечает на вопрос
+// "что съело 40 ГБ за неделю" без повторного сканирования
+
+use crate::i18n::Translations;
+use crate::palette::Status;
+use crate::pattern::{name_key, same_path};
+use crate::snapshot::{self, SnapshotHeader};
+use crate::snapshot_store;
+use crate::{format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Сколько изменившихся элементов папки показывается
+const MAX_CHILDREN: usize = 200;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Change {
+    Grew,
+    Shrank,
+    New,
+    Deleted,
+}
+
+impl Change {
+    fn all() -> [Change; 4] {
+        [Change::Grew, Change::Shrank, Change::New, Change::Deleted]
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            Change::Grew => regular::TREND_UP,
+            Change::Shrank => regular::TREND_DOWN,
+            Change::New => regular::PLUS_CIRCLE,
+            Change::Deleted => regular::MINUS_CIRCLE,
+        }
+    }
+
+    fn translation_key(self) -> &'static str {
+        match self {
+            Change::Grew => "diff_grew",
+            Change::Shrank => "diff_shrank",
+            Change::New => "diff_new",
+            Change::Deleted => "diff_deleted",
+        }
+    }
+}
+
+/// Элемент дерева изменений; неизменившиеся элементы в него не попадают
+pub struct DiffNode {
+    pub name: OsString,
+    pub is_file: bool,
+    /// Размер в первом снимке (None - элемента тогда не было)
+    pub before: Option<u64>,
+    pub after: Option<u64>,
+    /// Изменившиеся элементы, по величине изменения
+    pub children: Vec<DiffNode>,
+}
+
+impl DiffNode {
+    pub fn delta(&self) -> i128 {
+        self.after.unwrap_or(0) as i128 - self.before.unwrap_or(0) as i128
+    }
+
+    pub fn change(&self) -> Option<Change> {
+        match (self.before, self.after) {
+            (None, _) => Some(Change::New),
+            (_, None) => Some(Change::Deleted),
+            (Some(before), Some(after)) if after > before => Some(Change::Grew),
+            (Some(before), Some(after)) if after < before => Some(Change::Shrank),
+            _ => None,
+        }
+    }
+
+    /// Число новых и удалённых элементов поддерева; удалённая папка считается одной
+    fn count(&self, change: Change) -> u64 {
+        let own = u64::from(self.change() == Some(change));
+        own + self.children.iter().map(|c| c.count(change)).sum::<u64>()
+    }
+}
+
+/// Строит дерево изменений; у отсутствующей стороны None
+fn diff(before: Option<&DirNode>, after: Option<&DirNode>) -> DiffNode {
+    let node = after.or(before).expect("one side is present");
+    let mut children = Vec::new();
+    // Содержимое новой или удалённой папки не разворачивается: изменилась она целиком
+    if let (Some(before), Some(after)) = (before, after) {
+        if !node.is_file {
+            let in_before: HashMap<(String, bool), &DirNode> =
+                before.children.iter().map(|c| ((name_key(&c.display_name()), c.is_file), c)).collect();
+            let mut matched = HashSet::new();
+            for child in &after.children {
+                let key = (name_key(&child.display_name()), child.is_file);
+                children.push(diff(in_before.get(&key).copied(), Some(child)));
+                matched.insert(key);
+            }
+            for child in &before.children {
+                if !matched.contains(&(name_key(&child.display_name()), child.is_file)) {
+                    children.push(diff(Some(child), None));
+                }
+            }
+        }
+    }
+    children.retain(|c| c.change().is_some() || !c.children.is_empty());
+    children.sort_by_key(|c| std::cmp::Reverse(c.delta().unsigned_abs()));
+    DiffNode {
+        name: node.name.clone(),
+        is_file: node.is_file,
+        before: before.map(|n| n.size),
+        after: after.map(|n| n.size),
+        children,
+    }
+}
+
+pub struct SnapshotDiff {
+    pub before: SnapshotHeader,
+    pub after: SnapshotHeader,
+    pub root: DiffNode,
+}
+
+type Loaded = Result<SnapshotDiff, String>;
+
+/// Загружает оба снимка; более ранний становится первым, в каком бы порядке их ни выбрали.
+/// Снимки разных папок не сравниваются: разница была бы "удалено всё и добавлено всё"
+fn load_diff(a: &Path, b: &Path, t: &Translations) -> Loaded {
+    let load = |path: &Path| snapshot::load(path).map_err(|e| format!("{}: {}", path.display(), e));
+    let (mut before, mut after) = (load(a)?, load(b)?);
+    if !same_path(&before.0.root, &after.0.root) {
+        return Err(t.get_fmt(
+            "diff_different_roots",
+            &[&before.0.root.display().to_string(), &after.0.root.display().to_string()],
+        ));
+    }
+    if before.0.scanned_at > after.0.scanned_at {
+        std::mem::swap(&mut before, &mut after);
+    }
+    Ok(SnapshotDiff {
+        root: diff(Some(&before.1), Some(&after.1)),
+        before: before.0,
+        after: after.0,
+    })
+}
+
+/// Окно сравнения снимков: выбор файлов, загрузка в фоне и дерево изменений
+pub struct SnapshotDiffWindow {
+    a: String,
+    b: String,
+    result: Option<Loaded>,
+    /// Результат фоновой загрузки, пока она идёт
+    loading: Option<Arc<Mutex<Option<Loaded>>>>,
+}
+
+impl SnapshotDiffWindow {
+    pub fn new() -> Self {
+        Self {
+            a: String::new(),
+            b: String::new(),
+            result: None,
+            loading: None,
+        }
+    }
+
+    fn start(&mut self, t: &Translations) {
+        let (a, b) = (PathBuf::from(self.a.trim()), PathBuf::from(self.b.trim()));
+        let slot = Arc::new(Mutex::new(None));
+        let thread_slot = slot.clone();
+        let t = t.clone();
+        thread::spawn(move || {
+            let result = load_diff(&a, &b, &t);
+            *thread_slot.lock().unwrap() = Some(result);
+        });
+        self.loading = Some(slot);
+        self.result = None;
+    }
+
+    fn poll(&mut self) {
+        let finished = self.loading.as_ref().and_then(|slot| slot.try_lock().ok().and_then(|mut r| r.take()));
+        if let Some(result) = finished {
+            self.result = Some(result);
+            self.loading = None;
+        }
+    }
+}
+
+/// Поле ввода файла снимка с кнопкой выбора
+fn snapshot_field(ui: &mut egui::Ui, label: &str, path: &mut String, browse: &str) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(path).desired_width(420.0));
+        if ui.button(format!("{} {}", regular::FOLDER_OPEN, browse)).clicked() {
+            let mut dialog = rfd::FileDialog::new().add_filter("Cedar", &[snapshot::EXTENSION]);
+            // Автоматические снимки лежат в хранилище
+            if let Some(dir) = snapshot_store::store_dir().filter(|dir| dir.exists()) {
+                dialog = dialog.set_directory(dir);
+            }
+            if let Some(file) = dialog.pick_file() {
+                *path = file.display().to_string();
+            }
+        }
+    });
+    ui.end_row();
+}
+
+/// Изменение размера со знаком
+fn signed_size(delta: i128) -> String {
+    let size = format_size(delta.unsigned_abs().min(u64::MAX as u128) as u64);
+    match delta.signum() {
+        1 => format!("+{}", size),
+        -1 => format!("−{}", size),
+        _ => size,
+    }
+}
+
+struct DiffStyle {
+    grew: egui::Color32,
+    shrank: egui::Color32,
+}
+
+fn show_diff_node(ui: &mut egui::Ui, node: &DiffNode, path: &Path, style: &DiffStyle, t: &crate::i18n::Translations) {
+    for child in node.children.iter().take(MAX_CHILDREN) {
+        let child_path = path.join(&child.name);
+        let icon = if child.is_file { regular::FILE } else { regular::FOLDER };
+        let color = if child.delta() >= 0 { style.grew } else { style.shrank };
+        let change = child.change().map(|c| c.icon()).unwrap_or(regular::DOT);
+        let header = egui::RichText::new(format!(
+            "{} {} {}   {}",
+            change,
+            icon,
+            child.name.to_string_lossy(),
+            signed_size(child.delta())
+        ))
+        .color(color);
+
+        if child.children.is_empty() {
+            ui.horizontal(|ui| {
+                ui.add_space(ui.spacing().indent);
+                ui.label(header).on_hover_text(child_path.display().to_string());
+            });
+        } else {
+            egui::CollapsingHeader::new(header)
+                .id_salt(&child_path)
+                .show(ui, |ui| show_diff_node(ui, child, &child_path, style, t))
+                .header_response
+                .on_hover_text(child_path.display().to_string());
+        }
+    }
+    if node.children.len() > MAX_CHILDREN {
+        ui.label(
+            egui::RichText::new(t.get_fmt("leftovers_more", &[&t.format_count((node.children.len() - MAX_CHILDREN) as u64)]))
+                .small()
+                .weak(),
+        );
+    }
+}
+
+impl CedarApp {
+    /// Окно сравнения двух снимков
+    pub fn show_snapshot_diff_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.snapshot_diff else {
+            return;
+        };
+        window.poll();
+
+        let mut open = true;
+        let t = &self.translations;
+        let style = DiffStyle {
+            grew: self.config.palette.status_text(Status::Critical, self.config.dark_mode),
+            shrank: self.config.palette.status_text(Status::Good, self.config.dark_mode),
+        };
+        egui::Window::new(format!("{} {}", regular::GIT_DIFF, t.get("diff_snapshots")))
+            .id(egui::Id::new("snapshot_diff_window"))
+            .open(&mut open)
+            .default_size([720.0, 520.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(t.get("diff_snapshots_hint")).weak());
+                egui::Grid::new("diff_snapshots").num_columns(2).show(ui, |ui| {
+                    snapshot_field(ui, "A", &mut window.a, &t.get("browse"));
+                    snapshot_field(ui, "B", &mut window.b, &t.get("browse"));
+                });
+
+                ui.horizontal(|ui| {
+                    let (a, b) = (window.a.trim(), window.b.trim());
+                    let ready = !a.is_empty() && !b.is_empty() && a != b && window.loading.is_none();
+                    let button = egui::Button::new(format!("{} {}", regular::GIT_DIFF, t.get("compare")));
+                    if ui.add_enabled(ready, button).clicked() {
+                        window.start(t);
+                    }
+                    if window.loading.is_some() {
+                        ui.spinner();
+                        ui.label(t.get("diff_loading"));
+                        ui.ctx().request_repaint();
+                    }
+                });
+                ui.separator();
+
+                let diff = match &window.result {
+                    Some(Ok(diff)) => diff,
+                    Some(Err(error)) => {
+                        ui.colored_label(style.grew, error);
+                        return;
+                    }
+                    None => return,
+                };
+
+                let delta = diff.root.delta();
+                ui.strong(
+                    egui::RichText::new(t.get_fmt(
+                        "diff_summary",
+                        &[
+                            &diff.after.root.display().to_string(),
+                            &signed_size(delta),
+                            &t.format_date(diff.before.scanned_at),
+                            &t.format_date(diff.after.scanned_at),
+                        ],
+                    ))
+                    .color(if delta >= 0 { style.grew } else { style.shrank }),
+                );
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} → {}", format_size(diff.before.size), format_size(diff.after.size)));
+                    for change in [Change::New, Change::Deleted] {
+                        ui.separator();
+                        ui.label(format!(
+                            "{} {}: {}",
+                            change.icon(),
+                            t.get(change.translation_key()),
+                            t.format_count(diff.root.count(change))
+                        ));
+                    }
+                });
+                ui.horizontal_wrapped(|ui| {
+                    for change in Change::all() {
+                        ui.label(
+                            egui::RichText::new(format!("{} {}", change.icon(), t.get(change.translation_key())))
+                                .small()
+                                .weak(),
+                        );
+                    }
+                });
+                ui.separator();
+
+                if diff.root.children.is_empty() {
+                    ui.strong(format!("{} {}", regular::CHECK_CIRCLE, t.get("diff_unchanged")));
+                    return;
+                }
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| show_diff_node(ui, &diff.root, &diff.after.root, &style, t));
+            });
+
+        if !open {
+            self.snapshot_diff = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, size: u64) -> DirNode {
+        DirNode::new(PathBuf::from("/root").join(name), name.into(), size, true)
+    }
+
+    fn folder(children: Vec<DirNode>) -> DirNode {
+        let mut node = DirNode::new(PathBuf::from("/root"), "root".into(), 0, false);
+        node.size = children.iter().map(|c| c.size).sum();
+        node.children = children;
+        node
+    }
+
+    fn change_of(diff: &DiffNode, name: &str) -> Option<Change> {
+        diff.children.iter().find(|c| c.name == name).and_then(DiffNode::change)
+    }
+
+    #[test]
+    fn children_are_added_removed_grown_and_shrunk() {
+        let before = folder(vec![file("same", 5), file("grows", 10), file("shrinks", 30), file("removed", 7)]);
+        let after = folder(vec![file("same", 5), file("grows", 25), file("shrinks", 20), file("added", 40)]);
+        let diff = diff(Some(&before), Some(&after));
+
+        assert_eq!(diff.delta(), 90 - 52);
+        assert!(change_of(&diff, "grows") == Some(Change::Grew));
+        assert!(change_of(&diff, "shrinks") == Some(Change::Shrank));
+        assert!(change_of(&diff, "added") == Some(Change::New));
+        assert!(change_of(&diff, "removed") == Some(Change::Deleted));
+        // Неизменившиеся элементы не показываются, остальные - по величине изменения
+        let names: Vec<&OsString> = diff.children.iter().map(|c| &c.name).collect();
+        assert_eq!(names, ["added", "grows", "shrinks", "removed"]);
+        assert_eq!((diff.count(Change::New), diff.count(Change::Deleted)), (1, 1));
+    }
+}
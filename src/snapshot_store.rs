@@ -0,0 +1,544 @@
+// Автоматические снимки: после сканирования папки, для которой задано правило
+// хранения, дерево сохраняется в хранилище снимков в папке данных программы.
+// Старые снимки прореживаются в фоне: за последние дни остаётся по снимку в
+// день, за последние недели - по снимку в неделю, самый свежий - всегда. Так
+// история для сравнения копится сама и не разрастается без предела. Снимки
+// папок, правило которых удалено, остаются, пока их не удалят из настроек
+
+use crate::{format_size, platform, snapshot, CedarApp, DirNode};
+use chrono::{DateTime, Datelike, Local};
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Как часто пересчитывается занятое хранилищем место, пока открыты настройки
+const USAGE_REFRESH: Duration = Duration::from_secs(5);
+/// Метка в папке снимков корня: это сканирования целого диска. По ней снимки
+/// отключённого сейчас диска не принимаются за оставшиеся от удалённых правил
+const DRIVE_MARKER: &str = "drive";
+
+/// Правило хранения снимков одной папки
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    #[serde(with = "crate::os_serde")]
+    pub root: PathBuf,
+    /// Сколько дней хранится по снимку в день
+    pub daily_days: u32,
+    /// Сколько недель хранится по снимку в неделю
+    pub weekly_weeks: u32,
+    /// Правило последнего сканирования диска, а не заданное пользователем
+    #[serde(skip)]
+    pub drive: bool,
+}
+
+impl RetentionPolicy {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, daily_days: 7, weekly_weeks: 52, drive: false }
+    }
+
+    /// Только самый свежий снимок - последнее сканирование диска
+    pub fn latest_only(root: PathBuf) -> Self {
+        Self { root, daily_days: 0, weekly_weeks: 0, drive: true }
+    }
+}
+
+/// Папка хранилища
+pub fn store_dir() -> Option<PathBuf> {
+    crate::app_paths::data_path("snapshots")
+}
+
+/// Папка снимков одного корня: читаемое имя и хеш полного пути (FNV-1a), чтобы одноимённые папки не смешивались
+fn root_dir(root: &Path) -> Option<PathBuf> {
+    let hash = root
+        .as_os_str()
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+    let name: String = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    store_dir().map(|dir| dir.join(format!("{}-{:016x}", name, hash)))
+}
+
+/// Время сканирования из имени файла `<секунды>-<наносекунды>[-<номер>]`;
+/// у снимков старых версий в имени только секунды
+fn parse_name(stem: &str) -> Option<SystemTime> {
+    let mut parts = stem.split('-');
+    let secs = parts.next()?.parse::<u64>().ok()?;
+    let nanos = match parts.next() {
+        Some(nanos) => nanos.parse::<u32>().ok()?,
+        None => 0,
+    };
+    Some(UNIX_EPOCH + Duration::new(secs, nanos))
+}
+
+/// Снимки корня с временем сканирования из имени файла, от новых к старым
+fn list(dir: &Path) -> Vec<(SystemTime, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == snapshot::EXTENSION))
+        .filter_map(|path| Some((parse_name(path.file_stem()?.to_str()?)?, path)))
+        .collect();
+    snapshots.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+    snapshots
+}
+
+/// Какие снимки оставить; снимки упорядочены от новых к старым
+fn keep(times: &[SystemTime], policy: &RetentionPolicy, now: SystemTime) -> HashSet<usize> {
+    let today = DateTime::<Local>::from(now).date_naive();
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    let mut kept = HashSet::from([0]);
+    for (index, &time) in times.iter().enumerate() {
+        let date = DateTime::<Local>::from(time).date_naive();
+        let age = (today - date).num_days().max(0);
+        // Первый встреченный снимок дня или недели - самый свежий в ней
+        if age < i64::from(policy.daily_days) && days.insert(date) {
+            kept.insert(index);
+        }
+        let week = date.iso_week();
+        if age < i64::from(policy.weekly_weeks) * 7 && weeks.insert((week.year(), week.week())) {
+            kept.insert(index);
+        }
+    }
+    kept
+}
+
+/// Удаляет лишние снимки корня; возвращает, сколько удалено
+pub fn prune(policy: &RetentionPolicy) -> io::Result<usize> {
+    let Some(dir) = root_dir(&policy.root) else {
+        return Ok(0);
+    };
+    let snapshots = list(&dir);
+    let times: Vec<SystemTime> = snapshots.iter().map(|(time, _)| *time).collect();
+    let kept = keep(&times, policy, SystemTime::now());
+    let mut removed = 0;
+    for (index, (_, path)) in snapshots.iter().enumerate() {
+        if !kept.contains(&index) {
+            std::fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Файл нового снимка корня в хранилище. Файл сразу создаётся, чтобы два
+/// сохранения в одно и то же время не записали один снимок поверх другого
+fn new_snapshot_path(policy: &RetentionPolicy, scanned_at: SystemTime) -> io::Result<PathBuf> {
+    let dir = root_dir(&policy.root).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data folder"))?;
+    std::fs::create_dir_all(&dir)?;
+    if policy.drive {
+        std::fs::write(dir.join(DRIVE_MARKER), b"")?;
+    }
+    let since_epoch = scanned_at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let stem = format!("{}-{:09}", since_epoch.as_secs(), since_epoch.subsec_nanos());
+    for number in 0.. {
+        let name = match number {
+            0 => format!("{}.{}", stem, snapshot::EXTENSION),
+            _ => format!("{}-{}.{}", stem, number, snapshot::EXTENSION),
+        };
+        let path = dir.join(name);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+/// Сохранённый снимок. Неудачное прореживание не отменяет сохранения
+pub struct Saved {
+    pub path: PathBuf,
+    pub prune_error: Option<io::Error>,
+}
+
+fn save_with(policy: &RetentionPolicy, scanned_at: SystemTime, write: impl FnOnce(&Path) -> io::Result<()>) -> io::Result<Saved> {
+    let path = new_snapshot_path(policy, scanned_at)?;
+    if let Err(e) = write(&path) {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+    let prune_error = prune(policy).err();
+    Ok(Saved { path, prune_error })
+}
+
+/// Сохраняет снимок в хранилище и прореживает старые
+pub fn save(policy: &RetentionPolicy, root: &DirNode, scanned_at: SystemTime) -> io::Result<Saved> {
+    save_with(policy, scanned_at, |path| snapshot::save(path, root, scanned_at))
+}
+
+/// Самый свежий снимок корня и его заголовок
+pub fn latest(root: &Path) -> Option<(PathBuf, snapshot::SnapshotHeader)> {
+    let (_, path) = list(&root_dir(root)?).into_iter().next()?;
+    let header = snapshot::load_header(&path).ok()?;
+    Some((path, header))
+}
+
+/// Правило для отсканированной папки; снимки вложенных папок не делаются
+pub fn policy_for<'a>(policies: &'a [RetentionPolicy], root: &Path) -> Option<&'a RetentionPolicy> {
+    policies.iter().find(|policy| policy.root == root)
+}
+
+/// Сохраняет в фоне дерево, сжатое потоком сканирования
+pub fn save_in_background(policy: RetentionPolicy, encoded: snapshot::Encoded, scanned_at: SystemTime) -> JoinHandle<io::Result<Saved>> {
+    thread::spawn(move || save_with(&policy, scanned_at, |path| snapshot::save_encoded(path, &encoded, scanned_at)))
+}
+
+/// Сколько снимков и места в хранилище
+#[derive(Clone, Copy, Default)]
+pub struct Usage {
+    pub snapshots: u64,
+    pub bytes: u64,
+}
+
+impl Usage {
+    fn add(&mut self, other: Usage) {
+        self.snapshots += other.snapshots;
+        self.bytes += other.bytes;
+    }
+}
+
+fn usage_of(dir: &Path) -> Usage {
+    let mut usage = Usage::default();
+    for (_, path) in list(dir) {
+        usage.snapshots += 1;
+        usage.bytes += std::fs::metadata(&path).map_or(0, |m| m.len());
+    }
+    usage
+}
+
+/// Занятое место: всё хранилище, каждая папка с правилом и снимки папок,
+/// правило которых удалено
+pub struct StoreUsage {
+    measured: Instant,
+    pub total: Usage,
+    roots: Vec<(PathBuf, Usage)>,
+    pub orphaned: Usage,
+    orphaned_dirs: Vec<PathBuf>,
+    keep_drives: bool,
+}
+
+impl StoreUsage {
+    /// `kept` - папки с правилами; `keep_drives` - сканирования дисков тоже нужны,
+    /// в том числе дисков, которые сейчас не подключены
+    fn measure(kept: &[PathBuf], keep_drives: bool) -> Self {
+        let kept_dirs: HashSet<PathBuf> = kept.iter().filter_map(|root| root_dir(root)).collect();
+        let mut usage = Self {
+            keep_drives,
+            measured: Instant::now(),
+            total: Usage::default(),
+            roots: Vec::new(),
+            orphaned: Usage::default(),
+            orphaned_dirs: Vec::new(),
+        };
+        if let Some(Ok(entries)) = store_dir().map(std::fs::read_dir) {
+            for dir in entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()) {
+                let dir_usage = usage_of(&dir);
+                usage.total.add(dir_usage);
+                let drive = keep_drives && dir.join(DRIVE_MARKER).exists();
+                if !kept_dirs.contains(&dir) && !drive {
+                    usage.orphaned.add(dir_usage);
+                    usage.orphaned_dirs.push(dir);
+                }
+            }
+        }
+        usage.roots = kept.iter().map(|root| (root.clone(), root_dir(root).map(|dir| usage_of(&dir)).unwrap_or_default())).collect();
+        usage
+    }
+
+    fn of(&self, root: &Path) -> Option<Usage> {
+        self.roots.iter().find(|(r, _)| r == root).map(|(_, usage)| *usage)
+    }
+}
+
+/// Фоновые задачи хранилища: прореживание, удаление и подсчёт занятого места.
+/// Всё это читает и удаляет файлы, поэтому окно только забирает готовые результаты
+#[derive(Default)]
+pub struct StoreTasks {
+    /// Прореживание и удаление; результат - ошибки по корням
+    cleanups: Vec<JoinHandle<Vec<(PathBuf, io::Error)>>>,
+    measuring: Option<JoinHandle<StoreUsage>>,
+    usage: Option<StoreUsage>,
+    /// Подсчёт не отражает последних изменений; пока идёт новый, показывается старый
+    outdated: bool,
+}
+
+impl StoreTasks {
+    /// Прореживает снимки всех папок, например после изменения правил
+    pub fn prune(&mut self, policies: Vec<RetentionPolicy>) {
+        self.cleanups.push(thread::spawn(move || {
+            policies
+                .iter()
+                .filter_map(|policy| prune(policy).err().map(|error| (policy.root.clone(), error)))
+                .collect()
+        }));
+        self.outdated = true;
+    }
+
+    /// Удаляет снимки папок, правила которых удалены
+    fn delete_orphaned(&mut self) {
+        let Some(usage) = &mut self.usage else {
+            return;
+        };
+        let dirs = std::mem::take(&mut usage.orphaned_dirs);
+        self.outdated = true;
+        self.cleanups.push(thread::spawn(move || {
+            dirs.into_iter()
+                .filter_map(|dir| std::fs::remove_dir_all(&dir).err().map(|error| (dir, error)))
+                .collect()
+        }));
+    }
+
+    /// Забирает законченные задачи; None - ни прореживание, ни удаление не закончились,
+    /// иначе их ошибки
+    fn poll(&mut self) -> Option<Vec<(PathBuf, io::Error)>> {
+        if self.measuring.as_ref().is_some_and(|job| job.is_finished()) {
+            self.usage = self.measuring.take().and_then(|job| job.join().ok());
+        }
+        let (finished, running): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.cleanups).into_iter().partition(|job| job.is_finished());
+        self.cleanups = running;
+        if finished.is_empty() {
+            return None;
+        }
+        self.outdated = true;
+        Some(finished.into_iter().flat_map(|job| job.join().unwrap_or_default()).collect())
+    }
+
+    /// Подсчёт, если прошлый устарел или набор корней другой; пока идёт удаление, не считает
+    fn refresh(&mut self, ctx: &egui::Context, kept: Vec<PathBuf>, keep_drives: bool) {
+        let stale = self.outdated
+            || self.usage.as_ref().is_none_or(|usage| {
+                usage.measured.elapsed() > USAGE_REFRESH
+                    || usage.keep_drives != keep_drives
+                    || usage.roots.len() != kept.len()
+                    || kept.iter().any(|root| usage.of(root).is_none())
+            });
+        if !stale || self.measuring.is_some() || !self.cleanups.is_empty() {
+            return;
+        }
+        self.outdated = false;
+        let ctx = ctx.clone();
+        self.measuring = Some(thread::spawn(move || {
+            let usage = StoreUsage::measure(&kept, keep_drives);
+            ctx.request_repaint();
+            usage
+        }));
+    }
+
+    pub fn invalidate(&mut self) {
+        self.outdated = true;
+    }
+}
+
+fn usage_text(usage: Usage, t: &crate::i18n::Translations) -> String {
+    t.get_fmt("snapshot_store_usage", &[&t.format_count(usage.snapshots), &format_size(usage.bytes)])
+}
+
+impl CedarApp {
+    /// Правило автоматического снимка для корня сканирования; без своего правила
+    /// у целого диска хранится только последнее сканирование
+    pub fn auto_snapshot_policy(&self, root: &Path) -> Option<RetentionPolicy> {
+        let is_drive = self.available_drives.iter().any(|d| Path::new(&d.path) == root);
+        policy_for(&self.config.snapshot_policies, root)
+            .cloned()
+            .or_else(|| (is_drive && self.config.remember_drive_scans).then(|| RetentionPolicy::latest_only(root.to_path_buf())))
+    }
+
+    /// Проверяет фоновое сохранение автоматического снимка и задачи хранилища
+    pub fn poll_auto_snapshot(&mut self) {
+        if let Some(errors) = self.snapshot_store.poll() {
+            for (root, error) in errors {
+                self.log.error(self.translations.get_fmt("snapshot_prune_failed", &[&root.display().to_string(), &error.to_string()]));
+            }
+            // Удалённые снимки могли быть последними сканированиями дисков
+            self.drive_snapshots = None;
+        }
+        if !self.auto_snapshot.as_ref().is_some_and(|job| job.is_finished()) {
+            return;
+        }
+        let Some(job) = self.auto_snapshot.take() else {
+            return;
+        };
+        match job.join() {
+            Ok(Ok(saved)) => {
+                self.log.info(self.translations.get_fmt("auto_snapshot_saved", &[&saved.path.display().to_string()]));
+                if let (Some(error), Some(root)) = (saved.prune_error, saved.path.parent()) {
+                    self.log.error(self.translations.get_fmt("snapshot_prune_failed", &[&root.display().to_string(), &error.to_string()]));
+                }
+            }
+            Ok(Err(e)) => self.log.error(self.translations.get_fmt("snapshot_save_failed", &[&e.to_string()])),
+            Err(_) => {}
+        }
+        self.snapshot_store.invalidate();
+        self.drive_snapshots = None;
+    }
+
+    /// Раздел настроек: автоматические снимки и правила их хранения; возвращает true, если что-то изменилось
+    pub fn show_snapshot_store_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        let t = &self.translations;
+        ui.heading(t.get("auto_snapshots"));
+        ui.label(egui::RichText::new(t.get("auto_snapshots_hint")).small().color(ui.visuals().weak_text_color()));
+        ui.add_space(4.0);
+        let mut changed = ui
+            .checkbox(&mut self.config.remember_drive_scans, t.get("remember_drive_scans"))
+            .on_hover_text(t.get("remember_drive_scans_hint"))
+            .changed();
+        ui.add_space(4.0);
+
+        let kept: Vec<PathBuf> = self.config.snapshot_policies.iter().map(|policy| policy.root.clone()).collect();
+        self.snapshot_store.refresh(ui.ctx(), kept, self.config.remember_drive_scans);
+        let usage = self.snapshot_store.usage.as_ref();
+
+        let mut remove = None;
+        let mut prune_now = false;
+        for (index, policy) in self.config.snapshot_policies.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if crate::a11y::icon_button(ui, regular::X, &t.get("auto_snapshots_remove")).clicked() {
+                    remove = Some(index);
+                }
+                ui.label(format!("{} {}", regular::FOLDER_SIMPLE, policy.root.display()));
+            });
+            ui.indent(index, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t.get("auto_snapshots_daily"));
+                    let days = ui.add(egui::DragValue::new(&mut policy.daily_days).range(0..=365));
+                    ui.label(t.get("auto_snapshots_weekly"));
+                    let weeks = ui.add(egui::DragValue::new(&mut policy.weekly_weeks).range(0..=520));
+                    changed |= days.changed() || weeks.changed();
+                    // Прореживание - когда значение уже выбрано, а не на каждом шаге перетаскивания
+                    prune_now |= days.drag_stopped() || days.lost_focus() || weeks.drag_stopped() || weeks.lost_focus();
+                });
+                if let Some(root_usage) = usage.and_then(|usage| usage.of(&policy.root)) {
+                    ui.label(egui::RichText::new(usage_text(root_usage, t)).small().weak());
+                }
+            });
+        }
+        if let Some(index) = remove {
+            self.config.snapshot_policies.remove(index);
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            let root = self.root_node.as_ref().map(|root| root.path.clone());
+            let can_add = root.as_ref().is_some_and(|root| policy_for(&self.config.snapshot_policies, root).is_none());
+            let button = egui::Button::new(format!("{} {}", regular::PLUS, t.get("auto_snapshots_add")));
+            let response = ui.add_enabled(can_add, button);
+            if let Some(root) = &root {
+                response.clone().on_hover_text(root.display().to_string());
+            }
+            if response.clicked() {
+                if let Some(root) = root {
+                    self.config.snapshot_policies.push(RetentionPolicy::new(root));
+                    changed = true;
+                }
+            }
+        });
+        let mut delete_orphaned = false;
+        ui.horizontal(|ui| {
+            match usage {
+                Some(usage) => ui.label(format!("{} {}", regular::DATABASE, usage_text(usage.total, t))),
+                None => ui.spinner(),
+            };
+            if let Some(dir) = store_dir().filter(|dir| dir.exists()) {
+                if crate::a11y::icon_button(ui, regular::FOLDER_OPEN, &t.get("auto_snapshots_open_store")).clicked() {
+                    if let Err(e) = platform::reveal_in_file_manager(&dir) {
+                        self.log.error(format!("{}: {}", dir.display(), e));
+                    }
+                }
+            }
+        });
+
+        if let Some(usage) = usage.filter(|usage| !usage.orphaned_dirs.is_empty()) {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(t.get_fmt("auto_snapshots_orphaned", &[&usage_text(usage.orphaned, t)])).small().weak());
+                if crate::a11y::icon_button(ui, regular::TRASH, &t.get("auto_snapshots_delete_orphaned")).clicked() {
+                    delete_orphaned = true;
+                }
+            });
+        }
+
+        if delete_orphaned {
+            self.snapshot_store.delete_orphaned();
+        }
+        if prune_now {
+            self.snapshot_store.prune(self.config.snapshot_policies.clone());
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> SystemTime {
+        Local.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap().into()
+    }
+
+    fn policy(daily_days: u32, weekly_weeks: u32) -> RetentionPolicy {
+        RetentionPolicy { daily_days, weekly_weeks, ..RetentionPolicy::new(PathBuf::new()) }
+    }
+
+    fn kept(times: &[SystemTime], policy: &RetentionPolicy, now: SystemTime) -> Vec<usize> {
+        let mut kept: Vec<usize> = keep(times, policy, now).into_iter().collect();
+        kept.sort_unstable();
+        kept
+    }
+
+    #[test]
+    fn one_snapshot_per_day() {
+        let times = [at(2025, 1, 10, 11), at(2025, 1, 10, 10), at(2025, 1, 9, 20), at(2025, 1, 9, 8)];
+        assert_eq!(kept(&times, &policy(7, 0), at(2025, 1, 10, 12)), [0, 2]);
+    }
+
+    #[test]
+    fn daily_window_ends_after_the_last_day() {
+        let times = [at(2025, 1, 10, 9), at(2025, 1, 9, 9), at(2025, 1, 8, 9), at(2025, 1, 7, 9)];
+        assert_eq!(kept(&times, &policy(3, 0), at(2025, 1, 10, 12)), [0, 1, 2]);
+    }
+
+    #[test]
+    fn weekly_window_ends_after_the_last_week() {
+        // 15 января - среда; 12-го начинается предыдущая неделя, 1-е уже за окном в две недели
+        let times = [at(2025, 1, 15, 9), at(2025, 1, 13, 9), at(2025, 1, 12, 9), at(2025, 1, 2, 9), at(2025, 1, 1, 9)];
+        assert_eq!(kept(&times, &policy(0, 2), at(2025, 1, 15, 12)), [0, 2, 3]);
+    }
+
+    #[test]
+    fn weeks_cross_the_year_boundary() {
+        // 30 декабря 2024 - понедельник первой ISO-недели 2025 года, 29-е - последняя неделя 2024-го
+        let times = [at(2025, 1, 2, 9), at(2024, 12, 30, 9), at(2024, 12, 29, 9)];
+        assert_eq!(kept(&times, &policy(0, 4), at(2025, 1, 3, 12)), [0, 2]);
+    }
+
+    #[test]
+    fn newest_snapshot_is_always_kept() {
+        let times = [at(2020, 6, 1, 9), at(2020, 5, 1, 9)];
+        assert_eq!(kept(&times, &policy(7, 52), at(2025, 1, 3, 12)), [0]);
+        let times = [at(2025, 1, 3, 9), at(2025, 1, 2, 9)];
+        assert_eq!(kept(&times, &policy(0, 0), at(2025, 1, 3, 12)), [0]);
+    }
+
+    #[test]
+    fn file_names_with_and_without_nanoseconds() {
+        assert_eq!(parse_name("1700000000"), Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+        assert_eq!(parse_name("1700000000-000000005-2"), Some(UNIX_EPOCH + Duration::new(1_700_000_000, 5)));
+        assert_eq!(parse_name("notes"), None);
+    }
+}
@@ -0,0 +1,152 @@
+// Сводка сканирования в строке состояния: размер, число элементов, ошибки,
+// время последнего сканирования и активные фильтры. Каждая плашка кликабельна
+
+use crate::panels::Panel;
+use crate::{format_size, CedarApp};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::time::{Duration, SystemTime};
+
+/// Плашка-кнопка со значком
+fn chip(ui: &mut egui::Ui, icon: &str, text: &str, color: Option<egui::Color32>) -> egui::Response {
+    let mut text = egui::RichText::new(format!("{} {}", icon, text)).small();
+    if let Some(color) = color {
+        text = text.color(color);
+    }
+    ui.add(egui::Button::new(text).rounding(8.0).min_size(egui::vec2(0.0, 18.0)))
+}
+
+impl CedarApp {
+    /// Сколько времени прошло с последнего сканирования
+    pub fn scan_age_text(&self, finished: SystemTime) -> String {
+        let minutes = finished.elapsed().unwrap_or_default().as_secs() / 60;
+        match minutes {
+            0 => self.translations.get("scanned_just_now"),
+            1..=59 => self.translations.get_fmt("scanned_minutes_ago", &[&minutes.to_string()]),
+            60..=1439 => self.translations.get_fmt("scanned_hours_ago", &[&(minutes / 60).to_string()]),
+            _ => self.translations.get_fmt("scanned_days_ago", &[&(minutes / 1440).to_string()]),
+        }
+    }
+
+    /// Условия фильтра сканирования одной строкой
+    fn scan_filter_text(&self) -> String {
+        let filter = &self.config.scan_filter;
+        let mut parts = Vec::new();
+        if !filter.patterns.trim().is_empty() {
+            parts.push(filter.patterns.trim().to_string());
+        }
+        if filter.min_size_mb > 0 {
+            parts.push(format!("≥ {} MB", filter.min_size_mb));
+        }
+        if filter.min_age_days > 0 {
+            parts.push(format!("≥ {} {}", filter.min_age_days, self.translations.get("days")));
+        }
+        parts.join(", ")
+    }
+
+    /// Плашки сводки; рисуются справа налево
+    pub fn show_status_chips(&mut self, ui: &mut egui::Ui) {
+        let weak = ui.visuals().weak_text_color();
+
+        if self.audit_mode() {
+            let color = self.config.palette.status_text(crate::palette::Status::Good, self.config.dark_mode);
+            chip(ui, regular::SHIELD_CHECK, &self.translations.get("audit_mode"), Some(color))
+                .on_hover_text(self.translations.get("audit_mode_hint"));
+        }
+        if self.imported_tree {
+            chip(ui, regular::LOCK, &self.translations.get("imported_read_only"), None)
+                .on_hover_text(self.translations.get("imported_read_only_hint"));
+        }
+
+        let exclusion_count = crate::policy::exclusions(&self.config).len();
+        if exclusion_count > 0 {
+            let text = self.translations.get_fmt("chip_exclusions", &[&exclusion_count.to_string()]);
+            let mut hint = self.translations.get("chip_exclusions_hint");
+            if let Some(savings) = self.exclusion_savings_text() {
+                hint = format!("{}\n{}", savings, hint);
+            }
+            if chip(ui, regular::PROHIBIT, &text, Some(weak))
+                .on_hover_text(hint)
+                .clicked()
+            {
+                self.show_settings = true;
+            }
+        }
+
+        if self.config.scan_filter.is_active() {
+            let text = self.scan_filter_text();
+            let color = ui.visuals().selection.stroke.color;
+            if chip(ui, regular::FUNNEL, &text, Some(color))
+                .on_hover_text(self.translations.get("chip_filter_hint"))
+                .clicked()
+            {
+                self.config.scan_filter = Default::default();
+                self.save_config_to_file();
+            }
+        }
+
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        let root_path = root.path.clone();
+        let root_size = root.size;
+        let skipped = root.skipped;
+
+        if let Some(finished) = self.last_scan_finished {
+            let mut hint = self.translations.get("chip_rescan_hint");
+            if let Some(duration) = self.last_scan_duration {
+                hint = format!(
+                    "{}\n{}",
+                    self.translations.get_fmt(
+                        "chip_scan_took",
+                        &[&format!("{:.2}", duration.as_secs_f64()), &format!("{:.1}", self.scan_speed_mbps)],
+                    ),
+                    hint
+                );
+            }
+            let enabled = !self.is_scanning;
+            let response = ui
+                .add_enabled_ui(enabled, |ui| chip(ui, regular::CLOCK, &self.scan_age_text(finished), Some(weak)))
+                .inner
+                .on_hover_text(hint);
+            if response.clicked() {
+                self.start_scan(root_path.display().to_string());
+            }
+            // Подпись "N минут назад" должна обновляться и без движения мыши
+            ui.ctx().request_repaint_after(Duration::from_secs(30));
+        }
+
+        if skipped > 0 {
+            let color = self.config.palette.status_text(crate::palette::Status::Warning, self.config.dark_mode);
+            let text = self.translations.get_fmt("chip_errors", &[&skipped.to_string()]);
+            if chip(ui, regular::WARNING, &text, Some(color))
+                .on_hover_text(self.translations.get("chip_errors_hint"))
+                .clicked()
+            {
+                self.config.panels.open(Panel::Errors);
+                self.save_config_to_file();
+            }
+        }
+
+        if let Some(data) = self.panel_data() {
+            let (files, folders) = (data.files.to_string(), data.folders.to_string());
+            let text = self.translations.get_fmt("chip_items", &[&files, &folders]);
+            if chip(ui, regular::FILES, &text, None)
+                .on_hover_text(self.translations.get("chip_items_hint"))
+                .clicked()
+            {
+                self.config.panels.open(Panel::TopFiles);
+                self.save_config_to_file();
+            }
+        }
+
+        if chip(ui, regular::HARD_DRIVES, &format_size(root_size), None)
+            .on_hover_text(self.translations.get("chip_size_hint"))
+            .clicked()
+        {
+            self.selected_path = Some(root_path);
+            self.config.panels.open(Panel::Details);
+            self.save_config_to_file();
+        }
+    }
+}
@@ -0,0 +1,302 @@
+// Рекомендации по очистке в духе "Контроля памяти" Windows: старые загрузки,
+// предыдущие установки Windows, кэш оптимизации доставки и временные файлы.
+// Эти места измеряются отдельно от сканирования - их может не быть в дереве
+
+use crate::{format_size, CedarApp};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+const DAY: u64 = 24 * 60 * 60;
+/// Загрузки старше этого считаются забытыми
+const DOWNLOADS_AGE: Duration = Duration::from_secs(30 * DAY);
+/// Более свежие временные файлы могут быть ещё открыты
+const TEMP_AGE: Duration = Duration::from_secs(DAY);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Category {
+    OldDownloads,
+    PreviousWindows,
+    DeliveryOptimization,
+    TempFiles,
+}
+
+impl Category {
+    /// Категории, имеющие смысл на этой ОС
+    fn all() -> Vec<Category> {
+        if cfg!(windows) {
+            vec![
+                Category::OldDownloads,
+                Category::PreviousWindows,
+                Category::DeliveryOptimization,
+                Category::TempFiles,
+            ]
+        } else {
+            vec![Category::OldDownloads, Category::TempFiles]
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            Category::OldDownloads => regular::DOWNLOAD_SIMPLE,
+            Category::PreviousWindows => regular::WINDOWS_LOGO,
+            Category::DeliveryOptimization => regular::CLOUD_ARROW_DOWN,
+            Category::TempFiles => regular::HOURGLASS,
+        }
+    }
+
+    fn translation_key(self) -> &'static str {
+        match self {
+            Category::OldDownloads => "sense_old_downloads",
+            Category::PreviousWindows => "sense_previous_windows",
+            Category::DeliveryOptimization => "sense_delivery_optimization",
+            Category::TempFiles => "sense_temp_files",
+        }
+    }
+
+    fn hint_key(self) -> &'static str {
+        match self {
+            Category::OldDownloads => "sense_old_downloads_hint",
+            Category::PreviousWindows => "sense_previous_windows_hint",
+            Category::DeliveryOptimization => "sense_delivery_optimization_hint",
+            Category::TempFiles => "sense_temp_files_hint",
+        }
+    }
+
+    /// Папки, содержимое которых относится к категории.
+    /// Для предыдущих установок Windows удаляются сами папки, а не их содержимое
+    fn locations(self) -> Vec<PathBuf> {
+        let windows = std::env::var_os("WINDIR").map(PathBuf::from);
+        let system_drive = std::env::var("SystemDrive").ok().map(|d| PathBuf::from(format!("{}\\", d)));
+        match self {
+            Category::OldDownloads => dirs::download_dir().into_iter().collect(),
+            Category::PreviousWindows => system_drive
+                .map(|drive| ["Windows.old", "$Windows.~BT", "$Windows.~WS"].map(|name| drive.join(name)).to_vec())
+                .unwrap_or_default(),
+            Category::DeliveryOptimization => windows
+                .map(|windows| {
+                    vec![
+                        windows.join(r"ServiceProfiles\NetworkService\AppData\Local\Microsoft\Windows\DeliveryOptimization\Cache"),
+                        windows.join(r"SoftwareDistribution\DeliveryOptimization"),
+                    ]
+                })
+                .unwrap_or_default(),
+            Category::TempFiles => {
+                let mut temp = vec![std::env::temp_dir()];
+                temp.extend(windows.map(|windows| windows.join("Temp")));
+                temp.dedup();
+                temp
+            }
+        }
+    }
+
+    /// Минимальный возраст последнего изменения внутри элемента
+    fn min_age(self) -> Duration {
+        match self {
+            Category::OldDownloads => DOWNLOADS_AGE,
+            Category::TempFiles => TEMP_AGE,
+            Category::PreviousWindows | Category::DeliveryOptimization => Duration::ZERO,
+        }
+    }
+
+    /// Элементы к очистке с размерами
+    fn collect(self, now: SystemTime) -> Vec<(PathBuf, u64)> {
+        let mut items = Vec::new();
+        for location in self.locations() {
+            if self == Category::PreviousWindows {
+                if location.is_dir() {
+                    let (size, _) = measure(&location);
+                    items.push((location, size));
+                }
+                continue;
+            }
+            let Ok(entries) = std::fs::read_dir(&location) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let (size, newest) = measure(&path);
+                let age = newest.and_then(|t| now.duration_since(t).ok()).unwrap_or_default();
+                if age >= self.min_age() {
+                    items.push((path, size));
+                }
+            }
+        }
+        items.sort_unstable_by_key(|(_, size)| std::cmp::Reverse(*size));
+        items
+    }
+}
+
+/// Размер и самое позднее изменение внутри; ссылки не раскрываются
+pub fn measure(path: &Path) -> (u64, Option<SystemTime>) {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return (0, None);
+    };
+    let mut size = 0;
+    let mut newest = metadata.modified().ok();
+    if metadata.is_dir() {
+        for entry in std::fs::read_dir(path).into_iter().flatten().flatten() {
+            let (child_size, child_newest) = measure(&entry.path());
+            size += child_size;
+            newest = newest.max(child_newest);
+        }
+    } else {
+        size = metadata.len();
+    }
+    (size, newest)
+}
+
+pub struct Recommendation {
+    category: Category,
+    items: Vec<(PathBuf, u64)>,
+    size: u64,
+}
+
+/// Результаты последнего измерения; считаются в фоне
+#[derive(Default)]
+pub struct StorageSense {
+    /// Сюда фоновый поток кладёт готовый результат
+    incoming: Arc<Mutex<Option<Vec<Recommendation>>>>,
+    recommendations: Option<Vec<Recommendation>>,
+    measuring: bool,
+    /// Раскрытые списки элементов
+    expanded: Vec<usize>,
+}
+
+impl StorageSense {
+    fn measure(&mut self, ctx: &egui::Context) {
+        self.measuring = true;
+        self.recommendations = None;
+        self.expanded.clear();
+        let results = self.incoming.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let now = SystemTime::now();
+            let recommendations = Category::all()
+                .into_iter()
+                .map(|category| {
+                    let items = category.collect(now);
+                    let size = items.iter().map(|(_, size)| size).sum();
+                    Recommendation { category, items, size }
+                })
+                .collect();
+            *results.lock().unwrap() = Some(recommendations);
+            ctx.request_repaint();
+        });
+    }
+
+    fn receive(&mut self) {
+        if let Some(recommendations) = self.incoming.lock().unwrap().take() {
+            self.recommendations = Some(recommendations);
+            self.measuring = false;
+        }
+    }
+}
+
+impl CedarApp {
+    /// Панель рекомендаций по очистке
+    pub fn show_recommendations_panel(&mut self, ui: &mut egui::Ui) {
+        self.storage_sense.receive();
+        if self.storage_sense.recommendations.is_none() && !self.storage_sense.measuring {
+            // Первое открытие панели
+            self.storage_sense.measure(ui.ctx());
+        }
+
+        ui.horizontal(|ui| {
+            if let Some(recommendations) = &self.storage_sense.recommendations {
+                let total: u64 = recommendations.iter().map(|r| r.size).sum();
+                ui.strong(self.translations.get_fmt("sense_total", &[&format_size(total)]));
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.add_enabled_ui(!self.storage_sense.measuring, |ui| {
+                    if crate::a11y::icon_button(ui, regular::ARROWS_CLOCKWISE, &self.translations.get("sense_refresh"))
+                        .clicked()
+                    {
+                        self.storage_sense.measure(ui.ctx());
+                    }
+                });
+            });
+        });
+
+        let Some(recommendations) = &self.storage_sense.recommendations else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.weak(self.translations.get("sense_measuring"));
+            });
+            return;
+        };
+
+        let mut to_basket = None;
+        egui::ScrollArea::vertical()
+            .id_salt("recommendations_panel")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (index, recommendation) in recommendations.iter().enumerate() {
+                    let category = recommendation.category;
+                    ui.add_space(4.0);
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(category.icon()).size(18.0));
+                        ui.strong(self.translations.get(category.translation_key()));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(format_size(recommendation.size));
+                        });
+                    });
+                    ui.label(
+                        egui::RichText::new(self.translations.get(category.hint_key()))
+                            .small()
+                            .color(ui.visuals().weak_text_color()),
+                    );
+
+                    if recommendation.items.is_empty() {
+                        ui.weak(self.translations.get("sense_nothing"));
+                        ui.separator();
+                        continue;
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(format!("{} {}", regular::BASKET, self.translations.get("add_to_basket")))
+                            .clicked()
+                        {
+                            to_basket = Some(index);
+                        }
+                        let expanded = self.storage_sense.expanded.contains(&index);
+                        let count = recommendation.items.len().to_string();
+                        let toggle = self.translations.get_fmt("sense_items", &[&count]);
+                        if ui.selectable_label(expanded, toggle).clicked() {
+                            if expanded {
+                                self.storage_sense.expanded.retain(|&i| i != index);
+                            } else {
+                                self.storage_sense.expanded.push(index);
+                            }
+                        }
+                    });
+
+                    if self.storage_sense.expanded.contains(&index) {
+                        let copy_label = self.translations.get("copy_path");
+                        for (path, size) in &recommendation.items {
+                            ui.horizontal(|ui| {
+                                let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+                                let response = ui.add(egui::Label::new(name).truncate());
+                                crate::clipboard::path_tooltip(response, path, &copy_label);
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.weak(format_size(*size));
+                                });
+                            });
+                        }
+                    }
+                    ui.separator();
+                }
+            });
+
+        // Удаление идёт через корзину подготовки: там элементы ещё раз просматриваются
+        if let Some(index) = to_basket {
+            for (path, _) in &recommendations[index].items {
+                self.basket.add(path.clone());
+            }
+            self.show_basket = true;
+        }
+    }
+}
@@ -0,0 +1,422 @@
+// Представление "кольца" (sunburst, как в Baobab): фокус - круг в центре,
+// каждый следующий уровень дерева - кольцо вокруг, длина дуги пропорциональна размеру
+
+use crate::chart::{self, ChartStyle, Label, Merged, LABEL_SIZE};
+use crate::export::{escape, hex};
+use crate::icicle::{breadcrumb_bar, name_hue};
+use crate::{format_size, CedarApp, DirNode, NodeActions, NodeMenu};
+use eframe::egui::{self, Color32};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Сколько колец рисуется вокруг фокуса
+const MAX_RINGS: usize = 6;
+/// Дуги короче этого по внешнему краю не рисуются
+const MIN_ARC_LENGTH: f32 = 2.0;
+/// Более короткие дуги одной папки собираются в один сектор с их числом и общим размером
+const MIN_MERGE_ARC: f32 = 6.0;
+/// Шаг разбиения дуги на отрезки при отрисовке
+const ARC_STEP: f32 = 4.0;
+const MARGIN: f32 = 8.0;
+
+/// Геометрия сектора кольца; углы в радианах по часовой стрелке от верха
+#[derive(Clone, Copy)]
+pub struct Sector {
+    pub start: f32,
+    pub end: f32,
+    pub inner: f32,
+    pub outer: f32,
+}
+
+impl Sector {
+    fn point(center: egui::Pos2, radius: f32, angle: f32) -> egui::Pos2 {
+        center + radius * egui::vec2((angle - FRAC_PI_2).cos(), (angle - FRAC_PI_2).sin())
+    }
+
+    /// Контур сектора: внешняя дуга вперёд, внутренняя назад
+    fn outline(&self, center: egui::Pos2) -> Vec<egui::Pos2> {
+        let steps = ((self.end - self.start) * self.outer / ARC_STEP).ceil().max(1.0) as usize;
+        let angle = |i: usize| self.start + (self.end - self.start) * i as f32 / steps as f32;
+        let mut points: Vec<_> = (0..=steps).map(|i| Self::point(center, self.outer, angle(i))).collect();
+        points.extend((0..=steps).rev().map(|i| Self::point(center, self.inner, angle(i))));
+        points
+    }
+
+    fn is_center(&self) -> bool {
+        self.inner == 0.0
+    }
+
+    fn contains(&self, center: egui::Pos2, pos: egui::Pos2) -> bool {
+        let offset = pos - center;
+        let radius = offset.length();
+        let angle = (offset.y.atan2(offset.x) + FRAC_PI_2).rem_euclid(TAU);
+        radius >= self.inner && radius < self.outer && (self.is_center() || (angle >= self.start && angle < self.end))
+    }
+
+    /// Сектор как полоса треугольников между внешней и внутренней дугой
+    fn mesh(&self, center: egui::Pos2, fill: Color32) -> egui::Mesh {
+        let outline = self.outline(center);
+        let mut mesh = egui::Mesh::default();
+        let per_arc = outline.len() / 2;
+        for (i, &pos) in outline.iter().enumerate() {
+            mesh.colored_vertex(pos, fill);
+            if i + 1 < per_arc {
+                let inner = (outline.len() - 1 - i) as u32;
+                mesh.add_triangle(i as u32, i as u32 + 1, inner);
+                mesh.add_triangle(i as u32 + 1, inner - 1, inner);
+            }
+        }
+        mesh
+    }
+
+    /// Обводка сектора поверх отрисованных
+    fn highlight(&self, painter: &egui::Painter, center: egui::Pos2, stroke: egui::Stroke) {
+        if self.is_center() {
+            painter.circle_stroke(center, self.outer, stroke);
+        } else {
+            painter.add(egui::Shape::closed_line(self.outline(center), stroke));
+        }
+    }
+}
+
+/// Сектор элемента дерева
+pub struct Segment<'a> {
+    /// У объединённого сектора - папка, чьи элементы в нём собраны
+    pub node: &'a DirNode,
+    pub sector: Sector,
+    pub fill: Color32,
+    pub merged: Option<Merged>,
+}
+
+/// Раскладка в кольца: вложенные сектора идут после родителей
+pub struct Sunburst<'a> {
+    pub center: egui::Pos2,
+    pub segments: Vec<Segment<'a>>,
+}
+
+/// Глубина поддерева, но не больше `limit`
+fn depth(node: &DirNode, limit: usize) -> usize {
+    if limit == 0 {
+        return 0;
+    }
+    node.children.iter().map(|child| 1 + depth(child, limit - 1)).max().unwrap_or(0)
+}
+
+/// Состояние одного прохода раскладки
+struct SunburstLayout<'a, 's> {
+    segments: Vec<Segment<'a>>,
+    style: &'s ChartStyle,
+    /// Толщина кольца и радиус центра
+    ring: f32,
+}
+
+fn layout_node<'a>(layout: &mut SunburstLayout<'a, '_>, node: &'a DirNode, start: f32, sweep: f32, depth: usize, hue: f32) {
+    let outer = (depth + 1) as f32 * layout.ring;
+    if depth > MAX_RINGS || sweep * outer < MIN_ARC_LENGTH {
+        return;
+    }
+    layout.segments.push(Segment {
+        node,
+        sector: Sector {
+            start,
+            end: start + sweep,
+            inner: depth as f32 * layout.ring,
+            outer,
+        },
+        fill: layout.style.fill(node, hue, depth),
+        merged: None,
+    });
+
+    if node.size == 0 {
+        return;
+    }
+    let child_sweep = |child: &DirNode| sweep * (child.size as f64 / node.size as f64) as f32;
+    let child_outer = (depth + 2) as f32 * layout.ring;
+    // Мелкие дети идут одним сектором в конце, если их больше одного
+    let (small, large): (Vec<&DirNode>, Vec<&DirNode>) =
+        node.children.iter().partition(|child| child_sweep(child) * child_outer < MIN_MERGE_ARC);
+    let (shown, merged) = if small.len() > 1 {
+        (large, Some(Merged::of(&small)))
+    } else {
+        (node.children.iter().collect(), None)
+    };
+
+    let mut child_start = start;
+    for child in shown {
+        let child_sweep = child_sweep(child);
+        // На первом кольце каждая ветка получает свой оттенок
+        let child_hue = if depth == 0 { name_hue(&child.display_name()) } else { hue };
+        layout_node(layout, child, child_start, child_sweep, depth + 1, child_hue);
+        child_start += child_sweep;
+    }
+    if let Some(merged) = merged {
+        let merged_sweep = start + sweep - child_start;
+        if depth < MAX_RINGS && merged_sweep * child_outer >= MIN_ARC_LENGTH {
+            layout.segments.push(Segment {
+                node,
+                sector: Sector {
+                    start: child_start,
+                    end: start + sweep,
+                    inner: outer,
+                    outer: child_outer,
+                },
+                fill: layout.style.merged_fill(),
+                merged: Some(merged),
+            });
+        }
+    }
+}
+
+/// Раскладка поддерева `focus` в кольца, вписанные в `rect`
+pub fn layout<'a>(focus: &'a DirNode, rect: egui::Rect, style: &ChartStyle) -> Sunburst<'a> {
+    let radius = (rect.width().min(rect.height()) / 2.0 - MARGIN).max(0.0);
+    // Центр и кольца одной толщины; мелкое дерево не оставляет пустых колец
+    let mut layout = SunburstLayout {
+        segments: Vec::new(),
+        style,
+        ring: radius / (depth(focus, MAX_RINGS) + 1) as f32,
+    };
+    if layout.ring > 0.0 {
+        layout_node(&mut layout, focus, 0.0, TAU, 0, name_hue(&focus.display_name()));
+    }
+    Sunburst {
+        center: rect.center(),
+        segments: layout.segments,
+    }
+}
+
+impl Sunburst<'_> {
+    /// Подписи: имя и размер в центре, имена на секторах, где они помещаются при любом повороте
+    fn labels(&self, text_width: &dyn Fn(&str) -> f32) -> Vec<(egui::Pos2, String)> {
+        let mut labels = Vec::new();
+        for segment in &self.segments {
+            let sector = &segment.sector;
+            if sector.is_center() {
+                labels.push((self.center, format!("{}\n{}", segment.node.display_name(), format_size(segment.node.size))));
+                continue;
+            }
+            let middle = (sector.inner + sector.outer) / 2.0;
+            let arc = (sector.end - sector.start) * middle;
+            let name = match &segment.merged {
+                Some(merged) => merged.label().into(),
+                None => segment.node.display_name(),
+            };
+            let room = arc.min(sector.outer - sector.inner) - 4.0;
+            if room >= LABEL_SIZE && text_width(&name) <= room {
+                labels.push((Sector::point(self.center, middle, (sector.start + sector.end) / 2.0), name.into_owned()));
+            }
+        }
+        labels
+    }
+
+    /// Заливка секторов в растровый слой; границы - цветом фона
+    fn rasterize(&self, pixmap: &mut tiny_skia::Pixmap, transform: tiny_skia::Transform, style: &ChartStyle) {
+        let separator = chart::fill_paint(style.background());
+        let stroke = tiny_skia::Stroke::default();
+        for segment in &self.segments {
+            let sector = &segment.sector;
+            let path = if sector.is_center() {
+                tiny_skia::PathBuilder::from_circle(self.center.x, self.center.y, sector.outer)
+            } else {
+                let mut builder = tiny_skia::PathBuilder::new();
+                for (i, pos) in sector.outline(self.center).into_iter().enumerate() {
+                    if i == 0 {
+                        builder.move_to(pos.x, pos.y);
+                    } else {
+                        builder.line_to(pos.x, pos.y);
+                    }
+                }
+                builder.close();
+                builder.finish()
+            };
+            let Some(path) = path else {
+                continue;
+            };
+            pixmap.fill_path(&path, &chart::fill_paint(segment.fill), tiny_skia::FillRule::Winding, transform, None);
+            pixmap.stroke_path(&path, &separator, &stroke, transform, None);
+        }
+    }
+
+    /// SVG-элементы секторов и подписей; ширина текста оценивается по числу символов
+    pub fn write_svg(&self, svg: &mut String, style: &ChartStyle) {
+        let background = hex(style.background());
+        for segment in &self.segments {
+            let sector = &segment.sector;
+            if sector.is_center() {
+                let _ = writeln!(
+                    svg,
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}" stroke="{}"/>"#,
+                    self.center.x,
+                    self.center.y,
+                    sector.outer,
+                    hex(segment.fill),
+                    background,
+                );
+                continue;
+            }
+            let mut path = String::new();
+            for (i, pos) in sector.outline(self.center).iter().enumerate() {
+                let _ = write!(path, "{}{:.1},{:.1} ", if i == 0 { "M" } else { "L" }, pos.x, pos.y);
+            }
+            let _ = writeln!(svg, r#"<path d="{}Z" fill="{}" stroke="{}"/>"#, path, hex(segment.fill), background);
+        }
+
+        let width = |text: &str| text.chars().count() as f32 * LABEL_SIZE * 0.55;
+        for (pos, text) in self.labels(&width) {
+            let lines: Vec<&str> = text.lines().collect();
+            let first = pos.y - (lines.len() - 1) as f32 * LABEL_SIZE * 0.6;
+            for (i, line) in lines.iter().enumerate() {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    pos.x,
+                    first + i as f32 * LABEL_SIZE * 1.2,
+                    hex(style.text_color()),
+                    escape(line),
+                );
+            }
+        }
+    }
+}
+
+impl CedarApp {
+    /// Кольца текущего дерева
+    pub fn show_sunburst_view(&mut self, ui: &mut egui::Ui) {
+        let read_only = self.read_only();
+        let Some(root) = &self.root_node else {
+            return;
+        };
+
+        // Фокус общий с другими графическими представлениями; удалённый или чужой путь сбрасывает его на корень
+        let focus = self
+            .view_focus
+            .as_ref()
+            .and_then(|p| root.find(p))
+            .unwrap_or(root);
+
+        let mut new_focus = breadcrumb_bar(ui, root, &focus.path, &self.translations.get("sunburst_hint"));
+
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::click());
+        let style = self.chart_style();
+        let key = chart::LayerKey::new(&focus.path, rect, ui.ctx().pixels_per_point(), &style);
+        if !self.chart_layers.sunburst.as_ref().is_some_and(|layer| layer.is_current(&key)) {
+            let sunburst = layout(focus, rect, &style);
+            let areas = sunburst
+                .segments
+                .iter()
+                .map(|s| chart::Area {
+                    shape: s.sector,
+                    path: s.node.path.clone(),
+                    merged: s.merged.clone(),
+                })
+                .collect();
+            let font = egui::FontId::proportional(LABEL_SIZE);
+            let width = |text: &str| ui.fonts(|f| f.layout_no_wrap(text.to_string(), font.clone(), style.text_color()).size().x);
+            let labels = sunburst
+                .labels(&width)
+                .into_iter()
+                .map(|(pos, text)| {
+                    let label = Label {
+                        pos,
+                        align: egui::Align2::CENTER_CENTER,
+                        text,
+                    };
+                    (rect, label)
+                })
+                .collect();
+            self.chart_layers.sunburst = chart::ChartLayer::new(ui.ctx(), key, areas, labels, |pixmap, transform| {
+                sunburst.rasterize(pixmap, transform, &style)
+            });
+        }
+        let Some(layer) = &self.chart_layers.sunburst else {
+            return;
+        };
+        let painter = ui.painter_at(rect);
+        layer.paint(&painter, &style);
+
+        // Наведённый сектор подсвечивается поверх слоя
+        let center = rect.center();
+        let hovered = response
+            .hover_pos()
+            .and_then(|pos| layer.areas.iter().rfind(|area| area.shape.contains(center, pos)));
+        if let Some(area) = hovered {
+            let sector = &area.shape;
+            let light = egui::Color32::from_white_alpha(40);
+            if sector.is_center() {
+                painter.circle_filled(center, sector.outer, light);
+            } else {
+                painter.add(sector.mesh(center, light));
+            }
+        }
+        if let Some(index) = self.selected_path.as_deref().and_then(|path| layer.position(path)) {
+            layer.areas[index].shape.highlight(&painter, center, egui::Stroke::new(2.0, style.text_color()));
+        }
+        let hovered_merged = hovered.and_then(|area| area.merged.as_ref().map(|merged| (area, merged)));
+        let hovered = hovered.filter(|area| area.merged.is_none()).and_then(|area| root.find(&area.path));
+
+        let menu_id = ui.id().with("sunburst_menu");
+        let mut clicked_path: Option<PathBuf> = None;
+        let mut actions = NodeActions::default();
+
+        if let Some(node) = hovered {
+            // Щелчок по папке - приближение к ней, по центру - уровень вверх
+            if response.clicked() {
+                clicked_path = Some(node.path.clone());
+                if node.path == focus.path {
+                    if focus.path != root.path {
+                        new_focus = node.path.parent().map(Path::to_path_buf);
+                    }
+                } else if !node.is_file {
+                    new_focus = Some(node.path.clone());
+                }
+            }
+            if response.secondary_clicked() {
+                ui.data_mut(|d| d.insert_temp(menu_id, node.path.clone()));
+            }
+        }
+
+        response.context_menu(|ui| {
+            let path: Option<PathBuf> = ui.data(|d| d.get_temp(menu_id));
+            if let Some(node) = path.and_then(|p| root.find(&p)) {
+                let mut menu = NodeMenu {
+                    path_to_delete: &mut self.path_to_delete,
+                    read_only,
+                    basket: &mut self.basket,
+                    watchlist: &self.config.watchlist,
+                    scan_first: &self.config.scan_first,
+                    notes: &self.config.notes,
+                    translations: &self.translations,
+                    actions: &mut actions,
+                };
+                menu.show(ui, node);
+            }
+        });
+
+        let mut merged_click = None;
+        if let Some(node) = hovered {
+            let percent = node.size as f64 / focus.size.max(1) as f64 * 100.0;
+            response.on_hover_ui_at_pointer(|ui| {
+                ui.strong(node.path.display().to_string());
+                ui.label(format!("{} ({:.1}%)", format_size(node.size), percent));
+            });
+        } else if let Some((area, merged)) = hovered_merged {
+            merged_click = chart::merged_hover(response, area, merged, focus, &self.translations);
+        }
+
+        // Выделение и раскрытие общие с деревом
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if let Some(path) = new_focus {
+            self.view_focus = Some(path);
+        }
+        if let Some(click) = merged_click {
+            self.apply_merged_click(click);
+        }
+        self.apply_node_actions(actions);
+    }
+}
@@ -0,0 +1,165 @@
+// Ход сканирования на кнопке окна в панели задач Windows (ITaskbarList3): за
+// долгим сканированием можно следить, пока окно в фоне. Неудачное сканирование
+// подсвечивает кнопку красным, пока окно не окажется на переднем плане.
+// На других системах ничего не показывается
+
+use crate::CedarApp;
+use eframe::egui;
+
+/// Полная шкала прогресса на кнопке
+const SCALE: u64 = 1000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Idle,
+    /// Объём работы пока неизвестен
+    Indeterminate,
+    /// Доля из SCALE
+    Progress(u64),
+    Error,
+}
+
+#[derive(Default)]
+pub struct Taskbar {
+    /// Последнее сканирование не удалось, а окно с тех пор не было активным
+    failed: bool,
+    /// Состояние, уже переданное панели задач
+    shown: Option<State>,
+    #[cfg(windows)]
+    list: Option<windows::List>,
+}
+
+impl Taskbar {
+    /// Отмечает неудачное сканирование
+    pub fn fail(&mut self) {
+        self.failed = true;
+    }
+}
+
+impl CedarApp {
+    /// Передаёт панели задач ход текущего сканирования; вызывается каждый кадр
+    pub fn update_taskbar(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        if self.is_scanning || ctx.input(|i| i.focused) {
+            self.taskbar.failed = false;
+        }
+        let state = if self.is_scanning {
+            match self.scan_progress.lock().unwrap().fraction() {
+                Some(fraction) => State::Progress((fraction as f64 * SCALE as f64) as u64),
+                None => State::Indeterminate,
+            }
+        } else if self.taskbar.failed {
+            State::Error
+        } else {
+            State::Idle
+        };
+        if self.taskbar.shown == Some(state) {
+            return;
+        }
+        self.taskbar.shown = Some(state);
+
+        #[cfg(windows)]
+        {
+            if self.taskbar.list.is_none() {
+                self.taskbar.list = windows::List::new(frame);
+            }
+            if let Some(list) = &self.taskbar.list {
+                list.show(state);
+            }
+        }
+        #[cfg(not(windows))]
+        let _ = frame;
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{State, SCALE};
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use std::ffi::c_void;
+    use windows_sys::core::{GUID, HRESULT};
+    use windows_sys::Win32::Foundation::HWND;
+    use windows_sys::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+    use windows_sys::Win32::UI::Shell::{TaskbarList, TBPFLAG, TBPF_ERROR, TBPF_INDETERMINATE, TBPF_NOPROGRESS, TBPF_NORMAL};
+
+    const IID_ITASKBARLIST3: GUID = GUID::from_u128(0xea1afb91_9e28_4b86_90e9_9e9f8a5eefaf);
+
+    /// Начало таблицы методов ITaskbarList3 - до используемых методов включительно
+    #[repr(C)]
+    struct Vtbl {
+        query_interface: usize,
+        add_ref: usize,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+        hr_init: unsafe extern "system" fn(*mut c_void) -> HRESULT,
+        add_tab: usize,
+        delete_tab: usize,
+        activate_tab: usize,
+        set_active_alt: usize,
+        mark_fullscreen_window: usize,
+        set_progress_value: unsafe extern "system" fn(*mut c_void, HWND, u64, u64) -> HRESULT,
+        set_progress_state: unsafe extern "system" fn(*mut c_void, HWND, TBPFLAG) -> HRESULT,
+    }
+
+    pub struct List {
+        this: *mut c_void,
+        hwnd: HWND,
+    }
+
+    impl List {
+        pub fn new(frame: &eframe::Frame) -> Option<Self> {
+            let RawWindowHandle::Win32(handle) = frame.window_handle().ok()?.as_raw() else {
+                return None;
+            };
+            let hwnd = handle.hwnd.get() as HWND;
+            let mut this = std::ptr::null_mut();
+            unsafe {
+                // Поток окна обычно уже инициализирован для COM - повторный вызов безвреден
+                CoInitializeEx(std::ptr::null(), COINIT_APARTMENTTHREADED as u32);
+                if CoCreateInstance(&TaskbarList, std::ptr::null_mut(), CLSCTX_INPROC_SERVER, &IID_ITASKBARLIST3, &mut this) < 0
+                    || this.is_null()
+                {
+                    return None;
+                }
+                let list = Self { this, hwnd };
+                if (list.vtbl().hr_init)(this) < 0 {
+                    return None;
+                }
+                Some(list)
+            }
+        }
+
+        fn vtbl(&self) -> &Vtbl {
+            unsafe { &**(self.this as *const *const Vtbl) }
+        }
+
+        pub fn show(&self, state: State) {
+            let vtbl = self.vtbl();
+            unsafe {
+                match state {
+                    State::Idle => {
+                        (vtbl.set_progress_state)(self.this, self.hwnd, TBPF_NOPROGRESS);
+                    }
+                    State::Indeterminate => {
+                        (vtbl.set_progress_state)(self.this, self.hwnd, TBPF_INDETERMINATE);
+                    }
+                    State::Progress(value) => {
+                        (vtbl.set_progress_state)(self.this, self.hwnd, TBPF_NORMAL);
+                        (vtbl.set_progress_value)(self.this, self.hwnd, value, SCALE);
+                    }
+                    // Красная полоса во всю ширину
+                    State::Error => {
+                        (vtbl.set_progress_value)(self.this, self.hwnd, SCALE, SCALE);
+                        (vtbl.set_progress_state)(self.this, self.hwnd, TBPF_ERROR);
+                    }
+                }
+            }
+        }
+    }
+
+    impl Drop for List {
+        fn drop(&mut self) {
+            unsafe {
+                (self.vtbl().release)(self.this);
+            }
+        }
+    }
+}
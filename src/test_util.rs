@@ -0,0 +1,32 @@
+// Помощники модульных тестов, общие для нескольких модулей
+
+use crate::DirNode;
+use std::path::PathBuf;
+
+/// Файл во временной папке; удаляется в конце теста
+pub struct TempFile(pub PathBuf);
+
+impl TempFile {
+    /// Путь к файлу, который тест создаст сам; `name` не должно повторяться в других тестах
+    pub fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("cedar-{}-{}", std::process::id(), name)))
+    }
+
+    /// Файл с содержимым `contents`
+    pub fn with_contents(name: &str, contents: &str) -> Self {
+        let file = Self::new(name);
+        std::fs::write(&file.0, contents).unwrap();
+        file
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Дочерний элемент с именем `name`
+pub fn child<'a>(node: &'a DirNode, name: &str) -> &'a DirNode {
+    node.children.iter().find(|c| c.name == name).unwrap()
+}
@@ -0,0 +1,41 @@
+// Режим "как в системе": тема приложения следует за светлой/тёмной темой ОС.
+// Windows и macOS сообщают о смене темы событием окна; где окно этого не умеет,
+// системная настройка опрашивается в фоновом потоке
+
+use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct SystemTheme {
+    /// Последний результат опроса
+    polled_dark: Arc<AtomicBool>,
+    polling: bool,
+}
+
+impl SystemTheme {
+    /// Тёмная ли сейчас тема ОС
+    pub fn is_dark(&mut self, ctx: &egui::Context) -> bool {
+        if let Some(theme) = ctx.system_theme() {
+            return theme == egui::Theme::Dark;
+        }
+
+        if !self.polling {
+            self.polling = true;
+            self.polled_dark.store(crate::i18n::detect_system_theme(), Ordering::Relaxed);
+            let polled_dark = self.polled_dark.clone();
+            let ctx = ctx.clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let dark = crate::i18n::detect_system_theme();
+                if polled_dark.swap(dark, Ordering::Relaxed) != dark {
+                    ctx.request_repaint();
+                }
+            });
+        }
+        self.polled_dark.load(Ordering::Relaxed)
+    }
+}
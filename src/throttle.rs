@@ -0,0 +1,131 @@
+// Ограничение скорости чтения для фоновых задач. Хеширование при поиске
+// дубликатов и оценке освобождаемого места читает файлы целиком и может
+// занять диск полностью; предел делится между всеми потоками задачи.
+// Ограничивается только хеширование: сканирования удалённых хранилищ в
+// программе нет, и предел для него не вводится
+
+use crate::{format_size, CedarApp};
+use eframe::egui;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MB: u64 = 1024 * 1024;
+
+/// Сколько можно прочитать сразу после простоя, не дожидаясь очереди
+const BURST: Duration = Duration::from_millis(250);
+
+/// Текущая скорость пересчитывается не чаще этого
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+struct Rate {
+    since: Instant,
+    bytes: u64,
+    per_second: u64,
+}
+
+pub struct Throttle {
+    /// Байт в секунду; 0 - без ограничения
+    limit: AtomicU64,
+    /// Момент, с которого разрешено следующее чтение
+    next: Mutex<Option<Instant>>,
+    /// Всего прочитано - для текущей скорости
+    total: AtomicU64,
+    rate: Mutex<Option<Rate>>,
+}
+
+/// Чтение файлов для хеширования
+pub static HASHING: Throttle = Throttle::new();
+
+impl Throttle {
+    const fn new() -> Self {
+        Self {
+            limit: AtomicU64::new(0),
+            next: Mutex::new(None),
+            total: AtomicU64::new(0),
+            rate: Mutex::new(None),
+        }
+    }
+
+    /// Предел в мегабайтах в секунду; 0 - без ограничения
+    pub fn set_limit_mb(&self, mb: u32) {
+        self.limit.store(mb as u64 * MB, Ordering::Relaxed);
+    }
+
+    /// Учитывает прочитанные байты и ждёт, если задача обгоняет предел
+    pub fn consume(&self, bytes: u64) {
+        self.total.fetch_add(bytes, Ordering::Relaxed);
+        let limit = self.limit.load(Ordering::Relaxed);
+        if limit == 0 {
+            return;
+        }
+
+        // Каждое чтение занимает свой отрезок времени в общей очереди
+        let wait = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            // Вскоре после загрузки системы `now - BURST` может оказаться раньше начала отсчёта
+            let earliest = now.checked_sub(BURST).unwrap_or(now);
+            let start = next.map_or(now, |next| next.max(earliest));
+            let end = start + Duration::from_secs_f64(bytes as f64 / limit as f64);
+            *next = Some(end);
+            end.saturating_duration_since(now + BURST)
+        };
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Скорость чтения за последнюю секунду, байт в секунду
+    pub fn rate(&self) -> u64 {
+        let total = self.total.load(Ordering::Relaxed);
+        let mut rate = self.rate.lock().unwrap();
+        let rate = rate.get_or_insert_with(|| Rate { since: Instant::now(), bytes: total, per_second: 0 });
+        let elapsed = rate.since.elapsed();
+        if elapsed >= RATE_WINDOW {
+            rate.per_second = ((total - rate.bytes) as f64 / elapsed.as_secs_f64()) as u64;
+            rate.since = Instant::now();
+            rate.bytes = total;
+        }
+        rate.per_second
+    }
+}
+
+/// Подпись текущей скорости для окон задач
+pub fn rate_label(throttle: &Throttle) -> String {
+    format!("{}/s", format_size(throttle.rate()))
+}
+
+impl CedarApp {
+    /// Раздел настроек: предел скорости хеширования; возвращает true, если что-то изменилось
+    pub fn show_throttle_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.heading(self.translations.get("read_limits"));
+        ui.label(
+            egui::RichText::new(self.translations.get("read_limits_hint"))
+                .small()
+                .color(ui.visuals().weak_text_color()),
+        );
+        ui.add_space(4.0);
+
+        let unlimited = self.translations.get("unlimited");
+        let changed = ui
+            .horizontal(|ui| {
+                ui.label(self.translations.get("hashing_limit"));
+                ui.add(
+                    egui::DragValue::new(&mut self.config.hashing_limit_mb)
+                        .range(0..=10_000)
+                        .suffix(" MB/s")
+                        .custom_formatter(|value, _| {
+                            if value == 0.0 { unlimited.clone() } else { format!("{} MB/s", value) }
+                        }),
+                )
+                .changed()
+            })
+            .inner;
+        if changed {
+            HASHING.set_limit_mb(self.config.hashing_limit_mb);
+        }
+        changed
+    }
+}
@@ -0,0 +1,174 @@
+// Столбцы дерева: имя, размер, доля в родительской папке, число файлов и папок
+// во всём поддереве и дата изменения. По числу файлов видны папки из множества
+// мелких файлов, которые по размеру не выделяются. Щелчок по заголовку сортирует содержимое всех папок по
+// столбцу, повторный щелчок меняет направление. Порядок запоминается в
+// настройках и применяется к каждому новому дереву
+
+use crate::{CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeColumn {
+    Name,
+    Size,
+    /// Доля в родительской папке
+    Percent,
+    /// Файлов во всём поддереве; раньше столбец считал элементы самой папки
+    #[serde(alias = "items")]
+    Files,
+    /// Вложенных папок во всём поддереве
+    Folders,
+    Modified,
+}
+
+impl TreeColumn {
+    fn translation_key(self) -> &'static str {
+        match self {
+            TreeColumn::Name => "name",
+            TreeColumn::Size => "size",
+            TreeColumn::Percent => "column_percent",
+            TreeColumn::Files => "column_files",
+            TreeColumn::Folders => "column_folders",
+            TreeColumn::Modified => "column_modified",
+        }
+    }
+}
+
+/// Столбцы справа от имени и их ширина
+pub const COLUMNS: [(TreeColumn, f32); 5] = [
+    (TreeColumn::Size, 96.0),
+    (TreeColumn::Percent, 64.0),
+    (TreeColumn::Files, 80.0),
+    (TreeColumn::Folders, 72.0),
+    (TreeColumn::Modified, 104.0),
+];
+
+/// Порядок строк дерева
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TreeSort {
+    pub column: TreeColumn,
+    pub ascending: bool,
+}
+
+impl Default for TreeSort {
+    fn default() -> Self {
+        Self { column: TreeColumn::Size, ascending: false }
+    }
+}
+
+impl TreeSort {
+    /// Щелчок по заголовку: тот же столбец меняет направление, новый - сортирует по нему
+    fn toggled(self, column: TreeColumn) -> Self {
+        if self.column == column {
+            Self { column, ascending: !self.ascending }
+        } else {
+            // Имя удобнее читать по алфавиту, числа и даты - от больших и свежих
+            Self { column, ascending: column == TreeColumn::Name }
+        }
+    }
+
+    fn compare(self, a: &DirNode, b: &DirNode) -> Ordering {
+        let ordering = match self.column {
+            // Доли соседей считаются от одного родителя, поэтому порядок как у размера
+            TreeColumn::Size | TreeColumn::Percent => a.size.cmp(&b.size),
+            TreeColumn::Files => a.file_count().cmp(&b.file_count()),
+            TreeColumn::Folders => a.folders.cmp(&b.folders),
+            TreeColumn::Modified => a.modified.cmp(&b.modified),
+            TreeColumn::Name => Ordering::Equal,
+        };
+        if self.ascending { ordering } else { ordering.reverse() }
+    }
+
+    /// Сортирует содержимое одной папки
+    fn sort_children(self, children: &mut [DirNode]) {
+        if self.column == TreeColumn::Name {
+            children.par_sort_by_cached_key(|c| c.display_name().to_lowercase());
+            if !self.ascending {
+                children.reverse();
+            }
+        } else {
+            children.par_sort_by(|a, b| self.compare(a, b));
+        }
+    }
+
+    /// Сортирует всё дерево; поддеревья сортируются параллельно. `done` - счётчик папок для прогресса
+    pub fn sort_tree(self, node: &mut DirNode, done: &AtomicUsize) {
+        self.sort_children(&mut node.children);
+        done.fetch_add(1, AtomicOrdering::Relaxed);
+        node.children
+            .par_iter_mut()
+            .filter(|c| !c.is_file)
+            .for_each(|c| self.sort_tree(c, done));
+    }
+}
+
+/// Делит строку дерева на часть с именем и ячейки столбцов; справа остаётся место под полосу прокрутки
+pub fn split_row(ui: &egui::Ui, rect: egui::Rect) -> (egui::Rect, [egui::Rect; COLUMNS.len()]) {
+    let mut right = rect.right() - ui.spacing().scroll.allocated_width();
+    let mut cells = [egui::Rect::NOTHING; COLUMNS.len()];
+    for (cell, (_, width)) in cells.iter_mut().zip(COLUMNS).rev() {
+        *cell = egui::Rect::from_x_y_ranges(right - width..=right, rect.y_range());
+        right -= width;
+    }
+    let name = egui::Rect::from_x_y_ranges(rect.left()..=right.max(rect.left()), rect.y_range());
+    (name, cells)
+}
+
+/// Текст ячейки справа по ширине столбца
+pub fn cell(ui: &mut egui::Ui, rect: egui::Rect, text: impl Into<egui::WidgetText>) -> egui::Response {
+    let padded = rect.shrink2(egui::vec2(ui.spacing().item_spacing.x / 2.0, 0.0));
+    ui.allocate_new_ui(
+        egui::UiBuilder::new().max_rect(padded).layout(egui::Layout::right_to_left(egui::Align::Center)),
+        |ui| ui.add(egui::Label::new(text).truncate()),
+    )
+    .inner
+}
+
+impl CedarApp {
+    /// Заголовки столбцов над деревом; щелчок пересортировывает дерево
+    pub fn show_tree_header(&mut self, ui: &mut egui::Ui) {
+        let height = ui.text_style_height(&egui::TextStyle::Button) + ui.spacing().button_padding.y;
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), height), egui::Sense::hover());
+        let (name_rect, cells) = split_row(ui, rect);
+
+        let sort = self.config.tree_sort;
+        let mut clicked = None;
+        let columns = std::iter::once((TreeColumn::Name, name_rect)).chain(COLUMNS.iter().map(|(column, _)| *column).zip(cells));
+        for (column, rect) in columns {
+            let arrow = match (sort.column == column, sort.ascending) {
+                (true, true) => regular::CARET_UP,
+                (true, false) => regular::CARET_DOWN,
+                (false, _) => "",
+            };
+            let text = egui::RichText::new(format!("{} {}", self.translations.get(column.translation_key()), arrow)).strong();
+            let layout = if column == TreeColumn::Name {
+                egui::Layout::left_to_right(egui::Align::Center)
+            } else {
+                egui::Layout::right_to_left(egui::Align::Center)
+            };
+            let header = ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect).layout(layout), |ui| {
+                ui.add(egui::Button::new(text).frame(false))
+            });
+            if header.inner.clicked() {
+                clicked = Some(column);
+            }
+        }
+
+        if let Some(column) = clicked {
+            self.config.tree_sort = sort.toggled(column);
+            if let Some(root) = &mut self.root_node {
+                self.config.tree_sort.sort_tree(root, &AtomicUsize::new(0));
+            }
+            // Совпадения поиска идут в порядке строк, а подписи карт привязаны к прежнему порядку
+            self.search.invalidate();
+            self.chart_layers.invalidate();
+            self.save_config_to_file();
+        }
+    }
+}
@@ -0,0 +1,296 @@
+// Представление "карта" (treemap): папки - вложенные прямоугольники, площадь
+// которых пропорциональна размеру. Раскладка squarified держит блоки близкими к квадрату
+
+use crate::chart::{self, Block, ChartStyle, Label, Merged};
+use crate::icicle::{breadcrumb_bar, name_hue};
+use crate::{format_size, CedarApp, DirNode, NodeActions, NodeMenu};
+use eframe::egui;
+use std::path::{Path, PathBuf};
+
+/// Полоса с именем папки над её содержимым
+const HEADER_HEIGHT: f32 = 16.0;
+/// Блоки с меньшей стороной не рисуются - их всё равно не разглядеть
+const MIN_SIDE: f32 = 6.0;
+const PADDING: f32 = 2.0;
+const MIN_LABEL_WIDTH: f32 = 40.0;
+/// Элементы мельче этой площади собираются в один блок с их числом и общим размером
+const MIN_AREA: f32 = 10.0 * 10.0;
+
+/// Худшее соотношение сторон в ряду блоков площадью `row`, уложенном вдоль стороны `side`
+fn worst_ratio(row: &[f32], side: f32) -> f32 {
+    let sum: f32 = row.iter().sum();
+    let max = row.iter().copied().fold(f32::MIN, f32::max);
+    let min = row.iter().copied().fold(f32::MAX, f32::min);
+    let (side2, sum2) = (side * side, sum * sum);
+    (side2 * max / sum2).max(sum2 / (side2 * min))
+}
+
+/// Раскладка squarified: `areas` по убыванию, сумма равна площади `rect`
+fn squarify(areas: &[f32], mut rect: egui::Rect) -> Vec<egui::Rect> {
+    let mut rects = Vec::with_capacity(areas.len());
+    let mut start = 0;
+    while start < areas.len() {
+        let side = rect.width().min(rect.height());
+        if side <= 0.0 {
+            break;
+        }
+
+        // Добавляем блоки в ряд, пока соотношение сторон не начнёт ухудшаться
+        let mut end = start + 1;
+        let mut worst = worst_ratio(&areas[start..end], side);
+        while end < areas.len() {
+            let next = worst_ratio(&areas[start..=end], side);
+            if next > worst {
+                break;
+            }
+            worst = next;
+            end += 1;
+        }
+
+        let row = &areas[start..end];
+        let row_sum: f32 = row.iter().sum();
+        if rect.width() >= rect.height() {
+            // Ряд - колонка у левого края
+            let width = row_sum / rect.height();
+            let mut y = rect.top();
+            for area in row {
+                let height = area / width;
+                rects.push(egui::Rect::from_min_size(egui::pos2(rect.left(), y), egui::vec2(width, height)));
+                y += height;
+            }
+            rect.min.x += width;
+        } else {
+            // Ряд - строка у верхнего края
+            let height = row_sum / rect.width();
+            let mut x = rect.left();
+            for area in row {
+                let width = area / height;
+                rects.push(egui::Rect::from_min_size(egui::pos2(x, rect.top()), egui::vec2(width, height)));
+                x += width;
+            }
+            rect.min.y += height;
+        }
+        start = end;
+    }
+    rects
+}
+
+fn layout_node<'a>(blocks: &mut Vec<Block<'a>>, style: &ChartStyle, node: &'a DirNode, rect: egui::Rect, depth: usize, hue: f32) {
+    if rect.width() < MIN_SIDE || rect.height() < MIN_SIDE {
+        return;
+    }
+
+    let block = rect.shrink(0.5);
+    let show_children = !node.is_file
+        && node.children.iter().any(|c| c.size > 0)
+        && block.height() >= HEADER_HEIGHT + MIN_SIDE + PADDING
+        && block.width() >= MIN_SIDE + PADDING * 2.0;
+
+    // У папки с содержимым подпись в заголовке, у остальных - по центру
+    let label = (block.width() >= MIN_LABEL_WIDTH && block.height() >= HEADER_HEIGHT).then(|| {
+        let text = format!("{} ({})", node.display_name(), format_size(node.size));
+        if show_children {
+            Label {
+                pos: block.left_top() + egui::vec2(4.0, HEADER_HEIGHT / 2.0),
+                align: egui::Align2::LEFT_CENTER,
+                text,
+            }
+        } else {
+            Label {
+                pos: block.center(),
+                align: egui::Align2::CENTER_CENTER,
+                text,
+            }
+        }
+    });
+    blocks.push(Block {
+        node,
+        rect: block,
+        fill: style.fill(node, hue, depth),
+        label,
+        merged: None,
+    });
+
+    if !show_children {
+        return;
+    }
+
+    let inner = egui::Rect::from_min_max(
+        egui::pos2(block.left() + PADDING, block.top() + HEADER_HEIGHT),
+        egui::pos2(block.right() - PADDING, block.bottom() - PADDING),
+    );
+    let mut children: Vec<&DirNode> = node.children.iter().filter(|c| c.size > 0).collect();
+    children.sort_by_key(|c| std::cmp::Reverse(c.size));
+    let total: u64 = children.iter().map(|c| c.size).sum();
+    let scale = inner.area() as f64 / total as f64;
+    let area = |size: u64| (size as f64 * scale) as f32;
+
+    // Мелкий хвост (дети отсортированы) становится одним блоком, если в нём больше одного элемента
+    let split = children.iter().position(|c| area(c.size) < MIN_AREA).unwrap_or(children.len());
+    let merged = (children.len() - split > 1).then(|| Merged::of(&children[split..]));
+    if merged.is_some() {
+        children.truncate(split);
+    }
+    let mut areas: Vec<f32> = children.iter().map(|c| area(c.size)).collect();
+    // Объединённый блок встаёт в раскладку по своей площади
+    let merged_index = merged.as_ref().map(|merged| {
+        let index = areas.partition_point(|&a| a >= area(merged.size));
+        areas.insert(index, area(merged.size));
+        index
+    });
+    let mut rects = squarify(&areas, inner);
+
+    if let (Some(merged), Some(index)) = (merged, merged_index) {
+        let rect = rects.remove(index);
+        if rect.width() >= MIN_SIDE && rect.height() >= MIN_SIDE {
+            let block = rect.shrink(0.5);
+            blocks.push(Block {
+                node,
+                rect: block,
+                fill: style.merged_fill(),
+                label: (block.width() >= MIN_LABEL_WIDTH && block.height() >= HEADER_HEIGHT).then(|| Label {
+                    pos: block.center(),
+                    align: egui::Align2::CENTER_CENTER,
+                    text: merged.label(),
+                }),
+                merged: Some(merged),
+            });
+        }
+    }
+    for (child, child_rect) in children.into_iter().zip(rects) {
+        // На первом уровне под фокусом каждая ветка получает свой оттенок
+        let child_hue = if depth == 0 { name_hue(&child.display_name()) } else { hue };
+        layout_node(blocks, style, child, child_rect, depth + 1, child_hue);
+    }
+}
+
+/// Раскладка поддерева `focus` в прямоугольнике `rect`: вложенные блоки идут после родителей
+pub fn layout<'a>(focus: &'a DirNode, rect: egui::Rect, style: &ChartStyle) -> Vec<Block<'a>> {
+    let mut blocks = Vec::new();
+    layout_node(&mut blocks, style, focus, rect, 0, name_hue(&focus.display_name()));
+    blocks
+}
+
+impl CedarApp {
+    /// Карта текущего дерева
+    pub fn show_treemap_view(&mut self, ui: &mut egui::Ui) {
+        let read_only = self.read_only();
+        let Some(root) = &self.root_node else {
+            return;
+        };
+
+        // Фокус общий с icicle-представлением; удалённый или чужой путь сбрасывает его на корень
+        let focus = self
+            .view_focus
+            .as_ref()
+            .and_then(|p| root.find(p))
+            .unwrap_or(root);
+
+        let mut new_focus = breadcrumb_bar(ui, root, &focus.path, &self.translations.get("treemap_hint"));
+
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::click());
+        let style = self.chart_style();
+        let key = chart::LayerKey::new(&focus.path, rect, ui.ctx().pixels_per_point(), &style);
+        if !self.chart_layers.treemap.as_ref().is_some_and(|layer| layer.is_current(&key)) {
+            let blocks = layout(focus, rect, &style);
+            let areas = blocks
+                .iter()
+                .map(|b| chart::Area {
+                    shape: b.rect,
+                    path: b.node.path.clone(),
+                    merged: b.merged.clone(),
+                })
+                .collect();
+            let labels = blocks
+                .iter()
+                .filter_map(|b| b.label.as_ref().map(|label| (b.rect.shrink(2.0), label.clone())))
+                .collect();
+            self.chart_layers.treemap = chart::ChartLayer::new(ui.ctx(), key, areas, labels, |pixmap, transform| {
+                chart::rasterize_blocks(pixmap, transform, &blocks)
+            });
+        }
+        let Some(layer) = &self.chart_layers.treemap else {
+            return;
+        };
+        let painter = ui.painter_at(rect);
+        layer.paint(&painter, &style);
+
+        // Рамки рисуем поверх содержимого, иначе вложенные блоки их перекроют
+        let hovered = response
+            .hover_pos()
+            .and_then(|pos| layer.areas.iter().rfind(|area| area.shape.contains(pos)));
+        if let Some(area) = hovered {
+            painter.rect_stroke(area.shape, 2.0, egui::Stroke::new(1.0, style.text_color().gamma_multiply(0.6)));
+        }
+        if let Some(index) = self.selected_path.as_deref().and_then(|path| layer.position(path)) {
+            painter.rect_stroke(layer.areas[index].shape, 2.0, egui::Stroke::new(2.0, style.text_color()));
+        }
+        let hovered_merged = hovered.and_then(|area| area.merged.as_ref().map(|merged| (area, merged)));
+        let hovered = hovered.filter(|area| area.merged.is_none()).and_then(|area| root.find(&area.path));
+
+        // Путь элемента, для которого открыто контекстное меню
+        let menu_id = ui.id().with("treemap_menu");
+        let mut clicked_path: Option<PathBuf> = None;
+        let mut actions = NodeActions::default();
+
+        if let Some(node) = hovered {
+            // Щелчок по папке - приближение к ней, по заголовку текущего фокуса - уровень вверх
+            if response.clicked() {
+                clicked_path = Some(node.path.clone());
+                if node.path == focus.path {
+                    if focus.path != root.path {
+                        new_focus = node.path.parent().map(Path::to_path_buf);
+                    }
+                } else if !node.is_file {
+                    new_focus = Some(node.path.clone());
+                }
+            }
+            if response.secondary_clicked() {
+                ui.data_mut(|d| d.insert_temp(menu_id, node.path.clone()));
+            }
+        }
+
+        response.context_menu(|ui| {
+            let path: Option<PathBuf> = ui.data(|d| d.get_temp(menu_id));
+            if let Some(node) = path.and_then(|p| root.find(&p)) {
+                let mut menu = NodeMenu {
+                    path_to_delete: &mut self.path_to_delete,
+                    read_only,
+                    basket: &mut self.basket,
+                    watchlist: &self.config.watchlist,
+                    scan_first: &self.config.scan_first,
+                    notes: &self.config.notes,
+                    translations: &self.translations,
+                    actions: &mut actions,
+                };
+                menu.show(ui, node);
+            }
+        });
+
+        let mut merged_click = None;
+        if let Some(node) = hovered {
+            let percent = node.size as f64 / focus.size.max(1) as f64 * 100.0;
+            response.on_hover_ui_at_pointer(|ui| {
+                ui.strong(node.path.display().to_string());
+                ui.label(format!("{} ({:.1}%)", format_size(node.size), percent));
+            });
+        } else if let Some((area, merged)) = hovered_merged {
+            merged_click = chart::merged_hover(response, area, merged, focus, &self.translations);
+        }
+
+        // Выделение и раскрытие общие с деревом
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if let Some(path) = new_focus {
+            self.view_focus = Some(path);
+        }
+        if let Some(click) = merged_click {
+            self.apply_merged_click(click);
+        }
+        self.apply_node_actions(actions);
+    }
+}
@@ -0,0 +1,62 @@
+// Раскладка по папкам сканирования: представление и размещение панелей
+// запоминаются для корня, при котором их выбрали, и возвращаются при
+// следующем сканировании этой папки или вложенной в неё. Так диск с видео
+// открывается картой, а системный - деревом с рекомендациями по очистке
+
+use crate::{panels, AppConfig, CedarApp, ViewMode};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Сколько папок помнится; давно не открывавшиеся вытесняются
+const LAYOUT_LIMIT: usize = 50;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ViewLayout {
+    #[serde(with = "crate::os_serde")]
+    pub root: PathBuf,
+    pub view_mode: ViewMode,
+    pub panels: panels::PanelLayout,
+}
+
+/// Раскладка самой папки, иначе ближайшей сохранённой папки выше
+fn lookup<'a>(layouts: &'a [ViewLayout], root: &Path) -> Option<&'a ViewLayout> {
+    layouts
+        .iter()
+        .filter(|layout| root.starts_with(&layout.root))
+        .max_by_key(|layout| layout.root.components().count())
+}
+
+/// Возвращает раскладку, запомненную для нового корня; true - если что-то изменилось
+pub fn restore(config: &mut AppConfig, root: &Path) -> bool {
+    if !config.remember_view_layouts {
+        return false;
+    }
+    let Some(layout) = lookup(&config.view_layouts, root) else {
+        return false;
+    };
+    config.view_mode = layout.view_mode;
+    config.panels = layout.panels.clone();
+    true
+}
+
+impl CedarApp {
+    /// Представление или панели изменены пользователем: запоминаем для текущего корня
+    pub fn view_layout_changed(&mut self) {
+        if self.config.remember_view_layouts {
+            if let Some(root) = &self.root_node {
+                let layouts = &mut self.config.view_layouts;
+                layouts.retain(|layout| layout.root != root.path);
+                layouts.insert(
+                    0,
+                    ViewLayout {
+                        root: root.path.clone(),
+                        view_mode: self.config.view_mode,
+                        panels: self.config.panels.clone(),
+                    },
+                );
+                layouts.truncate(LAYOUT_LIMIT);
+            }
+        }
+        self.save_config_to_file();
+    }
+}
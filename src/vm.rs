@@ -0,0 +1,326 @@
+// Диски виртуальных машин в дереве сканирования, сгруппированные по гипервизору.
+// Диск без конфигурации машины рядом, которая бы на него ссылалась, считается брошенным:
+// такие остаются после удаления машины из гипервизора без удаления файлов
+
+use crate::{format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Конфигурации крупнее этого не читаются - это уже не конфигурации
+const MAX_CONFIG_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Дескриптор VMDK - текстовый файл со ссылками на части диска и родительские диски
+const MAX_VMDK_DESCRIPTOR_SIZE: u64 = 64 * 1024;
+
+/// Глубина поиска конфигураций от папки над папкой диска
+const CONFIG_SEARCH_DEPTH: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Hypervisor {
+    HyperV,
+    Wsl,
+    Docker,
+    VMware,
+    VirtualBox,
+    Qemu,
+    Parallels,
+}
+
+impl Hypervisor {
+    fn name(self) -> &'static str {
+        match self {
+            Hypervisor::HyperV => "Hyper-V",
+            Hypervisor::Wsl => "WSL",
+            Hypervisor::Docker => "Docker Desktop",
+            Hypervisor::VMware => "VMware",
+            Hypervisor::VirtualBox => "VirtualBox",
+            Hypervisor::Qemu => "QEMU / libvirt",
+            Hypervisor::Parallels => "Parallels",
+        }
+    }
+
+    /// Гипервизор по расширению диска и принятому у него расположению файлов
+    fn detect(path: &Path, extension: &str) -> Option<Hypervisor> {
+        let lower = path.to_string_lossy().to_lowercase().replace('\\', "/");
+        let by_folder = if lower.contains("/docker") {
+            Some(Hypervisor::Docker)
+        } else if lower.contains("/packages/") && lower.contains("/localstate/") || lower.contains("/wsl/") {
+            Some(Hypervisor::Wsl)
+        } else if lower.contains("/hyper-v/") {
+            Some(Hypervisor::HyperV)
+        } else if lower.contains("/virtualbox vms/") {
+            Some(Hypervisor::VirtualBox)
+        } else if lower.contains("/virtual machines/") && extension == "vmdk" {
+            Some(Hypervisor::VMware)
+        } else if lower.contains("/libvirt/") {
+            Some(Hypervisor::Qemu)
+        } else if lower.contains(".pvm/") {
+            Some(Hypervisor::Parallels)
+        } else {
+            None
+        };
+        let by_extension = match extension {
+            "vhd" | "vhdx" | "avhd" | "avhdx" => Some(Hypervisor::HyperV),
+            "vmdk" => Some(Hypervisor::VMware),
+            "vdi" => Some(Hypervisor::VirtualBox),
+            "qcow" | "qcow2" => Some(Hypervisor::Qemu),
+            "hdd" | "hds" if lower.contains(".pvm/") => Some(Hypervisor::Parallels),
+            _ => None,
+        }?;
+        Some(by_folder.unwrap_or(by_extension))
+    }
+
+    /// Диски WSL и Docker Desktop подключаются через реестр, а не через файлы конфигурации
+    fn has_config_files(self) -> bool {
+        !matches!(self, Hypervisor::Wsl | Hypervisor::Docker)
+    }
+}
+
+/// Файл, который может ссылаться на диск: конфигурация машины любого гипервизора
+fn is_config(node: &DirNode) -> bool {
+    let extension = Path::new(&node.name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "vmx" | "vmsd" | "vbox" | "vbox-prev" | "vmcx" | "xml" | "pvs" => node.size <= MAX_CONFIG_SIZE,
+        "vmdk" => node.size <= MAX_VMDK_DESCRIPTOR_SIZE,
+        _ => false,
+    }
+}
+
+/// Конфигурации машин libvirt хранятся отдельно от образов дисков
+fn libvirt_configs() -> Vec<PathBuf> {
+    let mut folders = vec![PathBuf::from("/etc/libvirt/qemu")];
+    folders.extend(dirs::config_dir().map(|d| d.join("libvirt/qemu")));
+    folders
+        .iter()
+        .flat_map(|folder| std::fs::read_dir(folder).into_iter().flatten().flatten())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "xml"))
+        .collect()
+}
+
+/// Упоминается ли имя файла в содержимом конфигурации - в UTF-8 или UTF-16
+/// (двоичные .vmcx Hyper-V хранят пути в UTF-16)
+fn mentions(content: &[u8], name: &str) -> bool {
+    let name = name.to_lowercase();
+    let utf8 = name.as_bytes().to_vec();
+    let utf16: Vec<u8> = name.encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+    [utf8, utf16]
+        .iter()
+        .any(|needle| !needle.is_empty() && content.windows(needle.len()).any(|w| w == needle.as_slice()))
+}
+
+/// Строка отчёта о дисках виртуальных машин
+pub struct VirtualDisk {
+    pub path: PathBuf,
+    pub hypervisor: Hypervisor,
+    pub size: u64,
+    /// `None` - диск подключается не через файлы конфигурации
+    pub orphaned: Option<bool>,
+}
+
+/// Собирает диски виртуальных машин: по гипервизорам, внутри - крупнейшие первыми
+pub fn collect_virtual_disks(root: &DirNode) -> Vec<VirtualDisk> {
+    /// Пути конфигураций в поддереве до заданной глубины
+    fn configs_near(node: &DirNode, depth: usize, configs: &mut Vec<PathBuf>) {
+        for child in &node.children {
+            if child.is_file {
+                if is_config(child) {
+                    configs.push(child.path.clone());
+                }
+            } else if depth > 0 {
+                configs_near(child, depth - 1, configs);
+            }
+        }
+    }
+
+    fn walk(node: &DirNode, parent: Option<&DirNode>, disks: &mut Vec<(VirtualDisk, Vec<PathBuf>)>) {
+        for child in &node.children {
+            if !child.is_file {
+                walk(child, Some(node), disks);
+                continue;
+            }
+            let extension = Path::new(&child.name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            // Маленький VMDK - дескриптор, сами данные лежат в других файлах
+            if extension == "vmdk" && child.size <= MAX_VMDK_DESCRIPTOR_SIZE {
+                continue;
+            }
+            let Some(hypervisor) = Hypervisor::detect(&child.path, &extension) else {
+                continue;
+            };
+            let mut configs = Vec::new();
+            if hypervisor.has_config_files() {
+                configs_near(parent.unwrap_or(node), CONFIG_SEARCH_DEPTH, &mut configs);
+                configs.retain(|c| c != &child.path);
+            }
+            let disk = VirtualDisk {
+                path: child.path.clone(),
+                hypervisor,
+                size: child.size,
+                orphaned: None,
+            };
+            disks.push((disk, configs));
+        }
+    }
+
+    let mut found = Vec::new();
+    walk(root, None, &mut found);
+
+    let shared = if found.iter().any(|(d, _)| d.hypervisor == Hypervisor::Qemu) {
+        libvirt_configs()
+    } else {
+        Vec::new()
+    };
+
+    // Каждая конфигурация читается один раз, даже если рядом с ней много дисков
+    let mut contents: HashMap<PathBuf, Vec<u8>> = HashMap::new();
+    let mut disks = Vec::new();
+    for (mut disk, mut configs) in found {
+        if disk.hypervisor.has_config_files() {
+            if disk.hypervisor == Hypervisor::Qemu {
+                configs.extend(shared.iter().cloned());
+            }
+            let name = disk.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let referenced = configs.iter().any(|config| {
+                let content = contents
+                    .entry(config.clone())
+                    .or_insert_with(|| std::fs::read(config).unwrap_or_default().to_ascii_lowercase());
+                mentions(content, &name)
+            });
+            disk.orphaned = Some(!referenced);
+        }
+        disks.push(disk);
+    }
+
+    disks.sort_unstable_by_key(|d| (d.hypervisor, std::cmp::Reverse(d.size)));
+    disks
+}
+
+impl CedarApp {
+    /// Окно отчёта о дисках виртуальных машин
+    pub fn show_vm_report_window(&mut self, ctx: &egui::Context) {
+        if !self.show_vm_report {
+            return;
+        }
+
+        if self.vm_report.is_none() {
+            self.vm_report = self.root_node.as_ref().map(collect_virtual_disks);
+        }
+
+        let mut open = self.show_vm_report;
+        let mut refresh = false;
+        let mut clicked_path = None;
+        let orphan_color = self.config.palette.status_text(crate::palette::Status::Warning, self.config.dark_mode);
+
+        egui::Window::new(format!("{} {}", regular::DESKTOP_TOWER, self.translations.get("vm_report")))
+            .open(&mut open)
+            .default_size([760.0, 480.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(self.translations.get("vm_report_hint"))
+                        .color(ui.visuals().weak_text_color()),
+                );
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    if let Some(disks) = &self.vm_report {
+                        let total: u64 = disks.iter().map(|d| d.size).sum();
+                        let orphaned: u64 = disks.iter().filter(|d| d.orphaned == Some(true)).map(|d| d.size).sum();
+                        ui.strong(self.translations.get_fmt(
+                            "vm_summary",
+                            &[&disks.len().to_string(), &format_size(total), &format_size(orphaned)],
+                        ));
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button(format!("{} {}", regular::ARROWS_CLOCKWISE, self.translations.get("refresh"))).clicked() {
+                            refresh = true;
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                let Some(disks) = &self.vm_report else {
+                    return;
+                };
+
+                if disks.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("vm_none"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        egui::Grid::new("vm_report_grid")
+                            .striped(true)
+                            .num_columns(3)
+                            .show(ui, |ui| {
+                                let mut current = None;
+                                for disk in disks {
+                                    // Заголовок группы с общим размером дисков гипервизора
+                                    if current != Some(disk.hypervisor) {
+                                        current = Some(disk.hypervisor);
+                                        let group: u64 = disks
+                                            .iter()
+                                            .filter(|d| d.hypervisor == disk.hypervisor)
+                                            .map(|d| d.size)
+                                            .sum();
+                                        ui.heading(disk.hypervisor.name());
+                                        ui.strong(format_size(group));
+                                        ui.label("");
+                                        ui.end_row();
+                                    }
+
+                                    let response = ui.selectable_label(
+                                        self.selected_path.as_ref() == Some(&disk.path),
+                                        disk.path.display().to_string(),
+                                    );
+                                    if response.clicked() {
+                                        clicked_path = Some(disk.path.clone());
+                                    }
+                                    ui.label(format_size(disk.size));
+                                    match disk.orphaned {
+                                        Some(true) => {
+                                            ui.label(
+                                                egui::RichText::new(format!("{} {}", regular::WARNING, self.translations.get("vm_orphaned")))
+                                                    .color(orphan_color),
+                                            )
+                                            .on_hover_text(self.translations.get("vm_orphaned_hint"));
+                                        }
+                                        Some(false) => {
+                                            ui.label(self.translations.get("vm_in_use"));
+                                        }
+                                        None => {
+                                            ui.weak("—").on_hover_text(self.translations.get("vm_managed_hint"));
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        if refresh {
+            self.vm_report = None;
+        }
+        self.show_vm_report = open;
+    }
+}
@@ -0,0 +1,208 @@
+// Список наблюдаемых папок: после каждого сканирования показывает,
+// насколько они выросли с прошлого раза
+
+use crate::palette::Status;
+use crate::webhook::OverLimit;
+use crate::{a11y, format_size, CedarApp, DirNode};
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Рост считается аномальным, если он больше этой доли прежнего размера...
+const ABNORMAL_GROWTH_RATIO: f64 = 0.25;
+/// ...и при этом больше этого абсолютного значения
+const ABNORMAL_GROWTH_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WatchedFolder {
+    #[serde(with = "crate::os_serde")]
+    pub path: PathBuf,
+    /// Размер по предпоследнему сканированию
+    pub previous_size: Option<u64>,
+    /// Размер по последнему сканированию
+    pub current_size: Option<u64>,
+    /// Предел размера в ГБ для уведомлений; 0 - без предела
+    #[serde(default)]
+    pub limit_gb: u64,
+}
+
+impl WatchedFolder {
+    /// Изменение размера с прошлого сканирования
+    fn change(&self) -> Option<i128> {
+        Some(self.current_size? as i128 - self.previous_size? as i128)
+    }
+
+    /// Предел в байтах, если задан
+    pub fn limit(&self) -> Option<u64> {
+        (self.limit_gb > 0).then(|| self.limit_gb * 1024 * 1024 * 1024)
+    }
+
+    fn is_abnormal(&self) -> bool {
+        match (self.previous_size, self.change()) {
+            (Some(previous), Some(change)) if change > 0 => {
+                change as u64 >= ABNORMAL_GROWTH_BYTES
+                    && change as f64 >= previous as f64 * ABNORMAL_GROWTH_RATIO
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Переключает наблюдение за папкой
+pub fn toggle(watchlist: &mut Vec<WatchedFolder>, path: PathBuf, root: Option<&DirNode>) {
+    if let Some(pos) = watchlist.iter().position(|w| w.path == path) {
+        watchlist.remove(pos);
+    } else {
+        // Текущий размер берём из дерева, чтобы следующее сканирование уже показало разницу
+        let current_size = root.and_then(|r| r.find(&path)).map(|n| n.size);
+        watchlist.push(WatchedFolder {
+            path,
+            previous_size: None,
+            current_size,
+            limit_gb: 0,
+        });
+    }
+}
+
+/// Наблюдаемые папки из дерева, которые больше своего предела
+pub fn over_limit(watchlist: &[WatchedFolder], root: &DirNode) -> Vec<OverLimit> {
+    watchlist
+        .iter()
+        .filter_map(|watched| {
+            let limit = watched.limit()?;
+            let node = root.find(&watched.path)?;
+            (node.size > limit).then(|| OverLimit {
+                path: node.path.clone(),
+                size: node.size,
+                limit,
+            })
+        })
+        .collect()
+}
+
+/// Запоминает размеры наблюдаемых папок из нового дерева.
+/// Возвращает true, если хотя бы одна папка попала в сканирование.
+pub fn record_scan(watchlist: &mut [WatchedFolder], root: &DirNode) -> bool {
+    let mut updated = false;
+    for watched in watchlist.iter_mut() {
+        if let Some(node) = root.find(&watched.path) {
+            watched.previous_size = watched.current_size;
+            watched.current_size = Some(node.size);
+            updated = true;
+        }
+    }
+    updated
+}
+
+fn format_change(change: i128) -> String {
+    let sign = if change < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_size(change.unsigned_abs() as u64))
+}
+
+impl CedarApp {
+    /// Окно наблюдаемых папок
+    pub fn show_watchlist_window(&mut self, ctx: &egui::Context) {
+        if !self.show_watchlist {
+            return;
+        }
+
+        let mut open = self.show_watchlist;
+        let mut to_remove = None;
+        let mut clicked_path = None;
+        let mut changed = false;
+        let warning_color = self.config.palette.status_text(Status::Warning, self.config.dark_mode);
+
+        egui::Window::new(format!("{} {}", regular::EYE, self.translations.get("watchlist")))
+            .open(&mut open)
+            .default_size([640.0, 320.0])
+            .show(ctx, |ui| {
+                if self.config.watchlist.is_empty() {
+                    ui.label(
+                        egui::RichText::new(self.translations.get("watchlist_empty"))
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        egui::Grid::new("watchlist_grid")
+                            .striped(true)
+                            .num_columns(6)
+                            .show(ui, |ui| {
+                                ui.strong(self.translations.get("folder"));
+                                ui.strong(self.translations.get("watch_previous"));
+                                ui.strong(self.translations.get("watch_current"));
+                                ui.strong(self.translations.get("watch_change"));
+                                ui.strong(self.translations.get("watch_limit"))
+                                    .on_hover_text(self.translations.get("watch_limit_hint"));
+                                ui.label("");
+                                ui.end_row();
+
+                                for watched in &mut self.config.watchlist {
+                                    let response = ui.selectable_label(
+                                        self.selected_path.as_ref() == Some(&watched.path),
+                                        watched.path.display().to_string(),
+                                    );
+                                    if response.clicked() {
+                                        clicked_path = Some(watched.path.clone());
+                                    }
+
+                                    let size_text = |size: Option<u64>| size.map(format_size).unwrap_or_else(|| "—".to_string());
+                                    ui.label(size_text(watched.previous_size));
+                                    let over_limit = watched.limit().zip(watched.current_size).is_some_and(|(limit, size)| size > limit);
+                                    if over_limit {
+                                        ui.colored_label(warning_color, size_text(watched.current_size));
+                                    } else {
+                                        ui.label(size_text(watched.current_size));
+                                    }
+
+                                    match watched.change() {
+                                        Some(change) if watched.is_abnormal() => {
+                                            ui.label(
+                                                egui::RichText::new(format!("{} {}", regular::WARNING, format_change(change)))
+                                                    .color(warning_color)
+                                                    .strong(),
+                                            )
+                                            .on_hover_text(self.translations.get("watch_abnormal_hint"));
+                                        }
+                                        Some(change) => {
+                                            ui.label(format_change(change));
+                                        }
+                                        None => {
+                                            ui.label("—");
+                                        }
+                                    }
+
+                                    changed |= ui
+                                        .add(egui::DragValue::new(&mut watched.limit_gb).range(0..=1_000_000).suffix(" GB"))
+                                        .changed();
+
+                                    if a11y::icon_button(ui, regular::X, &self.translations.get("unwatch_folder")).clicked()
+                                    {
+                                        to_remove = Some(watched.path.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = to_remove {
+            toggle(&mut self.config.watchlist, path, None);
+            changed = true;
+        }
+        if changed {
+            self.save_config_to_file();
+        }
+        if let Some(path) = clicked_path {
+            if let Some(root) = &mut self.root_node {
+                root.expand_to(&path);
+            }
+            self.selected_path = Some(path);
+        }
+        self.show_watchlist = open;
+    }
+}
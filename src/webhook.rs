@@ -0,0 +1,132 @@
+// Уведомления через веб-хук. Сканирование без окна отправляет JSON со сводкой,
+// когда заканчивается, и отдельное сообщение, когда наблюдаемая папка
+// превысила свой предел. Поле `text` - для чатов, которые показывают только его
+
+use crate::headless::Summary;
+use crate::{format_size, CedarApp};
+use eframe::egui;
+use egui_phosphor::regular;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Наблюдаемая папка больше своего предела
+#[derive(Serialize)]
+pub struct OverLimit {
+    #[serde(serialize_with = "crate::os_serde::serialize")]
+    pub path: PathBuf,
+    pub size: u64,
+    pub limit: u64,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: &'static str,
+    host: String,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<&'a Summary>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    over_limit: &'a [OverLimit],
+}
+
+fn send(url: &str, event: &'static str, text: String, summary: Option<&Summary>, over_limit: &[OverLimit]) -> Result<(), String> {
+    let payload = Payload {
+        event,
+        host: sysinfo::System::host_name().unwrap_or_default(),
+        text,
+        summary,
+        over_limit,
+    };
+    let json = serde_json::to_string(&payload).map_err(|e| e.to_string())?;
+    // Системный TLS: на Windows доверяет тем же сертификатам, что и сама система
+    let tls = native_tls::TlsConnector::new().map_err(|e| e.to_string())?;
+    let agent = ureq::AgentBuilder::new().tls_connector(Arc::new(tls)).timeout(TIMEOUT).build();
+    agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&json)
+        .map(|_| ())
+        .map_err(|e| format!("{}: {}", url, e))
+}
+
+/// Сканирование закончилось
+pub fn scan_complete(url: &str, summary: &Summary) -> Result<(), String> {
+    let text = format!(
+        "Scan of {} finished: {} in {} files",
+        summary.root.display(),
+        format_size(summary.size),
+        summary.files
+    );
+    send(url, "scan_complete", text, Some(summary), &[])
+}
+
+/// Наблюдаемые папки превысили пределы
+pub fn over_limit(url: &str, root: &Path, folders: &[OverLimit]) -> Result<(), String> {
+    let list: Vec<String> = folders
+        .iter()
+        .map(|f| format!("{} is {} (limit {})", f.path.display(), format_size(f.size), format_size(f.limit)))
+        .collect();
+    let text = format!("Watched folders over their limit in {}: {}", root.display(), list.join("; "));
+    send(url, "watched_folder_over_limit", text, None, folders)
+}
+
+/// Проверка адреса из настроек
+fn test(url: &str) -> Result<(), String> {
+    send(url, "test", "Cedar webhook test".to_string(), None, &[])
+}
+
+/// Пробная отправка из настроек, идущая в фоне
+pub type WebhookTest = Arc<Mutex<Option<Result<(), String>>>>;
+
+impl CedarApp {
+    /// Раздел настроек: адрес веб-хука; возвращает true, если что-то изменилось
+    pub fn show_webhook_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.heading(self.translations.get("notifications"));
+        ui.add_space(4.0);
+        ui.label(egui::RichText::new(self.translations.get("webhook_hint")).weak());
+
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(self.translations.get("webhook_url"));
+            changed = ui
+                .add(egui::TextEdit::singleline(&mut self.config.webhook_url).hint_text("https://").desired_width(260.0))
+                .changed();
+        });
+
+        ui.horizontal(|ui| {
+            let url = self.config.webhook_url.trim().to_string();
+            let sending = self.webhook_test.as_ref().is_some_and(|t| t.lock().unwrap().is_none());
+            let button = egui::Button::new(format!("{} {}", regular::PAPER_PLANE_TILT, self.translations.get("webhook_test")));
+            if ui.add_enabled(!url.is_empty() && !sending, button).clicked() {
+                let result: WebhookTest = Arc::new(Mutex::new(None));
+                let thread_result = result.clone();
+                thread::spawn(move || {
+                    *thread_result.lock().unwrap() = Some(test(&url));
+                });
+                self.webhook_test = Some(result);
+            }
+
+            if let Some(test) = &self.webhook_test {
+                match &*test.lock().unwrap() {
+                    None => {
+                        ui.spinner();
+                        ui.ctx().request_repaint();
+                    }
+                    Some(Ok(())) => {
+                        ui.label(self.translations.get("webhook_sent"));
+                    }
+                    Some(Err(error)) => {
+                        let color = self.config.palette.status_text(crate::palette::Status::Critical, self.config.dark_mode);
+                        ui.colored_label(color, error);
+                    }
+                }
+            }
+        });
+        changed
+    }
+}
@@ -0,0 +1,173 @@
+// Размещение окна для каждой конфигурации мониторов и чёткие иконки при любом
+// масштабе экрана. Конфигурация определяется по монитору, на котором стоит окно.
+// В заголовке окна - корень и время сканирования, чтобы несколько запущенных
+// копий различались на панели задач
+
+use crate::CedarApp;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Пауза после перемещения или изменения размера, после которой размещение сохраняется
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+/// Время на применение восстановленного размещения: до этого окно ещё на старом месте
+const RESTORE_DELAY: Duration = Duration::from_millis(500);
+/// Заголовок окна без сканирования
+pub const TITLE: &str = "Cedar Folder Size";
+
+/// Положение и размер окна в точках интерфейса
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    pub position: Option<[f32; 2]>,
+    pub size: [f32; 2],
+    pub maximized: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowPlacements {
+    /// Конфигурация мониторов при последнем запуске
+    last_monitor: Option<String>,
+    placements: BTreeMap<String, WindowPlacement>,
+}
+
+impl WindowPlacements {
+    /// Начальные параметры окна - размещение, сохранённое для последней конфигурации мониторов
+    pub fn apply_to(&self, mut viewport: egui::ViewportBuilder) -> egui::ViewportBuilder {
+        let Some(placement) = self.last_monitor.as_ref().and_then(|key| self.placements.get(key)) else {
+            return viewport;
+        };
+        viewport = viewport.with_inner_size(placement.size).with_maximized(placement.maximized);
+        if let Some(position) = placement.position {
+            viewport = viewport.with_position(position);
+        }
+        viewport
+    }
+}
+
+/// Ключ конфигурации: разрешение монитора в пикселях и масштаб, например `2560x1440@150%`
+fn monitor_key(info: &egui::ViewportInfo) -> Option<String> {
+    let size = info.monitor_size?;
+    let scale = info.native_pixels_per_point?;
+    Some(format!(
+        "{}x{}@{}%",
+        (size.x * scale).round(),
+        (size.y * scale).round(),
+        (scale * 100.0).round()
+    ))
+}
+
+/// Состояние отслеживания окна в текущем сеансе
+#[derive(Default)]
+pub struct WindowTracker {
+    /// Первая конфигурация мониторов уже обработана
+    started: bool,
+    /// Когда размещение изменилось последний раз, если оно ещё не сохранено
+    changed_at: Option<Instant>,
+    /// До этого момента окно не отслеживается - применяется восстановленное размещение
+    restoring_until: Option<Instant>,
+    /// Заголовок, переданный окну последним
+    title: String,
+}
+
+impl CedarApp {
+    /// Запоминает размещение окна для текущей конфигурации мониторов.
+    /// При запуске на другой конфигурации восстанавливает размещение, сохранённое для неё
+    pub fn track_window(&mut self, ctx: &egui::Context) {
+        let info = ctx.input(|i| i.viewport().clone());
+        let Some(key) = monitor_key(&info) else {
+            return;
+        };
+        let placements = &mut self.config.window;
+
+        if !self.window_tracker.started {
+            self.window_tracker.started = true;
+            if placements.last_monitor.as_ref() != Some(&key) {
+                if let Some(placement) = placements.placements.get(&key) {
+                    if let Some(position) = placement.position {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position.into()));
+                    }
+                    ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(placement.size.into()));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Maximized(placement.maximized));
+                    placements.last_monitor = Some(key);
+                    self.window_tracker.restoring_until = Some(Instant::now() + RESTORE_DELAY);
+                    ctx.request_repaint_after(RESTORE_DELAY);
+                    return;
+                }
+            }
+        }
+
+        if self.window_tracker.restoring_until.is_some_and(|until| Instant::now() < until) {
+            return;
+        }
+        if info.minimized == Some(true) || info.fullscreen == Some(true) {
+            return;
+        }
+        let maximized = info.maximized == Some(true);
+        let previous = placements.placements.get(&key);
+        let placement = match (maximized, previous) {
+            // У развёрнутого окна запоминаем только флаг, обычные размеры остаются прежними
+            (true, Some(previous)) => WindowPlacement {
+                maximized: true,
+                ..previous.clone()
+            },
+            _ => {
+                let Some(inner) = info.inner_rect else {
+                    return;
+                };
+                WindowPlacement {
+                    position: info.outer_rect.map(|r| [r.left().round(), r.top().round()]),
+                    size: [inner.width().round(), inner.height().round()],
+                    maximized,
+                }
+            }
+        };
+
+        if previous != Some(&placement) || placements.last_monitor.as_ref() != Some(&key) {
+            placements.placements.insert(key.clone(), placement);
+            placements.last_monitor = Some(key);
+            self.window_tracker.changed_at = Some(Instant::now());
+        }
+
+        // Сохраняем, когда окно перестало двигаться
+        if let Some(changed_at) = self.window_tracker.changed_at {
+            let elapsed = changed_at.elapsed();
+            if elapsed >= SAVE_DELAY {
+                self.window_tracker.changed_at = None;
+                self.save_config_to_file();
+            } else {
+                ctx.request_repaint_after(SAVE_DELAY - elapsed);
+            }
+        }
+    }
+
+    /// Заголовок окна по текущему сканированию; окну передаётся только при изменении
+    pub fn update_title(&mut self, ctx: &egui::Context) {
+        let root = if self.is_scanning {
+            Some((self.scan_progress.lock().unwrap().root.clone(), self.translations.get("title_scanning")))
+        } else {
+            self.root_node.as_ref().map(|root| {
+                let when = self
+                    .last_scan_finished
+                    .map(|finished| self.translations.format_time(finished))
+                    .unwrap_or_default();
+                (root.path.display().to_string(), self.translations.get_fmt("title_scanned", &[&when]))
+            })
+        };
+        let title = match root {
+            Some((path, state)) => format!("{} — {} ({})", TITLE, path, state),
+            None => TITLE.to_string(),
+        };
+        if title != self.window_tracker.title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_tracker.title = title;
+        }
+    }
+
+    /// Иконки под текущий масштаб экрана, чтобы они не были размытыми
+    pub fn refresh_icons(&mut self, ctx: &egui::Context) {
+        self.shell_icons.set_pixels_per_point(ctx.pixels_per_point());
+        self.icons.update(ctx);
+    }
+}